@@ -0,0 +1,66 @@
+// Weighs the census's SSE2 counting of candidates, four cells at a time, against counting a cell at a time, on the rows of the samples with their candidates noted and on blank rows of the bigger grids. The `simd` feature is only worth having while the SSE2 times are the lower. Run with `cargo bench --bench census`.
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use christopher::{scalar_counts, Coord, Debugger, Puzzle};
+
+const PUZZLES: [(&str, &str); 3] = [
+    ("easy", include_str!("../samples/easy.txt")),
+    ("evil", include_str!("../samples/evil.txt")),
+    ("blank-16", ""),
+];
+
+fn census(c: &mut Criterion) {
+    for (sample, input) in PUZZLES {
+        let rows = rows(input);
+        let mut group = c.benchmark_group(format!("census/{}", sample));
+        group.bench_function("scalar", |b| {
+            b.iter(|| {
+                rows.iter()
+                    .map(|row| scalar_counts(black_box(row))[1])
+                    .sum::<u8>()
+            })
+        });
+        #[cfg(target_arch = "x86_64")]
+        group.bench_function("sse2", |b| {
+            b.iter(|| {
+                rows.iter()
+                    .map(|row| christopher::sse2_counts(black_box(row))[1])
+                    .sum::<u8>()
+            })
+        });
+        group.finish();
+    }
+}
+
+// Each row's candidates as bits, nought for a cell filled in, as the hidden singles count them. No input is a blank 16x16 grid.
+fn rows(input: &str) -> Vec<Vec<u32>> {
+    let input = if input.is_empty() {
+        ".".repeat(256)
+    } else {
+        input.to_string()
+    };
+    let debugger = Debugger::new(Puzzle::parse(&input));
+    let puzzle = debugger.puzzle();
+    let size = puzzle.shape().size();
+    (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    let cell = puzzle[Coord::new(row, col)];
+                    match cell.number() {
+                        Some(_) => 0,
+                        None => cell
+                            .candidates()
+                            .iter()
+                            .fold(0, |bits, digit| bits | 1 << digit),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+criterion_group!(benches, census);
+criterion_main!(benches);
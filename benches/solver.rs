@@ -0,0 +1,65 @@
+// Times parsing, candidate assignment, each technique, and whole solves on the sample puzzles, so a slowdown in the solver loop shows up as a regression. Run with `cargo bench`, optionally naming the benchmarks to run, e.g. `cargo bench -- evil`.
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use christopher::{solve_with_guesses, Debugger, Puzzle, PuzzleStatus, SolverConfig, Technique};
+
+const PUZZLES: [(&str, &str); 3] = [
+    ("easy", include_str!("../samples/easy.txt")),
+    ("hard", include_str!("../samples/hard.txt")),
+    ("evil", include_str!("../samples/evil.txt")),
+];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (sample, input) in PUZZLES {
+        group.bench_function(sample, |b| b.iter(|| Puzzle::parse(input)));
+    }
+    group.finish();
+}
+
+fn candidates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("candidates");
+    for (sample, input) in PUZZLES {
+        let puzzle = Puzzle::parse(input);
+        group.bench_function(sample, |b| {
+            b.iter_batched(|| puzzle.clone(), Debugger::new, BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+// Each technique on its own, guessing wherever it can't make progress, so a slow technique shows up against the others.
+fn techniques(c: &mut Criterion) {
+    for technique in Technique::ALL {
+        let mut group = c.benchmark_group(format!("techniques/{}", technique));
+        for (sample, input) in PUZZLES {
+            group.bench_function(sample, |b| b.iter(|| solve(input, vec![technique])));
+        }
+        group.finish();
+    }
+}
+
+fn solves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    for (sample, input) in PUZZLES {
+        group.bench_function(sample, |b| b.iter(|| solve(input, Technique::ALL.to_vec())));
+    }
+    group.finish();
+}
+
+// Solve the puzzle quietly with just the techniques given, guessing if they can't finish it.
+fn solve(input: &str, techniques: Vec<Technique>) -> Puzzle {
+    let mut puzzle = Puzzle::parse(input);
+    puzzle.set_config(SolverConfig::with_techniques(techniques));
+    puzzle.solve().unwrap();
+
+    if puzzle.status() == PuzzleStatus::Unsolved {
+        puzzle = solve_with_guesses(puzzle.clone())
+            .unwrap()
+            .unwrap_or(puzzle);
+    }
+    puzzle
+}
+
+criterion_group!(benches, parse, candidates, techniques, solves);
+criterion_main!(benches);
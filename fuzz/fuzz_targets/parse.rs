@@ -0,0 +1,9 @@
+#![no_main]
+
+use christopher::Puzzle;
+use libfuzzer_sys::fuzz_target;
+
+// Whatever the input, parsing reports what's wrong with it rather than panicking
+fuzz_target!(|input: &str| {
+    let _ = Puzzle::parse_with_report(input);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use std::time::Duration;
+
+use christopher::{Puzzle, SolverConfig};
+use libfuzzer_sys::fuzz_target;
+
+// The techniques never disagree with the brute force search: no elimination rules out the solution's digit, and no grid they fill in breaks the rules
+fuzz_target!(|puzzle: Puzzle| {
+    let mut puzzle = puzzle;
+    puzzle.set_config(SolverConfig {
+        wall_clock_timeout: Some(Duration::from_secs(10)),
+        ..SolverConfig::default()
+    });
+    if let Some(disagreement) = puzzle.cross_check() {
+        panic!("{}\n{}", puzzle, disagreement.describe());
+    }
+});
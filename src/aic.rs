@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{cells_see_each_other, Consolidation, Coord, Justification, Puzzle, Technique};
+
+// Default limit on the number of links in an alternating inference chain.
+const AIC_DEFAULT_MAX_LINKS: usize = 15;
+
+// A single candidate: a digit in a grid position.
+type Node = (usize, usize, u8);
+
+// While searching, a node is reached either needing a strong link next or a weak link next.
+type State = (Node, bool);
+
+impl Puzzle {
+    // Alternating inference chains (AIC) using the default maximum chain length.
+    pub fn reduce_candidates_with_aics(&mut self) -> Vec<Consolidation> {
+        self.reduce_candidates_with_aics_of_length(AIC_DEFAULT_MAX_LINKS)
+    }
+
+    // Alternating inference chains: candidates joined by alternating strong links (if one is false the other is true) and weak links (if one is true the other is false), beginning and ending with a strong link. Links may be:
+    //
+    //    * single-digit: a digit held by two cells sharing a unit. Strong when they are the only two places for it in that unit.
+    //    * bivalue: two digits within one cell. Strong when they are the cell's only candidates.
+    //
+    // At least one end of such a chain must be true, which gives two kinds of eliminations:
+    //
+    //    * Type 1: both ends are the same digit. Any other cell seeing both ends can't hold it.
+    //    * Type 2: the ends are different digits in cells that see each other. Each end's digit can be removed from the other end's cell (or, if both ends are in one cell, every other candidate goes).
+    //
+    // Chains are written in the usual notation, e.g. `(4)r1c2 = (4)r1c8 - (4)r5c8 = (6)r5c8`, with `=` for strong links and `-` for weak ones.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_aics_of_length(
+        &mut self,
+        max_links: usize,
+    ) -> Vec<Consolidation> {
+        let mut nodes: Vec<Node> = Vec::new();
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                for digit in self.grid[row][col].candidates.iter() {
+                    nodes.push((row, col, digit));
+                }
+            }
+        }
+
+        for start in nodes {
+            let eliminations = self.search_aics_from(start, max_links);
+            if !eliminations.is_empty() {
+                return eliminations;
+            }
+        }
+
+        vec![]
+    }
+
+    // Breadth first search from the given candidate, so the shortest chain to each candidate is found first. Chains never visit a candidate twice.
+    fn search_aics_from(&mut self, start: Node, max_links: usize) -> Vec<Consolidation> {
+        let mut parents: BTreeMap<State, State> = BTreeMap::new();
+        let mut queue: VecDeque<(State, usize)> = VecDeque::new();
+        queue.push_back(((start, true), 0));
+        parents.insert((start, true), (start, true));
+
+        while let Some(((node, strong_next), links)) = queue.pop_front() {
+            if links >= max_links {
+                continue;
+            }
+
+            let neighbours = if strong_next {
+                self.strong_links(node)
+            } else {
+                self.weak_links(node)
+            };
+
+            let path = reconstruct_chain(&parents, (node, strong_next));
+            for neighbour in neighbours {
+                let state = (neighbour, !strong_next);
+                if parents.contains_key(&state) || path.contains(&neighbour) {
+                    continue;
+                }
+                parents.insert(state, (node, strong_next));
+
+                // Chains end on a strong link, and need at least three links to say anything new
+                if strong_next && links >= 2 {
+                    let chain = reconstruct_chain(&parents, state);
+                    let eliminations = self.eliminate_from_aic(&chain);
+                    if !eliminations.is_empty() {
+                        return eliminations;
+                    }
+                }
+
+                queue.push_back((state, links + 1));
+            }
+        }
+
+        vec![]
+    }
+
+    fn strong_links(&self, node: Node) -> Vec<Node> {
+        let (row, col, digit) = node;
+        let mut links: Vec<Node> = Vec::new();
+
+        let candidates = self.grid[row][col].candidates;
+        if candidates.count() == 2 {
+            for other in candidates.iter().filter(|&d| d != digit) {
+                links.push((row, col, other));
+            }
+        }
+
+        let coord = Coord::new(row, col);
+        for &unit in self.geometry.units_of(coord) {
+            let positions: Vec<Coord> = self
+                .geometry
+                .cells(unit)
+                .iter()
+                .copied()
+                .filter(|&p| self[p].candidates.contains(digit))
+                .collect();
+            if positions.len() == 2 {
+                for p in positions.into_iter().filter(|&p| p != coord) {
+                    if !links.contains(&(p.row, p.col, digit)) {
+                        links.push((p.row, p.col, digit));
+                    }
+                }
+            }
+        }
+
+        links
+    }
+
+    fn weak_links(&self, node: Node) -> Vec<Node> {
+        let (row, col, digit) = node;
+        let mut links: Vec<Node> = Vec::new();
+
+        for other in self.grid[row][col].candidates.iter() {
+            if other != digit {
+                links.push((row, col, other));
+            }
+        }
+
+        for &peer in self.geometry.peers(Coord::new(row, col)) {
+            if self[peer].candidates.contains(digit) {
+                links.push((peer.row, peer.col, digit));
+            }
+        }
+
+        links
+    }
+
+    fn eliminate_from_aic(&mut self, chain: &[Node]) -> Vec<Consolidation> {
+        let (r0, c0, d0) = chain[0];
+        let (r1, c1, d1) = chain[chain.len() - 1];
+
+        // Every candidate ruled out by the fact that at least one end of the chain is true
+        let mut targets: Vec<Node> = Vec::new();
+        if d0 == d1 {
+            if (r0, c0) == (r1, c1) {
+                return vec![];
+            }
+
+            for &peer in self.geometry.peers(Coord::new(r0, c0)) {
+                if cells_see_each_other(self.shape, (r1, c1), peer.into()) {
+                    targets.push((peer.row, peer.col, d0));
+                }
+            }
+        } else if (r0, c0) == (r1, c1) {
+            for digit in self.grid[r0][c0].candidates.iter() {
+                if digit != d0 && digit != d1 {
+                    targets.push((r0, c0, digit));
+                }
+            }
+        } else if cells_see_each_other(self.shape, (r0, c0), (r1, c1)) {
+            targets.push((r1, c1, d0));
+            targets.push((r0, c0, d1));
+        }
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for (row, col, digit) in targets {
+            if self.grid[row][col].remove_candidate(digit) {
+                let kind = if d0 == d1 { 1 } else { 2 };
+                log!(
+                    self,
+                    "🔁🔁🔁🔁🔁 AIC type {} eliminated candidate {} at grid position ({}, {}): {}",
+                    kind,
+                    digit,
+                    row,
+                    col,
+                    describe_aic(chain)
+                );
+                removals.push(((row, col), digit));
+            }
+        }
+
+        // Each cell along the chain, once
+        let mut pattern: Vec<(usize, usize)> = Vec::new();
+        for &(row, col, _) in chain.iter() {
+            if !pattern.contains(&(row, col)) {
+                pattern.push((row, col));
+            }
+        }
+
+        Consolidation::eliminations(
+            Technique::AlternatingInferenceChain,
+            Justification::Pattern(pattern),
+            &removals,
+        )
+    }
+}
+
+// Walk the parent links back to the start of the search, returning the chain from its start.
+fn reconstruct_chain(parents: &BTreeMap<State, State>, end: State) -> Vec<Node> {
+    let mut chain: Vec<Node> = vec![end.0];
+    let mut state = end;
+
+    while parents[&state] != state {
+        state = parents[&state];
+        chain.push(state.0);
+    }
+
+    chain.reverse();
+    chain
+}
+
+// E.g. "(4)r1c2 = (4)r1c8 - (4)r5c8 = (6)r5c8". Chains always start with a strong link, and rows and columns are counted from 1 as in the sudoku literature.
+fn describe_aic(chain: &[Node]) -> String {
+    let mut description = String::new();
+
+    for (i, (row, col, digit)) in chain.iter().enumerate() {
+        if i > 0 {
+            description.push_str(if i % 2 == 1 { " = " } else { " - " });
+        }
+        description.push_str(format!("({}){}", digit, Coord::new(*row, *col)).as_str());
+    }
+
+    description
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Cell, Puzzle};
+    use super::describe_aic;
+
+    #[test]
+    fn xy_chain() {
+        // (1)r1c1 = (2)r1c1 - (2)r1c5 = (3)r1c5 - (3)r5c5 = (1)r5c5
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[4][4] = Cell::with_candidates(vec![1, 3]);
+        puzzle.grid[4][0] = Cell::with_candidates(vec![1, 7, 8]);
+
+        assert!(!puzzle.reduce_candidates_with_aics().is_empty());
+        assert_eq!(vec![7, 8], puzzle.grid[4][0].candidates.to_vec());
+    }
+
+    #[test]
+    fn too_short() {
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[4][4] = Cell::with_candidates(vec![1, 3]);
+        puzzle.grid[4][0] = Cell::with_candidates(vec![1, 7, 8]);
+
+        assert!(puzzle.reduce_candidates_with_aics_of_length(2).is_empty());
+    }
+
+    #[test]
+    fn notation() {
+        assert_eq!(
+            "(4)r1c2 = (4)r1c8 - (4)r5c8 = (6)r5c8",
+            describe_aic(&[(0, 1, 4), (0, 7, 4), (4, 7, 4), (4, 7, 6)])
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use super::{
+    cells_see_each_other, combinations, units, Candidates, Consolidation, Justification, Puzzle,
+    Technique,
+};
+
+// Largest almost locked set considered. Bigger sets are rarely useful and make the search explode.
+const ALS_MAX_CELLS: usize = 4;
+
+// Longest chain of almost locked sets considered. Two sets is ALS-XZ, three is ALS-XY-Wing.
+const ALS_CHAIN_MAX_LENGTH: usize = 4;
+
+// An almost locked set (ALS): N unsolved cells within one row, column, or block holding exactly N+1 candidates between them. A lone bivalue cell is the smallest ALS.
+//
+// Removing any one digit from an ALS "locks" it: the remaining N digits must fill its N cells.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct AlmostLockedSet {
+    pub(super) cells: Vec<(usize, usize)>,
+    pub(super) digits: Vec<u8>,
+}
+
+impl AlmostLockedSet {
+    // Cells of the set which could hold the given digit.
+    pub(super) fn cells_with(&self, puzzle: &Puzzle, digit: u8) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .filter(|&&(r, c)| puzzle.grid[r][c].candidates.contains(digit))
+            .copied()
+            .collect()
+    }
+
+    fn overlaps(&self, other: &AlmostLockedSet) -> bool {
+        self.cells.iter().any(|p| other.cells.contains(p))
+    }
+}
+
+impl fmt::Display for AlmostLockedSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (r, c)) in self.cells.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "({}, {})", r, c)?;
+        }
+        write!(f, "}} = {:?}", self.digits)
+    }
+}
+
+// One hop in an ALS chain: the next set and the restricted common candidate linking it to the previous one.
+#[derive(Clone, Copy, Debug)]
+struct Link {
+    to: usize,
+    digit: u8,
+}
+
+impl Puzzle {
+    pub(super) fn almost_locked_sets(&self) -> Vec<AlmostLockedSet> {
+        let mut sets: Vec<AlmostLockedSet> = Vec::new();
+
+        for unit in units(self.shape) {
+            let unsolved: Vec<(usize, usize)> = unit
+                .into_iter()
+                .filter(|&(r, c)| self.grid[r][c].number.is_none())
+                .collect();
+
+            for size in 1..=ALS_MAX_CELLS {
+                for subset in combinations(unsolved.len(), size) {
+                    let cells: Vec<(usize, usize)> = subset.iter().map(|&i| unsolved[i]).collect();
+
+                    let digits = cells.iter().fold(Candidates::none(), |digits, &(r, c)| {
+                        digits.union(self.grid[r][c].candidates)
+                    });
+
+                    if digits.count() != size + 1 {
+                        continue;
+                    }
+
+                    // Sets within both a block and a row/column show up twice
+                    let mut sorted = cells.clone();
+                    sorted.sort();
+                    if sets.iter().any(|s| s.cells == sorted) {
+                        continue;
+                    }
+
+                    sets.push(AlmostLockedSet {
+                        cells: sorted,
+                        digits: digits.to_vec(),
+                    });
+                }
+            }
+        }
+
+        sets
+    }
+
+    // Restricted common candidates between two non-overlapping sets: digits held by both where every instance in one set sees every instance in the other. Such a digit can be in at most one of the two sets.
+    fn restricted_common_candidates(&self, a: &AlmostLockedSet, b: &AlmostLockedSet) -> Vec<u8> {
+        if a.overlaps(b) {
+            return vec![];
+        }
+
+        a.digits
+            .iter()
+            .filter(|d| b.digits.contains(d))
+            .filter(|&&d| {
+                let b_cells = b.cells_with(self, d);
+                a.cells_with(self, d).iter().all(|&p| {
+                    b_cells
+                        .iter()
+                        .all(|&q| cells_see_each_other(self.shape, p, q))
+                })
+            })
+            .copied()
+            .collect()
+    }
+
+    // Almost locked set chains: a sequence of ALSs where each neighbouring pair shares a restricted common candidate, and consecutive links use different digits.
+    //
+    // If the first set doesn't hold some digit Z, it locks and must hold its first link digit, which strips that digit from the second set, locking it onto its next link digit, and so on down the chain until the last set locks onto all its other digits. So Z (if it's in both end sets and isn't either end's link digit) must be in the first or last set, and can be eliminated from every cell seeing all of their Z cells.
+    //
+    //    * 2 sets: ALS-XZ
+    //    * 3 sets: ALS-XY-Wing, the middle set being the "pivot"
+    //    * 4 sets: a short ALS chain
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_als_chains(&mut self) -> Vec<Consolidation> {
+        let sets = self.almost_locked_sets();
+
+        let mut links: Vec<Vec<Link>> = vec![Vec::new(); sets.len()];
+        for i in 0..sets.len() {
+            for j in (i + 1)..sets.len() {
+                for digit in self.restricted_common_candidates(&sets[i], &sets[j]) {
+                    links[i].push(Link { to: j, digit });
+                    links[j].push(Link { to: i, digit });
+                }
+            }
+        }
+
+        for start in 0..sets.len() {
+            let mut chain: Vec<usize> = vec![start];
+            let mut digits: Vec<u8> = Vec::new();
+
+            let eliminations = self.extend_als_chain(&sets, &links, &mut chain, &mut digits);
+            if !eliminations.is_empty() {
+                return eliminations;
+            }
+        }
+
+        vec![]
+    }
+
+    fn extend_als_chain(
+        &mut self,
+        sets: &[AlmostLockedSet],
+        links: &[Vec<Link>],
+        chain: &mut Vec<usize>,
+        digits: &mut Vec<u8>,
+    ) -> Vec<Consolidation> {
+        if chain.len() >= 2 {
+            let eliminations = self.eliminate_from_als_chain(sets, chain, digits);
+            if !eliminations.is_empty() {
+                return eliminations;
+            }
+        }
+
+        if chain.len() == ALS_CHAIN_MAX_LENGTH {
+            return vec![];
+        }
+
+        let last = chain[chain.len() - 1];
+        for link in links[last].iter() {
+            if digits.last() == Some(&link.digit)
+                || chain.iter().any(|&i| sets[i].overlaps(&sets[link.to]))
+            {
+                continue;
+            }
+
+            chain.push(link.to);
+            digits.push(link.digit);
+
+            let eliminations = self.extend_als_chain(sets, links, chain, digits);
+            if !eliminations.is_empty() {
+                return eliminations;
+            }
+
+            chain.pop();
+            digits.pop();
+        }
+
+        vec![]
+    }
+
+    fn eliminate_from_als_chain(
+        &mut self,
+        sets: &[AlmostLockedSet],
+        chain: &[usize],
+        digits: &[u8],
+    ) -> Vec<Consolidation> {
+        let first = &sets[chain[0]];
+        let last = &sets[chain[chain.len() - 1]];
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for z in first.digits.iter().filter(|d| last.digits.contains(d)) {
+            if *z == digits[0] || *z == digits[digits.len() - 1] {
+                continue;
+            }
+
+            let mut z_cells = first.cells_with(self, *z);
+            z_cells.append(&mut last.cells_with(self, *z));
+
+            for row in 0..self.shape.size() {
+                for col in 0..self.shape.size() {
+                    if chain.iter().any(|&i| sets[i].cells.contains(&(row, col)))
+                        || !z_cells
+                            .iter()
+                            .all(|&p| cells_see_each_other(self.shape, p, (row, col)))
+                    {
+                        continue;
+                    }
+
+                    if self.grid[row][col].remove_candidate(*z) {
+                        log!(
+                            self,
+                            "⛓️⛓️⛓️⛓️⛓️ {} eliminated candidate {} at grid position ({}, {}): {}",
+                            als_chain_name(chain.len()),
+                            z,
+                            row,
+                            col,
+                            describe_als_chain(sets, chain, digits, *z)
+                        );
+                        removals.push(((row, col), *z));
+                    }
+                }
+            }
+        }
+
+        let pattern: Vec<(usize, usize)> = chain
+            .iter()
+            .flat_map(|&i| sets[i].cells.iter().copied())
+            .collect();
+
+        Consolidation::eliminations(
+            Technique::AlmostLockedSetChain,
+            Justification::Pattern(pattern),
+            &removals,
+        )
+    }
+}
+
+fn als_chain_name(length: usize) -> &'static str {
+    match length {
+        2 => "ALS-XZ",
+        3 => "ALS-XY-Wing",
+        _ => "ALS chain",
+    }
+}
+
+// E.g. "{(0, 1)} = [3, 7] -(7)- {(4, 1) (4, 2)} = [2, 5, 7] => 3 must be in the first or last set"
+fn describe_als_chain(sets: &[AlmostLockedSet], chain: &[usize], digits: &[u8], z: u8) -> String {
+    let mut description = sets[chain[0]].to_string();
+    for (i, digit) in digits.iter().enumerate() {
+        description.push_str(format!(" -({})- {}", digit, sets[chain[i + 1]]).as_str());
+    }
+    description.push_str(format!(" => {} must be in the first or last set", z).as_str());
+
+    description
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Cell, Puzzle};
+
+    #[test]
+    fn xy_wing() {
+        // The smallest ALS-XY-Wing is three bivalue cells, i.e. a plain XY-Wing
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[4][0] = Cell::with_candidates(vec![1, 3]);
+        puzzle.grid[4][4] = Cell::with_candidates(vec![3, 5]);
+        puzzle.grid[3][3] = Cell::with_candidates(vec![3, 6]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_als_chains().len());
+        assert_eq!(vec![5], puzzle.grid[4][4].candidates.to_vec());
+        assert_eq!(vec![3, 6], puzzle.grid[3][3].candidates.to_vec());
+    }
+
+    #[test]
+    fn xz() {
+        // {(0, 0) (0, 1)} = [1, 2, 4] and {(3, 0)} = [1, 4], linked by 1
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][1] = Cell::with_candidates(vec![2, 4]);
+        puzzle.grid[3][0] = Cell::with_candidates(vec![1, 4]);
+        puzzle.grid[3][1] = Cell::with_candidates(vec![4, 8]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_als_chains().len());
+        assert_eq!(vec![8], puzzle.grid[3][1].candidates.to_vec());
+    }
+}
@@ -0,0 +1,141 @@
+use super::als::AlmostLockedSet;
+use super::{cells_see_each_other, Consolidation, Justification, Puzzle, Technique};
+
+impl Puzzle {
+    // Aligned pair exclusion (APE): two cells that see each other can only take certain pairs of values together. A pair is excluded when:
+    //
+    //    * both cells would hold the same digit, or
+    //    * some almost locked set seen by both cells holds both digits, and would be left with fewer digits than cells once they were placed (a bivalue cell holding exactly the pair is the simplest case)
+    //
+    // A candidate of either cell which only appears in excluded pairs can be eliminated.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_aligned_pair_exclusion(&mut self) -> Vec<Consolidation> {
+        let sets = self.almost_locked_sets();
+
+        let mut unsolved: Vec<(usize, usize)> = Vec::new();
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                if self.grid[row][col].number.is_none() {
+                    unsolved.push((row, col));
+                }
+            }
+        }
+
+        for (i, &a) in unsolved.iter().enumerate() {
+            for &b in unsolved.iter().skip(i + 1) {
+                if !cells_see_each_other(self.shape, a, b) {
+                    continue;
+                }
+
+                let eliminations = self.exclude_aligned_pair(a, b, &sets);
+                if !eliminations.is_empty() {
+                    return eliminations;
+                }
+            }
+        }
+
+        vec![]
+    }
+
+    fn exclude_aligned_pair(
+        &mut self,
+        a: (usize, usize),
+        b: (usize, usize),
+        sets: &[AlmostLockedSet],
+    ) -> Vec<Consolidation> {
+        // Only sets that are fully visible to the pair can be broken by it
+        let relevant: Vec<&AlmostLockedSet> = sets
+            .iter()
+            .filter(|s| !s.cells.contains(&a) && !s.cells.contains(&b))
+            .filter(|s| {
+                s.cells.iter().all(|&p| {
+                    cells_see_each_other(self.shape, p, a) || cells_see_each_other(self.shape, p, b)
+                })
+            })
+            .collect();
+
+        let a_candidates = self.grid[a.0][a.1].candidates;
+        let b_candidates = self.grid[b.0][b.1].candidates;
+
+        // The pair itself, then the cells of every set which excluded a pair
+        let mut pattern: Vec<(usize, usize)> = vec![a, b];
+
+        let mut allowed: Vec<(u8, u8)> = Vec::new();
+        for x in a_candidates.iter() {
+            for y in b_candidates.iter() {
+                if x == y {
+                    continue;
+                }
+
+                let broken = relevant.iter().find(|s| {
+                    s.digits.contains(&x)
+                        && s.digits.contains(&y)
+                        && s.cells_with(self, x)
+                            .iter()
+                            .all(|&p| cells_see_each_other(self.shape, p, a))
+                        && s.cells_with(self, y)
+                            .iter()
+                            .all(|&p| cells_see_each_other(self.shape, p, b))
+                });
+
+                match broken {
+                    Some(set) => {
+                        for &p in set.cells.iter() {
+                            if !pattern.contains(&p) {
+                                pattern.push(p);
+                            }
+                        }
+                    }
+                    None => allowed.push((x, y)),
+                }
+            }
+        }
+
+        if allowed.is_empty() {
+            // No valid pair at all. The puzzle is broken and `status()` will say so.
+            return vec![];
+        }
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for x in a_candidates.iter() {
+            if !allowed.iter().any(|&(ax, _)| ax == x) && self.grid[a.0][a.1].remove_candidate(x) {
+                log!(self, "🧲🧲🧲🧲🧲 Aligned pair exclusion on ({}, {}) and ({}, {}) eliminated candidate {} at grid position ({}, {}): allowed pairs {:?}", a.0, a.1, b.0, b.1, x, a.0, a.1, allowed);
+                removals.push((a, x));
+            }
+        }
+        for y in b_candidates.iter() {
+            if !allowed.iter().any(|&(_, by)| by == y) && self.grid[b.0][b.1].remove_candidate(y) {
+                log!(self, "🧲🧲🧲🧲🧲 Aligned pair exclusion on ({}, {}) and ({}, {}) eliminated candidate {} at grid position ({}, {}): allowed pairs {:?}", a.0, a.1, b.0, b.1, y, b.0, b.1, allowed);
+                removals.push((b, y));
+            }
+        }
+
+        Consolidation::eliminations(
+            Technique::AlignedPairExclusion,
+            Justification::Pattern(pattern),
+            &removals,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Cell, Puzzle};
+
+    #[test]
+    fn excluded_by_bivalue_cells() {
+        // If (0, 1) were 3, (0, 5) would have to be 1 and (2, 2) would have to be 2, leaving nothing for (0, 0)
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][1] = Cell::with_candidates(vec![1, 2, 3]);
+        puzzle.grid[0][5] = Cell::with_candidates(vec![1, 3]);
+        puzzle.grid[2][2] = Cell::with_candidates(vec![2, 3]);
+
+        assert_eq!(
+            1,
+            puzzle.reduce_candidates_with_aligned_pair_exclusion().len()
+        );
+        assert_eq!(vec![1, 2], puzzle.grid[0][1].candidates.to_vec());
+    }
+}
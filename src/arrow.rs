@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use super::{
+    Candidates, Consolidation, Constraint, Coord, IllDefinedReason, Justification, Puzzle, Shape,
+    Technique, MAX_SIZE,
+};
+
+/// An arrow sudoku clue: a circle, and an arrow out of it whose digits add up to the circle's. Digits may repeat along an arrow, as long as they don't repeat in a row, column, or block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arrow {
+    // The circle, then each cell along the arrow
+    cells: Vec<Coord>,
+}
+
+impl Arrow {
+    // The arrow out of the circle through the cells given, in order. There must be at least one, none of them the circle or another twice, and fewer than there are digits.
+    pub fn new(circle: Coord, path: &[Coord]) -> Result<Arrow, String> {
+        if path.is_empty() {
+            return Err(format!("The arrow from {} has no cells along it", circle));
+        }
+        if path.len() >= MAX_SIZE {
+            return Err(format!(
+                "The arrow from {} has {} cells along it, more than any could add up to",
+                circle,
+                path.len()
+            ));
+        }
+
+        let mut cells = Vec::with_capacity(path.len() + 1);
+        for &coord in [circle].iter().chain(path) {
+            if cells.contains(&coord) {
+                return Err(format!("The arrow from {} crosses {} twice", circle, coord));
+            }
+            cells.push(coord);
+        }
+        Ok(Arrow { cells })
+    }
+
+    pub fn circle(&self) -> Coord {
+        self.cells[0]
+    }
+
+    // The cells along the arrow, from the circle out.
+    pub fn path(&self) -> Vec<Coord> {
+        self.cells[1..].to_vec()
+    }
+
+    // The circle, then the cells along the arrow.
+    pub fn cells(&self) -> Vec<Coord> {
+        self.cells.clone()
+    }
+}
+
+/// Arrow sudoku's arrows, in the order given, as many as there are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arrows {
+    arrows: Vec<Arrow>,
+}
+
+impl Arrows {
+    pub const NONE: Arrows = Arrows { arrows: Vec::new() };
+
+    pub fn push(&mut self, arrow: Arrow) {
+        self.arrows.push(arrow);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arrow> {
+        self.arrows.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.arrows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arrows.is_empty()
+    }
+}
+
+impl Default for Arrows {
+    fn default() -> Arrows {
+        Arrows::NONE
+    }
+}
+
+// The sums the cells' digits could add up to, bit `s` set for each sum `s`, none past the largest digit as no circle could hold them.
+fn sums<'a>(size: usize, cells: impl Iterator<Item = &'a Candidates>) -> u64 {
+    cells.fold(1, |sums, cell| {
+        let next = cell.iter().fold(0, |next, digit| next | sums << digit);
+        next & ((1 << (size + 1)) - 1)
+    })
+}
+
+// What each cell of an arrow could hold in some arrangement adding up: the circle first, then the cells along it. Cells are given as the digits they could be, a number as itself alone. Every cell comes back empty when nothing adds up.
+//
+// Digits are free to repeat along the arrow here, even where its cells share a row, column, or block, so a little more may be kept than could really go, but nothing that could is lost.
+pub(super) fn fits(size: usize, cells: &[Candidates]) -> Vec<Candidates> {
+    let (circle, path) = (cells[0], &cells[1..]);
+    let totals = sums(size, path.iter());
+    let circle: Candidates = circle
+        .iter()
+        .filter(|&digit| totals & (1 << digit) != 0)
+        .collect();
+
+    let mut fits = vec![circle];
+    for i in 0..path.len() {
+        let others = sums(
+            size,
+            path.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, cell)| cell),
+        );
+        fits.push(
+            path[i]
+                .iter()
+                .filter(|&digit| {
+                    circle
+                        .iter()
+                        .any(|total| total >= digit && others & (1 << (total - digit)) != 0)
+                })
+                .collect(),
+        );
+    }
+
+    fits
+}
+
+// Each arrow is a constraint of its own: a circle can't hold more than the arrow's cells could add up to, nor an arrow's cell more than the circle could hold less the least the others could add up to, and so on. A candidate no sum has a place for can go.
+impl Constraint for Arrow {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        let positions = self.cells();
+        let cells: Vec<Candidates> = positions
+            .iter()
+            .map(|&coord| match puzzle[coord].number {
+                Some(number) => [number].into_iter().collect(),
+                None => puzzle[coord].candidates,
+            })
+            .collect();
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for (&coord, fit) in positions.iter().zip(fits(puzzle.shape.size(), &cells)) {
+            if puzzle[coord].number.is_some() {
+                continue;
+            }
+
+            for digit in puzzle[coord].candidates.difference(fit).iter() {
+                puzzle[coord].remove_candidate(digit);
+                log!(
+                    puzzle,
+                    "🏹🏹🏹🏹🏹 Arrow from {} eliminated candidate {} at grid position ({}, {})",
+                    self.circle(),
+                    digit,
+                    coord.row,
+                    coord.col
+                );
+                removals.push((coord.into(), digit));
+            }
+        }
+
+        Consolidation::eliminations(
+            Technique::Arrow,
+            Justification::Arrow(positions.into_iter().map(Into::into).collect()),
+            &removals,
+        )
+    }
+
+    // Broken once filled in, circle and all, if its digits don't add up to the circle's.
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        let numbers: Vec<u8> = self
+            .cells()
+            .into_iter()
+            .map(|coord| puzzle[coord].number)
+            .collect::<Option<_>>()?;
+        let total: u16 = numbers[1..].iter().map(|&n| u16::from(n)).sum();
+        (total != u16::from(numbers[0]))
+            .then(|| IllDefinedReason::ArrowDoesNotAddUp(self.circle().into()))
+    }
+
+    fn fits(&self, shape: Shape, cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        let cells: Vec<Candidates> = self.cells.iter().map(|&coord| cell(coord)).collect();
+        self.cells
+            .iter()
+            .copied()
+            .zip(fits(shape.size(), &cells))
+            .collect()
+    }
+}
+
+impl Puzzle {
+    // Arrows: the digits along each arrow must add up to the one in its circle.
+    //
+    // Returns the eliminations made, an arrow at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_arrows(&mut self) -> Vec<Consolidation> {
+        let clues = Arc::clone(&self.clues);
+        let arrows = &clues.arrows;
+        if arrows.is_empty() {
+            return vec![];
+        }
+
+        for arrow in arrows.iter() {
+            let found = arrow.prune(self);
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        vec![]
+    }
+
+    // The circles of arrows filled in, circle and all, whose digits don't add up to the circle's.
+    pub(super) fn wrong_arrows(&self) -> Vec<Coord> {
+        self.clues
+            .arrows
+            .iter()
+            .filter(|arrow| arrow.check(self).is_some())
+            .map(Arrow::circle)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Coord, Puzzle, PuzzleStatus, Shape, SolverConfig, Technique};
+    use super::{fits, Arrow, Arrows};
+
+    #[test]
+    fn arrows() {
+        let coord = |row, col| Coord::new(row, col);
+        let arrow = Arrow::new(coord(0, 0), &[coord(0, 1), coord(1, 2)]).unwrap();
+        assert_eq!(coord(0, 0), arrow.circle());
+        assert_eq!(vec![coord(0, 1), coord(1, 2)], arrow.path());
+        assert_eq!(3, arrow.cells().len());
+
+        assert!(Arrow::new(coord(0, 0), &[]).is_err());
+        assert!(Arrow::new(coord(0, 0), &[coord(0, 1), coord(0, 0)]).is_err());
+        assert!(Arrow::new(coord(0, 0), &[coord(0, 1), coord(0, 1)]).is_err());
+
+        // As many arrows as are given, however many
+        let mut arrows = Arrows::NONE;
+        assert!(arrows.is_empty());
+        for _ in 0..100 {
+            arrows.push(arrow.clone());
+        }
+        assert_eq!(100, arrows.len());
+    }
+
+    #[test]
+    fn sums() {
+        let set = |digits: &[u8]| -> Candidates { digits.iter().collect() };
+        let all = Shape::STANDARD.digits();
+
+        // Two cells add up to at least 2, so the circle can't be 1, nor either cell 9 or 8
+        let found = fits(9, &[all, all, all]);
+        assert_eq!(set(&[2, 3, 4, 5, 6, 7, 8, 9]), found[0]);
+        assert_eq!(set(&[1, 2, 3, 4, 5, 6, 7, 8]), found[1]);
+
+        // A 4 in the circle, and a 3 along, leaves the other a 1
+        let found = fits(9, &[set(&[4]), set(&[3]), all]);
+        assert_eq!(vec![set(&[4]), set(&[3]), set(&[1])], found);
+
+        // A small circle caps the cells along it, and large cells raise the circle
+        let found = fits(9, &[set(&[3, 5, 9]), all, all]);
+        assert_eq!(set(&[3, 5, 9]), found[0]);
+        let found = fits(9, &[all, set(&[6, 7]), set(&[2, 3])]);
+        assert_eq!(set(&[8, 9]), found[0]);
+        assert_eq!(set(&[6, 7]), found[1]);
+
+        // One cell along is the circle's digit over again
+        let found = fits(9, &[set(&[2, 7]), set(&[7, 8])]);
+        assert_eq!(vec![set(&[7]), set(&[7])], found);
+
+        // Nothing adds up
+        assert!(fits(9, &[set(&[2]), set(&[5]), all])
+            .iter()
+            .all(Candidates::is_empty));
+    }
+
+    #[test]
+    fn reduce() {
+        let coord = |row, col| Coord::new(row, col);
+        let mut arrows = Arrows::NONE;
+        arrows.push(Arrow::new(coord(0, 0), &[coord(1, 1), coord(2, 2)]).unwrap());
+
+        let mut puzzle = Puzzle::parse("");
+        puzzle.set_arrows(arrows);
+        puzzle.config = SolverConfig::with_techniques(vec![Technique::Arrow]);
+        puzzle.grid[0][0].number = Some(4);
+        puzzle.assign_candidates();
+
+        // Two cells adding up to 4 hold 1 thru 3
+        let found = puzzle.reduce_candidates_with_arrows();
+        assert_eq!(Some(Technique::Arrow), found[0].technique());
+        assert_eq!(vec![1, 2, 3], puzzle.grid[1][1].candidates.to_vec());
+        assert!(puzzle.reduce_candidates_with_arrows().is_empty());
+
+        // Filled in, they must add up
+        let mut filled = puzzle.clone();
+        filled.grid[1][1].number = Some(1);
+        filled.grid[2][2].number = Some(3);
+        assert!(filled.wrong_arrows().is_empty());
+        filled.grid[2][2].number = Some(2);
+        assert_eq!(vec![coord(0, 0)], filled.wrong_arrows());
+        filled.grid[2][2].number = None;
+        assert!(filled.wrong_arrows().is_empty());
+
+        // A circle too small for its arrow can't be solved
+        let mut stuck = puzzle.clone();
+        stuck.grid[0][0].number = Some(1);
+        while !stuck.reduce_candidates_with_arrows().is_empty() {}
+        assert!(matches!(stuck.status(), PuzzleStatus::IllDefined(_)));
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::{Duration, Instant};
+
+use super::engine::{position, read_position};
+use super::pipeline::add_time;
+use super::progress::{Progress, Tracker};
+use super::{solve_with_guesses, Puzzle, PuzzleStatus, SolveError, SolverConfig, Technique};
+
+/// The outcome of solving each puzzle of a batch, one puzzle to a line.
+pub struct Batch {
+    pub(super) results: Vec<BatchResult>,
+}
+
+pub(super) struct BatchResult {
+    pub(super) puzzle: String,
+    // The grid as far as the solver got, `.` for blanks, or empty if the puzzle couldn't be read
+    pub(super) solution: String,
+    pub(super) status: String,
+    pub(super) techniques: Vec<String>,
+    pub(super) technique_times: Vec<(Technique, Duration)>,
+    // The steps the solve took, guesses and all, the guesses made across every branch of the search, and the time it took
+    pub(super) steps: usize,
+    pub(super) guesses: usize,
+    pub(super) elapsed: Duration,
+}
+
+// Solve each puzzle in the input, given as a line of 81 characters as for the engine's `position`. Blank lines and lines starting with `#` are skipped. The solver keeps quiet whatever the config says, as a batch may run to thousands of puzzles.
+pub fn batch(input: &str, config: &SolverConfig) -> Batch {
+    batch_with_progress(input, config, |_| {})
+}
+
+// As `batch()`, telling the callback how far it's got after each puzzle, to show progress through a long batch.
+pub fn batch_with_progress(
+    input: &str,
+    config: &SolverConfig,
+    progress: impl FnMut(Progress),
+) -> Batch {
+    let lines: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let mut tracker = Tracker::new(lines.len(), progress);
+    let mut puzzle = line_puzzle(config);
+    let results = lines
+        .into_iter()
+        .map(|line| {
+            let result = solve_line(&mut puzzle, line);
+            tracker.tick();
+            result
+        })
+        .collect();
+
+    Batch { results }
+}
+
+// A puzzle to read each line into in turn, solving with the config but quietly. Reading them all into the one puzzle keeps the buffers its steps and the rest are held in from line to line.
+pub(super) fn line_puzzle(config: &SolverConfig) -> Puzzle {
+    let mut puzzle = Puzzle::parse("");
+    puzzle.set_config(SolverConfig {
+        verbose: false,
+        ..config.clone()
+    });
+    puzzle
+}
+
+// Solve the line's puzzle, read into the puzzle given in place of whatever it held.
+pub(super) fn solve_line(puzzle: &mut Puzzle, line: &str) -> BatchResult {
+    if read_position(puzzle, line).is_err() {
+        return BatchResult {
+            puzzle: line.to_string(),
+            solution: String::new(),
+            status: "invalid".to_string(),
+            techniques: vec![],
+            technique_times: vec![],
+            steps: 0,
+            guesses: 0,
+            elapsed: Duration::ZERO,
+        };
+    }
+
+    // Guesses are searched on copies of the puzzle, so the outcome is another puzzle once it comes to guessing, or once a limit cuts the solve short
+    let start = Instant::now();
+    let (other, exceeded) = match puzzle.solve() {
+        Ok(()) if puzzle.status() == PuzzleStatus::Unsolved => {
+            match solve_with_guesses(puzzle.clone()) {
+                Ok(solved) => (solved, false),
+                Err(SolveError::BudgetExceeded { progress, .. }) => (Some(*progress), true),
+            }
+        }
+        Ok(()) => (None, false),
+        Err(SolveError::BudgetExceeded { progress, .. }) => (Some(*progress), true),
+    };
+    let elapsed = start.elapsed();
+    let puzzle = other.as_ref().unwrap_or(puzzle);
+
+    let status = match puzzle.status() {
+        _ if exceeded => "budget-exceeded",
+        PuzzleStatus::Solved => "solved",
+        PuzzleStatus::Unsolved => "unsolved",
+        PuzzleStatus::IllDefined(_) => "ill-defined",
+    };
+
+    BatchResult {
+        puzzle: line.to_string(),
+        solution: position(puzzle),
+        status: status.to_string(),
+        techniques: techniques_used(puzzle),
+        steps: puzzle.steps.len(),
+        guesses: puzzle.guesses_by_depth.iter().sum(),
+        technique_times: puzzle.technique_times.clone(),
+        elapsed,
+    }
+}
+
+// Solve the puzzle with the techniques, guessing once they're stuck, and give back the puzzle as far as it got. True with it if a limit set in the config cut the solve short.
+#[cfg(feature = "library")]
+pub(crate) fn solve_fully(mut puzzle: Puzzle) -> (Puzzle, bool) {
+    let solved = match puzzle.solve() {
+        Ok(()) if puzzle.status() == PuzzleStatus::Unsolved => {
+            solve_with_guesses(puzzle.clone()).map(|solved| solved.unwrap_or(puzzle))
+        }
+        Ok(()) => Ok(puzzle),
+        Err(error) => Err(error),
+    };
+    let (puzzle, exceeded) = match solved {
+        Ok(puzzle) => (puzzle, false),
+        Err(SolveError::BudgetExceeded { progress, .. }) => (*progress, true),
+    };
+    (puzzle, exceeded)
+}
+
+// Each technique the solve used once, in the order first used, with any guessing as `guess`.
+pub(crate) fn techniques_used(puzzle: &Puzzle) -> Vec<String> {
+    let mut techniques: Vec<String> = Vec::new();
+    for consolidation in puzzle
+        .steps
+        .iter()
+        .flat_map(|step| step.consolidations.iter())
+    {
+        let name = consolidation
+            .technique()
+            .as_ref()
+            .map_or("guess", Technique::name)
+            .to_string();
+        if !techniques.contains(&name) {
+            techniques.push(name);
+        }
+    }
+    techniques
+}
+
+impl Batch {
+    // A line per puzzle after a header: `puzzle,solution,status,techniques`, where status is `solved`, `unsolved`, `ill-defined`, `budget-exceeded` if the solve hit a limit set in the config, or `invalid` for a line that isn't a puzzle, and the techniques are separated by spaces.
+    pub fn csv(&self) -> String {
+        let mut csv = CSV_HEADER.to_string();
+        for result in self.results.iter() {
+            push_row(&mut csv, result);
+        }
+        csv
+    }
+
+    // How many puzzles ended up with each status, e.g. "100 puzzles: 96 solved (12 by guessing), 1 unsolved, 1 ill-defined, 1 budget-exceeded, 1 invalid".
+    pub fn summary(&self) -> String {
+        let mut tally = Tally::default();
+        for result in self.results.iter() {
+            tally.add(result);
+        }
+        tally.summary()
+    }
+
+    // Time spent in each technique over the whole batch, in the order first tried, to see where a batch's time goes.
+    pub fn technique_times(&self) -> Vec<(Technique, Duration)> {
+        let mut times = vec![];
+        for &(technique, time) in self.results.iter().flat_map(|r| r.technique_times.iter()) {
+            add_time(&mut times, technique, time);
+        }
+        times
+    }
+}
+
+pub(super) const CSV_HEADER: &str = "puzzle,solution,status,techniques\n";
+
+// Add the result's line of CSV, as `Batch::csv()` writes it.
+pub(super) fn push_row(csv: &mut String, result: &BatchResult) {
+    csv.push_str(&result.puzzle);
+    csv.push(',');
+    csv.push_str(&result.solution);
+    csv.push(',');
+    csv.push_str(&result.status);
+    csv.push(',');
+    csv.push_str(&result.techniques.join(" "));
+    csv.push('\n');
+}
+
+/// How many puzzles of a batch ended up with each status, counted as they're solved.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub solved: usize,
+    // Those of the solved puzzles that took guessing
+    pub guessed: usize,
+    pub unsolved: usize,
+    pub ill_defined: usize,
+    pub budget_exceeded: usize,
+    pub invalid: usize,
+}
+
+impl Tally {
+    pub(super) fn add(&mut self, result: &BatchResult) {
+        match result.status.as_str() {
+            "solved" => {
+                self.solved += 1;
+                if result.techniques.iter().any(|t| t == "guess") {
+                    self.guessed += 1;
+                }
+            }
+            "unsolved" => self.unsolved += 1,
+            "ill-defined" => self.ill_defined += 1,
+            "budget-exceeded" => self.budget_exceeded += 1,
+            _ => self.invalid += 1,
+        }
+    }
+
+    pub(super) fn merge(&mut self, other: &Tally) {
+        self.solved += other.solved;
+        self.guessed += other.guessed;
+        self.unsolved += other.unsolved;
+        self.ill_defined += other.ill_defined;
+        self.budget_exceeded += other.budget_exceeded;
+        self.invalid += other.invalid;
+    }
+
+    // Every puzzle counted, whatever became of it.
+    pub fn total(&self) -> usize {
+        self.solved + self.unsolved + self.ill_defined + self.budget_exceeded + self.invalid
+    }
+
+    // As `Batch::summary()` puts it.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} puzzles: {} solved ({} by guessing), {} unsolved, {} ill-defined, {} budget-exceeded, {} invalid",
+            self.total(),
+            self.solved,
+            self.guessed,
+            self.unsolved,
+            self.ill_defined,
+            self.budget_exceeded,
+            self.invalid
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{SolverConfig, Technique};
+    use super::{batch, batch_with_progress};
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+    const EASY_SOLVED: &str =
+        "341562789768934152952718364574291638189346275623875491215687943496153827837429516";
+
+    #[test]
+    fn batch_csv() {
+        let input = format!("# A comment\n{}\n\n44{}\n123\n", EASY, &EASY[2..]);
+        let batch = batch(&input, &SolverConfig::default());
+        let csv = batch.csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(4, lines.len());
+        assert_eq!("puzzle,solution,status,techniques", lines[0]);
+        assert!(lines[1].starts_with(&format!("{},{},solved,", EASY, EASY_SOLVED)));
+        assert!(lines[1].contains("naked-singles"));
+        assert!(!lines[1].contains("guess"));
+        assert!(lines[2].contains(",ill-defined,"));
+        assert_eq!("123,,invalid,", lines[3]);
+
+        assert_eq!(
+            "3 puzzles: 1 solved (0 by guessing), 0 unsolved, 1 ill-defined, 0 budget-exceeded, 1 invalid",
+            batch.summary()
+        );
+
+        let mut reported = vec![];
+        batch_with_progress(&input, &SolverConfig::default(), |progress| {
+            reported.push((progress.done, progress.total))
+        });
+        assert_eq!(vec![(1, 3), (2, 3), (3, 3)], reported);
+
+        let tried: Vec<Technique> = batch.technique_times().iter().map(|&(t, _)| t).collect();
+        assert!(tried.contains(&Technique::NakedSingle));
+        // Each line is read into the puzzle the last was solved in, yet solved as if alone
+        let again = format!("{}\n44{}\n{}\n", EASY, &EASY[2..], EASY);
+        let again = super::batch(&again, &SolverConfig::default()).csv();
+        let lines: Vec<&str> = again.lines().collect();
+        assert_eq!(lines[1], lines[3]);
+    }
+}
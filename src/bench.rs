@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use super::batch::{batch_with_progress, Batch};
+use super::progress::Progress;
+use super::SolverConfig;
+
+/// A public list of puzzles, one to a line, that solvers are commonly measured against.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dataset {
+    pub name: &'static str,
+    pub url: &'static str,
+}
+
+// The classic lists from Peter Norvig's essay on solving every sudoku: 95 hard puzzles, and 11 put forward as the hardest.
+pub const DATASETS: [Dataset; 2] = [
+    Dataset {
+        name: "top95",
+        url: "https://norvig.com/top95.txt",
+    },
+    Dataset {
+        name: "hardest",
+        url: "https://norvig.com/hardest.txt",
+    },
+];
+
+impl Dataset {
+    // The dataset of that name, e.g. "top95".
+    pub fn find(name: &str) -> Option<Dataset> {
+        DATASETS.into_iter().find(|d| d.name == name)
+    }
+
+    // The file it's kept in once downloaded, e.g. "top95.txt".
+    pub fn file_name(&self) -> String {
+        format!("{}.txt", self.name)
+    }
+}
+
+/// How the solver fared on each of a set of puzzle lists, to quantify an improvement against a public baseline.
+pub struct Benchmark {
+    scores: Vec<Score>,
+}
+
+struct Score {
+    name: String,
+    // The puzzles that could be read, those of them solved without guessing, and every guess made across them all
+    puzzles: usize,
+    logical: usize,
+    guesses: usize,
+    most_guesses: usize,
+    elapsed: Duration,
+}
+
+impl Score {
+    fn of(name: &str, batch: &Batch) -> Score {
+        let results: Vec<_> = batch
+            .results
+            .iter()
+            .filter(|r| r.status != "invalid")
+            .collect();
+
+        Score {
+            name: name.to_string(),
+            puzzles: results.len(),
+            logical: results
+                .iter()
+                .filter(|r| r.status == "solved" && !r.techniques.iter().any(|t| t == "guess"))
+                .count(),
+            guesses: results.iter().map(|r| r.guesses).sum(),
+            most_guesses: results.iter().map(|r| r.guesses).max().unwrap_or(0),
+            elapsed: results.iter().map(|r| r.elapsed).sum(),
+        }
+    }
+}
+
+// Solve every puzzle of each named list, given a line to a puzzle as for `batch()`, guessing where the config allows once the techniques are stuck.
+pub fn benchmark(lists: &[(&str, &str)], config: &SolverConfig) -> Benchmark {
+    benchmark_with_progress(lists, config, |_, _| {})
+}
+
+// As `benchmark()`, telling the callback how far it's got through each list, by name, after each puzzle.
+pub fn benchmark_with_progress(
+    lists: &[(&str, &str)],
+    config: &SolverConfig,
+    mut progress: impl FnMut(&str, Progress),
+) -> Benchmark {
+    let scores = lists
+        .iter()
+        .map(|&(name, input)| {
+            let batch = batch_with_progress(input, config, |p| progress(name, p));
+            Score::of(name, &batch)
+        })
+        .collect();
+
+    Benchmark { scores }
+}
+
+impl Benchmark {
+    // A line per list under a header, lined up: the puzzles solved without guessing and what share of them that is, the time taken on average a puzzle, and the guesses made on average and at most. E.g.
+    //
+    //    dataset  no guessing   rate  avg time  avg guesses  max guesses
+    //    top95          12/95  12.6%    3.1ms         41.2          512
+    pub fn table(&self) -> String {
+        let name_width = self
+            .scores
+            .iter()
+            .map(|s| s.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("dataset".len());
+
+        let mut table = format!(
+            "{:<width$}  {:>11}  {:>6}  {:>9}  {:>11}  {:>11}\n",
+            "dataset",
+            "no guessing",
+            "rate",
+            "avg time",
+            "avg guesses",
+            "max guesses",
+            width = name_width
+        );
+        for score in self.scores.iter() {
+            let puzzles = score.puzzles.max(1);
+            table.push_str(&format!(
+                "{:<width$}  {:>11}  {:>5.1}%  {:>9}  {:>11.1}  {:>11}\n",
+                score.name,
+                format!("{}/{}", score.logical, score.puzzles),
+                100.0 * score.logical as f64 / puzzles as f64,
+                format!("{:.1?}", score.elapsed / puzzles as u32),
+                score.guesses as f64 / puzzles as f64,
+                score.most_guesses,
+                width = name_width
+            ));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{SolverConfig, Technique};
+    use super::{benchmark, Dataset};
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+    const EXPERT: &str =
+        "....8.4.99......626.........7.....1315..4.....3..9......8....45.....17....2..3...";
+
+    #[test]
+    fn datasets() {
+        assert_eq!("top95.txt", Dataset::find("top95").unwrap().file_name());
+        assert!(Dataset::find("hardest")
+            .unwrap()
+            .url
+            .ends_with("hardest.txt"));
+        assert_eq!(None, Dataset::find("easiest"));
+    }
+
+    #[test]
+    fn benchmarks() {
+        let both = format!("{}\nnot a puzzle\n{}\n", EASY, EXPERT);
+        let config = SolverConfig::with_techniques(vec![Technique::NakedSingle]);
+        let table = benchmark(&[("easy", EASY), ("both", &both)], &config).table();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("dataset  no guessing    rate   avg time  avg guesses"));
+        // Naked singles alone only solve the easy one, and the expert one takes guessing
+        assert!(lines[1].starts_with("easy             1/1  100.0%"));
+        assert!(lines[1].ends_with("0.0            0"));
+        assert!(lines[2].starts_with("both             1/2   50.0%"));
+        assert!(!lines[2].ends_with("0.0            0"));
+    }
+}
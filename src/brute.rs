@@ -0,0 +1,336 @@
+use std::sync::Arc;
+
+use super::{Candidates, Constraint, Coord, Puzzle, Shape, MAX_SIZE};
+
+// A plain backtracking search over the numbers alone, ignoring candidates and techniques. It's no help explaining a solve, but is quick to tell whether a puzzle has no solution, just the one, or many. It fills in the cell with the fewest numbers left first, keeping each row, column, block, and diagonal's numbers as bits. It isn't dancing links: exact cover only has room for every digit once in every house, not for sums, lines, or marks, nor for house rules like anti-knight, whereas a backtracker checks those as it places each number, and it's quick enough that there's been no call for both.
+impl Puzzle {
+    // How many solutions the numbers placed so far allow, counting no further than `limit`. Nought if they already break the rules, as a number repeated in a row does.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.search(limit).len()
+    }
+
+    // Up to `limit` different solutions of the puzzle, each with every cell filled in. Numbers filled in aren't givens, so the givens of the puzzle stand out as ever. Broken puzzles show how many ways they go wrong, and puzzles being made can be checked to have just the one.
+    pub fn solutions(&self, limit: usize) -> Vec<Puzzle> {
+        self.search(limit)
+            .iter()
+            .map(|numbers| {
+                let mut grid = self.grid;
+                for (coord, &number) in self.shape.coords().zip(numbers.iter()) {
+                    let cell = &mut grid[coord.row][coord.col];
+                    cell.number = Some(number);
+                    cell.candidates = Candidates::none();
+                }
+                Puzzle {
+                    clues: Arc::clone(&self.clues),
+                    ..Puzzle::with_grid(self.shape, grid)
+                }
+            })
+            .collect()
+    }
+
+    fn search(&self, limit: usize) -> Vec<Cells> {
+        let mut found = vec![];
+        if let Some(mut search) = Search::new(self) {
+            search.run(limit, &mut found);
+        }
+        found
+    }
+}
+
+// The numbers of every cell, row by row, with 0 for a blank.
+type Cells = [u8; MAX_SIZE * MAX_SIZE];
+
+// The grid as the search fills it in, with the numbers used in each row, column, block, and diagonal as bits 1 thru the size.
+struct Search<'a> {
+    shape: Shape,
+    // The puzzle's clues and house rules, to narrow what each cell's allowed
+    constraints: Vec<&'a dyn Constraint>,
+    // The bits of every digit
+    all: u32,
+    cells: Cells,
+    rows: [u32; MAX_SIZE],
+    columns: [u32; MAX_SIZE],
+    blocks: [u32; MAX_SIZE],
+    // Only ever used in X-Sudoku
+    diagonals: [u32; 2],
+    // The puzzle filled in as far as the search, for its house rules to check and prune, if it has any
+    ruled: Option<Puzzle>,
+}
+
+impl<'a> Search<'a> {
+    // None if a number repeats in a row, column, block, or diagonal.
+    fn new(puzzle: &'a Puzzle) -> Option<Search<'a>> {
+        let shape = puzzle.shape;
+        let mut search = Search {
+            shape,
+            constraints: puzzle.constraints().collect(),
+            all: (1 << (shape.size() + 1)) - 2,
+            cells: [0; MAX_SIZE * MAX_SIZE],
+            rows: [0; MAX_SIZE],
+            columns: [0; MAX_SIZE],
+            blocks: [0; MAX_SIZE],
+            diagonals: [0; 2],
+            ruled: match puzzle.config.house_rules.is_empty() {
+                true => None,
+                false => {
+                    let mut ruled = puzzle.clone();
+                    ruled.config.verbose = false;
+                    Some(ruled)
+                }
+            },
+        };
+
+        for (i, coord) in shape.coords().enumerate() {
+            if let Some(number) = puzzle[coord].number {
+                if search.options(i) & (1 << number) == 0 {
+                    return None;
+                }
+                search.place(i, number);
+            }
+        }
+
+        Some(search)
+    }
+
+    // The row, column, and block of the i-th cell.
+    fn units(&self, i: usize) -> (usize, usize, usize) {
+        let size = self.shape.size();
+        let (row, col) = (i / size, i % size);
+        (row, col, self.shape.block_of(Coord::new(row, col)))
+    }
+
+    // The diagonals the i-th cell's on, if they're units.
+    fn diagonals(&self, i: usize) -> impl Iterator<Item = usize> {
+        let size = self.shape.size();
+        self.shape.diagonals_of(Coord::new(i / size, i % size))
+    }
+
+    fn options(&self, i: usize) -> u32 {
+        let (row, col, block) = self.units(i);
+        let used = self.diagonals(i).fold(
+            self.rows[row] | self.columns[col] | self.blocks[block],
+            |used, d| used | self.diagonals[d],
+        );
+        self.all & !used
+    }
+
+    fn place(&mut self, i: usize, number: u8) {
+        let (row, col, block) = self.units(i);
+        self.cells[i] = number;
+        self.rows[row] |= 1 << number;
+        self.columns[col] |= 1 << number;
+        self.blocks[block] |= 1 << number;
+        for d in self.diagonals(i) {
+            self.diagonals[d] |= 1 << number;
+        }
+        if let Some(ruled) = &mut self.ruled {
+            ruled[Coord::new(row, col)].number = Some(number);
+        }
+    }
+
+    fn unplace(&mut self, i: usize) {
+        let (row, col, block) = self.units(i);
+        let bit = !(1 << self.cells[i]);
+        self.cells[i] = 0;
+        self.rows[row] &= bit;
+        self.columns[col] &= bit;
+        self.blocks[block] &= bit;
+        for d in self.diagonals(i) {
+            self.diagonals[d] &= bit;
+        }
+        if let Some(ruled) = &mut self.ruled {
+            ruled[Coord::new(row, col)].number = None;
+        }
+    }
+
+    // The i-th cell as the digits it could be, a number as itself alone.
+    fn candidates(&self, i: usize) -> Candidates {
+        match self.cells[i] {
+            0 => Candidates::from_bits(self.options(i)),
+            number => [number].into_iter().collect(),
+        }
+    }
+
+    // What the puzzle's constraints still allow each cell, every digit where none says. None if a sandwich, arrow, or pair of cells side by side can't be made to add up, a line can't keep to its rule, or a cell is left with nothing.
+    fn sums(&self) -> Option<[u32; MAX_SIZE * MAX_SIZE]> {
+        let mut allowed = [u32::MAX; MAX_SIZE * MAX_SIZE];
+        let size = self.shape.size();
+        let cell = |coord: Coord| self.candidates(coord.row * size + coord.col);
+
+        for constraint in &self.constraints {
+            for (coord, fit) in constraint.fits(self.shape, &cell) {
+                let i = coord.row * size + coord.col;
+                allowed[i] &= fit.bits();
+                if allowed[i] == 0 {
+                    return None;
+                }
+            }
+        }
+
+        Some(allowed)
+    }
+
+    // Narrow what each cell's allowed to what the house rules still allow, given what the rows, columns, blocks, and the rest allow, as the rules prune it until they can't any more. False if they're broken, or leave a cell with nothing.
+    fn ruled(&mut self, allowed: &mut [u32; MAX_SIZE * MAX_SIZE]) -> bool {
+        let blanks: Vec<(usize, Coord, u32)> = self
+            .shape
+            .coords()
+            .enumerate()
+            .filter(|&(i, _)| self.cells[i] == 0)
+            .map(|(i, coord)| (i, coord, self.options(i) & allowed[i]))
+            .collect();
+        let Some(ruled) = &mut self.ruled else {
+            return true;
+        };
+        let rules = ruled.config.house_rules.clone();
+        if rules.iter().any(|rule| rule.check(ruled).is_some()) {
+            return false;
+        }
+
+        for &(_, coord, options) in &blanks {
+            ruled[coord].candidates = Candidates::from_bits(options);
+        }
+        while rules.iter().any(|rule| !rule.prune(ruled).is_empty()) {}
+
+        for (i, coord, _) in blanks {
+            allowed[i] = ruled[coord].candidates.bits();
+            if allowed[i] == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    // A cell that's the only place left for a digit in one of its units, and the digit's bit, to fill in before the cell with the fewest options. House rules leave a search on numbers alone many more dead ends to find, and this finds them sooner. None if a digit has no place left in a unit.
+    fn hidden_single(&self, allowed: &[u32; MAX_SIZE * MAX_SIZE]) -> Option<Option<(usize, u32)>> {
+        let size = self.shape.size();
+        let mut single = None;
+        for unit in self.shape.units() {
+            let (mut once, mut more) = (0, 0);
+            let mut placed = 0;
+            for (row, col) in unit.cells(self.shape) {
+                let i = row * size + col;
+                match self.cells[i] {
+                    0 => {
+                        let options = self.options(i) & allowed[i];
+                        more |= once & options;
+                        once |= options;
+                    }
+                    number => placed |= 1 << number,
+                }
+            }
+            if (once | placed) != self.all {
+                return None;
+            }
+            let lone = once & !more & !placed;
+            if single.is_none() && lone != 0 {
+                let bit = lone & lone.wrapping_neg();
+                let i = unit
+                    .cells(self.shape)
+                    .into_iter()
+                    .map(|(row, col)| row * size + col)
+                    .find(|&i| self.cells[i] == 0 && (self.options(i) & allowed[i]) & bit != 0)?;
+                single = Some((i, bit));
+            }
+        }
+        Some(single)
+    }
+
+    // Fill in the cell with the fewest options first, trying each in turn, until `limit` solutions are found.
+    fn run(&mut self, limit: usize, found: &mut Vec<Cells>) {
+        let mut allowed = if self.constraints.is_empty() {
+            [u32::MAX; MAX_SIZE * MAX_SIZE]
+        } else {
+            match self.sums() {
+                Some(allowed) => allowed,
+                None => return,
+            }
+        };
+        if self.ruled.is_some() && !self.ruled(&mut allowed) {
+            return;
+        }
+
+        let mut best = None;
+        let mut fewest = u32::MAX;
+        for i in (0..self.shape.cell_count()).filter(|&i| self.cells[i] == 0) {
+            let count = (self.options(i) & allowed[i]).count_ones();
+            if count < fewest {
+                best = Some(i);
+                fewest = count;
+                if count <= 1 {
+                    break;
+                }
+            }
+        }
+
+        let mut i = match best {
+            Some(i) => i,
+            None => {
+                found.push(self.cells);
+                return;
+            }
+        };
+
+        let mut options = self.options(i) & allowed[i];
+        if fewest > 1 && self.ruled.is_some() {
+            match self.hidden_single(&allowed) {
+                Some(Some((j, bit))) => (i, options) = (j, bit),
+                Some(None) => {}
+                None => return,
+            }
+        }
+        for number in 1..=self.shape.size() as u8 {
+            if options & (1 << number) == 0 {
+                continue;
+            }
+            self.place(i, number);
+            self.run(limit, found);
+            self.unplace(i);
+            if found.len() >= limit {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+
+    #[test]
+    fn count_solutions() {
+        let easy = Puzzle::parse(include_str!("../samples/easy.txt"));
+        assert_eq!(1, easy.count_solutions(2));
+        let evil = Puzzle::parse(include_str!("../samples/evil.txt"));
+        assert_eq!(1, evil.count_solutions(2));
+
+        let empty = Puzzle::parse("");
+        assert_eq!(2, empty.count_solutions(2));
+        assert_eq!(10, empty.count_solutions(10));
+
+        let clashing = Puzzle::parse("11");
+        assert_eq!(0, clashing.count_solutions(2));
+    }
+
+    #[test]
+    fn solutions() {
+        let easy = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let solutions = easy.solutions(5);
+        assert_eq!(1, solutions.len());
+        assert!(solutions[0].verify(&easy).is_empty());
+        assert_eq!(36, solutions[0].givens().len());
+
+        // Without its first row the easy puzzle has three, all found though more were asked for
+        let mut lines: Vec<&str> = include_str!("../samples/easy.txt").lines().collect();
+        lines[0] = ".........";
+        let broken = Puzzle::parse(&lines.join("\n"));
+        let solutions = broken.solutions(5);
+        assert_eq!(3, solutions.len());
+        for (i, solution) in solutions.iter().enumerate() {
+            assert!(solution.verify(&broken).is_empty());
+            assert!(solutions[..i]
+                .iter()
+                .all(|other| other.grid != solution.grid));
+        }
+    }
+}
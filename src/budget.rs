@@ -0,0 +1,231 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::pipeline::add_time;
+use super::{Instant, Puzzle, SolverConfig, Technique};
+
+/// A limit set in a `SolverConfig` on how much work a solve may do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    Iterations(usize),
+    GuessNodes(usize),
+    WallClock(Duration),
+}
+
+/// Why a solve was given up on.
+#[derive(Clone, Debug)]
+pub enum SolveError {
+    // The solve hit the limit. The puzzle is as far as it got: for a search of guesses, the branch being searched at the time, guesses and all.
+    BudgetExceeded { limit: Limit, progress: Box<Puzzle> },
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Iterations(n) => write!(f, "{} steps", n),
+            Limit::GuessNodes(n) => write!(f, "{} guesses", n),
+            Limit::WallClock(timeout) => write!(f, "{:?}", timeout),
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::BudgetExceeded { limit, .. } => write!(f, "Gave up after {}", limit),
+        }
+    }
+}
+
+// The limits of a solve as it goes on, shared by every branch of a search of guesses.
+pub(super) struct Budget {
+    max_iterations: Option<usize>,
+    max_guess_nodes: Option<usize>,
+    timeout: Option<(Duration, Instant)>,
+    max_guess_depth: Option<usize>,
+    guesses: AtomicUsize,
+
+    // How many guesses have been made at each depth, shallowest first
+    by_depth: Mutex<Vec<usize>>,
+
+    // Time spent in each technique on top of the guesses, in the order first tried
+    technique_times: Mutex<Vec<(Technique, Duration)>>,
+
+    // Set once a branch of the search solves the puzzle or runs out of budget, calling off the others
+    pub(super) stop: AtomicBool,
+}
+
+impl Budget {
+    // The clock starts now.
+    pub(super) fn new(config: &SolverConfig) -> Budget {
+        Budget {
+            max_iterations: config.max_iterations,
+            max_guess_nodes: config.max_guess_nodes,
+            // A timeout too far off for the clock to reach is no deadline at all
+            timeout: config
+                .wall_clock_timeout
+                .and_then(|timeout| Some((timeout, Instant::now().checked_add(timeout)?))),
+            max_guess_depth: config.max_guess_depth,
+            guesses: AtomicUsize::new(0),
+            by_depth: Mutex::new(vec![]),
+            technique_times: Mutex::new(vec![]),
+            stop: AtomicBool::new(false),
+        }
+    }
+
+    // Before each step: whether the puzzle has taken as many steps as it may, or time's up.
+    pub(super) fn check_step(&self, puzzle: &Puzzle) -> Result<(), SolveError> {
+        if let Some(max) = self.max_iterations {
+            if puzzle.iteration >= max {
+                return Err(exceeded(Limit::Iterations(max), puzzle));
+            }
+        }
+        self.check_clock(puzzle)
+    }
+
+    // Before each guess: whether as many guesses have been made as may be, across every branch, or time's up. Otherwise the guess is counted.
+    pub(super) fn check_guess(&self, puzzle: &Puzzle, depth: usize) -> Result<(), SolveError> {
+        let guesses = self.guesses.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(max) = self.max_guess_nodes {
+            if guesses > max {
+                return Err(exceeded(Limit::GuessNodes(max), puzzle));
+            }
+        }
+        self.check_clock(puzzle)?;
+
+        let mut by_depth = self.by_depth.lock().unwrap();
+        if by_depth.len() < depth {
+            by_depth.resize(depth, 0);
+        }
+        by_depth[depth - 1] += 1;
+        Ok(())
+    }
+
+    // Whether guesses may be made at the depth, i.e. on top of one fewer others.
+    pub(super) fn within_depth(&self, depth: usize) -> bool {
+        self.max_guess_depth.is_none_or(|max| depth <= max)
+    }
+
+    pub(super) fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|(_, deadline)| deadline)
+    }
+
+    pub(super) fn guesses_by_depth(&self) -> Vec<usize> {
+        self.by_depth.lock().unwrap().clone()
+    }
+
+    pub(super) fn add_technique_times(&self, times: &[(Technique, Duration)]) {
+        let mut technique_times = self.technique_times.lock().unwrap();
+        for &(technique, time) in times {
+            add_time(&mut technique_times, technique, time);
+        }
+    }
+
+    pub(super) fn technique_times(&self) -> Vec<(Technique, Duration)> {
+        self.technique_times.lock().unwrap().clone()
+    }
+
+    // Whether time's up.
+    pub(super) fn check_clock(&self, puzzle: &Puzzle) -> Result<(), SolveError> {
+        match self.timeout {
+            Some((timeout, deadline)) if Instant::now() >= deadline => {
+                Err(exceeded(Limit::WallClock(timeout), puzzle))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn exceeded(limit: Limit, puzzle: &Puzzle) -> SolveError {
+    // The progress is free to be solved further, with no clock of its own
+    let mut progress = puzzle.clone();
+    progress.deadline = None;
+    SolveError::BudgetExceeded {
+        limit,
+        progress: Box::new(progress),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{solve_with_guesses, Puzzle, SolverConfig, Technique};
+    use super::{Limit, SolveError};
+    use std::time::{Duration, Instant};
+
+    const EVIL: &str = include_str!("../samples/evil.txt");
+
+    // Evil can't be solved with singles alone, so comes to guessing
+    fn evil(config: SolverConfig) -> Puzzle {
+        let mut puzzle = Puzzle::parse(EVIL);
+        puzzle.set_config(SolverConfig {
+            techniques: vec![Technique::NakedSingle, Technique::HiddenSingle],
+            ..config
+        });
+        puzzle
+    }
+
+    #[test]
+    fn budgets() {
+        let mut puzzle = evil(SolverConfig {
+            max_iterations: Some(2),
+            ..SolverConfig::default()
+        });
+        match puzzle.solve() {
+            Err(SolveError::BudgetExceeded { limit, progress }) => {
+                assert_eq!(Limit::Iterations(2), limit);
+                assert_eq!(2, progress.iteration);
+                assert_eq!(2, progress.steps.len());
+            }
+            Ok(()) => panic!("Expected to run out of steps"),
+        }
+
+        let mut puzzle = evil(SolverConfig {
+            max_guess_nodes: Some(1),
+            ..SolverConfig::default()
+        });
+        puzzle.solve().unwrap();
+        let error = solve_with_guesses(puzzle.clone()).unwrap_err();
+        assert_eq!("Gave up after 1 guesses", error.to_string());
+
+        // Plenty of guesses still solves it
+        puzzle.config.max_guess_nodes = Some(1000);
+        assert!(solve_with_guesses(puzzle).unwrap().is_some());
+
+        let mut puzzle = evil(SolverConfig {
+            wall_clock_timeout: Some(Duration::ZERO),
+            ..SolverConfig::default()
+        });
+        let SolveError::BudgetExceeded { limit, progress } = puzzle.solve().unwrap_err();
+        assert_eq!(Limit::WallClock(Duration::ZERO), limit);
+        assert_eq!(0, progress.iteration);
+    }
+
+    #[test]
+    fn gives_up_on_time() {
+        // The techniques take the best part of a minute over a blank 16x16 grid, the longer searches most of it
+        let mut puzzle = Puzzle::parse(&".".repeat(256));
+        puzzle.set_config(SolverConfig {
+            wall_clock_timeout: Some(Duration::from_secs(1)),
+            ..SolverConfig::default()
+        });
+        let started = Instant::now();
+        let SolveError::BudgetExceeded { limit, progress } = puzzle.solve().unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert_eq!(Limit::WallClock(Duration::from_secs(1)), limit);
+
+        // The progress has no clock of its own left
+        assert_eq!(None, progress.deadline);
+    }
+
+    #[test]
+    fn never_runs_out_of_a_huge_timeout() {
+        let mut puzzle = evil(SolverConfig {
+            wall_clock_timeout: Some(Duration::from_secs(u64::MAX)),
+            ..SolverConfig::default()
+        });
+        puzzle.solve().unwrap();
+        assert!(solve_with_guesses(puzzle).unwrap().is_some());
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use super::MAX_SIZE;
+
+/// A set of digits, as a cell's candidates are kept: 1 thru 9 in a standard grid, and up to the size of any other.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Candidates(u32);
+
+// Bit `d` is set for each digit `d` held
+const MAX_DIGIT: u8 = MAX_SIZE as u8;
+
+impl Candidates {
+    pub fn none() -> Candidates {
+        Candidates(0)
+    }
+
+    pub fn contains(&self, digit: u8) -> bool {
+        (1..=MAX_DIGIT).contains(&digit) && self.0 & (1 << digit) != 0
+    }
+
+    pub fn insert(&mut self, digit: u8) {
+        assert!(
+            (1..=MAX_DIGIT).contains(&digit),
+            "Invalid candidate: {}",
+            digit
+        );
+        self.0 |= 1 << digit;
+    }
+
+    // Returns true if the digit was a candidate.
+    pub fn remove(&mut self, digit: u8) -> bool {
+        let held = self.contains(digit);
+        if held {
+            self.0 &= !(1 << digit);
+        }
+        held
+    }
+
+    pub fn union(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & other.0)
+    }
+
+    // The digits held here but not in the other.
+    pub fn difference(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & !other.0)
+    }
+
+    pub fn is_subset(&self, other: Candidates) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // The digit, if it's the only one.
+    pub fn single(&self) -> Option<u8> {
+        if self.count() == 1 {
+            Some(self.0.trailing_zeros() as u8)
+        } else {
+            None
+        }
+    }
+
+    // The digits in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> {
+        let bits = self.0;
+        (1..=MAX_DIGIT).filter(move |d| bits & (1 << d) != 0)
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.iter().collect()
+    }
+
+    // The digits as bits, as the brute force search keeps them.
+    pub(crate) fn from_bits(bits: u32) -> Candidates {
+        Candidates(bits)
+    }
+
+    pub(crate) fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+impl FromIterator<u8> for Candidates {
+    fn from_iter<I: IntoIterator<Item = u8>>(digits: I) -> Candidates {
+        let mut candidates = Candidates::none();
+        for digit in digits {
+            candidates.insert(digit);
+        }
+        candidates
+    }
+}
+
+impl<'a> FromIterator<&'a u8> for Candidates {
+    fn from_iter<I: IntoIterator<Item = &'a u8>>(digits: I) -> Candidates {
+        digits.into_iter().copied().collect()
+    }
+}
+
+// As a list, e.g. `[2, 5, 7]`
+impl fmt::Debug for Candidates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Candidates;
+
+    #[test]
+    fn set_algebra() {
+        let a: Candidates = [1, 3, 5, 7].iter().collect();
+        let b: Candidates = [3, 4, 5].iter().collect();
+
+        assert_eq!(vec![1, 3, 4, 5, 7], a.union(b).to_vec());
+        assert_eq!(vec![3, 5], a.intersection(b).to_vec());
+        assert_eq!(vec![1, 7], a.difference(b).to_vec());
+        assert!(a.intersection(b).is_subset(a));
+        assert!(!a.is_subset(b));
+
+        assert_eq!(4, a.count());
+        assert_eq!(16, (1..=16).collect::<Candidates>().count());
+        assert!(Candidates::none().is_empty());
+        assert_eq!(None, a.single());
+        assert_eq!(Some(4), b.difference(a).single());
+        assert_eq!("[1, 3, 5, 7]", format!("{:?}", a));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut candidates = Candidates::none();
+        candidates.insert(16);
+        candidates.insert(9);
+        candidates.insert(2);
+        assert!(candidates.contains(9));
+        assert!(!candidates.contains(0));
+        assert!(!candidates.contains(3));
+        assert!(!candidates.contains(17));
+        assert!(candidates.remove(16));
+
+        assert!(candidates.remove(9));
+        assert!(!candidates.remove(9));
+        assert_eq!(Some(2), candidates.single());
+    }
+}
@@ -0,0 +1,153 @@
+use super::engine::position;
+use super::{Puzzle, Shape};
+
+// The ways of ordering three things
+const ORDERS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+impl Puzzle {
+    // The puzzle written the one way every puzzle like it is: the same for any two puzzles that differ only by relabelling the digits, swapping rows within a band or the bands themselves, likewise for columns and stacks, or reflecting along the diagonal. Rotations and other reflections are made of those. Written as 81 characters, `.` for blanks, it's the least of all the ways of writing it, with digits numbered in the order they first appear. Bigger grids have far too many ways of writing them to try each, and a variant's clues don't move with the digits, so both are written just as they are.
+    pub fn canonical(&self) -> String {
+        if self.shape != Shape::STANDARD || !self.clues.is_empty() {
+            return position(self);
+        }
+
+        let mut numbers = [[0u8; 9]; 9];
+        for (row, cells) in self.rows().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                numbers[row][col] = cell.number.unwrap_or(0);
+            }
+        }
+
+        let mut best: Option<[u8; 81]> = None;
+        for transposed in [false, true] {
+            for stacks in ORDERS {
+                for a in ORDERS {
+                    for b in ORDERS {
+                        for c in ORDERS {
+                            let within = [a, b, c];
+                            let mut columns = [[0u8; 9]; 9];
+                            for row in 0..9 {
+                                for (col, new) in (0..9).enumerate() {
+                                    let stack = stacks[new / 3];
+                                    let old = stack * 3 + within[stack][col % 3];
+                                    columns[row][new] = if transposed {
+                                        numbers[old][row]
+                                    } else {
+                                        numbers[row][old]
+                                    };
+                                }
+                            }
+
+                            let mut current = [0u8; 81];
+                            order_rows(&columns, &mut vec![], [0; 10], 1, &mut current, &mut best);
+                        }
+                    }
+                }
+            }
+        }
+
+        best.unwrap_or([0; 81])
+            .iter()
+            .map(|&n| if n == 0 { '.' } else { (b'0' + n) as char })
+            .collect()
+    }
+
+    // Whether the two are the same puzzle up to the symmetries of `canonical()`.
+    pub fn is_equivalent_to(&self, other: &Puzzle) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+// Try each order of the rows, keeping bands together, writing them out with digits relabelled in the order they appear, and keep the least. Orders are abandoned as soon as they start out greater than the least so far.
+fn order_rows(
+    grid: &[[u8; 9]; 9],
+    order: &mut Vec<usize>,
+    labels: [u8; 10],
+    next: u8,
+    current: &mut [u8; 81],
+    best: &mut Option<[u8; 81]>,
+) {
+    let k = order.len();
+    if k == 9 {
+        *best = Some(*current);
+        return;
+    }
+
+    // A band is begun with any row of a band not yet used, then finished with the rest of its rows
+    let rows: Vec<usize> = if k.is_multiple_of(3) {
+        (0..9)
+            .filter(|row| !order.iter().any(|used| used / 3 == row / 3))
+            .collect()
+    } else {
+        let band = order[k - 1] / 3;
+        (band * 3..band * 3 + 3)
+            .filter(|row| !order.contains(row))
+            .collect()
+    };
+
+    for row in rows {
+        let (mut labels, mut next) = (labels, next);
+        for col in 0..9 {
+            let n = grid[row][col];
+            if n != 0 && labels[n as usize] == 0 {
+                labels[n as usize] = next;
+                next += 1;
+            }
+            current[k * 9 + col] = labels[n as usize];
+        }
+
+        let end = (k + 1) * 9;
+        if best.is_some_and(|best| current[..end] > best[..end]) {
+            continue;
+        }
+
+        order.push(row);
+        order_rows(grid, order, labels, next, current, best);
+        order.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn equivalence() {
+        let easy = Puzzle::parse(EASY);
+        let canonical = easy.canonical();
+        assert_eq!(81, canonical.len());
+        assert_eq!(36, canonical.chars().filter(|&c| c != '.').count());
+
+        // Turned a quarter, with the digits swapped about, is still the same puzzle
+        let lines: Vec<Vec<char>> = EASY
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim().chars().collect())
+            .collect();
+        let turned: Vec<String> = (0..9)
+            .map(|row| {
+                (0..9)
+                    .map(|col| match lines[8 - col][row] {
+                        '.' => '.',
+                        n => (b'1' + (n as u8 - b'0') % 9) as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        let turned = Puzzle::parse(&turned.join("\n"));
+        assert!(easy.is_equivalent_to(&turned));
+        assert_eq!(canonical, turned.canonical());
+
+        let medium = Puzzle::parse(include_str!("../samples/medium.txt"));
+        assert!(!easy.is_equivalent_to(&medium));
+    }
+}
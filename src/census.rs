@@ -0,0 +1,104 @@
+use super::MAX_SIZE;
+
+// Counting the cells of a unit that hold each digit as a candidate, the scan the hidden singles make of every unit on every pass, given each cell's candidates as bits with nought for a cell filled in. With the `simd` feature on x86_64 the cells are counted four at a time with SSE2, which every x86_64 processor has, and a cell at a time otherwise. Either way gives the same counts, indexed by digit.
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+pub(crate) fn digit_counts(cells: &[u32]) -> [u8; MAX_SIZE + 1] {
+    scalar_counts(cells)
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub(crate) fn digit_counts(cells: &[u32]) -> [u8; MAX_SIZE + 1] {
+    sse2_counts(cells)
+}
+
+// The counts a cell at a time. Both ways are built whatever the features, so the census benchmark can weigh one against the other.
+pub fn scalar_counts(cells: &[u32]) -> [u8; MAX_SIZE + 1] {
+    count_each(cells, [0; MAX_SIZE + 1])
+}
+
+// The counts four cells at a time, and any left over a cell at a time.
+#[cfg(target_arch = "x86_64")]
+pub fn sse2_counts(cells: &[u32]) -> [u8; MAX_SIZE + 1] {
+    use std::arch::x86_64::{
+        __m128i, _mm_add_epi32, _mm_and_si128, _mm_cvtsi32_si128, _mm_loadu_si128, _mm_set1_epi32,
+        _mm_setzero_si128, _mm_srl_epi32, _mm_storeu_si128,
+    };
+
+    let digits = highest_digit(cells);
+    let chunks = cells.chunks_exact(4);
+    let rest = chunks.remainder();
+
+    let mut counts = [0; MAX_SIZE + 1];
+    // SAFETY: SSE2 is part of the x86_64 baseline, and the loads and stores are unaligned ones of four u32s, each within a chunk of four or the lanes array.
+    unsafe {
+        let one = _mm_set1_epi32(1);
+        let mut totals = [_mm_setzero_si128(); MAX_SIZE + 1];
+        for chunk in chunks {
+            let bits = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+            for (digit, total) in totals.iter_mut().enumerate().take(digits + 1).skip(1) {
+                let held = _mm_and_si128(_mm_srl_epi32(bits, _mm_cvtsi32_si128(digit as i32)), one);
+                *total = _mm_add_epi32(*total, held);
+            }
+        }
+        for (count, total) in counts.iter_mut().zip(totals.iter()).take(digits + 1) {
+            let mut lanes = [0u32; 4];
+            _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, *total);
+            *count = lanes.iter().sum::<u32>() as u8;
+        }
+    }
+
+    count_each(rest, counts)
+}
+
+// Add the digits of each cell, one cell at a time, to the counts so far.
+fn count_each(cells: &[u32], mut counts: [u8; MAX_SIZE + 1]) -> [u8; MAX_SIZE + 1] {
+    for &bits in cells {
+        let mut left = bits;
+        while left != 0 {
+            counts[left.trailing_zeros() as usize] += 1;
+            left &= left - 1;
+        }
+    }
+    counts
+}
+
+// The highest digit any of the cells hold, so a 9x9 grid's scans stop at 9.
+#[cfg(target_arch = "x86_64")]
+fn highest_digit(cells: &[u32]) -> usize {
+    let all = cells.iter().fold(0, |all, &bits| all | bits);
+    (u32::BITS - all.leading_zeros()).saturating_sub(1) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::rng::Rng;
+    use super::super::MAX_SIZE;
+    #[cfg(target_arch = "x86_64")]
+    use super::sse2_counts;
+    use super::{digit_counts, scalar_counts};
+
+    #[test]
+    fn counts() {
+        let cells = [0b10_0110, 0, 0b00_0100, 0b10_0000, 0b11_1110];
+        let counts = digit_counts(&cells);
+        assert_eq!([0, 2, 3, 1, 1, 3, 0], counts[..7]);
+        assert_eq!([0; MAX_SIZE + 1], digit_counts(&[]));
+
+        // Whole units of every size, against counting a digit at a time
+        let mut rng = Rng::new(2664);
+        for size in [4, 6, 9, 12, 16, 25] {
+            let all = (1u32 << (size + 1)) - 2;
+            let cells: Vec<u32> = (0..size).map(|_| rng.next_u64() as u32 & all).collect();
+            let counts = digit_counts(&cells);
+            #[cfg(target_arch = "x86_64")]
+            assert_eq!(scalar_counts(&cells), sse2_counts(&cells));
+            for (digit, &count) in counts.iter().enumerate() {
+                let held = cells
+                    .iter()
+                    .filter(|&&bits| bits & (1 << digit) != 0)
+                    .count();
+                assert_eq!(held, count as usize);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use super::{Coord, IllDefinedReason, Puzzle, Unit};
+
+// Something wrong with a grid handed in as the solution to a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mistake {
+    // A cell left without a number
+    Blank(Coord),
+    // A number used more than once in a row, column, or block, or a diagonal in X-Sudoku
+    Repeated(IllDefinedReason),
+    // A given of the puzzle changed to another number
+    GivenChanged { at: Coord, given: u8, found: u8 },
+    // A row or column whose digits between its 1 and 9 don't add up to its sandwich sum
+    Sandwich(Unit),
+    // An arrow whose digits don't add up to its circle's, given by its circle
+    Arrow(Coord),
+    // Two cells side by side breaking the X or V between them, or adding up to 5 or 10 without one under the negative constraint
+    Xv(Coord, Coord),
+    // A renban or whisper line breaking its rule, given by its start
+    Line(Coord),
+    // A house rule of the original puzzle's config broken, as the rule tells it
+    HouseRule(IllDefinedReason),
+}
+
+impl fmt::Display for Mistake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mistake::Blank(at) => write!(f, "{} is blank", at),
+            Mistake::Repeated(IllDefinedReason::NumberRepeatsInRow(n, row)) => {
+                write!(f, "{} is in row {} more than once", n, row + 1)
+            }
+            Mistake::Repeated(IllDefinedReason::NumberRepeatsInColumn(n, col)) => {
+                write!(f, "{} is in column {} more than once", n, col + 1)
+            }
+            Mistake::Repeated(IllDefinedReason::NumberRepeatsInBlock(n, b)) => {
+                write!(f, "{} is in block {} more than once", n, b + 1)
+            }
+            Mistake::Repeated(IllDefinedReason::NumberRepeatsInDiagonal(n, 0)) => {
+                write!(
+                    f,
+                    "{} is in the diagonal from the top left more than once",
+                    n
+                )
+            }
+            Mistake::Repeated(IllDefinedReason::NumberRepeatsInDiagonal(n, _)) => {
+                write!(
+                    f,
+                    "{} is in the diagonal from the top right more than once",
+                    n
+                )
+            }
+            Mistake::Repeated(reason) => write!(f, "{:?}", reason),
+            Mistake::GivenChanged { at, given, found } => {
+                write!(f, "{} should be the given {}, not {}", at, given, found)
+            }
+            Mistake::Sandwich(Unit::Row(row)) => {
+                write!(f, "The sandwich in row {} doesn't add up", row + 1)
+            }
+            Mistake::Sandwich(Unit::Column(col)) => {
+                write!(f, "The sandwich in column {} doesn't add up", col + 1)
+            }
+            Mistake::Sandwich(unit) => write!(f, "The sandwich in {:?} doesn't add up", unit),
+            Mistake::Arrow(circle) => write!(f, "The arrow from {} doesn't add up", circle),
+            Mistake::Xv(a, b) => write!(f, "{} and {} break the XV rule between them", a, b),
+            Mistake::Line(start) => write!(f, "The line from {} breaks its rule", start),
+            Mistake::HouseRule(IllDefinedReason::HouseRuleBroken(name, at)) => {
+                write!(f, "{} breaks the {} rule", Coord::from(*at), name)
+            }
+            Mistake::HouseRule(reason) => write!(f, "{:?}", reason),
+        }
+    }
+}
+
+// What's known of a puzzle before solving it: whether it's sound, and whether it has just the one solution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Validation {
+    pub givens: usize,
+    // Everything wrong with the numbers given, as `Puzzle::diagnose()` finds once candidates are assigned
+    pub problems: Vec<IllDefinedReason>,
+    // Solutions found, counting no further than 2. None if there were problems, as then there's none to find.
+    pub solutions: Option<usize>,
+}
+
+impl Validation {
+    // The outcome in a word: `valid`, `ill-defined`, `no-solution`, or `multiple-solutions`.
+    pub fn verdict(&self) -> &'static str {
+        match self.solutions {
+            None => "ill-defined",
+            Some(0) => "no-solution",
+            Some(1) => "valid",
+            Some(_) => "multiple-solutions",
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.solutions == Some(1)
+    }
+}
+
+impl Puzzle {
+    // Check the puzzle can be solved, and only one way, without solving it. Candidates are worked out afresh from the numbers, whatever's been noted.
+    pub fn validate(&self) -> Validation {
+        let mut fresh = self.clone();
+        fresh.assign_candidates();
+        let problems = fresh.diagnose();
+        let solutions = if problems.is_empty() {
+            Some(self.count_solutions(2))
+        } else {
+            None
+        };
+
+        Validation {
+            givens: self.givens().len(),
+            problems,
+            solutions,
+        }
+    }
+
+    // Check this grid as a solution to the original puzzle: every cell filled, no number repeated in a row, column, or block, every sandwich, arrow, and XV mark adding up, every line keeping to its rule, every house rule of the original's config kept, and every given of the original kept. Lists every mistake found, so an empty list means it's right.
+    pub fn verify(&self, original: &Puzzle) -> Vec<Mistake> {
+        let mut mistakes = vec![];
+
+        for at in self.shape.coords() {
+            let (cell, clue) = (self[at], original[at]);
+
+            match (cell.number, clue.number) {
+                (None, _) => mistakes.push(Mistake::Blank(at)),
+                (Some(found), Some(given)) if clue.given && found != given => {
+                    mistakes.push(Mistake::GivenChanged { at, given, found })
+                }
+                _ => {}
+            }
+        }
+
+        mistakes.extend(
+            self.diagnose()
+                .into_iter()
+                .filter(|reason| {
+                    matches!(
+                        reason,
+                        IllDefinedReason::NumberRepeatsInRow(..)
+                            | IllDefinedReason::NumberRepeatsInColumn(..)
+                            | IllDefinedReason::NumberRepeatsInBlock(..)
+                            | IllDefinedReason::NumberRepeatsInDiagonal(..)
+                    )
+                })
+                .map(Mistake::Repeated),
+        );
+        mistakes.extend(self.wrong_sandwiches().into_iter().map(Mistake::Sandwich));
+        mistakes.extend(self.wrong_arrows().into_iter().map(Mistake::Arrow));
+        mistakes.extend(self.wrong_xv().into_iter().map(|[a, b]| Mistake::Xv(a, b)));
+        mistakes.extend(self.wrong_lines().into_iter().map(Mistake::Line));
+        mistakes.extend(
+            self.broken_house_rules(&original.config.house_rules)
+                .into_iter()
+                .map(Mistake::HouseRule),
+        );
+
+        mistakes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Coord, IllDefinedReason, Puzzle};
+    use super::Mistake;
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    fn solved() -> Puzzle {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.solve().unwrap();
+        puzzle
+    }
+
+    #[test]
+    fn validate() {
+        let validation = Puzzle::parse(EASY).validate();
+        assert_eq!("valid", validation.verdict());
+        assert_eq!(36, validation.givens);
+
+        let validation = Puzzle::parse(&EASY.replacen('.', "4", 1)).validate();
+        assert_eq!("ill-defined", validation.verdict());
+        assert_eq!(
+            IllDefinedReason::NumberRepeatsInRow(4, 0),
+            validation.problems[0]
+        );
+
+        // 8 breaks no rule in the top left, but the right number there is 3
+        let validation = Puzzle::parse(&EASY.replacen('.', "8", 1)).validate();
+        assert_eq!(37, validation.givens);
+        assert_eq!(Some(0), validation.solutions);
+        assert_eq!("no-solution", validation.verdict());
+
+        assert_eq!("multiple-solutions", Puzzle::parse("").validate().verdict());
+    }
+
+    #[test]
+    fn verify() {
+        let original = Puzzle::parse(EASY);
+        let solution = Puzzle::parse(&solved().csv());
+        assert_eq!(Vec::<Mistake>::new(), solution.verify(&original));
+
+        // Swapping two numbers of a row changes a given, and repeats each in the other's column
+        let mut swapped = solution.clone();
+        swapped.grid[0].swap(0, 1);
+        let mistakes = swapped.verify(&original);
+        assert_eq!(
+            Mistake::GivenChanged {
+                at: Coord::new(0, 1),
+                given: 4,
+                found: swapped.grid[0][1].number.unwrap(),
+            },
+            mistakes[0]
+        );
+        assert!(
+            mistakes.contains(&Mistake::Repeated(IllDefinedReason::NumberRepeatsInColumn(
+                4, 0
+            )))
+        );
+
+        let mut blank = solution.clone();
+        blank.grid[8][8].number = None;
+        assert_eq!(
+            vec![Mistake::Blank(Coord::new(8, 8))],
+            blank.verify(&original)
+        );
+        assert_eq!(
+            "r9c9 is blank",
+            Mistake::Blank(Coord::new(8, 8)).to_string()
+        );
+    }
+}
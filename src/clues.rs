@@ -0,0 +1,20 @@
+use super::{Arrows, Edges, Lines, Sandwiches};
+
+/// A variant's clues, drawn on and around the grid beyond its givens: sandwich sudoku's sums, arrow sudoku's arrows, XV sudoku's marks, and renban and whisper lines. None by default, as for a classic sudoku. They're the puzzle's, not its `Shape`'s, which is the grid's geometry alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Clues {
+    pub(crate) sandwiches: Sandwiches,
+    pub(crate) arrows: Arrows,
+    pub(crate) xv: Edges,
+    pub(crate) lines: Lines,
+}
+
+impl Clues {
+    // Whether there are none at all, as for a classic sudoku.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sandwiches.is_empty()
+            && self.arrows.is_empty()
+            && self.xv.is_none()
+            && self.lines.is_empty()
+    }
+}
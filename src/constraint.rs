@@ -0,0 +1,340 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::{
+    flexed, reduce_by_uniqueness, Candidates, Consolidation, Coord, IllDefinedReason,
+    Justification, Puzzle, Shape, Technique, Unit, MAX_SIZE,
+};
+
+/// A rule the digits must keep to. The rows, columns, blocks, and diagonals are constraints, as are each variant's clues, and house rules of your own can be added to `SolverConfig::house_rules` without changing the solver.
+pub trait Constraint: Send + Sync {
+    // Remove the candidates the rule rules out, returning what was removed. House rules remove them with `Puzzle::eliminate()`, which says what it removed. Like the techniques, called repeatedly until nothing more can be removed.
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation>;
+
+    // Why the numbers placed so far break the rule, or None while they keep to it. Cells not yet filled in mustn't count against it, as the search checks partly filled grids too.
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason>;
+
+    // What the rule leaves each of its cells, given what each cell could be, a number as itself alone, for the search to narrow them to as it fills the grid in. A cell left nothing can't keep to it. Rules that don't say are left to `prune()` and `check()`.
+    fn fits(&self, _shape: Shape, _cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        vec![]
+    }
+}
+
+/// A `Constraint` to add to `SolverConfig::house_rules`, shared by every copy of the config, including those of any guesses.
+#[derive(Clone)]
+pub struct HouseRule(Arc<dyn Constraint>);
+
+impl HouseRule {
+    pub fn new(rule: impl Constraint + 'static) -> HouseRule {
+        HouseRule(Arc::new(rule))
+    }
+}
+
+impl fmt::Debug for HouseRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HouseRule")
+    }
+}
+
+// Configs are the same only if they share the same rules.
+impl PartialEq for HouseRule {
+    fn eq(&self, other: &HouseRule) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Constraint for HouseRule {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        self.0.prune(puzzle)
+    }
+
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        self.0.check(puzzle)
+    }
+
+    fn fits(&self, shape: Shape, cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        self.0.fits(shape, cell)
+    }
+}
+
+// A row, column, block, or diagonal of X-Sudoku holds each digit once. Cells pinned to a set of as many digits as there are cells take those digits from the rest of the unit, as naked subsets.
+impl Constraint for Unit {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        let positions = self.cells(puzzle.shape);
+        let mut sets = [Candidates::none(); MAX_SIZE];
+        for (set, &p) in sets.iter_mut().zip(&positions) {
+            *set = puzzle[p].candidates;
+        }
+        let candidates = &sets[..positions.len()];
+
+        let reduced = reduce_by_uniqueness(candidates);
+        let mut eliminations: Vec<Consolidation> = Vec::new();
+        for j in 0..positions.len() {
+            if puzzle.update_candidates(positions[j].into(), reduced[j]) {
+                eliminations.push(flexed(puzzle.shape, *self, j, candidates, reduced[j]));
+            }
+        }
+        eliminations
+    }
+
+    // Broken by the smallest number placed more than once.
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        let mut seen = Candidates::none();
+        let mut repeated = Candidates::none();
+        for p in self.cells(puzzle.shape) {
+            if let Some(number) = puzzle[p].number {
+                if seen.contains(number) {
+                    repeated.insert(number);
+                }
+                seen.insert(number);
+            }
+        }
+
+        let number = repeated.iter().next()?;
+        Some(match *self {
+            Unit::Row(row) => IllDefinedReason::NumberRepeatsInRow(number, row),
+            Unit::Column(col) => IllDefinedReason::NumberRepeatsInColumn(number, col),
+            Unit::Block(b) => IllDefinedReason::NumberRepeatsInBlock(number, b),
+            Unit::Diagonal(d) => IllDefinedReason::NumberRepeatsInDiagonal(number, d),
+        })
+    }
+}
+
+/// Anti-knight, the house rule most often added to a sudoku: no digit a knight's move away from the same digit. Add it with `HouseRule::new(AntiKnight)`.
+pub struct AntiKnight;
+
+impl AntiKnight {
+    // The cells a knight's move from the cell, within the grid.
+    fn moves(puzzle: &Puzzle, from: Coord) -> impl Iterator<Item = Coord> {
+        let size = puzzle.shape.size() as isize;
+        let jumps = [
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ];
+        jumps
+            .into_iter()
+            .map(move |(dr, dc)| (from.row as isize + dr, from.col as isize + dc))
+            .filter(move |&(row, col)| (0..size).contains(&row) && (0..size).contains(&col))
+            .map(|(row, col)| Coord::new(row as usize, col as usize))
+    }
+}
+
+// A number placed takes its digit from the cells a knight's move away, each number justifying its own eliminations.
+impl Constraint for AntiKnight {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        let mut found = vec![];
+        for from in puzzle.shape.coords() {
+            let Some(number) = puzzle[from].number else {
+                continue;
+            };
+            let removals: Vec<(Coord, u8)> = AntiKnight::moves(puzzle, from)
+                .filter(|&to| puzzle[to].number.is_none() && puzzle[to].candidates.contains(number))
+                .map(|to| (to, number))
+                .collect();
+            if !removals.is_empty() {
+                found.extend(puzzle.eliminate(Justification::Peers(vec![from.into()]), &removals));
+            }
+        }
+        found
+    }
+
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        puzzle.shape.coords().find_map(|from| {
+            let number = puzzle[from].number?;
+            AntiKnight::moves(puzzle, from)
+                .any(|to| puzzle[to].number == Some(number))
+                .then(|| IllDefinedReason::HouseRuleBroken("anti-knight", from.into()))
+        })
+    }
+}
+
+impl Puzzle {
+    // Remove the digits from the cells' candidates for a house rule, justified as given. Cells with a number and digits already gone are left be. Returns what was removed, grouped as the techniques group theirs, for `Constraint::prune()` to return.
+    pub fn eliminate(
+        &mut self,
+        justification: Justification,
+        removals: &[(Coord, u8)],
+    ) -> Vec<Consolidation> {
+        let mut removed: Vec<((usize, usize), u8)> = Vec::new();
+        for &(coord, digit) in removals {
+            if self[coord].number.is_none() && self[coord].remove_candidate(digit) {
+                log!(
+                    self,
+                    "📜📜📜📜📜 A house rule eliminated candidate {} at grid position ({}, {})",
+                    digit,
+                    coord.row,
+                    coord.col
+                );
+                removed.push((coord.into(), digit));
+            }
+        }
+
+        Consolidation::eliminations(Technique::HouseRule, justification, &removed)
+    }
+
+    // House rules: each of the config's in turn, until one removes something.
+    //
+    // Returns the eliminations made, a rule at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_house_rules(&mut self) -> Vec<Consolidation> {
+        if self.config.house_rules.is_empty() {
+            return vec![];
+        }
+
+        let rules = self.config.house_rules.clone();
+        for rule in rules {
+            let found = rule.prune(self);
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        vec![]
+    }
+
+    // Every rule beyond the rows, columns, blocks, and diagonals: the variant's clues, a sandwich's sums, each arrow, the XV marks, and each line, then the config's house rules. Whatever keeps to them all goes through these, so a new kind of clue need only be added here.
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        let clues = &*self.clues;
+        let sandwiches = (!clues.sandwiches.is_empty()).then_some(&clues.sandwiches);
+        let xv = (!clues.xv.is_none()).then_some(&clues.xv);
+        let rules = self.config.house_rules.iter();
+
+        sandwiches
+            .into_iter()
+            .map(|sandwiches| sandwiches as &dyn Constraint)
+            .chain(clues.arrows.iter().map(|arrow| arrow as &dyn Constraint))
+            .chain(xv.into_iter().map(|xv| xv as &dyn Constraint))
+            .chain(clues.lines.iter().map(|line| line as &dyn Constraint))
+            .chain(rules.map(|rule| rule as &dyn Constraint))
+    }
+
+    // Why the numbers placed break the config's house rules, a reason for each rule broken.
+    pub(super) fn broken_house_rules(&self, rules: &[HouseRule]) -> Vec<IllDefinedReason> {
+        rules.iter().filter_map(|rule| rule.check(self)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::solve_with_guesses;
+    use super::super::{
+        Constraint, IllDefinedReason, Mistake, Puzzle, PuzzleStatus, SolverConfig, Technique, Unit,
+    };
+    use super::{AntiKnight, HouseRule};
+
+    fn anti_knight(puzzle: &mut Puzzle, techniques: Vec<Technique>) {
+        puzzle.set_config(SolverConfig {
+            house_rules: vec![HouseRule::new(AntiKnight)],
+            ..SolverConfig::with_techniques(techniques)
+        });
+    }
+
+    #[test]
+    fn units() {
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        puzzle.assign_candidates();
+        assert_eq!(None, Unit::Row(0).check(&puzzle));
+
+        // A naked pair takes its digits from the rest of the row
+        let mut pair = Puzzle::parse("");
+        pair.assign_candidates();
+        for col in 0..2 {
+            pair.grid[0][col].candidates = [1, 2].iter().collect();
+        }
+        let found = Unit::Row(0).prune(&mut pair);
+        assert_eq!(7, found.len());
+        assert!(!pair.grid[0][5].candidates.contains(1));
+        assert!(Unit::Row(0).prune(&mut pair).is_empty());
+
+        pair.grid[4][0].number = Some(3);
+        pair.grid[5][1].number = Some(3);
+        pair.grid[6][0].number = Some(3);
+        assert_eq!(
+            Some(IllDefinedReason::NumberRepeatsInColumn(3, 0)),
+            Unit::Column(0).check(&pair)
+        );
+        assert_eq!(
+            Some(IllDefinedReason::NumberRepeatsInBlock(3, 3)),
+            Unit::Block(3).check(&pair)
+        );
+        assert_eq!(None, Unit::Column(1).check(&pair));
+    }
+
+    #[test]
+    fn house_rules() {
+        let mut puzzle = Puzzle::parse("");
+        anti_knight(&mut puzzle, vec![Technique::HouseRule]);
+        puzzle.grid[4][4].number = Some(5);
+        puzzle.assign_candidates();
+
+        // The 5 rules itself out a knight's move away
+        let found = puzzle.reduce_candidates_with_house_rules();
+        assert_eq!(Some(Technique::HouseRule), found[0].technique());
+        assert!(!puzzle.grid[2][3].candidates.contains(5));
+        assert!(!puzzle.grid[6][5].candidates.contains(5));
+        assert!(puzzle.reduce_candidates_with_house_rules().is_empty());
+        assert!(puzzle.grid[6][6].candidates.contains(5));
+
+        // Breaking it makes the puzzle ill-defined, and a wrong solution
+        puzzle.grid[3][2].number = Some(5);
+        assert_eq!(
+            PuzzleStatus::IllDefined(IllDefinedReason::HouseRuleBroken("anti-knight", (3, 2))),
+            puzzle.status()
+        );
+    }
+
+    #[test]
+    fn solve() {
+        // Guessed through from nothing, the grid keeps to the rule
+        let mut puzzle = Puzzle::parse("");
+        anti_knight(
+            &mut puzzle,
+            vec![
+                Technique::HouseRule,
+                Technique::SaraFlex,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+            ],
+        );
+        assert_eq!(2, puzzle.count_solutions(2));
+        let original = puzzle.clone();
+        puzzle.solve().unwrap();
+        let solved = solve_with_guesses(puzzle).unwrap().unwrap();
+        assert_eq!(PuzzleStatus::Solved, solved.status());
+        assert!(solved.verify(&original).is_empty());
+        assert_eq!(None, AntiKnight.check(&solved));
+
+        // A classic solution breaks it
+        let mut classic = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let solved = classic.solutions(1).remove(0);
+        anti_knight(&mut classic, vec![Technique::HouseRule]);
+        assert_eq!(0, classic.count_solutions(2));
+        assert!(matches!(
+            solved.verify(&classic)[..],
+            [Mistake::HouseRule(IllDefinedReason::HouseRuleBroken(
+                "anti-knight",
+                _
+            ))]
+        ));
+    }
+
+    #[test]
+    fn constraints() {
+        // A classic sudoku has none beyond its houses
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        assert_eq!(0, puzzle.constraints().count());
+        anti_knight(&mut puzzle, vec![Technique::HouseRule]);
+        assert_eq!(1, puzzle.constraints().count());
+
+        // Each arrow is a rule of its own
+        let mut arrows = Puzzle::parse(include_str!("../samples/arrow.txt"));
+        assert_eq!(9, arrows.constraints().count());
+        anti_knight(&mut arrows, vec![Technique::HouseRule]);
+        assert_eq!(10, arrows.constraints().count());
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use super::{Cell, Puzzle, Shape, MAX_SIZE};
+
+/// A cell's position in the grid. Rows and columns are numbered from 0, starting in top left.
+///
+/// Written and parsed as in sudoku books, counting from 1: `r4c7` for the fourth row's seventh cell, or `b5p3` for the centre box's third cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, col: usize) -> Coord {
+        assert!(
+            row < MAX_SIZE && col < MAX_SIZE,
+            "Invalid cell: ({}, {})",
+            row,
+            col
+        );
+        Coord { row, col }
+    }
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((row, col): (usize, usize)) -> Coord {
+        Coord::new(row, col)
+    }
+}
+
+impl From<Coord> for (usize, usize) {
+    fn from(coord: Coord) -> (usize, usize) {
+        (coord.row, coord.col)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
+    }
+}
+
+impl FromStr for Coord {
+    type Err = String;
+
+    // `r4c7` or `b5p3`, in either case. Box notation counts the blocks of a standard 9x9 grid; `Shape::parse_cell()` reads it for any grid.
+    fn from_str(name: &str) -> Result<Coord, String> {
+        let lower = name.to_lowercase();
+        if lower.starts_with('b') {
+            return Shape::STANDARD.parse_cell(name);
+        }
+
+        let (row, col) = lower
+            .strip_prefix('r')
+            .and_then(|rest| rest.split_once('c'))
+            .and_then(|(a, b)| Some((a.parse::<usize>().ok()?, b.parse::<usize>().ok()?)))
+            .filter(|&(row, col)| (1..=MAX_SIZE).contains(&row) && (1..=MAX_SIZE).contains(&col))
+            .ok_or_else(|| format!("Expected a cell such as r4c7 or b5p3, not '{}'", name))?;
+        Ok(Coord::new(row - 1, col - 1))
+    }
+}
+
+impl Index<Coord> for Puzzle {
+    type Output = Cell;
+
+    fn index(&self, coord: Coord) -> &Cell {
+        &self.grid[coord.row][coord.col]
+    }
+}
+
+impl IndexMut<Coord> for Puzzle {
+    fn index_mut(&mut self, coord: Coord) -> &mut Cell {
+        &mut self.grid[coord.row][coord.col]
+    }
+}
+
+impl Index<(usize, usize)> for Puzzle {
+    type Output = Cell;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Cell {
+        &self.grid[row][col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Puzzle {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Cell {
+        &mut self.grid[row][col]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+    use super::Coord;
+
+    #[test]
+    fn notation() {
+        assert_eq!(Ok(Coord::new(3, 6)), "r4c7".parse());
+        assert_eq!(Ok(Coord::new(0, 8)), "R1C9".parse());
+        assert_eq!(Ok(Coord::new(15, 9)), "r16c10".parse());
+        assert_eq!(Ok(Coord::new(3, 5)), "b5p3".parse());
+        assert_eq!(Ok(Coord::new(8, 8)), "B9P9".parse());
+        assert!("r0c1".parse::<Coord>().is_err());
+        assert!("r26c1".parse::<Coord>().is_err());
+        assert!("b5p10".parse::<Coord>().is_err());
+        assert!("r4".parse::<Coord>().is_err());
+
+        assert_eq!("r4c7", Coord::new(3, 6).to_string());
+        for i in 0..256 {
+            let coord = Coord::new(i / 16, i % 16);
+            assert_eq!(Ok(coord), coord.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn index() {
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let coord = Coord::new(0, 1);
+        assert_eq!(puzzle.grid[0][1], puzzle[coord]);
+        assert_eq!(puzzle[(0, 1)], puzzle[coord]);
+
+        puzzle[(4, 4)].number = Some(7);
+        assert_eq!(Some(7), puzzle[Coord::new(4, 4)].number());
+    }
+}
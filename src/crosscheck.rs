@@ -0,0 +1,219 @@
+use super::engine::parse_position;
+use super::progress::{Progress, Tracker};
+use super::{Coord, IllDefinedReason, Puzzle, PuzzleStatus, SolverConfig};
+
+/// Where the techniques and the brute force search disagree about a puzzle, a bug in one or the other. The search is the backtracker `Puzzle::solutions()` uses, not dancing links, so it keeps to the variants and house rules just as the techniques do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Disagreement {
+    // The techniques filled in every cell, but the numbers break the rules
+    WrongSolution,
+    // The techniques found the puzzle ill-defined, for this reason, yet the search solves it
+    FalseIllDefined(IllDefinedReason),
+    // The techniques filled in a solution other than the one the search finds
+    SolutionMismatch,
+    // The techniques ruled out the digit the solution has in the cell, or filled the cell in with another
+    EliminatedSolution(Coord, u8),
+}
+
+impl Disagreement {
+    // The disagreement in words, e.g. "R4C7 lost 2, the digit the search solves it with."
+    pub fn describe(&self) -> String {
+        match self {
+            Disagreement::WrongSolution => {
+                "The techniques filled the grid in with numbers breaking the rules.".to_string()
+            }
+            Disagreement::FalseIllDefined(reason) => format!(
+                "The techniques found it ill-defined ({:?}), but the search solves it.",
+                reason
+            ),
+            Disagreement::SolutionMismatch => {
+                "The techniques solved it differently from the search.".to_string()
+            }
+            Disagreement::EliminatedSolution(coord, digit) => format!(
+                "{} lost {}, the digit the search solves it with.",
+                coord.to_string().to_uppercase(),
+                digit
+            ),
+        }
+    }
+}
+
+impl Puzzle {
+    // Solve the puzzle both ways, with the configured techniques alone and by the brute force search, and say where they disagree, or None where they don't. Only puzzles with the one solution are held to it, as the uniqueness techniques may rightly go astray on others, though a grid the techniques fill in must keep to the rules regardless. Nor are solves cut short by a limit in the config.
+    pub fn cross_check(&self) -> Option<Disagreement> {
+        let mut logical = self.clone();
+        logical.config.verbose = false;
+        logical.solve().ok()?;
+
+        disagreement(&logical, &self.solutions(2))
+    }
+}
+
+// Where the logical solve, as far as it got, disagrees with the solutions the search found.
+fn disagreement(logical: &Puzzle, solutions: &[Puzzle]) -> Option<Disagreement> {
+    let filled = logical
+        .shape
+        .coords()
+        .all(|coord| logical[coord].number.is_some());
+    if filled && logical.count_solutions(1) == 0 {
+        return Some(Disagreement::WrongSolution);
+    }
+    let [solution] = solutions else {
+        return None;
+    };
+
+    match logical.status() {
+        PuzzleStatus::IllDefined(reason) => Some(Disagreement::FalseIllDefined(reason)),
+        PuzzleStatus::Solved => logical
+            .shape
+            .coords()
+            .any(|coord| logical[coord].number != solution[coord].number)
+            .then_some(Disagreement::SolutionMismatch),
+        _ => logical.shape.coords().find_map(|coord| {
+            let digit = solution[coord].number?;
+            let kept = match logical[coord].number {
+                Some(number) => number == digit,
+                None => logical[coord].candidates.contains(digit),
+            };
+            (!kept).then_some(Disagreement::EliminatedSolution(coord, digit))
+        }),
+    }
+}
+
+/// The puzzles of a batch on which the techniques and the search disagree.
+pub struct CrossCheck {
+    checked: usize,
+    disagreements: Vec<(String, Disagreement)>,
+}
+
+// Cross-check each puzzle of the input, given a line to a puzzle as for `batch()`, with the config's techniques. Lines that aren't puzzles are skipped.
+pub fn cross_check(input: &str, config: &SolverConfig) -> CrossCheck {
+    cross_check_with_progress(input, config, |_| {})
+}
+
+// As `cross_check()`, telling the callback how far it's got after each puzzle.
+pub fn cross_check_with_progress(
+    input: &str,
+    config: &SolverConfig,
+    progress: impl FnMut(Progress),
+) -> CrossCheck {
+    let lines: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let mut tracker = Tracker::new(lines.len(), progress);
+    let mut checked = 0;
+    let mut disagreements = vec![];
+    for line in lines {
+        if let Ok(mut puzzle) = parse_position(line) {
+            puzzle.set_config(SolverConfig {
+                verbose: false,
+                ..config.clone()
+            });
+            checked += 1;
+            if let Some(disagreement) = puzzle.cross_check() {
+                disagreements.push((line.to_string(), disagreement));
+            }
+        }
+        tracker.tick();
+    }
+
+    CrossCheck {
+        checked,
+        disagreements,
+    }
+}
+
+impl CrossCheck {
+    // Each puzzle the two disagree on, as given, and how they disagree.
+    pub fn disagreements(&self) -> &[(String, Disagreement)] {
+        &self.disagreements
+    }
+
+    // E.g. "95 puzzles cross-checked: 1 disagreement".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} puzzles cross-checked: {} disagreement{}",
+            self.checked,
+            self.disagreements.len(),
+            if self.disagreements.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Coord, IllDefinedReason, Puzzle, SolverConfig};
+    use super::{cross_check, disagreement, Disagreement};
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+    const EXPERT: &str =
+        "....8.4.99......626.........7.....1315..4.....3..9......8....45.....17....2..3...";
+
+    #[test]
+    fn agreements() {
+        let input = format!("{}\nnot a puzzle\n{}\n", EASY, EXPERT);
+        let checked = cross_check(&input, &SolverConfig::default());
+        assert!(checked.disagreements().is_empty());
+        assert_eq!(
+            "2 puzzles cross-checked: 0 disagreements",
+            checked.summary()
+        );
+    }
+
+    #[test]
+    fn disagreements() {
+        let puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let solutions = puzzle.solutions(2);
+        let mut logical = puzzle.clone();
+        logical.solve().unwrap();
+        assert_eq!(None, disagreement(&logical, &solutions));
+
+        // A digit of the solution ruled out
+        let mut lost = puzzle.clone();
+        lost.assign_candidates();
+        let blank = lost
+            .shape
+            .coords()
+            .find(|&c| lost[c].number.is_none())
+            .unwrap();
+        let digit = solutions[0][blank].number.unwrap();
+        lost[blank].remove_candidate(digit);
+        assert_eq!(
+            Some(Disagreement::EliminatedSolution(blank, digit)),
+            disagreement(&lost, &solutions)
+        );
+
+        // A solved grid with two numbers swapped breaks the rules
+        let mut wrong = logical.clone();
+        let (a, b) = (Coord::new(0, 0), Coord::new(0, 2));
+        let (x, y) = (wrong[a].number, wrong[b].number);
+        wrong[a].number = y;
+        wrong[b].number = x;
+        assert_eq!(
+            Some(Disagreement::WrongSolution),
+            disagreement(&wrong, &solutions)
+        );
+
+        // Found ill-defined, though it has a solution
+        let mut stuck = lost.clone();
+        stuck[blank].candidates = Candidates::none();
+        assert_eq!(
+            Some(Disagreement::FalseIllDefined(
+                IllDefinedReason::NoPossibleSolutionForCell(blank.into())
+            )),
+            disagreement(&stuck, &solutions)
+        );
+        assert!(disagreement(&stuck, &[]).is_none());
+        assert!(Disagreement::EliminatedSolution(Coord::new(3, 6), 2)
+            .describe()
+            .starts_with("R4C7 lost 2"));
+    }
+}
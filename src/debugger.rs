@@ -0,0 +1,264 @@
+use super::shape::digit;
+use super::{cells_see_each_other, symbol, Consolidation, Coord, Puzzle, Shape, Technique};
+
+const HELP: &str = "Commands:
+  step               take a step of the solve, as the solver would
+  step <technique>   apply just the technique to the candidates as they stand, e.g. step xwing
+  show               show the grid
+  show r4c7          show the cell's number or candidates, also named as b5p3
+  why r4c7 != 5      explain why 5 isn't a candidate for the cell
+  undo               take back the last step
+  redo               take the last step undone again
+  snapshot [name]    write the grid to tmp/, as the solver does after each step
+  help               show this
+  quit               stop debugging";
+
+/// Steps through a solve a command at a time, for working on the techniques without editing the solver.
+pub struct Debugger {
+    puzzle: Puzzle,
+}
+
+impl Debugger {
+    // Candidates are assigned up front, so techniques can be tried straight away.
+    pub fn new(mut puzzle: Puzzle) -> Debugger {
+        puzzle.assign_candidates();
+        Debugger { puzzle }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn help() -> &'static str {
+        HELP
+    }
+
+    // Run a command, returning what to print. See `help()` for the commands.
+    pub fn run(&mut self, command: &str) -> Result<String, String> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => Ok(String::new()),
+            ["help"] => Ok(HELP.to_string()),
+            ["step"] => {
+                self.puzzle.step();
+                Ok(self.last_step())
+            }
+            ["step", names] => {
+                let techniques = Technique::parse_list(names)?;
+                let before = self.puzzle.grid;
+
+                let mut found: Vec<Consolidation> = Vec::new();
+                for technique in techniques.iter() {
+                    found.append(&mut technique.apply(&mut self.puzzle));
+                }
+                if found.is_empty() {
+                    return Ok(format!("{} found nothing.", names));
+                }
+
+                self.puzzle.iteration += 1;
+                self.puzzle.last_consolidation = found;
+                self.puzzle.record_step(before);
+                Ok(self.last_step())
+            }
+            ["show"] => Ok(self.puzzle.display()),
+            ["show", cell] => {
+                let (row, col) = parse_cell(self.puzzle.shape, cell)?;
+                let cell = self.puzzle.grid[row][col];
+
+                Ok(match cell.number {
+                    Some(n) if cell.given => format!("{} is {}, a given.", cell_name(row, col), n),
+                    Some(n) => format!("{} is {}.", cell_name(row, col), n),
+                    None => format!(
+                        "{} can be {:?}.",
+                        cell_name(row, col),
+                        cell.candidates.to_vec()
+                    ),
+                })
+            }
+            ["why", cell, "!=", name] => {
+                let shape = self.puzzle.shape;
+                let (row, col) = parse_cell(shape, cell)?;
+                let mut chars = name.chars();
+                match (
+                    chars.next().and_then(|c| digit(c, shape.size())),
+                    chars.next(),
+                ) {
+                    (Some(digit), None) => Ok(self.why_not(row, col, digit)),
+                    _ => Err(format!(
+                        "Expected a digit from 1 to {}, not '{}'",
+                        symbol(shape.size() as u8),
+                        name
+                    )),
+                }
+            }
+            ["undo"] if self.puzzle.undo() => Ok(self.puzzle.display()),
+            ["undo"] => Err("Nothing to undo".to_string()),
+            ["redo"] if self.puzzle.redo() => Ok(self.last_step()),
+            ["redo"] => Err("Nothing to redo".to_string()),
+            ["snapshot"] => self.snapshot(&format!("debug-s{}", self.puzzle.iteration)),
+            ["snapshot", name] => self.snapshot(name),
+            _ => Err(format!("Unknown command '{}'. Try help", command.trim())),
+        }
+    }
+
+    fn last_step(&self) -> String {
+        match self.puzzle.steps.last() {
+            Some(step) if !step.consolidations.is_empty() => format!(
+                "Step {}:\n{}\n{}",
+                step.iteration,
+                step.describe(),
+                self.puzzle.display()
+            ),
+            _ => format!("Step {} found nothing.", self.puzzle.iteration),
+        }
+    }
+
+    // Why the digit isn't a candidate for the cell: a peer holds it, or a step eliminated it.
+    fn why_not(&self, row: usize, col: usize, digit: u8) -> String {
+        let name = cell_name(row, col);
+        let cell = self.puzzle.grid[row][col];
+
+        if let Some(n) = cell.number {
+            return if n == digit {
+                format!("{} is {}.", name, digit)
+            } else {
+                format!("{} is {}, so it can't be {}.", name, n, digit)
+            };
+        }
+        if cell.candidates.contains(digit) {
+            return format!("{} can still be {}.", name, digit);
+        }
+
+        let shape = self.puzzle.shape;
+        let peer = shape.coords().map(<(usize, usize)>::from).find(|&(r, c)| {
+            cells_see_each_other(shape, (row, col), (r, c))
+                && self.puzzle.grid[r][c].number == Some(digit)
+        });
+        if let Some((r, c)) = peer {
+            return format!(
+                "{} can't be {}: {} already holds it.",
+                name,
+                digit,
+                cell_name(r, c)
+            );
+        }
+
+        // Candidates are worked out afresh at the start of each step, so only the latest elimination counts
+        let elimination = self.puzzle.steps.iter().rev().find_map(|step| {
+            step.consolidations.iter().find(|c| match c {
+                Consolidation::CandidatesEliminated { cells, digits, .. } => {
+                    cells.contains(&(row, col)) && digits.contains(&digit)
+                }
+                _ => false,
+            })
+        });
+        match elimination {
+            Some(consolidation) => consolidation.describe(),
+            None => format!("Nothing on record rules out {} for {}.", digit, name),
+        }
+    }
+
+    fn snapshot(&self, name: &str) -> Result<String, String> {
+        if name.contains('/') {
+            return Err("Snapshots are written to tmp/, so need a plain file name".to_string());
+        }
+
+        match self.puzzle.write_iteration(name.to_string(), None) {
+            Ok(()) => Ok(format!("Wrote tmp/{}", name)),
+            Err(error) => Err(format!("Couldn't write tmp/{}: {}", name, error)),
+        }
+    }
+}
+
+// A cell of a grid of the shape named as in sudoku books, e.g. `r4c7` for the fourth row's seventh cell or `b5p3` for the centre box's third cell.
+fn parse_cell(shape: Shape, name: &str) -> Result<(usize, usize), String> {
+    shape.parse_cell(name).map(Into::into)
+}
+
+fn cell_name(row: usize, col: usize) -> String {
+    Coord::new(row, col).to_string().to_uppercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Puzzle, Shape};
+    use super::{parse_cell, Debugger};
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn cells() {
+        let shape = Shape::STANDARD;
+        assert_eq!(Ok((3, 6)), parse_cell(shape, "r4c7"));
+        assert_eq!(Ok((0, 8)), parse_cell(shape, "R1C9"));
+        assert_eq!(Ok((3, 5)), parse_cell(shape, "b5p3"));
+        assert!(parse_cell(shape, "r0c1").is_err());
+        assert!(parse_cell(shape, "b0p3").is_err());
+        assert!(parse_cell(shape, "r10c1").is_err());
+        assert_eq!(Ok((9, 0)), parse_cell(Shape::HEXADOKU, "r10c1"));
+    }
+
+    #[test]
+    fn commands() {
+        let mut debugger = Debugger::new(Puzzle::parse(EASY));
+
+        assert_eq!(
+            Ok("R1C2 is 4, a given.".to_string()),
+            debugger.run("show r1c2")
+        );
+        assert_eq!(
+            Ok("R1C1 can be [1, 3, 8].".to_string()),
+            debugger.run("show r1c1")
+        );
+        assert_eq!(
+            Ok("R1C1 can't be 2: R1C6 already holds it.".to_string()),
+            debugger.run("why r1c1 != 2")
+        );
+        assert_eq!(
+            Ok("R1C1 can still be 3.".to_string()),
+            debugger.run("why r1c1 != 3")
+        );
+        assert!(debugger.run("why r1c1 != 0").is_err());
+        assert!(debugger.run("undo").is_err());
+        assert!(debugger.run("frobnicate").is_err());
+
+        let stepped = debugger.run("step singles").unwrap();
+        assert!(stepped.starts_with("Step 1:\n"));
+        assert!(stepped.contains(" must be "));
+        assert_eq!(1, debugger.puzzle().steps.len());
+
+        assert!(debugger.run("undo").is_ok());
+        assert_eq!(0, debugger.puzzle().steps.len());
+        assert_eq!(0, debugger.puzzle().iteration);
+        assert_eq!(Ok(stepped), debugger.run("redo"));
+        assert!(debugger.run("redo").is_err());
+    }
+
+    #[test]
+    fn why_not() {
+        let mut debugger = Debugger::new(Puzzle::parse(EASY));
+        let before = debugger.puzzle.clone();
+        debugger.run("step naked-subsets,pointing").unwrap();
+
+        // Find something the step ruled out, and check it's explained
+        let (row, col, digit) = debugger
+            .puzzle
+            .shape
+            .coords()
+            .map(<(usize, usize)>::from)
+            .find_map(|(row, col)| {
+                let remaining = debugger.puzzle.grid[row][col].candidates;
+                before.grid[row][col]
+                    .candidates
+                    .iter()
+                    .find(|&d| !remaining.contains(d))
+                    .map(|d| (row, col, d))
+            })
+            .unwrap();
+        let why = debugger.why_not(row, col, digit);
+        assert!(why.contains(&format!("R{}C{}", row + 1, col + 1)));
+        assert!(why.contains(" can't be "));
+        assert!(!why.contains("already holds it"));
+    }
+}
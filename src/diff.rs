@@ -0,0 +1,208 @@
+use super::explain::{cell_name, list};
+use super::{Candidates, Coord, Puzzle};
+
+/// What changed from one state of a puzzle to another: the numbers put in, and the candidates crossed off.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GridDiff {
+    // Numbers in cells that were empty, row by row
+    pub assignments: Vec<(Coord, u8)>,
+
+    // Candidates gone from cells still empty, row by row
+    pub eliminations: Vec<(Coord, Candidates)>,
+}
+
+impl GridDiff {
+    pub fn is_empty(&self) -> bool {
+        self.assignments.is_empty() && self.eliminations.is_empty()
+    }
+
+    // The changes, a line each, e.g. "R4C7 is 2" or "R1C6 can't be 4 or 9".
+    pub fn describe(&self) -> String {
+        let assigned = self
+            .assignments
+            .iter()
+            .map(|&(coord, number)| format!("{} is {}", cell_name(coord.into()), number));
+        let eliminated = self.eliminations.iter().map(|&(coord, digits)| {
+            let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+            format!(
+                "{} can't be {}",
+                cell_name(coord.into()),
+                list(&digits, "or")
+            )
+        });
+
+        assigned
+            .chain(eliminated)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// How two grids of the same shape differ, as two transcriptions of one newspaper puzzle might: the cells they number differently, and the numbers only one of them has.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Discrepancies {
+    // Cells numbered in both, the first grid's number then the second's, row by row
+    pub disagreements: Vec<(Coord, u8, u8)>,
+
+    // Numbers only the second grid has, row by row
+    pub added: Vec<(Coord, u8)>,
+
+    // Numbers only the first grid has, row by row
+    pub removed: Vec<(Coord, u8)>,
+}
+
+impl Discrepancies {
+    pub fn is_empty(&self) -> bool {
+        self.disagreements.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    // The differences, a line each, e.g. "R4C7 is 2 in one, 7 in the other", "R1C1 4 added", or "R9C3 5 removed".
+    pub fn describe(&self) -> String {
+        let disagreed = self.disagreements.iter().map(|&(coord, first, second)| {
+            format!(
+                "{} is {} in one, {} in the other",
+                cell_name(coord.into()),
+                first,
+                second
+            )
+        });
+        let added = self
+            .added
+            .iter()
+            .map(|&(coord, number)| format!("{} {} added", cell_name(coord.into()), number));
+        let removed = self
+            .removed
+            .iter()
+            .map(|&(coord, number)| format!("{} {} removed", cell_name(coord.into()), number));
+
+        disagreed
+            .chain(added)
+            .chain(removed)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Puzzle {
+    // Where this grid and another of the same shape differ, cell by cell. Unlike `diff()`, neither need follow from the other, and only the numbers count, not candidates.
+    pub fn discrepancies(&self, other: &Puzzle) -> Discrepancies {
+        let mut discrepancies = Discrepancies::default();
+        for coord in self.shape.coords() {
+            match (self[coord].number, other[coord].number) {
+                (Some(first), Some(second)) if first != second => {
+                    discrepancies.disagreements.push((coord, first, second))
+                }
+                (None, Some(number)) => discrepancies.added.push((coord, number)),
+                (Some(number), None) => discrepancies.removed.push((coord, number)),
+                _ => {}
+            }
+        }
+        discrepancies
+    }
+
+    // Whether this grid carries on from the earlier one consistently: every number of it kept, nothing but numbers added, and a solution still to be had. A grid continues itself, if it can be solved.
+    pub fn continues(&self, earlier: &Puzzle) -> bool {
+        let discrepancies = earlier.discrepancies(self);
+        self.shape == earlier.shape
+            && self.clues == earlier.clues
+            && discrepancies.disagreements.is_empty()
+            && discrepancies.removed.is_empty()
+            && self.count_solutions(1) == 1
+    }
+
+    // What changed from this puzzle to the later one. Numbers taken out again, as by `undo()`, and candidates put back aren't changes it lists, nor are candidates in cells the later puzzle has none noted for, as they've yet to be worked out.
+    pub fn diff(&self, later: &Puzzle) -> GridDiff {
+        let mut diff = GridDiff::default();
+        for coord in self.shape.coords() {
+            let (was, is) = (self[coord], later[coord]);
+            match (was.number, is.number) {
+                (None, Some(number)) => diff.assignments.push((coord, number)),
+                (None, None) if !is.candidates.is_empty() => {
+                    let gone = was.candidates.difference(is.candidates);
+                    if !gone.is_empty() {
+                        diff.eliminations.push((coord, gone));
+                    }
+                }
+                _ => {}
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Coord, Puzzle};
+
+    #[test]
+    fn diff() {
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let start = puzzle.clone();
+        assert!(start.diff(&puzzle).is_empty());
+
+        puzzle.step();
+        let step = puzzle.solution().steps()[0].clone();
+        let diff = start.noted().diff(&puzzle);
+        let assigned: Vec<(Coord, u8)> = step
+            .consolidations()
+            .iter()
+            .filter_map(|c| c.assignment())
+            .map(|(position, number)| (Coord::from(position), number))
+            .collect();
+        assert_eq!(assigned.len(), diff.assignments.len());
+        assert!(assigned.iter().all(|a| diff.assignments.contains(a)));
+        assert!(!diff.eliminations.is_empty());
+        assert_eq!(
+            diff.assignments.len() + diff.eliminations.len(),
+            diff.describe().lines().count()
+        );
+
+        // Going back changes nothing it lists
+        assert!(puzzle.diff(&start).is_empty());
+    }
+
+    #[test]
+    fn discrepancies() {
+        let puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        assert!(puzzle.discrepancies(&puzzle).is_empty());
+        assert!(puzzle.continues(&puzzle));
+
+        // Filling in a cell of the solution carries on from the puzzle, and not the other way about
+        let solution = &puzzle.solutions(1)[0];
+        let blank = puzzle
+            .shape
+            .coords()
+            .find(|&c| puzzle[c].number.is_none())
+            .unwrap();
+        let right = solution[blank].number.unwrap();
+        let mut later = puzzle.clone();
+        later[blank].number = Some(right);
+        assert_eq!(vec![(blank, right)], puzzle.discrepancies(&later).added);
+        assert!(later.continues(&puzzle));
+        assert!(!puzzle.continues(&later));
+        assert_eq!(vec![(blank, right)], later.discrepancies(&puzzle).removed);
+
+        // A wrong number keeps every given, but leaves nothing to solve
+        let mut wrong = puzzle.clone();
+        wrong[blank].number = Some(right % 9 + 1);
+        assert!(!wrong.continues(&puzzle));
+
+        // Nor is a grid misreading a given a continuation of either
+        let given = puzzle
+            .shape
+            .coords()
+            .find(|&c| puzzle[c].number.is_some())
+            .unwrap();
+        let read = puzzle[given].number.unwrap();
+        let mut misread = later.clone();
+        misread[given].number = Some(read % 9 + 1);
+        let discrepancies = puzzle.discrepancies(&misread);
+        assert_eq!(
+            vec![(given, read, read % 9 + 1)],
+            discrepancies.disagreements
+        );
+        assert!(!misread.continues(&puzzle));
+        assert!(!puzzle.continues(&misread));
+        assert_eq!(2, discrepancies.describe().lines().count());
+    }
+}
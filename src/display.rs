@@ -0,0 +1,1089 @@
+use std::io::IsTerminal;
+
+use super::{symbol, Cell, Coord, Puzzle, Shape};
+
+const RESET: &str = "\x1b[0m";
+
+/// Whether to color the output: always, never, or only when writing to a terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(name: &str) -> Result<ColorChoice, String> {
+        match name {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "Unknown color choice '{}'. Expected auto, always, or never",
+                name
+            )),
+        }
+    }
+
+    // Auto colors only a terminal, and respects `NO_COLOR` (https://no-color.org)
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+        }
+    }
+}
+
+/// How to draw the lines of the grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GridStyle {
+    // Dashes and pipes, as `display()` draws it
+    Ascii,
+
+    // Box-drawing characters, with double lines around each block
+    Unicode,
+
+    // No lines at all, just a gap between blocks, as `{}` formats a puzzle
+    Compact,
+}
+
+impl GridStyle {
+    pub fn parse(name: &str) -> Result<GridStyle, String> {
+        match name {
+            "ascii" => Ok(GridStyle::Ascii),
+            "unicode" => Ok(GridStyle::Unicode),
+            "compact" => Ok(GridStyle::Compact),
+            _ => Err(format!(
+                "Unknown grid style '{}'. Expected ascii, unicode, or compact",
+                name
+            )),
+        }
+    }
+}
+
+/// How unsolved cells show their candidates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CandidateStyle {
+    // Not at all, just a dot
+    Hidden,
+
+    // As a list, e.g. `[2,5,7]`
+    List,
+
+    // At fixed places, 1 top left thru 9 bottom right, as newspapers print them. Lines up candidates across cells, so patterns like X-Wings stand out.
+    PencilMarks,
+}
+
+/// The ANSI escape codes to draw each part of the grid with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorScheme {
+    pub given: String,
+    pub solved: String,
+
+    // Numbers placed in the last step
+    pub placed: String,
+
+    pub candidate: String,
+
+    // Candidates eliminated in the last step, which are still shown
+    pub eliminated: String,
+
+    // The cells in `DisplayOptions::highlight`
+    pub highlight: String,
+
+    // The cells the last step rests on, e.g. the naked pair, the fish's corners, or the chain
+    pub defining: String,
+
+    // The cells the last step acted on, given a number or losing candidates
+    pub target: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> ColorScheme {
+        Theme::Default.colors()
+    }
+}
+
+/// The colors to draw with, in the terminal and the HTML report, picked so each theme's highlights stay distinguishable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    // Givens in bold, numbers placed in the last step in green and earlier ones in blue, candidates dimmed with those just eliminated struck through in red, and highlighted cells on yellow. The last step's cells are on magenta, and those it rests on on cyan.
+    Default,
+
+    // For red-green colorblindness: blue, orange, and yellow in place of green, red, and magenta, from Okabe and Ito's palette
+    Deuteranopia,
+
+    // Bright colors on backgrounds dark text stands out on, and candidates left undimmed
+    HighContrast,
+
+    // No colors at all, just bold, underlined, italic, struck through, and reversed text
+    Mono,
+}
+
+impl Theme {
+    pub fn parse(name: &str) -> Result<Theme, String> {
+        match name {
+            "default" => Ok(Theme::Default),
+            "deuteranopia" => Ok(Theme::Deuteranopia),
+            "high-contrast" => Ok(Theme::HighContrast),
+            "mono" => Ok(Theme::Mono),
+            _ => Err(format!(
+                "Unknown theme '{}'. Expected default, deuteranopia, high-contrast, or mono",
+                name
+            )),
+        }
+    }
+
+    // The ANSI escape codes to draw the grid with in this theme.
+    pub fn colors(&self) -> ColorScheme {
+        match self {
+            Theme::Default => ColorScheme {
+                given: "\x1b[1m".to_string(),
+                solved: "\x1b[34m".to_string(),
+                placed: "\x1b[1;32m".to_string(),
+                candidate: "\x1b[2m".to_string(),
+                eliminated: "\x1b[9;31m".to_string(),
+                highlight: "\x1b[43m".to_string(),
+                defining: "\x1b[46m".to_string(),
+                target: "\x1b[45m".to_string(),
+            },
+            Theme::Deuteranopia => ColorScheme {
+                given: "\x1b[1m".to_string(),
+                solved: "\x1b[38;5;25m".to_string(),
+                placed: "\x1b[1;38;5;33m".to_string(),
+                candidate: "\x1b[2m".to_string(),
+                eliminated: "\x1b[9;38;5;166m".to_string(),
+                highlight: "\x1b[48;5;228m".to_string(),
+                defining: "\x1b[48;5;117m".to_string(),
+                target: "\x1b[48;5;214m".to_string(),
+            },
+            Theme::HighContrast => ColorScheme {
+                given: "\x1b[1m".to_string(),
+                solved: "\x1b[94m".to_string(),
+                placed: "\x1b[1;4;92m".to_string(),
+                candidate: String::new(),
+                eliminated: "\x1b[9;91m".to_string(),
+                highlight: "\x1b[7m".to_string(),
+                defining: "\x1b[30;103m".to_string(),
+                target: "\x1b[30;105m".to_string(),
+            },
+            Theme::Mono => ColorScheme {
+                given: "\x1b[1m".to_string(),
+                solved: String::new(),
+                placed: "\x1b[1;4m".to_string(),
+                candidate: "\x1b[2m".to_string(),
+                eliminated: "\x1b[9m".to_string(),
+                highlight: "\x1b[7m".to_string(),
+                defining: "\x1b[3m".to_string(),
+                target: "\x1b[4m".to_string(),
+            },
+        }
+    }
+}
+
+/// How to draw the grid as text. The default draws it as `display()` does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayOptions {
+    pub candidates: CandidateStyle,
+
+    // Columns given to each cell, or `None` for the usual width of the candidates shown. Cells are widened to fit their contents regardless.
+    pub cell_width: Option<usize>,
+
+    pub style: GridStyle,
+
+    // Cells to pick out with the highlight color. Only shown with colors.
+    pub highlight: Vec<(usize, usize)>,
+
+    // The colors to draw with, which also show off the last move, or `None` for plain text
+    pub colors: Option<ColorScheme>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> DisplayOptions {
+        DisplayOptions {
+            candidates: CandidateStyle::List,
+            cell_width: None,
+            style: GridStyle::Ascii,
+            highlight: vec![],
+            colors: None,
+        }
+    }
+}
+
+impl DisplayOptions {
+    // Just the numbers, as `{}` formats a puzzle
+    pub fn compact() -> DisplayOptions {
+        DisplayOptions {
+            candidates: CandidateStyle::Hidden,
+            style: GridStyle::Compact,
+            ..DisplayOptions::default()
+        }
+    }
+}
+
+// Text both as is and as it's to be shown, which may carry escape codes
+type Text = (String, String);
+
+struct LastMove {
+    placed: Vec<(usize, usize)>,
+    eliminated: Vec<((usize, usize), u8)>,
+    defining: Vec<(usize, usize)>,
+    targets: Vec<(usize, usize)>,
+}
+
+impl Puzzle {
+    // The grid drawn as the options say. Every text rendering of the grid, `display()` and `{}` included, goes through here.
+    pub fn display_with(&self, options: &DisplayOptions) -> String {
+        let colors = options.colors.as_ref();
+        let last_move = colors.map(|_| self.last_move());
+
+        // Lists sit flush against the pipes and compact numbers against each other, as they always have, but otherwise cells look cramped without a space before them
+        let margin = match (options.style, options.candidates) {
+            (GridStyle::Ascii, CandidateStyle::List)
+            | (GridStyle::Compact, CandidateStyle::Hidden) => "",
+            _ => " ",
+        };
+
+        let cells: Vec<Vec<Vec<Text>>> = (0..self.shape.size())
+            .map(|row| {
+                (0..self.shape.size())
+                    .map(|col| {
+                        cell_lines(
+                            self.shape,
+                            &self.grid[row][col],
+                            (row, col),
+                            options.candidates,
+                            colors.zip(last_move.as_ref()),
+                        )
+                        .into_iter()
+                        .map(|(plain, shown)| {
+                            (margin.to_string() + &plain, margin.to_string() + &shown)
+                        })
+                        .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Compact cells are only as wide as the widest of them
+        let usual = match (options.style, options.candidates) {
+            (GridStyle::Compact, _) => 0,
+            (_, CandidateStyle::List) => 13,
+            (_, CandidateStyle::PencilMarks) => 7,
+            (_, CandidateStyle::Hidden) => 3,
+        };
+        let widest = cells
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|(plain, _)| plain.chars().count())
+            .max()
+            .unwrap_or(0);
+        let outside = Outside::new(self, margin);
+        let width = options
+            .cell_width
+            .unwrap_or(usual)
+            .max(widest)
+            .max(outside.widest_column);
+
+        let cells: Vec<Vec<Vec<String>>> = cells
+            .iter()
+            .enumerate()
+            .map(|(row, texts)| {
+                texts
+                    .iter()
+                    .enumerate()
+                    .map(|(col, lines)| {
+                        // Highlighted cells stand out over the last step's, its targets over the cells defining it
+                        let position = (row, col);
+                        let highlight = match colors.zip(last_move.as_ref()) {
+                            Some((colors, _)) if options.highlight.contains(&position) => {
+                                Some(colors.highlight.as_str())
+                            }
+                            Some((colors, last_move)) if last_move.targets.contains(&position) => {
+                                Some(colors.target.as_str())
+                            }
+                            Some((colors, last_move)) if last_move.defining.contains(&position) => {
+                                Some(colors.defining.as_str())
+                            }
+                            _ => None,
+                        };
+                        lines
+                            .iter()
+                            .map(|line| pad(line, width, highlight))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let outside = outside.padded(width);
+        match options.style {
+            GridStyle::Ascii => draw_ascii(self.shape, &cells, width, &outside),
+            GridStyle::Unicode => draw_unicode(self.shape, &cells, width, &outside),
+            GridStyle::Compact => draw_compact(self.shape, &cells, &outside),
+        }
+    }
+
+    // The cells given a number in the last step, the candidates it eliminated, and the cells its consolidations rest on and act on.
+    fn last_move(&self) -> LastMove {
+        let mut placed: Vec<(usize, usize)> = Vec::new();
+        let mut eliminated: Vec<((usize, usize), u8)> = Vec::new();
+        let mut defining: Vec<(usize, usize)> = Vec::new();
+        let mut targets: Vec<(usize, usize)> = Vec::new();
+
+        if let Some(step) = self.steps.last() {
+            for (row, col) in self.shape.coords().map(<(usize, usize)>::from) {
+                let (before, after) = (step.before[row][col], step.after[row][col]);
+                if before.number.is_none() && after.number.is_some() {
+                    placed.push((row, col));
+                } else if after.number.is_none() {
+                    for candidate in before.candidates.iter() {
+                        if !after.candidates.contains(candidate) {
+                            eliminated.push(((row, col), candidate));
+                        }
+                    }
+                }
+            }
+
+            for consolidation in step.consolidations.iter() {
+                defining.extend(consolidation.defining_cells());
+                targets.extend(consolidation.targets());
+            }
+        }
+
+        LastMove {
+            placed,
+            eliminated,
+            defining,
+            targets,
+        }
+    }
+}
+
+// The lines of text for a cell of a grid of the shape, colored to show off the last move if there are colors to do it with. Pencil marks are laid out as the grid's blocks are, so take three lines of three on a 9x9 grid, with a number in the middle line.
+fn cell_lines(
+    shape: Shape,
+    cell: &Cell,
+    position: (usize, usize),
+    candidates: CandidateStyle,
+    colors: Option<(&ColorScheme, &LastMove)>,
+) -> Vec<Text> {
+    if let Some(n) = cell.number {
+        let n = symbol(n);
+        let shown = match colors {
+            None => n.to_string(),
+            Some((colors, last_move)) => {
+                let color = if cell.given {
+                    &colors.given
+                } else if last_move.placed.contains(&position) {
+                    &colors.placed
+                } else {
+                    &colors.solved
+                };
+                format!("{}{}{}", color, n, RESET)
+            }
+        };
+
+        return match candidates {
+            CandidateStyle::PencilMarks => {
+                let indent = " ".repeat(shape.block_cols() - 1);
+                let mut lines = vec![(String::new(), String::new()); shape.block_rows()];
+                lines[shape.block_rows() / 2] =
+                    (format!("{}{}", indent, n), format!("{}{}", indent, shown));
+                lines
+            }
+            _ => vec![(n.to_string(), shown)],
+        };
+    }
+
+    if candidates == CandidateStyle::Hidden {
+        return vec![("·".to_string(), "·".to_string())];
+    }
+
+    let eliminated: Vec<u8> = colors.map_or(vec![], |(_, last_move)| {
+        last_move
+            .eliminated
+            .iter()
+            .filter(|(p, _)| *p == position)
+            .map(|(_, d)| *d)
+            .collect()
+    });
+    let mut digits = cell.candidates.to_vec();
+    digits.extend(&eliminated);
+    digits.sort();
+
+    // Dimmed as a whole, with those just eliminated struck through
+    let (start, end) = colors.map_or(("", ""), |(colors, _)| (colors.candidate.as_str(), RESET));
+    let shown = |digit: u8| match colors {
+        Some((colors, _)) if eliminated.contains(&digit) => {
+            format!(
+                "{}{}{}{}",
+                colors.eliminated,
+                symbol(digit),
+                RESET,
+                colors.candidate
+            )
+        }
+        _ => symbol(digit).to_string(),
+    };
+
+    if candidates == CandidateStyle::List {
+        let plain: Vec<String> = digits.iter().map(|&d| symbol(d).to_string()).collect();
+        let colored: Vec<String> = digits.iter().map(|&d| shown(d)).collect();
+        return vec![(
+            format!("[{}]", plain.join(",")),
+            format!("{}[{}]{}", start, colored.join(","), end),
+        )];
+    }
+
+    let across = shape.block_cols();
+    (0..shape.block_rows())
+        .map(|line| {
+            let first = (across * line + 1) as u8;
+            let (plain, colored): (Vec<String>, Vec<String>) = (first..first + across as u8)
+                .map(|digit| {
+                    if digits.contains(&digit) {
+                        (symbol(digit).to_string(), shown(digit))
+                    } else {
+                        (" ".to_string(), " ".to_string())
+                    }
+                })
+                .unzip();
+            (
+                plain.join(" "),
+                format!("{}{}{}", start, colored.join(" "), end),
+            )
+        })
+        .collect()
+}
+
+// The text as shown, padded to the width given ignoring any escape codes, and highlighted if asked.
+fn pad((plain, shown): &Text, width: usize, highlight: Option<&str>) -> String {
+    let padding = " ".repeat(width.saturating_sub(plain.chars().count()));
+
+    match highlight {
+        // Each reset within would otherwise end the highlight early
+        Some(highlight) => format!(
+            "{}{}{}{}",
+            highlight,
+            shown.replace(RESET, &format!("{}{}", RESET, highlight)),
+            padding,
+            RESET
+        ),
+        None => format!("{}{}", shown, padding),
+    }
+}
+
+// Sandwich sudoku's sums, drawn outside the grid: each row's to its left, and the columns' along the top. With no sums, nothing at all is drawn.
+struct Outside {
+    // Each row's sum right-aligned, or spaces as wide where there's none
+    rows: Vec<String>,
+    // Each column's sum after the margin cells have, padded to the width of a cell once it's known, or none at all without sums for the columns
+    columns: Vec<String>,
+    // The widest of them, that cells must be as wide as
+    widest_column: usize,
+    // Spaces as wide as the rows' sums, for the lines between
+    blank: String,
+}
+
+impl Outside {
+    fn new(puzzle: &Puzzle, margin: &str) -> Outside {
+        let size = puzzle.shape.size();
+        let sandwiches = puzzle.sandwiches();
+        let text = |sum: Option<u16>| sum.map_or(String::new(), |sum| sum.to_string());
+
+        let rows = &sandwiches.rows[..size];
+        let widest = rows.iter().map(|&sum| text(sum).len()).max().unwrap_or(0);
+        let gutter = if widest > 0 { widest + 1 } else { 0 };
+        let columns: Vec<String> = match sandwiches.columns[..size].iter().any(Option::is_some) {
+            true => sandwiches.columns[..size]
+                .iter()
+                .map(|&sum| format!("{}{}", margin, text(sum)))
+                .collect(),
+            false => vec![],
+        };
+
+        Outside {
+            rows: rows
+                .iter()
+                .map(|&sum| match widest {
+                    0 => String::new(),
+                    _ => format!("{:>w$} ", text(sum), w = widest),
+                })
+                .collect(),
+            // Without a margin before cells, a space after each sum keeps them apart
+            widest_column: columns
+                .iter()
+                .map(|sum| sum.len() + usize::from(margin.is_empty()))
+                .max()
+                .unwrap_or(0),
+            columns,
+            blank: " ".repeat(gutter),
+        }
+    }
+
+    fn padded(self, width: usize) -> Outside {
+        Outside {
+            columns: self
+                .columns
+                .iter()
+                .map(|sum| format!("{:w$}", sum, w = width))
+                .collect(),
+            ..self
+        }
+    }
+
+    // What comes before a line of the row's cells, the sum on the middle line of them
+    fn left(&self, row: usize, line: usize, height: usize) -> &str {
+        match line == height / 2 {
+            true => self.rows[row].as_str(),
+            false => self.blank.as_str(),
+        }
+    }
+
+    // The line of the columns' sums, starting as far in as the lines of cells do, and spaced as their borders would be after each cell
+    fn header(&self, lead: &str, after: impl Fn(usize) -> &'static str) -> String {
+        if self.columns.is_empty() {
+            return String::new();
+        }
+
+        let mut line = format!("{}{}", self.blank, lead);
+        for (col, sum) in self.columns.iter().enumerate() {
+            line.push_str(sum);
+            line.push_str(after(col));
+        }
+        line.trim_end().to_string() + "\n"
+    }
+}
+
+// Lay out the cells of a grid of the shape, each one or more lines of text already padded to the width given.
+fn draw_ascii(shape: Shape, cells: &[Vec<Vec<String>>], width: usize, outside: &Outside) -> String {
+    if shape.is_jigsaw() {
+        return draw_ascii_jigsaw(shape, cells, width, outside);
+    }
+
+    let (size, block_rows, block_cols) = (shape.size(), shape.block_rows(), shape.block_cols());
+    let stacks = size / block_cols;
+    let rule = format!(
+        "\n{}{}\n",
+        outside.blank,
+        "-".repeat(size * width + stacks + 1)
+    );
+    let height = cells[0][0].len();
+
+    let header = outside.header(" ", |col| match (col + 1) % block_cols {
+        0 => " ",
+        _ => "",
+    });
+    let mut r = format!("\n{}{}", header, &rule[1..]);
+    for (row, texts) in cells.iter().enumerate() {
+        for line in 0..height {
+            if line > 0 {
+                r.push('\n');
+            }
+            r.push_str(outside.left(row, line, height));
+            r.push('|');
+
+            for (col, text) in texts.iter().enumerate() {
+                r.push_str(&text[line]);
+
+                if (col + 1) % block_cols == 0 {
+                    r.push('|');
+                }
+            }
+        }
+
+        if (row + 1) % block_rows == 0 {
+            r.push_str(&rule);
+        } else if height > 1 {
+            // Cells of several lines need a gap between them
+            let gap = vec![" ".repeat(block_cols * width); stacks];
+            r.push_str(&format!("\n{}|{}|\n", outside.blank, gap.join("|")));
+        } else {
+            r.push('\n');
+        }
+    }
+
+    r
+}
+
+// A jigsaw's regions turn anywhere, so there's room for a border after every cell and below every row, left blank where the region carries on.
+fn draw_ascii_jigsaw(
+    shape: Shape,
+    cells: &[Vec<Vec<String>>],
+    width: usize,
+    outside: &Outside,
+) -> String {
+    let size = shape.size();
+    let right = |row: usize, col: usize| shape.border_right(Coord::new(row, col));
+    let below = |row: usize, col: usize| shape.border_below(Coord::new(row, col));
+
+    let mut r = format!(
+        "\n{}{}{}\n",
+        outside.header(" ", |_| " "),
+        outside.blank,
+        "-".repeat(size * (width + 1) + 1)
+    );
+    for (row, texts) in cells.iter().enumerate() {
+        let height = texts[0].len();
+        for line in 0..height {
+            r.push_str(outside.left(row, line, height));
+            r.push('|');
+            for (col, text) in texts.iter().enumerate() {
+                r.push_str(&text[line]);
+                r.push(if right(row, col) { '|' } else { ' ' });
+            }
+            r.push('\n');
+        }
+
+        r.push_str(&outside.blank);
+        r.push(if below(row, 0) { '-' } else { '|' });
+        for col in 0..size {
+            let fill = if below(row, col) { "-" } else { " " };
+            r.push_str(&fill.repeat(width));
+
+            let across = below(row, col) || (col + 1 < size && below(row, col + 1));
+            let down = right(row, col) && (row + 1 == size || right(row + 1, col));
+            r.push(match (across, down) {
+                (true, _) => '-',
+                (false, true) => '|',
+                (false, false) => ' ',
+            });
+        }
+        r.push('\n');
+    }
+
+    r
+}
+
+// Blocks' borders are drawn double and the lines between cells single, where they meet taking the one or other from each way.
+fn draw_unicode(
+    shape: Shape,
+    cells: &[Vec<Vec<String>>],
+    width: usize,
+    outside: &Outside,
+) -> String {
+    let size = shape.size();
+    let right = |row: usize, col: usize| shape.border_right(Coord::new(row, col));
+    let below = |row: usize, col: usize| shape.border_below(Coord::new(row, col));
+
+    // The line below the row, or above the first for none: its ends, how it's drawn across cells, and where it meets the lines between cells
+    let rule = |row: Option<usize>| {
+        let (top, bottom) = (row.is_none(), row == Some(size - 1));
+        let across = |col: usize| row.is_none_or(|row| below(row, col));
+        let down = |col: usize| match row {
+            None => right(0, col),
+            Some(row) if row == size - 1 => right(row, col),
+            Some(row) => right(row, col) || right(row + 1, col),
+        };
+
+        let mut line = outside.blank.clone();
+        line.push_str(match (top, bottom, across(0)) {
+            (true, _, _) => "╔",
+            (_, true, _) => "╚",
+            (_, _, true) => "╠",
+            _ => "╟",
+        });
+        for col in 0..size {
+            line.push_str(&(if across(col) { "═" } else { "─" }).repeat(width));
+            line.push_str(if col == size - 1 {
+                match (top, bottom, across(col)) {
+                    (true, _, _) => "╗",
+                    (_, true, _) => "╝",
+                    (_, _, true) => "╣",
+                    _ => "╢",
+                }
+            } else {
+                let double = across(col) || across(col + 1);
+                match (top, bottom, double, down(col)) {
+                    (true, _, _, true) => "╦",
+                    (true, _, _, false) => "╤",
+                    (_, true, _, true) => "╩",
+                    (_, true, _, false) => "╧",
+                    (_, _, true, true) => "╬",
+                    (_, _, true, false) => "╪",
+                    (_, _, false, true) => "╫",
+                    (_, _, false, false) => "┼",
+                }
+            });
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut r = format!("\n{}{}", outside.header(" ", |_| " "), rule(None));
+    for (row, texts) in cells.iter().enumerate() {
+        let height = texts[0].len();
+        for line in 0..height {
+            r.push_str(outside.left(row, line, height));
+            r.push('║');
+
+            for (col, text) in texts.iter().enumerate() {
+                r.push_str(&text[line]);
+                r.push(if right(row, col) { '║' } else { '│' });
+            }
+            r.push('\n');
+        }
+
+        r.push_str(&rule(Some(row)));
+    }
+
+    r
+}
+
+fn draw_compact(shape: Shape, cells: &[Vec<Vec<String>>], outside: &Outside) -> String {
+    // A jigsaw's regions don't line up to leave gaps between
+    let gap = |col: usize| match !shape.is_jigsaw() && (col + 1).is_multiple_of(shape.block_cols())
+    {
+        true => "  ",
+        false => "",
+    };
+    let mut r = outside.header("", gap);
+
+    for (row, texts) in cells.iter().enumerate() {
+        let height = texts[0].len();
+        for line in 0..height {
+            r.push_str(outside.left(row, line, height));
+            for (col, text) in texts.iter().enumerate() {
+                r.push_str(&text[line]);
+                r.push_str(gap(col));
+            }
+            r.push('\n');
+        }
+
+        if !shape.is_jigsaw() && (row + 1) % shape.block_rows() == 0 {
+            r.push('\n');
+        }
+    }
+
+    r
+}
+
+// The text without its emoji, nor the spaces after each, for output read by scripts or kept in logs. Lines are otherwise kept as they are, grids and all.
+pub fn strip_emoji(text: &str) -> String {
+    let is_emoji = |c: char| matches!(c as u32, 0x1F000..=0x1FAFF | 0x2300..=0x23FF | 0x2600..=0x27BF | 0x203C | 0x2049 | 0x200D | 0xFE0F);
+
+    let mut plain = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_emoji(c) {
+            while chars.next_if(|&c| c == ' ' || is_emoji(c)).is_some() {}
+        } else {
+            plain.push(c);
+        }
+    }
+
+    let lines: Vec<&str> = plain.split('\n').map(str::trim_end).collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Consolidation, Justification, Puzzle, Technique, Unit};
+    use super::{
+        strip_emoji, CandidateStyle, ColorChoice, ColorScheme, DisplayOptions, GridStyle, Theme,
+        RESET,
+    };
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    fn strip(colored: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = colored.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    fn colored(options: DisplayOptions) -> DisplayOptions {
+        DisplayOptions {
+            colors: Some(ColorScheme::default()),
+            ..options
+        }
+    }
+
+    fn pencil(style: GridStyle) -> DisplayOptions {
+        DisplayOptions {
+            candidates: CandidateStyle::PencilMarks,
+            style,
+            ..DisplayOptions::default()
+        }
+    }
+
+    #[test]
+    fn last_move() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.assign_candidates();
+        let before = puzzle.grid;
+        puzzle.grid[0][0].number = Some(3);
+        puzzle.grid[0][0].candidates = Candidates::none();
+        puzzle.grid[0][2].remove_candidate(3);
+        puzzle.record_step(before);
+
+        let colors = ColorScheme::default();
+        let colored = puzzle.display_with(&colored(DisplayOptions::default()));
+        assert!(colored.contains(&format!("|{}3{}", colors.placed, RESET)));
+        assert!(colored.contains(&format!("{}4{}", colors.given, RESET)));
+        assert!(colored.contains(&format!("1,{}3{}", colors.eliminated, RESET)));
+
+        // Only the colors differ from the plain display
+        assert_eq!(
+            puzzle.display().replacen("[1,8]  ", "[1,3,8]", 1),
+            strip(&colored)
+        );
+    }
+
+    #[test]
+    fn annotations() {
+        let mut puzzle = Puzzle::parse("");
+        puzzle.assign_candidates();
+        let before = puzzle.grid;
+        puzzle.grid[0][5].remove_candidate(4);
+        puzzle.last_consolidation = vec![Consolidation::CandidatesEliminated {
+            technique: Technique::WaterCannon,
+            cells: vec![(0, 5)],
+            digits: vec![4],
+            justification: Justification::LockedCells(Unit::Block(0), vec![(0, 0), (0, 2)]),
+        }];
+        puzzle.record_step(before);
+
+        // The pointing cells on one color, the cell losing the 4 on another
+        let colors = ColorScheme::default();
+        let colored = puzzle.display_with(&colored(pencil(GridStyle::Unicode)));
+        let line = colored.lines().nth(2).unwrap();
+        let cells: Vec<&str> = line.split(['║', '│']).skip(1).collect();
+        assert!(cells[0].starts_with(&colors.defining));
+        assert!(!cells[1].contains(&colors.defining));
+        assert!(cells[2].starts_with(&colors.defining));
+        assert!(cells[5].starts_with(&colors.target));
+        assert!(!cells[8].contains(&colors.target));
+    }
+
+    #[test]
+    fn choices() {
+        assert_eq!(Ok(ColorChoice::Always), ColorChoice::parse("always"));
+        assert!(ColorChoice::parse("sometimes").is_err());
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+
+        assert_eq!(Ok(GridStyle::Unicode), GridStyle::parse("unicode"));
+        assert_eq!(Ok(GridStyle::Compact), GridStyle::parse("compact"));
+        assert!(GridStyle::parse("fancy").is_err());
+
+        assert_eq!(Ok(Theme::HighContrast), Theme::parse("high-contrast"));
+        assert!(Theme::parse("neon").is_err());
+        assert_eq!(ColorScheme::default(), Theme::Default.colors());
+
+        // Each theme tells the last step's cells apart from each other and from the highlighted ones
+        for theme in [
+            Theme::Default,
+            Theme::Deuteranopia,
+            Theme::HighContrast,
+            Theme::Mono,
+        ] {
+            let colors = theme.colors();
+            assert_ne!(colors.defining, colors.target);
+            assert_ne!(colors.highlight, colors.defining);
+            assert_ne!(colors.highlight, colors.target);
+            assert_ne!(colors.placed, colors.solved);
+        }
+    }
+
+    #[test]
+    fn grid_styles() {
+        let mut puzzle = Puzzle::parse(EASY);
+
+        assert!(format!("{}", puzzle)
+            .starts_with("·4·  5·2  ···  \n76·  ···  1·2  \n9··  ·18  ·64  \n\n"));
+        assert_eq!(
+            format!("{}", puzzle),
+            puzzle.display_with(&DisplayOptions::compact())
+        );
+
+        puzzle.assign_candidates();
+        let unicode = puzzle.display_with(&DisplayOptions {
+            style: GridStyle::Unicode,
+            ..DisplayOptions::default()
+        });
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!(20, lines.len());
+        assert!(lines[1].starts_with("╔═════════════╤═════════════╤═════════════╦═══"));
+        assert!(lines[2].starts_with("║ [1,3,8]     │ 4           │ [1,3,8]     ║ 5           │"));
+        assert!(lines[3].starts_with("╟─────────────┼─────────────┼─────────────╫───"));
+        assert!(lines[7].starts_with("╠═════════════╪═════════════╪═════════════╬═══"));
+        assert!(lines[19].ends_with("═════════════╧═════════════╝"));
+        assert!(lines[2..]
+            .iter()
+            .all(|line| line.chars().count() == lines[1].chars().count()));
+
+        // A jigsaw's borders go wherever its regions do
+        let jigsaw = Puzzle::parse("1...\n....\n....\n...4\n# regions\n1112\n1222\n3334\n3444");
+        let numbers = |style| DisplayOptions {
+            style,
+            candidates: CandidateStyle::Hidden,
+            cell_width: Some(2),
+            ..DisplayOptions::default()
+        };
+        let unicode = jigsaw.display_with(&numbers(GridStyle::Unicode));
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!("╔══╤══╤══╦══╗", lines[1]);
+        assert_eq!("║ 1│ ·│ ·║ ·║", lines[2]);
+        assert_eq!("╟──╬══╪══╬──╢", lines[3]);
+        assert_eq!("║ ·║ ·│ ·│ ·║", lines[4]);
+        assert_eq!("╚══╩══╧══╧══╝", lines[9]);
+
+        let ascii = jigsaw.display_with(&numbers(GridStyle::Ascii));
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!("| 1  ·  ·| ·|", lines[2]);
+        assert_eq!("|  -------  |", lines[3]);
+        assert_eq!("-------------", lines[5]);
+        assert!(format!("{}", jigsaw).starts_with("1···\n····\n"));
+    }
+
+    #[test]
+    fn sandwiches() {
+        let puzzle =
+            Puzzle::parse("1...\n....\n....\n...4\n# sandwiches\nrows 2 . . 0\ncolumns . 5 . .");
+        let numbers = |style| DisplayOptions {
+            style,
+            candidates: CandidateStyle::Hidden,
+            cell_width: Some(2),
+            ..DisplayOptions::default()
+        };
+
+        let ascii = puzzle.display_with(&numbers(GridStyle::Ascii));
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!("      5", lines[1]);
+        assert_eq!("  -----------", lines[2]);
+        assert_eq!("2 | 1 ·| · ·|", lines[3]);
+        assert_eq!("  | · ·| · ·|", lines[4]);
+        assert_eq!("0 | · ·| · 4|", lines[7]);
+
+        let unicode = puzzle.display_with(&numbers(GridStyle::Unicode));
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!("       5", lines[1]);
+        assert_eq!("  ╔══╤══╦══╤══╗", lines[2]);
+        assert_eq!("2 ║ 1│ ·║ ·│ ·║", lines[3]);
+
+        let compact = format!("{}", puzzle);
+        assert!(
+            compact.starts_with("    5\n2 1 ·   · ·   \n  · ·   · ·   \n"),
+            "{}",
+            compact
+        );
+
+        // Without sums, nothing's drawn outside the grid
+        let plain = Puzzle::parse("1...\n....\n....\n...4\n");
+        assert!(plain
+            .display_with(&numbers(GridStyle::Ascii))
+            .starts_with("\n-----------\n| 1 ·| · ·|\n"));
+    }
+
+    #[test]
+    fn cell_width() {
+        let puzzle = Puzzle::parse(EASY);
+        let numbers = puzzle.display_with(&DisplayOptions {
+            candidates: CandidateStyle::Hidden,
+            cell_width: Some(2),
+            ..DisplayOptions::default()
+        });
+        assert!(numbers.starts_with(&format!("\n{}\n| · 4 ·| 5 · 2| · · ·|\n", "-".repeat(22))));
+
+        // Never narrower than the widest cell
+        let mut blank = Puzzle::parse("");
+        blank.assign_candidates();
+        let candidates = blank.display_with(&DisplayOptions {
+            cell_width: Some(5),
+            ..DisplayOptions::default()
+        });
+        assert!(candidates.starts_with(&format!(
+            "\n{}\n|[1,2,3,4,5,6,7,8,9][1,2",
+            "-".repeat(9 * 19 + 4)
+        )));
+    }
+
+    #[test]
+    fn highlight() {
+        let puzzle = Puzzle::parse(EASY);
+        let options = DisplayOptions {
+            highlight: vec![(0, 1)],
+            ..DisplayOptions::default()
+        };
+        assert_eq!(puzzle.display(), puzzle.display_with(&options));
+
+        let colors = ColorScheme::default();
+        assert!(puzzle.display_with(&colored(options)).contains(&format!(
+            "{0}{1}4{2}{0}{3}{2}",
+            colors.highlight,
+            colors.given,
+            RESET,
+            " ".repeat(12)
+        )));
+    }
+
+    #[test]
+    fn pencil_marks() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.assign_candidates();
+
+        // [1,3,8], 4, [1,3,8], 5 along the top
+        let unicode = puzzle.display_with(&pencil(GridStyle::Unicode));
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!(38, lines.len());
+        assert!(lines[1].starts_with("╔═══════╤═══════╤═══════╦"));
+        assert!(lines[2].starts_with("║ 1   3 │       │ 1   3 ║       │"));
+        assert!(lines[3].starts_with("║       │   4   │       ║   5   │"));
+        assert!(lines[4].starts_with("║   8   │       │   8   ║       │"));
+        assert!(lines[5].starts_with("╟───────┼───────┼───────╫"));
+        assert!(lines
+            .iter()
+            .skip(1)
+            .all(|line| line.chars().count() == 9 * 7 + 10));
+
+        let ascii = puzzle.display_with(&pencil(GridStyle::Ascii));
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(38, lines.len());
+        assert!(lines[2].starts_with("| 1   3         1   3 |"));
+        assert!(lines[5].starts_with("|                     |"));
+
+        // Colors change nothing else
+        let mut last_move = puzzle.clone();
+        let before = last_move.grid;
+        last_move.grid[0][2].remove_candidate(3);
+        last_move.record_step(before);
+        let colored = last_move.display_with(&colored(pencil(GridStyle::Ascii)));
+        assert_eq!(ascii, strip(&colored));
+        assert!(colored.contains(&format!("{}3{}", ColorScheme::default().eliminated, RESET)));
+
+        // Laid out as the cells of a block, two rows of three in a 6x6 grid
+        let mut six = Puzzle::parse(include_str!("../samples/six.txt"));
+        six.assign_candidates();
+        let unicode = six.display_with(&pencil(GridStyle::Unicode));
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!(20, lines.len());
+        assert!(lines[1].starts_with("╔═══════╤═══════╤═══════╦═══════╤"));
+        assert!(lines[2].starts_with("║ 1   3 │     3 │ 1   3 ║       │"));
+        assert!(lines[3].starts_with("║ 4   6 │ 4   6 │ 4   6 ║ 4     │   2   │"));
+        assert!(lines[4].starts_with("╟───────┼───────┼───────╫"));
+    }
+
+    #[test]
+    fn emoji() {
+        assert_eq!(
+            "Gave up after 5 steps",
+            strip_emoji("⏱️  Gave up after 5 steps")
+        );
+        assert_eq!("Solved!\n|3 4 1|", strip_emoji("Solved! 🙌🙌🙌\n|3 4 1|"));
+        assert_eq!("", strip_emoji("🧶🧶🧶 🧶"));
+        assert_eq!("r1c1 → r1c2 ═", strip_emoji("r1c1 → r1c2 ═"));
+    }
+}
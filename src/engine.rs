@@ -0,0 +1,276 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use super::shape::digit;
+use super::{
+    solve_with_guesses, symbol, Cell, Consolidation, Coord, Puzzle, PuzzleStatus, Shape, Step,
+};
+
+// A `go solve` running in the background, and the flag to stop it with
+struct Search {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+// Drive the solver over a line protocol, in the spirit of the UCI protocol chess GUIs drive engines with. Commands, one per line:
+//
+//    * `position <81 characters>`: the puzzle row by row, `1` to `9` for givens and anything else, such as `.` or `0`, for blanks. Other grids take as many as they have cells, 256 for 16x16, with `A` on for the givens past 9
+//    * `go hint`: an `info` line explaining the next step, then `hint assign r4c7 2`, `hint eliminate r1c6,r1c9 4`, or `hint none`
+//    * `go solve`: solve in the background, with an `info step <iteration> <explanation>` line for each consolidation as it's made, then `solution <81 characters>`, `unsolved <81 characters>` with `.` for blanks, or `illdefined <reason>`
+//    * `stop`: end a `go solve` after the step it's on, which then replies `unsolved` with the grid as far as it got. Guessing, once started, runs to the end.
+//    * `isready`: reply `readyok`, even mid-solve
+//    * `quit`: stop and exit
+//
+// Anything else is answered with `error <message>`. Once the input ends any solve in progress is left to finish, and the output is handed back.
+pub fn engine<R: BufRead, W: Write + Send + 'static>(input: R, output: W) -> std::io::Result<W> {
+    let output = Arc::new(Mutex::new(output));
+    let mut puzzle: Option<Puzzle> = None;
+    let mut search: Option<Search> = None;
+    let mut quit = false;
+
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => {}
+            ["position", grid] => {
+                end(search.take(), true);
+                match parse_position(grid) {
+                    Ok(position) => puzzle = Some(position),
+                    Err(message) => reply(&output, &format!("error {}", message))?,
+                }
+            }
+            ["go", "hint"] => match &puzzle {
+                Some(puzzle) => {
+                    for line in hint(puzzle) {
+                        reply(&output, &line)?;
+                    }
+                }
+                None => reply(&output, "error No position to hint at")?,
+            },
+            ["go", "solve"] => {
+                end(search.take(), true);
+                match &puzzle {
+                    Some(puzzle) => search = Some(solve(puzzle.clone(), output.clone())),
+                    None => reply(&output, "error No position to solve")?,
+                }
+            }
+            ["stop"] => end(search.take(), true),
+            ["isready"] => reply(&output, "readyok")?,
+            ["quit"] => {
+                quit = true;
+                break;
+            }
+            _ => reply(&output, &format!("error Unknown command '{}'", line.trim()))?,
+        }
+    }
+
+    end(search.take(), quit);
+
+    match Arc::try_unwrap(output) {
+        Ok(output) => Ok(output.into_inner().unwrap()),
+        Err(_) => unreachable!("The search holding the output has ended"),
+    }
+}
+
+fn reply<W: Write>(output: &Mutex<W>, line: &str) -> std::io::Result<()> {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", line)?;
+    output.flush()
+}
+
+// Wait for the search to end, stopping it first if asked.
+fn end(search: Option<Search>, stop: bool) {
+    if let Some(search) = search {
+        if stop {
+            search.stop.store(true, Ordering::Relaxed);
+        }
+        let _ = search.handle.join();
+    }
+}
+
+pub(super) fn parse_position(grid: &str) -> Result<Puzzle, String> {
+    let mut puzzle = Puzzle::parse("");
+    read_position(&mut puzzle, grid)?;
+    Ok(puzzle)
+}
+
+// Read the position into the puzzle in place, as `parse_position()` reads one, starting it over with its config and buffers kept.
+pub(super) fn read_position(puzzle: &mut Puzzle, grid: &str) -> Result<(), String> {
+    let count = grid.chars().count();
+    let shape = match Shape::of_cell_count(count) {
+        Some(shape) => shape,
+        None => {
+            return Err(format!(
+            "Expected a grid's worth of characters for the position, such as 81 for 9x9, not {}",
+            count
+        ))
+        }
+    };
+
+    puzzle.start_over(shape);
+    let size = shape.size();
+    for (i, c) in grid.chars().enumerate() {
+        if let Some(number) = digit(c, size) {
+            puzzle.grid[i / size][i % size] = Cell::with_number(number);
+        }
+    }
+    Ok(())
+}
+
+// The grid as a position, `.` for blanks.
+pub(super) fn position(puzzle: &Puzzle) -> String {
+    puzzle
+        .shape
+        .coords()
+        .map(|coord| match puzzle[coord].number {
+            Some(n) => symbol(n),
+            None => '.',
+        })
+        .collect()
+}
+
+fn hint(puzzle: &Puzzle) -> Vec<String> {
+    let cell = |position: (usize, usize)| Coord::from(position).to_string();
+
+    let hint = match puzzle.hint() {
+        Some(hint) => hint,
+        None => return vec!["hint none".to_string()],
+    };
+
+    let action = match (&hint, hint.assignment()) {
+        (_, Some((position, number))) => format!("hint assign {} {}", cell(position), number),
+        (Consolidation::CandidatesEliminated { cells, digits, .. }, None) => {
+            let cells: Vec<String> = cells.iter().map(|&p| cell(p)).collect();
+            let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+            format!("hint eliminate {} {}", cells.join(","), digits.join(","))
+        }
+        _ => unreachable!("Every other consolidation is an assignment"),
+    };
+
+    vec![format!("info {}", hint.describe()), action]
+}
+
+// `solve_reporting()` in the background, replying with each step as it's taken, then the outcome.
+fn solve<W: Write + Send + 'static>(puzzle: Puzzle, output: Arc<Mutex<W>>) -> Search {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopping = stop.clone();
+
+    let handle = std::thread::spawn(move || {
+        let puzzle = solve_reporting(puzzle, &stopping, |step| {
+            for consolidation in step.consolidations.iter() {
+                let line = format!("info step {} {}", step.iteration, consolidation.describe());
+                let _ = reply(&output, &line);
+            }
+        });
+
+        let outcome = match puzzle.status() {
+            PuzzleStatus::Solved => format!("solution {}", position(&puzzle)),
+            PuzzleStatus::Unsolved => format!("unsolved {}", position(&puzzle)),
+            PuzzleStatus::IllDefined(reason) => format!("illdefined {:?}", reason),
+        };
+        let _ = reply(&output, &outcome);
+    });
+
+    Search { stop, handle }
+}
+
+// Like `solve()` then `solve_with_guesses()`, looking out for `stop` between steps and reporting each step as it's taken. Returns the puzzle as far as it got.
+pub(super) fn solve_reporting(
+    mut puzzle: Puzzle,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&Step),
+) -> Puzzle {
+    let mut reported = 0;
+    let mut report_new = |puzzle: &Puzzle| {
+        for step in puzzle.steps[reported..].iter() {
+            report(step);
+        }
+        reported = puzzle.steps.len();
+    };
+
+    while !stop.load(Ordering::Relaxed) {
+        let progress = puzzle.step();
+        report_new(&puzzle);
+
+        if !progress.iter().any(Consolidation::is_assignment)
+            || puzzle.status() != PuzzleStatus::Unsolved
+        {
+            break;
+        }
+    }
+
+    if !stop.load(Ordering::Relaxed) && puzzle.status() == PuzzleStatus::Unsolved {
+        if let Ok(Some(solved)) = solve_with_guesses(puzzle.clone()) {
+            puzzle = solved;
+            report_new(&puzzle);
+        }
+    }
+
+    puzzle
+}
+
+#[cfg(test)]
+mod test {
+    use super::engine;
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+    const EASY_SOLVED: &str =
+        "341562789768934152952718364574291638189346275623875491215687943496153827837429516";
+
+    fn run(input: &str) -> Vec<String> {
+        let output = engine(input.as_bytes(), Vec::new()).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn commands() {
+        let replies = run("isready\ngo hint\nposition 123\nfrobnicate\nstop\n");
+        assert_eq!("readyok", replies[0]);
+        assert_eq!("error No position to hint at", replies[1]);
+        assert_eq!(
+            "error Expected a grid's worth of characters for the position, such as 81 for 9x9, not 3",
+            replies[2]
+        );
+        assert_eq!("error Unknown command 'frobnicate'", replies[3]);
+        assert_eq!(4, replies.len());
+    }
+
+    #[test]
+    fn hint() {
+        let replies = run(&format!("position {}\ngo hint\n", EASY));
+        assert_eq!(2, replies.len());
+        assert!(replies[0].starts_with("info R"));
+        assert!(replies[0].contains(" must be "));
+        assert!(replies[1].starts_with("hint assign r"));
+
+        let solved = run(&format!("position {}\ngo hint\n", EASY_SOLVED));
+        assert_eq!(vec!["hint none".to_string()], solved);
+    }
+
+    #[test]
+    fn solve() {
+        let replies = run(&format!("position {}\ngo solve\n", EASY.replace('.', "0")));
+        assert_eq!(
+            format!("solution {}", EASY_SOLVED),
+            *replies.last().unwrap()
+        );
+        assert!(replies.len() > 1);
+        assert!(replies[..replies.len() - 1]
+            .iter()
+            .all(|line| line.starts_with("info step ")));
+        assert!(replies[0].starts_with("info step 1 "));
+
+        // Two givens of 4 in the first row
+        let broken = run(&format!("position 44{}\ngo solve\n", &EASY[2..]));
+        assert!(broken.last().unwrap().starts_with("illdefined "));
+    }
+}
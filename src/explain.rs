@@ -0,0 +1,282 @@
+use super::{Consolidation, Coord, Justification, LineKind, Technique, Unit};
+
+impl Consolidation {
+    // The consolidation as a sentence, e.g. "R4C7 must be 2: it is the only cell in box 6 that can hold 2."
+    //
+    // Rows, columns, and boxes are counted from 1 here, as a person solving on paper would.
+    pub fn describe(&self) -> String {
+        if let Some((position, number)) = self.assignment() {
+            let cell = cell_name(position);
+
+            return match self.justification() {
+                Justification::Guess => format!(
+                    "Guess {} is {}, to be abandoned if it leads to a contradiction.",
+                    cell, number
+                ),
+                Justification::OnlyPlaceInUnit(unit) => format!(
+                    "{} must be {}: it is the only cell in {} that can hold {}.",
+                    cell,
+                    number,
+                    unit_name(unit),
+                    number
+                ),
+                Justification::Peers(peers) if !peers.is_empty() => format!(
+                    "{} must be {}: it is the only number left for the cell after those in {}.",
+                    cell,
+                    number,
+                    list(&cell_names(peers), "and")
+                ),
+                _ => format!(
+                    "{} must be {}: it is the only number left for the cell.",
+                    cell, number
+                ),
+            };
+        }
+
+        let Consolidation::CandidatesEliminated {
+            technique,
+            cells,
+            digits,
+            justification,
+        } = self
+        else {
+            unreachable!("Every other consolidation is an assignment");
+        };
+
+        let digit_names: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+        let eliminated = format!(
+            "{} can't be {}",
+            list(&cell_names(cells), "and"),
+            list(&digit_names, "or")
+        );
+
+        match justification {
+            Justification::LockedCells(unit, locked) => format!(
+                "{}: in {}, {} must go in {}.",
+                eliminated,
+                unit_name(unit),
+                list(&digit_names, "and"),
+                list(&cell_names(locked), "or")
+            ),
+            Justification::Pattern(pattern) => format!(
+                "{}: it is ruled out by the {} on {}.",
+                eliminated,
+                technique_name(*technique),
+                list(&cell_names(pattern), "and")
+            ),
+            Justification::Peers(peers) => format!(
+                "{}: {} already hold it.",
+                eliminated,
+                list(&cell_names(peers), "and")
+            ),
+            Justification::OnlyPlaceInUnit(unit) => format!(
+                "{}: it belongs elsewhere in {}.",
+                eliminated,
+                unit_name(unit)
+            ),
+            Justification::Sandwich(unit, sum) => format!(
+                "{}: the digits sandwiched between the 1 and 9 of {} must add up to {}.",
+                eliminated,
+                unit_name(unit),
+                sum
+            ),
+            Justification::Arrow(arrow) => format!(
+                "{}: the digits along the arrow through {} must add up to the one circled in {}.",
+                eliminated,
+                list(&cell_names(&arrow[1..]), "and"),
+                cell_name(arrow[0])
+            ),
+            Justification::Xv([a, b], Some(mark)) => format!(
+                "{}: the {} between {} and {} means their digits must add up to {}.",
+                eliminated,
+                mark.letter(),
+                cell_name(*a),
+                cell_name(*b),
+                mark.sum()
+            ),
+            Justification::Xv([a, b], None) => format!(
+                "{}: with no X or V between {} and {}, their digits can't add up to 10 or 5.",
+                eliminated,
+                cell_name(*a),
+                cell_name(*b)
+            ),
+            Justification::Line(LineKind::Renban, cells) => format!(
+                "{}: the renban line through {} must hold a run of consecutive digits, none repeated.",
+                eliminated,
+                list(&cell_names(cells), "and")
+            ),
+            Justification::Line(LineKind::Whisper, cells) => format!(
+                "{}: digits side by side along the whisper through {} must be at least 5 apart.",
+                eliminated,
+                list(&cell_names(cells), "and")
+            ),
+            Justification::Guess => format!("{}: it's a guess.", eliminated),
+        }
+    }
+
+    pub(super) fn justification(&self) -> &Justification {
+        match self {
+            Consolidation::SingleCandidateForCell(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForBlock(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForRow(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForColumn(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForDiagonal(assignment)
+            | Consolidation::Guess(assignment) => &assignment.justification,
+            Consolidation::CandidatesEliminated { justification, .. } => justification,
+        }
+    }
+
+    // The cells the move rests on, as its justification names them: the naked pair, the fish's corners, the chain. A hidden single's unit and a sandwich's sum name no cells of their own.
+    pub fn defining_cells(&self) -> Vec<(usize, usize)> {
+        match self.justification() {
+            Justification::Peers(cells)
+            | Justification::LockedCells(_, cells)
+            | Justification::Pattern(cells)
+            | Justification::Arrow(cells)
+            | Justification::Line(_, cells) => cells.clone(),
+            Justification::Xv(cells, _) => cells.to_vec(),
+            Justification::OnlyPlaceInUnit(_)
+            | Justification::Sandwich(..)
+            | Justification::Guess => vec![],
+        }
+    }
+
+    // The cells the move acts on: the cell given a number, or those losing candidates.
+    pub fn targets(&self) -> Vec<(usize, usize)> {
+        match self {
+            Consolidation::CandidatesEliminated { cells, .. } => cells.clone(),
+            _ => self
+                .assignment()
+                .map(|(position, _)| position)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn cell_name(position: (usize, usize)) -> String {
+    Coord::from(position).to_string().to_uppercase()
+}
+
+fn cell_names(positions: &[(usize, usize)]) -> Vec<String> {
+    positions.iter().map(|&p| cell_name(p)).collect()
+}
+
+pub(super) fn unit_name(unit: &Unit) -> String {
+    match unit {
+        Unit::Row(row) => format!("row {}", row + 1),
+        Unit::Column(col) => format!("column {}", col + 1),
+        Unit::Block(b) => format!("box {}", b + 1),
+        Unit::Diagonal(0) => "the diagonal from the top left".to_string(),
+        Unit::Diagonal(_) => "the diagonal from the top right".to_string(),
+    }
+}
+
+pub(super) fn technique_name(technique: Technique) -> &'static str {
+    match technique {
+        Technique::NakedSingle => "naked single",
+        Technique::HiddenSingle => "hidden single",
+        Technique::SaraFlex => "naked subset",
+        Technique::WaterCannon => "pointing candidates",
+        Technique::XWing => "X-Wing",
+        Technique::UniqueRectangle => "unique rectangle",
+        Technique::UniqueLoop => "unique loop",
+        Technique::BugLite => "BUG-lite pattern",
+        Technique::AlternatingInferenceChain => "alternating inference chain",
+        Technique::AlignedPairExclusion => "aligned pair exclusion",
+        Technique::AlmostLockedSetChain => "almost locked set chain",
+        Technique::ForcingChain => "forcing chain",
+        Technique::Sandwich => "sandwich sum",
+        Technique::Arrow => "arrow",
+        Technique::Xv => "XV",
+        Technique::Renban => "renban",
+        Technique::Whisper => "German whisper",
+        Technique::HouseRule => "house rule",
+    }
+}
+
+// "a", "a and b", "a, b and c"
+pub(super) fn list(items: &[String], conjunction: &str) -> String {
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} {} {}", rest.join(", "), conjunction, last),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{CellAssignment, Consolidation, Justification, Technique, Unit};
+
+    #[test]
+    fn assignments() {
+        let hidden = Consolidation::OnlyOnePossibleCandidateForRow(CellAssignment {
+            number: 2,
+            block: 5,
+            row: 3,
+            col: 6,
+            justification: Justification::OnlyPlaceInUnit(Unit::Block(5)),
+        });
+        assert_eq!(
+            "R4C7 must be 2: it is the only cell in box 6 that can hold 2.",
+            hidden.describe()
+        );
+
+        let naked = Consolidation::SingleCandidateForCell(CellAssignment {
+            number: 9,
+            block: 4,
+            row: 3,
+            col: 5,
+            justification: Justification::Peers(vec![(3, 0), (8, 5)]),
+        });
+        assert_eq!(
+            "R4C6 must be 9: it is the only number left for the cell after those in R4C1 and R9C6.",
+            naked.describe()
+        );
+
+        let guess = Consolidation::Guess(CellAssignment {
+            number: 7,
+            block: 8,
+            row: 8,
+            col: 8,
+            justification: Justification::Guess,
+        });
+        assert_eq!(
+            "Guess R9C9 is 7, to be abandoned if it leads to a contradiction.",
+            guess.describe()
+        );
+    }
+
+    #[test]
+    fn eliminations() {
+        let pointing = Consolidation::CandidatesEliminated {
+            technique: Technique::WaterCannon,
+            cells: vec![(0, 5), (0, 8)],
+            digits: vec![4],
+            justification: Justification::LockedCells(Unit::Block(0), vec![(0, 0), (0, 2)]),
+        };
+        assert_eq!(
+            "R1C6 and R1C9 can't be 4: in box 1, 4 must go in R1C1 or R1C3.",
+            pointing.describe()
+        );
+
+        let x_wing = Consolidation::CandidatesEliminated {
+            technique: Technique::XWing,
+            cells: vec![(3, 2)],
+            digits: vec![7, 8],
+            justification: Justification::Pattern(vec![(1, 2), (1, 6), (5, 6), (5, 2)]),
+        };
+        assert_eq!(
+            "R4C3 can't be 7 or 8: it is ruled out by the X-Wing on R2C3, R2C7, R6C7 and R6C3.",
+            x_wing.describe()
+        );
+
+        // The pattern defines the move, and the cells losing candidates are its targets
+        assert_eq!(
+            vec![(1, 2), (1, 6), (5, 6), (5, 2)],
+            x_wing.defining_cells()
+        );
+        assert_eq!(vec![(3, 2)], x_wing.targets());
+        assert_eq!(vec![(0, 0), (0, 2)], pointing.defining_cells());
+    }
+}
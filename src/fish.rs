@@ -0,0 +1,93 @@
+use super::{Consolidation, Justification, Puzzle, Technique};
+
+impl Puzzle {
+    // X-Wing: a digit with exactly two places in each of two rows, both in the same two columns. Whichever way round it goes, the digit takes both columns within those rows, so it can be removed from the rest of the two columns. Likewise with rows and columns swapped.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_x_wings(&mut self) -> Vec<Consolidation> {
+        for digit in self.shape.digits().iter() {
+            for by_rows in [true, false] {
+                // Positions of the digit along each line, as (line, position within the line)
+                let places: Vec<Vec<usize>> = (0..self.shape.size())
+                    .map(|line| {
+                        (0..self.shape.size())
+                            .filter(|&i| {
+                                let (row, col) = if by_rows { (line, i) } else { (i, line) };
+                                self.grid[row][col].candidates.contains(digit)
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                for a in 0..self.shape.size() {
+                    for b in (a + 1)..self.shape.size() {
+                        if places[a].len() != 2 || places[a] != places[b] {
+                            continue;
+                        }
+
+                        let eliminations =
+                            self.eliminate_x_wing(digit, by_rows, [a, b], &places[a]);
+                        if !eliminations.is_empty() {
+                            return eliminations;
+                        }
+                    }
+                }
+            }
+        }
+
+        vec![]
+    }
+
+    fn eliminate_x_wing(
+        &mut self,
+        digit: u8,
+        by_rows: bool,
+        lines: [usize; 2],
+        crossings: &[usize],
+    ) -> Vec<Consolidation> {
+        let position = |line: usize, i: usize| if by_rows { (line, i) } else { (i, line) };
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for &i in crossings {
+            for line in (0..self.shape.size()).filter(|l| !lines.contains(l)) {
+                let (row, col) = position(line, i);
+                if self.grid[row][col].remove_candidate(digit) {
+                    log!(self, "🐟🐟🐟🐟🐟 X-Wing on {} in {} {} and {} eliminated candidate {} at grid position ({}, {})", digit, if by_rows { "rows" } else { "columns" }, lines[0], lines[1], digit, row, col);
+                    removals.push(((row, col), digit));
+                }
+            }
+        }
+
+        let corners = vec![
+            position(lines[0], crossings[0]),
+            position(lines[0], crossings[1]),
+            position(lines[1], crossings[1]),
+            position(lines[1], crossings[0]),
+        ];
+
+        Consolidation::eliminations(Technique::XWing, Justification::Pattern(corners), &removals)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Cell, Puzzle};
+
+    #[test]
+    fn rows() {
+        // 7 can only go in columns 2 and 6 of rows 1 and 5
+        let mut puzzle = Puzzle::parse("");
+        puzzle.grid[1][2] = Cell::with_candidates(vec![3, 7]);
+        puzzle.grid[1][6] = Cell::with_candidates(vec![4, 7]);
+        puzzle.grid[5][2] = Cell::with_candidates(vec![7, 8]);
+        puzzle.grid[5][6] = Cell::with_candidates(vec![7, 9]);
+        puzzle.grid[3][2] = Cell::with_candidates(vec![1, 7]);
+        puzzle.grid[8][6] = Cell::with_candidates(vec![2, 7]);
+        puzzle.grid[3][4] = Cell::with_candidates(vec![5, 7]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_x_wings().len());
+        assert_eq!(vec![1], puzzle.grid[3][2].candidates.to_vec());
+        assert_eq!(vec![2], puzzle.grid[8][6].candidates.to_vec());
+        assert_eq!(vec![5, 7], puzzle.grid[3][4].candidates.to_vec());
+    }
+}
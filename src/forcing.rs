@@ -0,0 +1,257 @@
+use super::{
+    Candidates, Consolidation, Coord, Geometry, Grid, Justification, Puzzle, Technique, Unit,
+};
+
+// How many rounds of singles a branch may propagate before we stop following it. Keeps forcing chains a bounded lookahead rather than a full search like `solve_with_guesses`.
+const FORCING_CHAIN_MAX_DEPTH: usize = 12;
+
+impl Puzzle {
+    // Forcing chains: assume each alternative of a choice in turn and follow the singles each assumption forces. Whatever is falsified in every branch is false, since one of the alternatives must be true.
+    //
+    //    * Cell forcing chains branch on each candidate of a single cell
+    //    * Unit forcing chains branch on each position of a digit within a row, column, or block
+    //
+    // A branch ending in a contradiction falsifies everything, so it's simply skipped.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_forcing_chains(&mut self) -> Vec<Consolidation> {
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                if self.out_of_time() {
+                    return vec![];
+                }
+
+                let candidates = self.grid[row][col].candidates;
+                if self.grid[row][col].number.is_some() || candidates.count() < 2 {
+                    continue;
+                }
+
+                let assumptions: Vec<(usize, usize, u8)> =
+                    candidates.iter().map(|d| (row, col, d)).collect();
+                let source = format!("Cell forcing chain from ({}, {})", row, col);
+
+                let eliminations = self.eliminate_by_forcing(&assumptions, &source);
+                if !eliminations.is_empty() {
+                    return eliminations;
+                }
+            }
+        }
+
+        for unit in self.shape.units() {
+            let cells = unit.cells(self.shape);
+            for digit in self.shape.digits().iter() {
+                if self.out_of_time() {
+                    return vec![];
+                }
+
+                let assumptions: Vec<(usize, usize, u8)> = cells
+                    .iter()
+                    .filter(|&&(r, c)| self.grid[r][c].candidates.contains(digit))
+                    .map(|&(r, c)| (r, c, digit))
+                    .collect();
+                if assumptions.len() < 2 {
+                    continue;
+                }
+
+                let source = format!("Unit forcing chain on {} in {}", digit, unit_name(unit));
+
+                let eliminations = self.eliminate_by_forcing(&assumptions, &source);
+                if !eliminations.is_empty() {
+                    return eliminations;
+                }
+            }
+        }
+
+        vec![]
+    }
+
+    // Follow each assumption and remove every candidate that none of the surviving branches allow.
+    fn eliminate_by_forcing(
+        &mut self,
+        assumptions: &[(usize, usize, u8)],
+        source: &str,
+    ) -> Vec<Consolidation> {
+        let branches: Vec<Grid> = assumptions
+            .iter()
+            .filter_map(|&assumption| follow_assumption(&self.geometry, self.grid, assumption))
+            .collect();
+
+        if branches.is_empty() {
+            // Every alternative fails. The puzzle is broken and `status()` will say so.
+            return vec![];
+        }
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                for candidate in self.grid[row][col].candidates.iter() {
+                    if branches
+                        .iter()
+                        .any(|grid| allows(grid, row, col, candidate))
+                    {
+                        continue;
+                    }
+
+                    if self.grid[row][col].remove_candidate(candidate) {
+                        log!(
+                            self,
+                            "🔀🔀🔀🔀🔀 {} eliminated candidate {} at grid position ({}, {})",
+                            source,
+                            candidate,
+                            row,
+                            col
+                        );
+                        removals.push(((row, col), candidate));
+                    }
+                }
+            }
+        }
+
+        // The cells assumed in turn
+        let mut pattern: Vec<(usize, usize)> = Vec::new();
+        for &(row, col, _) in assumptions.iter() {
+            if !pattern.contains(&(row, col)) {
+                pattern.push((row, col));
+            }
+        }
+
+        Consolidation::eliminations(
+            Technique::ForcingChain,
+            Justification::Pattern(pattern),
+            &removals,
+        )
+    }
+}
+
+// Place the assumed digit and keep placing naked and hidden singles for up to `FORCING_CHAIN_MAX_DEPTH` rounds. Returns the grid as it stood when propagation ran out of singles or depth, or `None` if the assumption led to a cell or unit with no options left.
+fn follow_assumption(
+    geometry: &Geometry,
+    mut grid: Grid,
+    assumption: (usize, usize, u8),
+) -> Option<Grid> {
+    let shape = geometry.shape();
+    let mut placements = vec![assumption];
+
+    for _ in 0..FORCING_CHAIN_MAX_DEPTH {
+        for (row, col, number) in placements.drain(..) {
+            if let Some(existing) = grid[row][col].number {
+                if existing != number {
+                    return None;
+                }
+                continue;
+            }
+            if !grid[row][col].candidates.contains(number) {
+                return None;
+            }
+
+            grid[row][col].number = Some(number);
+            grid[row][col].candidates = Candidates::none();
+
+            for &peer in geometry.peers(Coord::new(row, col)) {
+                grid[peer.row][peer.col].remove_candidate(number);
+            }
+        }
+
+        // Naked singles, and cells left with nothing
+        for (row, cells) in grid[..shape.size()].iter().enumerate() {
+            for (col, cell) in cells[..shape.size()].iter().enumerate() {
+                if cell.number.is_some() {
+                    continue;
+                }
+
+                match cell.candidates.count() {
+                    0 => return None,
+                    1 => placements.push((row, col, cell.candidates.single()?)),
+                    _ => {}
+                }
+            }
+        }
+
+        // Hidden singles, and digits with nowhere left to go
+        for &unit in geometry.units() {
+            let cells = geometry.cells(unit);
+            for digit in shape.digits().iter() {
+                if cells
+                    .iter()
+                    .any(|p| grid[p.row][p.col].number == Some(digit))
+                {
+                    continue;
+                }
+
+                let positions: Vec<&Coord> = cells
+                    .iter()
+                    .filter(|p| grid[p.row][p.col].candidates.contains(digit))
+                    .collect();
+                match positions.len() {
+                    0 => return None,
+                    1 => placements.push((positions[0].row, positions[0].col, digit)),
+                    _ => {}
+                }
+            }
+        }
+
+        if placements.is_empty() {
+            break;
+        }
+    }
+
+    Some(grid)
+}
+
+// Whether the branch's grid still allows the digit in the given cell.
+fn allows(grid: &Grid, row: usize, col: usize, digit: u8) -> bool {
+    match grid[row][col].number {
+        Some(number) => number == digit,
+        None => grid[row][col].candidates.contains(digit),
+    }
+}
+
+fn unit_name(unit: Unit) -> String {
+    match unit {
+        Unit::Row(row) => format!("row {}", row),
+        Unit::Column(col) => format!("column {}", col),
+        Unit::Block(block) => format!("block {}", block),
+        Unit::Diagonal(diagonal) => format!("diagonal {}", diagonal),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+
+    const EVIL: &str = include_str!("../samples/evil.txt");
+    const EVIL_SOLUTION: &str =
+        "315874962284956317697321458573419286921768534846235791439187625768592143152643879";
+
+    #[test]
+    fn never_eliminates_the_solution() {
+        let mut puzzle = Puzzle::parse(EVIL);
+        puzzle.assign_candidates();
+
+        let mut total = 0;
+        loop {
+            let eliminations = puzzle.reduce_candidates_with_forcing_chains();
+            if eliminations.is_empty() {
+                break;
+            }
+            total += eliminations.len();
+        }
+        assert!(total > 0, "Expected forcing chains to make progress");
+
+        for (i, digit) in EVIL_SOLUTION.chars().enumerate() {
+            let digit = digit.to_digit(10).unwrap() as u8;
+            let cell = puzzle.grid[i / 9][i % 9];
+
+            match cell.number {
+                Some(number) => assert_eq!(digit, number),
+                None => assert!(
+                    cell.candidates.contains(digit),
+                    "Solution digit {} eliminated at ({}, {})",
+                    digit,
+                    i / 9,
+                    i % 9
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::{symbol, Puzzle};
+
+// Puzzles as other programs write them. Reading them is left to `Puzzle::parse()`, which takes each of these formats as they come.
+impl Puzzle {
+    // The grid as Simple Sudoku saves it in .ss files, many published puzzle packs among them: `.` for blanks, pipes between blocks, and rules around and between them. Numbers placed by the solver are written as givens.
+    //
+    //    *-----------*
+    //    |.4.|5.2|...|
+    //    |76.|...|1.2|
+    //    |9..|.18|.64|
+    //    |---+---+---|
+    //    ...
+    //    *-----------*
+    pub fn ss(&self) -> String {
+        let (size, block_rows, block_cols) = (
+            self.shape.size(),
+            self.shape.block_rows(),
+            self.shape.block_cols(),
+        );
+        let stacks = vec!["-".repeat(block_cols); size / block_cols];
+        let edge = format!("*{}*\n", "-".repeat(size + stacks.len() - 1));
+        let mut r = edge.clone();
+
+        for (row, cells) in self.rows().enumerate() {
+            r.push('|');
+            for (col, cell) in cells.iter().enumerate() {
+                match cell.number {
+                    Some(number) => r.push(symbol(number)),
+                    None => r.push('.'),
+                }
+                if col % block_cols == block_cols - 1 {
+                    r.push('|');
+                }
+            }
+            r.push('\n');
+
+            if row == size - 1 {
+                r.push_str(&edge);
+            } else if row % block_rows == block_rows - 1 {
+                r.push_str(&format!("|{}|\n", stacks.join("+")));
+            }
+        }
+
+        r
+    }
+
+    // The grid as a spreadsheet saves it in CSV: a line to a row, cells separated by commas, and blanks left empty. Numbers placed by the solver are written as givens.
+    pub fn csv(&self) -> String {
+        let mut r = String::new();
+
+        for cells in self.rows() {
+            let fields: Vec<String> = cells
+                .iter()
+                .map(|cell| cell.number.map_or(String::new(), |n| symbol(n).to_string()))
+                .collect();
+            r.push_str(&fields.join(","));
+            r.push('\n');
+        }
+
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn ss() {
+        let puzzle = Puzzle::parse(EASY);
+        let ss = puzzle.ss();
+        assert!(ss.starts_with("*-----------*\n|.4.|5.2|...|\n|76.|...|1.2|\n"));
+        assert!(ss.ends_with("|...|4.9|.1.|\n*-----------*\n"));
+        assert_eq!(13, ss.lines().count());
+
+        let (parsed, report) = Puzzle::parse_with_report(&ss).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(puzzle.grid, parsed.grid);
+
+        // Older packs write blanks as X
+        let (parsed, _) = Puzzle::parse_with_report(&ss.replace('.', "X")).unwrap();
+        assert_eq!(puzzle.grid, parsed.grid);
+    }
+
+    #[test]
+    fn csv() {
+        let puzzle = Puzzle::parse(EASY);
+        let csv = puzzle.csv();
+        assert!(csv.starts_with(",4,,5,,2,,,\n7,6,,,,,1,,2\n"));
+        assert_eq!(9, csv.lines().count());
+
+        let (parsed, report) = Puzzle::parse_with_report(&csv).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(puzzle.grid, parsed.grid);
+
+        // As spreadsheets save with Windows line endings and spaces in cells
+        let spaced = csv.replace(",,", ", ,").replace('\n', "\r\n");
+        let (parsed, _) = Puzzle::parse_with_report(&spaced).unwrap();
+        assert_eq!(puzzle.grid, parsed.grid);
+
+        let report = Puzzle::parse_with_report(&csv.replacen(",4,", ",4,,", 1)).unwrap_err();
+        assert_eq!(
+            "line 1, column 13: Row 1 has 10 cells rather than 9",
+            report.errors[0].to_string()
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use super::progress::{Progress, Tracker};
+use super::{
+    AntiKnight, Candidates, Cell, Coord, HouseRule, Puzzle, Rng, Shape, SolverConfig, Technique,
+    MAX_SIZE,
+};
+
+/// The pattern the clues of a generated puzzle make, as newspapers like them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    // Clues anywhere
+    None,
+    // The same when turned half way round
+    Rotational,
+    // The same when reflected along the diagonal from the top left
+    Diagonal,
+    // The same however it's turned or reflected
+    Dihedral,
+}
+
+impl Symmetry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Diagonal => "diagonal",
+            Symmetry::Dihedral => "dihedral",
+        }
+    }
+
+    // `none`, `rotational`, `diagonal`, or `dihedral`.
+    pub fn parse(name: &str) -> Result<Symmetry, String> {
+        match name {
+            "none" => Ok(Symmetry::None),
+            "rotational" => Ok(Symmetry::Rotational),
+            "diagonal" => Ok(Symmetry::Diagonal),
+            "dihedral" => Ok(Symmetry::Dihedral),
+            _ => Err(format!(
+                "Unknown symmetry '{}'. Expected none, rotational, diagonal, or dihedral",
+                name
+            )),
+        }
+    }
+
+    // The cells of a grid of the shape that must be clues or blanks together with the cell: itself and wherever the symmetry takes it.
+    fn images(&self, shape: Shape, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let last = shape.size() - 1;
+        let mut images = match self {
+            Symmetry::None => vec![(row, col)],
+            Symmetry::Rotational => vec![(row, col), (last - row, last - col)],
+            Symmetry::Diagonal => vec![(row, col), (col, row)],
+            Symmetry::Dihedral => vec![
+                (row, col),
+                (col, last - row),
+                (last - row, last - col),
+                (last - col, row),
+                (col, row),
+                (last - row, col),
+                (last - col, last - row),
+                (row, last - col),
+            ],
+        };
+        images.sort();
+        images.dedup();
+        images
+    }
+}
+
+/// How to make a new puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratorOptions {
+    // Where the random choices come from. The same options always make the same puzzle.
+    pub seed: u64,
+    pub symmetry: Symmetry,
+    // The variants to make it for: X-Sudoku, each digit once in each main diagonal too, and anti-knight, no digit a knight's move from itself
+    pub diagonals: bool,
+    pub anti_knight: bool,
+}
+
+impl Puzzle {
+    // A new puzzle with just the one solution, made from the seed, with clues anywhere. The same seed always makes the same puzzle, so a puzzle can be known by its seed alone, as puzzle #12345.
+    pub fn generate(seed: u64) -> Puzzle {
+        Puzzle::generate_with(&GeneratorOptions {
+            seed,
+            symmetry: Symmetry::None,
+            diagonals: false,
+            anti_knight: false,
+        })
+    }
+
+    // A new puzzle made as the options ask. A solution is filled in at random, then clues taken away in a random order for as long as the solution stays the only one, as the brute force search counts solutions keeping to the variants. Clues the symmetry ties together are taken away together. Puzzles are always 9x9, and anti-knight puzzles come with the rule in their config.
+    pub fn generate_with(options: &GeneratorOptions) -> Puzzle {
+        let shape = Shape::STANDARD.with_diagonals(options.diagonals);
+        let config = SolverConfig {
+            house_rules: match options.anti_knight {
+                true => vec![HouseRule::new(AntiKnight)],
+                false => vec![],
+            },
+            ..SolverConfig::default()
+        };
+        let puzzle = |grid| Puzzle {
+            config: config.clone(),
+            ..Puzzle::with_grid(shape, grid)
+        };
+        let mut rng = Rng::new(options.seed);
+        let blank = Cell {
+            number: None,
+            given: false,
+            candidates: Candidates::none(),
+        };
+
+        let mut grid = [[blank; MAX_SIZE]; MAX_SIZE];
+        if options.diagonals || options.anti_knight {
+            // The variants only care which digits are alike, so any one solution relabelled is another, and the first row can be filled in any way
+            let mut digits: Vec<u8> = shape.digits().iter().collect();
+            rng.shuffle(&mut digits);
+            for (col, digit) in digits.into_iter().enumerate() {
+                grid[0][col].number = Some(digit);
+            }
+        } else {
+            // The blocks on the diagonal share no row or column, so can be filled in any way, and the rest solved around them
+            let blocks_across = shape.size() / shape.block_cols();
+            for i in 0..blocks_across.min(shape.size() / shape.block_rows()) {
+                let block = i * blocks_across + i;
+                let mut digits: Vec<u8> = shape.digits().iter().collect();
+                rng.shuffle(&mut digits);
+                for (Coord { row, col }, digit) in shape.block_coords(block).zip(digits) {
+                    grid[row][col].number = Some(digit);
+                }
+            }
+        }
+        let mut grid = match puzzle(grid).solutions(1).pop() {
+            Some(solution) => solution.grid,
+            None => return puzzle(grid),
+        };
+        for coord in shape.coords() {
+            grid[coord.row][coord.col].given = true;
+        }
+
+        // Each set of cells the symmetry ties together, listed once, from its first cell
+        let mut orbits: Vec<Vec<(usize, usize)>> = shape
+            .coords()
+            .map(|Coord { row, col }| ((row, col), options.symmetry.images(shape, row, col)))
+            .filter(|(cell, orbit)| orbit[0] == *cell)
+            .map(|(_, orbit)| orbit)
+            .collect();
+        rng.shuffle(&mut orbits);
+        for orbit in orbits {
+            let clues = grid;
+            for &(row, col) in &orbit {
+                grid[row][col] = blank;
+            }
+            if puzzle(grid).count_solutions(2) != 1 {
+                grid = clues;
+            }
+        }
+
+        puzzle(grid)
+    }
+
+    // A puzzle made as the options ask that `requires()` the technique, for teaching it. Seeds are tried one after another from the options' seed, up to `attempts` of them, and the seed that made the puzzle comes back with it, to know it by.
+    pub fn generate_requiring(
+        options: &GeneratorOptions,
+        technique: Technique,
+        attempts: usize,
+    ) -> Option<(u64, Puzzle)> {
+        Puzzle::generate_requiring_with_progress(options, technique, attempts, |_| {})
+    }
+
+    // As `generate_requiring()`, telling the callback how far it's got after each puzzle tried. Most searches end well short of the attempts allowed, so the time left is the most it could take.
+    pub fn generate_requiring_with_progress(
+        options: &GeneratorOptions,
+        technique: Technique,
+        attempts: usize,
+        progress: impl FnMut(Progress),
+    ) -> Option<(u64, Puzzle)> {
+        let mut tracker = Tracker::new(attempts, progress);
+        (0..attempts as u64)
+            .map(|i| options.seed.wrapping_add(i))
+            .map(|seed| {
+                let options = GeneratorOptions { seed, ..*options };
+                (seed, Puzzle::generate_with(&options))
+            })
+            .find(|(_, puzzle)| {
+                let required = puzzle.requires(technique);
+                tracker.tick();
+                required
+            })
+    }
+
+    // Whether solving the puzzle by hand takes the technique: the singles, the variant's own techniques, and the techniques up to and including it in the default order solve it, but not without it. The harder techniques after it are left out, as they can often make the same eliminations some other way.
+    pub fn requires(&self, technique: Technique) -> bool {
+        let position = Technique::ALL.iter().position(|&t| t == technique);
+        let easiest = self.easiest_techniques();
+        let with: Vec<Technique> = Technique::ALL
+            .iter()
+            .enumerate()
+            .filter(|&(i, t)| Some(i) <= position || easiest.contains(t))
+            .map(|(_, &t)| t)
+            .collect();
+        let without: Vec<Technique> = with.iter().copied().filter(|&t| t != technique).collect();
+
+        self.solves_with(with) && !self.solves_with(without)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Puzzle, Shape};
+    use super::{GeneratorOptions, Symmetry, Technique};
+
+    #[test]
+    fn generate() {
+        let puzzle = Puzzle::generate(12345);
+        assert_eq!(1, puzzle.count_solutions(2));
+        assert!(puzzle.givens().len() >= 17);
+
+        // Taking away any clue left would let in another solution
+        for coord in puzzle.givens() {
+            let mut fewer = puzzle.clone();
+            fewer.grid[coord.row][coord.col].number = None;
+            assert_eq!(2, fewer.count_solutions(2));
+        }
+
+        assert_eq!(puzzle.grid, Puzzle::generate(12345).grid);
+        assert_ne!(puzzle.grid, Puzzle::generate(12346).grid);
+        assert!(puzzle.ss().contains("|..5|...|.9.|\n|...|..6|...|"));
+    }
+
+    #[test]
+    fn requiring() {
+        let options = GeneratorOptions {
+            seed: 1,
+            symmetry: Symmetry::None,
+            diagonals: false,
+            anti_knight: false,
+        };
+        let (seed, puzzle) =
+            Puzzle::generate_requiring(&options, Technique::WaterCannon, 50).unwrap();
+        assert!(puzzle.requires(Technique::WaterCannon));
+        assert!(!puzzle.requires(Technique::XWing));
+
+        // The seed alone makes the puzzle again
+        let options = GeneratorOptions { seed, ..options };
+        assert_eq!(puzzle.grid, Puzzle::generate_with(&options).grid);
+    }
+
+    #[test]
+    fn symmetry() {
+        for symmetry in [Symmetry::Rotational, Symmetry::Diagonal, Symmetry::Dihedral] {
+            let options = GeneratorOptions {
+                seed: 7,
+                symmetry,
+                diagonals: false,
+                anti_knight: false,
+            };
+            let puzzle = Puzzle::generate_with(&options);
+            assert_eq!(1, puzzle.count_solutions(2));
+
+            for row in 0..9 {
+                for col in 0..9 {
+                    let clue = puzzle.grid[row][col].given;
+                    for (r, c) in symmetry.images(Shape::STANDARD, row, col) {
+                        assert_eq!(clue, puzzle.grid[r][c].given, "{:?}", symmetry);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(Ok(Symmetry::Rotational), Symmetry::parse("rotational"));
+        assert!(Symmetry::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn variants() {
+        for (diagonals, anti_knight) in [(true, false), (false, true)] {
+            let options = GeneratorOptions {
+                seed: 3,
+                symmetry: Symmetry::Rotational,
+                diagonals,
+                anti_knight,
+            };
+            let puzzle = Puzzle::generate_with(&options);
+            assert_eq!(diagonals, puzzle.shape().has_diagonals());
+            assert_eq!(anti_knight, !puzzle.config.house_rules.is_empty());
+            assert_eq!(1, puzzle.count_solutions(2));
+
+            // Only the variants pin it down
+            let mut classic = Puzzle::parse(&puzzle.ss());
+            assert_eq!(2, classic.count_solutions(2));
+            classic.set_diagonals(diagonals);
+            assert_eq!(!anti_knight, classic.count_solutions(2) == 1);
+        }
+    }
+}
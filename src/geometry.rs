@@ -0,0 +1,153 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::{Coord, Shape, Unit};
+
+// How many shapes' tables are kept about at once. A batch is nearly always of one shape, but a batch of jigsaws could otherwise grow the cache without end.
+const CACHED_SHAPES: usize = 16;
+
+// Tables worked out so far, most recently built last.
+static TABLES: Mutex<Vec<Arc<Geometry>>> = Mutex::new(Vec::new());
+
+/// The geometry of a grid of one shape, worked out once and looked up from then on rather than redone in every technique: the cells of each unit, and for each cell the units it's in and the peers it sees. Puzzles of the same shape share the tables, across threads too.
+pub(crate) struct Geometry {
+    shape: Shape,
+    // Every row, then every column, then every block, then the diagonals if they're units, as `Shape::units()` lists them
+    units: Vec<Unit>,
+    cells: Vec<Vec<Coord>>,
+    // By cell, row by row
+    units_of: Vec<Vec<Unit>>,
+    peers: Vec<Vec<Coord>>,
+}
+
+impl Geometry {
+    // The tables for the shape, built the first time they're asked for.
+    pub(crate) fn of(shape: Shape) -> Arc<Geometry> {
+        let mut tables = TABLES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(geometry) = tables.iter().find(|g| g.shape == shape) {
+            return Arc::clone(geometry);
+        }
+
+        let geometry = Arc::new(Geometry::build(shape));
+        if tables.len() == CACHED_SHAPES {
+            tables.remove(0);
+        }
+        tables.push(Arc::clone(&geometry));
+        geometry
+    }
+
+    fn build(shape: Shape) -> Geometry {
+        let units = shape.units();
+        let cells: Vec<Vec<Coord>> = units
+            .iter()
+            .map(|unit| unit.cells(shape).into_iter().map(Coord::from).collect())
+            .collect();
+
+        let units_of = shape
+            .coords()
+            .map(|coord| {
+                units
+                    .iter()
+                    .zip(&cells)
+                    .filter(|(_, cells)| cells.contains(&coord))
+                    .map(|(&unit, _)| unit)
+                    .collect()
+            })
+            .collect();
+        let peers = shape
+            .coords()
+            .map(|coord| {
+                shape
+                    .coords()
+                    .filter(|&peer| shape.sees(coord, peer))
+                    .collect()
+            })
+            .collect();
+
+        Geometry {
+            shape,
+            units,
+            cells,
+            units_of,
+            peers,
+        }
+    }
+
+    pub(crate) fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub(crate) fn units(&self) -> &[Unit] {
+        &self.units
+    }
+
+    // Where the unit comes in `units()`.
+    pub(crate) fn index_of(&self, unit: Unit) -> usize {
+        let size = self.shape.size();
+        match unit {
+            Unit::Row(row) => row,
+            Unit::Column(col) => size + col,
+            Unit::Block(b) => 2 * size + b,
+            Unit::Diagonal(d) => 3 * size + d,
+        }
+    }
+
+    // The unit's cells, in the order `Unit::cells()` gives them.
+    pub(crate) fn cells(&self, unit: Unit) -> &[Coord] {
+        &self.cells[self.index_of(unit)]
+    }
+
+    // The row, column, and block the cell's in, then any diagonals.
+    pub(crate) fn units_of(&self, coord: Coord) -> &[Unit] {
+        &self.units_of[self.index(coord)]
+    }
+
+    // The other cells sharing a unit with the cell, row by row: 20 of them in a standard grid.
+    pub(crate) fn peers(&self, coord: Coord) -> &[Coord] {
+        &self.peers[self.index(coord)]
+    }
+
+    fn index(&self, coord: Coord) -> usize {
+        coord.row * self.shape.size() + coord.col
+    }
+}
+
+// Just the shape, as the tables are long and follow from it.
+impl fmt::Debug for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Geometry({})", self.shape.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::super::{Coord, Shape, Unit};
+    use super::Geometry;
+
+    #[test]
+    fn tables() {
+        let geometry = Geometry::of(Shape::STANDARD);
+        let centre = Coord::new(4, 4);
+        assert_eq!(20, geometry.peers(centre).len());
+        assert!(!geometry.peers(centre).contains(&centre));
+        assert!(geometry.peers(centre).contains(&Coord::new(3, 5)));
+        assert_eq!(
+            &[Unit::Row(4), Unit::Column(4), Unit::Block(4)],
+            geometry.units_of(centre)
+        );
+        assert_eq!(Coord::new(5, 3), geometry.cells(Unit::Block(4))[6]);
+        assert_eq!(27, geometry.units().len());
+        assert_eq!(22, geometry.index_of(Unit::Block(4)));
+
+        // Shared by puzzles of the same shape, and not by those of another
+        assert!(Arc::ptr_eq(&geometry, &Geometry::of(Shape::STANDARD)));
+        let x = Geometry::of(Shape::STANDARD.with_diagonals(true));
+        assert!(!Arc::ptr_eq(&geometry, &x));
+        assert_eq!(32, x.peers(centre).len());
+        assert_eq!(Coord::new(8, 0), x.cells(Unit::Diagonal(1))[8]);
+    }
+}
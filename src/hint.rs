@@ -0,0 +1,233 @@
+use super::explain::{list, technique_name, unit_name};
+use super::{Consolidation, Coord, Justification, Puzzle, Shape, Technique, Unit};
+
+/// A hint at the next move, given away a little at a time. Level 1 says where to look, level 2 what to look for there, and level 3 the move itself, as `Consolidation::describe()` tells it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    shape: Shape,
+    consolidation: Consolidation,
+}
+
+impl Hint {
+    pub const LEVELS: usize = 3;
+
+    pub fn new(shape: Shape, consolidation: Consolidation) -> Hint {
+        Hint {
+            shape,
+            consolidation,
+        }
+    }
+
+    pub fn consolidation(&self) -> &Consolidation {
+        &self.consolidation
+    }
+
+    // The hint told as far as the level, e.g. "Look at box 5.", then "Look for a naked subset in row 2, which rules out 3 and 7.", then the move in full. Levels past the last tell it all, and those before the first are the first.
+    pub fn reveal(&self, level: usize) -> String {
+        let area = unit_name(&self.area());
+        match level {
+            0 | 1 => format!("Look at {}.", area),
+            2 => {
+                let technique = match self.consolidation.technique() {
+                    Some(technique) => technique_name(technique),
+                    None => "guess",
+                };
+                let (verb, digits) = match &self.consolidation {
+                    Consolidation::CandidatesEliminated { digits, .. } => {
+                        ("rules out", digits.clone())
+                    }
+                    c => (
+                        "places",
+                        c.assignment().map(|(_, n)| n).into_iter().collect(),
+                    ),
+                };
+                let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+                format!(
+                    "Look for {} in {}, which {} {}.",
+                    a(technique),
+                    area,
+                    verb,
+                    list(&digits, "and")
+                )
+            }
+            _ => self.consolidation.describe(),
+        }
+    }
+
+    // Where the move's to be found: the unit it's worked out in, if there's one, or else the box of the first cell it changes.
+    fn area(&self) -> Unit {
+        match self.consolidation.justification() {
+            Justification::LockedCells(unit, _)
+            | Justification::OnlyPlaceInUnit(unit)
+            | Justification::Sandwich(unit, _) => return *unit,
+            _ => {}
+        }
+
+        let first = match &self.consolidation {
+            Consolidation::CandidatesEliminated { cells, .. } => cells.first().copied(),
+            c => c.assignment().map(|(position, _)| position),
+        };
+        Unit::Block(self.shape.block_of(Coord::from(first.unwrap_or_default())))
+    }
+}
+
+// The technique's name with "a" or "an" before it, or alone when it's plural.
+fn a(name: &str) -> String {
+    match name.chars().next() {
+        _ if name.ends_with('s') => name.to_string(),
+        Some('a' | 'e' | 'i' | 'o' | 'X') => format!("an {}", name),
+        _ => format!("a {}", name),
+    }
+}
+
+impl Puzzle {
+    // What to do next: the first number the configured techniques can place, or failing that the first candidates they can eliminate. `None` if they're stuck. The puzzle is left as is.
+    //
+    // Candidates are worked out afresh from the numbers on the grid, as at the start of every step, then narrowed to any already noted in a cell. That way a puzzle loaded with the player's pencil marks gets a hint that follows on from them.
+    pub fn hint(&self) -> Option<Consolidation> {
+        let found = self.noted().run_pipeline();
+
+        found
+            .iter()
+            .find(|c| c.is_assignment())
+            .or(found.first())
+            .cloned()
+    }
+
+    // The hint, to be given away a level at a time with `Hint::reveal()`.
+    pub fn tiered_hint(&self) -> Option<Hint> {
+        self.hint()
+            .map(|consolidation| Hint::new(self.shape, consolidation))
+    }
+
+    // Every move each configured technique could make next, for showing that more than one way forward is open. Each technique is tried on the candidates as `hint()` works them out, alone, so one's findings don't open up or steal another's. Those that find nothing are left out. The puzzle is left as is.
+    pub fn analyze(&self) -> Vec<(Technique, Vec<Consolidation>)> {
+        let noted = self.noted();
+        self.config
+            .techniques
+            .iter()
+            .map(|&technique| (technique, technique.apply(&mut noted.clone())))
+            .filter(|(_, found)| !found.is_empty())
+            .collect()
+    }
+
+    // A copy with candidates worked out afresh, narrowed to any already noted.
+    pub(super) fn noted(&self) -> Puzzle {
+        let mut puzzle = self.clone();
+        puzzle.assign_candidates();
+        for coord in self.shape.coords() {
+            let noted = self[coord].candidates;
+            if puzzle[coord].number.is_none() && !noted.is_empty() {
+                puzzle[coord].candidates = puzzle[coord].candidates.intersection(noted);
+            }
+        }
+        puzzle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        CellAssignment, Consolidation, Justification, Puzzle, Shape, SolverConfig, Technique, Unit,
+    };
+    use super::Hint;
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn hint() {
+        let puzzle = Puzzle::parse(EASY);
+        let hint = puzzle.hint().unwrap();
+        assert!(hint.assignment().is_some());
+
+        // The puzzle itself is untouched
+        assert!(puzzle
+            .grid
+            .iter()
+            .flatten()
+            .all(|c| c.candidates.is_empty()));
+
+        // Singles get nowhere on an empty grid
+        let mut blank = Puzzle::parse("");
+        blank.set_config(SolverConfig::with_techniques(vec![
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+        ]));
+        assert_eq!(None, blank.hint());
+
+        // Unless pencil marks have it down to one
+        let all = "[1,2,3,4,5,6,7,8,9] ";
+        let marks = format!(
+            "[5] {}\n{}",
+            all.repeat(8),
+            format!("{}\n", all.repeat(9)).repeat(8)
+        );
+        let mut marked = Puzzle::parse(&marks);
+        marked.set_config(blank.config.clone());
+        let hint = marked.hint().unwrap();
+        assert_eq!(Some(((0, 0), 5)), hint.assignment());
+    }
+
+    #[test]
+    fn levels() {
+        let pair = Hint::new(
+            Shape::STANDARD,
+            Consolidation::CandidatesEliminated {
+                technique: Technique::SaraFlex,
+                cells: vec![(1, 4)],
+                digits: vec![3, 7],
+                justification: Justification::LockedCells(Unit::Row(1), vec![(1, 0), (1, 2)]),
+            },
+        );
+        assert_eq!("Look at row 2.", pair.reveal(1));
+        assert_eq!(
+            "Look for a naked subset in row 2, which rules out 3 and 7.",
+            pair.reveal(2)
+        );
+        assert_eq!(pair.consolidation().describe(), pair.reveal(3));
+        assert_eq!(pair.reveal(1), pair.reveal(0));
+        assert_eq!(pair.reveal(3), pair.reveal(9));
+
+        // Without a unit to go on, the box of the cell
+        let naked = Hint::new(
+            Shape::STANDARD,
+            Consolidation::SingleCandidateForCell(CellAssignment {
+                number: 9,
+                block: 4,
+                row: 3,
+                col: 5,
+                justification: Justification::Peers(vec![(3, 0), (8, 5)]),
+            }),
+        );
+        assert_eq!("Look at box 5.", naked.reveal(1));
+        assert_eq!(
+            "Look for a naked single in box 5, which places 9.",
+            naked.reveal(2)
+        );
+
+        let puzzle = Puzzle::parse(EASY);
+        let hint = puzzle.tiered_hint().unwrap();
+        assert_eq!(&puzzle.hint().unwrap(), hint.consolidation());
+    }
+
+    #[test]
+    fn analyze() {
+        let puzzle = Puzzle::parse(EASY);
+        let analysis = puzzle.analyze();
+        let techniques: Vec<Technique> = analysis.iter().map(|(t, _)| *t).collect();
+        assert!(techniques.contains(&Technique::NakedSingle));
+        assert!(techniques.contains(&Technique::HiddenSingle));
+        assert!(analysis.iter().all(|(_, found)| !found.is_empty()));
+
+        let (_, singles) = analysis
+            .iter()
+            .find(|(t, _)| *t == Technique::NakedSingle)
+            .unwrap();
+        assert!(singles.iter().all(|c| c.assignment().is_some()));
+        assert!(puzzle
+            .grid
+            .iter()
+            .flatten()
+            .all(|c| c.candidates.is_empty()));
+    }
+}
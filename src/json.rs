@@ -0,0 +1,256 @@
+// Just enough JSON to read the puzzle files other programs write. Numbers are kept as `f64`, and objects as their keys and values in order.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // The value under the key, if this is an object holding it.
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    // Parse the text as a single value. Errors give the line and column, counting from 1, where the text stopped making sense.
+    pub(crate) fn parse(text: &str) -> Result<Json, ((usize, usize), String)> {
+        let mut reader = Reader {
+            chars: text.chars().collect(),
+            at: 0,
+        };
+        let value = reader.value().and_then(|value| {
+            reader.skip_whitespace();
+            match reader.peek() {
+                None => Ok(value),
+                Some(c) => Err(format!("Expected the end, not '{}'", c)),
+            }
+        });
+        value.map_err(|message| (reader.location(), message))
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    at: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.at).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.at += 1;
+        c
+    }
+
+    fn location(&self) -> (usize, usize) {
+        let before = &self.chars[..self.at.min(self.chars.len())];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        (line, column)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), String> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(format!("Expected '{}'", word));
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Json::String),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(format!("Expected a value, not '{}'", c)),
+            None => Err("Expected a value, not the end".to_string()),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.next();
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err("Expected a key in quotes".to_string());
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.peek() != Some(':') {
+                return Err(format!("Expected ':' after the key '{}'", key));
+            }
+            self.next();
+            entries.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.at += 1,
+                Some('}') => {
+                    self.at += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err("Expected ',' or '}' in the object".to_string()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.next();
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.at += 1,
+                Some(']') => {
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err("Expected ',' or ']' in the array".to_string()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.next();
+        let mut s = String::new();
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or(format!("Expected 4 hex digits after \\u, not '{}'", hex))?;
+                        s.push(c);
+                    }
+                    Some(c) => s.push(c),
+                    None => return Err("Expected the string to end".to_string()),
+                },
+                Some(c) => s.push(c),
+                None => return Err("Expected the string to end".to_string()),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.at;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.at += 1;
+        }
+
+        let text: String = self.chars[start..self.at].iter().collect();
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| format!("Expected a number, not '{}'", text))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Json;
+
+    #[test]
+    fn parse() {
+        let json = Json::parse(r#"{"size": 9, "grid": [[{"value": 5, "given": true}, {}]], "title": "A \"test\"!", "x": null, "y": -1.5e1}"#).unwrap();
+        assert_eq!(Some(9.0), json.get("size").and_then(Json::as_f64));
+        assert_eq!(Some(-15.0), json.get("y").and_then(Json::as_f64));
+        assert_eq!(Some(&Json::Null), json.get("x"));
+        assert_eq!(
+            Some(&Json::String("A \"test\"!".to_string())),
+            json.get("title")
+        );
+
+        let row = &json.get("grid").and_then(Json::as_array).unwrap()[0];
+        let cell = &row.as_array().unwrap()[0];
+        assert_eq!(Some(true), cell.get("given").and_then(Json::as_bool));
+        assert_eq!(Some(&Json::Object(vec![])), row.as_array().unwrap().get(1));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Err(((2, 3), "Expected ',' or '}' in the object".to_string())),
+            Json::parse("{\"a\": 1\n  \"b\": 2}")
+        );
+        assert_eq!(
+            Err(((1, 4), "Expected the end, not 'x'".to_string())),
+            Json::parse("[] x")
+        );
+        assert!(Json::parse("[1, ").is_err());
+    }
+}
@@ -0,0 +1,2484 @@
+use std::{
+    fmt,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+// The clock solves are timed by. Browsers have no `std::time::Instant`, so there it's theirs.
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use web_time::Instant;
+
+// Print the solver's workings, unless the puzzle is configured to keep quiet. Defined ahead of the modules so they can use it too.
+macro_rules! log {
+    ($puzzle:expr, $($arg:tt)*) => {
+        if $puzzle.config.verbose {
+            $puzzle.config.say(&format!($($arg)*));
+        }
+    };
+}
+
+mod aic;
+mod als;
+mod ape;
+mod arrow;
+mod batch;
+mod bench;
+mod brute;
+mod budget;
+mod candidates;
+mod canonical;
+mod census;
+mod check;
+mod clues;
+mod constraint;
+mod coord;
+mod crosscheck;
+mod debugger;
+mod diff;
+mod display;
+mod engine;
+mod explain;
+mod fish;
+mod forcing;
+mod formats;
+mod generate;
+mod geometry;
+mod hint;
+mod json;
+#[cfg(feature = "library")]
+mod library;
+mod line;
+mod multi;
+mod observer;
+mod parse;
+mod pdf;
+mod pipeline;
+mod play;
+mod png;
+mod progress;
+mod race;
+mod rating;
+mod refute;
+mod report;
+mod rng;
+mod sandwich;
+#[cfg(feature = "ocr")]
+mod scan;
+#[cfg(feature = "server")]
+mod server;
+mod shape;
+mod snapshot;
+mod solution;
+mod state;
+mod steps;
+mod stream;
+#[cfg(feature = "testing")]
+mod testing;
+mod transcript;
+mod uniqueness;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod xv;
+
+pub use arrow::{Arrow, Arrows};
+pub use batch::{batch, batch_with_progress, Batch, Tally};
+pub use bench::{benchmark, benchmark_with_progress, Benchmark, Dataset, DATASETS};
+pub use budget::{Limit, SolveError};
+pub use candidates::Candidates;
+// The census's two ways of counting candidates, for its benchmark alone
+#[doc(hidden)]
+pub use census::scalar_counts;
+#[cfg(target_arch = "x86_64")]
+#[doc(hidden)]
+pub use census::sse2_counts;
+pub use check::{Mistake, Validation};
+pub use constraint::{AntiKnight, Constraint, HouseRule};
+pub use coord::Coord;
+pub use crosscheck::{cross_check, cross_check_with_progress, CrossCheck, Disagreement};
+pub use debugger::Debugger;
+pub use diff::{Discrepancies, GridDiff};
+pub use display::{
+    strip_emoji, CandidateStyle, ColorChoice, ColorScheme, DisplayOptions, GridStyle, Theme,
+};
+pub use engine::engine;
+pub use generate::{GeneratorOptions, Symmetry};
+pub use hint::Hint;
+#[cfg(feature = "library")]
+pub use library::{Added, Entry, Library};
+pub use line::{Line, LineKind, Lines};
+pub use multi::{MultiPuzzle, SAMURAI};
+pub use observer::{Observer, SolveObserver};
+pub use parse::{ParseIssue, ParseReport};
+pub use pdf::{worksheet, PageSize, WorksheetOptions};
+pub use pipeline::{GuessOrder, SolverConfig, Technique};
+pub use play::{Game, Key};
+pub use progress::Progress;
+pub use race::{race, Pipeline, Race};
+pub use rating::Difficulty;
+pub use refute::Refutation;
+pub use sandwich::Sandwiches;
+#[cfg(feature = "server")]
+pub use server::serve;
+pub use shape::{symbol, Shape, MAX_SIZE};
+pub use snapshot::GridState;
+pub use solution::{GuessReport, Solution, SolveStats, Step};
+pub use steps::Steps;
+pub use stream::stream;
+pub use transcript::Transcript;
+pub use xv::{Edge, Edges, Mark};
+
+use budget::Budget;
+use clues::Clues;
+use geometry::Geometry;
+use pipeline::add_time;
+use rng::Rng;
+
+/// A square of the grid: its number, if it has one, otherwise the candidates it could still hold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    number: Option<u8>,
+    given: bool,
+
+    candidates: Candidates,
+}
+
+// The cells of a grid of any shape, those past its size left blank.
+type Grid = [[Cell; MAX_SIZE]; MAX_SIZE];
+
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    iteration: usize,
+    shape: Shape,
+    grid: Grid,
+    // The shape's units and peers, looked up rather than worked out
+    geometry: Arc<Geometry>,
+    // A variant's sums, arrows, marks, and lines, if any, shared by every copy of the puzzle as the geometry is
+    clues: Arc<Clues>,
+
+    // Cells numbered since the candidates were last brought up to date, their peers still to lose the number
+    queue: Vec<Coord>,
+
+    // Whether the candidates have been worked out and kept up to date since, so a step need only take the queued numbers from their peers
+    propagating: bool,
+    last_consolidation: Vec<Consolidation>,
+    config: SolverConfig,
+    steps: Vec<Step>,
+
+    // Steps taken back by `undo()`, most recent last, ready to be redone
+    undone: Vec<Step>,
+
+    // The guesses `solve_with_guesses()` made at each depth to solve the puzzle, shallowest first, across every branch of the search
+    guesses_by_depth: Vec<usize>,
+
+    // Time spent in `solve()`, and in any search of guesses that solved the puzzle
+    elapsed: Duration,
+
+    // Time spent in each technique tried, in the order first tried, across every branch of any search of guesses
+    technique_times: Vec<(Technique, Duration)>,
+
+    // When a solve with a wall clock timeout must give up, so the longer searches can stop partway
+    deadline: Option<Instant>,
+}
+
+// How many of the block, row, and column inferences `assign_hidden_singles` makes per pass
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConsolidationMode {
+    // Stop at the first one, as a person solving by hand would
+    FirstFinding,
+
+    // Collect every one the grid supports and apply them together
+    AllFindings,
+}
+
+// The type of consolidation performed during a step towards the solution
+#[derive(Clone, Debug, PartialEq)]
+pub enum Consolidation {
+    SingleCandidateForCell(CellAssignment),
+    OnlyOnePossibleCandidateForBlock(CellAssignment),
+    OnlyOnePossibleCandidateForRow(CellAssignment),
+    OnlyOnePossibleCandidateForColumn(CellAssignment),
+    OnlyOnePossibleCandidateForDiagonal(CellAssignment),
+
+    // Nothing else worked, so the solver tried a number to see where it leads.
+    Guess(CellAssignment),
+
+    // Every digit listed was removed from the candidates of every cell listed.
+    CandidatesEliminated {
+        technique: Technique,
+        cells: Vec<(usize, usize)>,
+        digits: Vec<u8>,
+        justification: Justification,
+    },
+}
+
+// A row, column, or block of the grid, each numbered from 0, or in X-Sudoku a diagonal: 0 from the top left, 1 from the top right
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Block(usize),
+    Diagonal(usize),
+}
+
+// The evidence behind a consolidation: which peer cells, unit, or pattern forced it. Grid positions are absolute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Justification {
+    // Solved peers holding each of the cell's other digits. Digits removed by earlier eliminations have no peer listed.
+    Peers(Vec<(usize, usize)>),
+
+    // No other cell of the unit can hold the digit.
+    OnlyPlaceInUnit(Unit),
+
+    // These cells of the unit hold the digits between them, so no other cell of the unit can.
+    LockedCells(Unit, Vec<(usize, usize)>),
+
+    // The cells of the rectangle, loop, chain, or sets found by the technique.
+    Pattern(Vec<(usize, usize)>),
+
+    // The row or column's sandwich: its digits between the 1 and 9 must add up to the sum.
+    Sandwich(Unit, u16),
+
+    // The arrow's cells, its circle first: the digits along it must add up to the circle's.
+    Arrow(Vec<(usize, usize)>),
+
+    // Two cells side by side and the X or V between them, their digits adding up to 10 or 5, or with no mark, under XV sudoku's negative constraint, to neither.
+    Xv([(usize, usize); 2], Option<Mark>),
+
+    // The cells along a renban or whisper line, from its start: a renban's digits must be a run of consecutive digits, and a whisper's side by side at least 5 apart.
+    Line(LineKind, Vec<(usize, usize)>),
+
+    // Nothing: it's a guess, abandoned if it leads to a contradiction.
+    Guess,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PuzzleStatus {
+    Solved,
+    Unsolved,
+
+    IllDefined(IllDefinedReason),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IllDefinedReason {
+    NoPossibleSolutionForCell((usize, usize)),
+    NumberRepeatsInRow(u8, usize),
+    NumberRepeatsInColumn(u8, usize),
+    NumberRepeatsInBlock(u8, usize),
+    // 0 for the diagonal from the top left, 1 from the top right
+    NumberRepeatsInDiagonal(u8, usize),
+    // Two cells of a unit left with the same number as their only candidate
+    SameOnlyCandidate(u8, (usize, usize), (usize, usize)),
+    // A number missing from a unit with no cell left to go in
+    NumberHasNoPlace(u8, Unit),
+    // A row or column filled in from its 1 to its 9, the digits between not adding up to its sandwich sum
+    SandwichDoesNotAddUp(Unit),
+    // An arrow filled in, circle and all, its digits not adding up to the circle's, given by its circle
+    ArrowDoesNotAddUp((usize, usize)),
+    // Two cells side by side filled in, their digits not adding up as the X or V between them says, or adding up to 5 or 10 without one under the negative constraint
+    XvBroken((usize, usize), (usize, usize)),
+    // A renban or whisper line filled in that breaks its rule, given by its start
+    LineBroken((usize, usize)),
+    // A house rule broken, by its name and a cell breaking it
+    HouseRuleBroken(&'static str, (usize, usize)),
+}
+
+#[derive(Debug, PartialEq)]
+enum WaterCannonSights {
+    Row(usize),
+    Column(usize),
+    None,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellAssignment {
+    pub number: u8,
+    pub block: usize,
+    pub row: usize,
+    pub col: usize,
+    pub justification: Justification,
+}
+
+impl Cell {
+    fn with_number(number: u8) -> Cell {
+        Cell {
+            number: Some(number),
+            given: true,
+            candidates: Candidates::none(),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn with_candidates(candidates: Vec<u8>) -> Cell {
+        Cell {
+            number: None,
+            given: false,
+            candidates: candidates.iter().collect(),
+        }
+    }
+
+    pub fn number(&self) -> Option<u8> {
+        self.number
+    }
+
+    // Whether the number was given in the puzzle, rather than solved for.
+    pub fn is_given(&self) -> bool {
+        self.given
+    }
+
+    // The numbers the cell could still hold.
+    pub fn candidates(&self) -> Candidates {
+        self.candidates
+    }
+
+    fn remove_candidate(&mut self, number: u8) -> bool {
+        self.candidates.remove(number)
+    }
+}
+
+impl Unit {
+    // Grid positions of the unit's cells in a grid of the shape. Blocks are read left to right, top to bottom.
+    fn cells(&self, shape: Shape) -> Vec<(usize, usize)> {
+        let size = shape.size();
+        match *self {
+            Unit::Row(row) => (0..size).map(|col| (row, col)).collect(),
+            Unit::Column(col) => (0..size).map(|row| (row, col)).collect(),
+            Unit::Block(b) => shape.block_coords(b).map(Into::into).collect(),
+            Unit::Diagonal(d) => shape.diagonal(d).map(Into::into).collect(),
+        }
+    }
+}
+
+impl Consolidation {
+    // Group the candidates removed by one application of a technique, each group carrying the same justification. Removals from a single cell are kept together, otherwise they're grouped by digit.
+    fn eliminations(
+        technique: Technique,
+        justification: Justification,
+        removals: &[((usize, usize), u8)],
+    ) -> Vec<Consolidation> {
+        if removals.is_empty() {
+            return vec![];
+        }
+
+        if removals.iter().all(|(p, _)| *p == removals[0].0) {
+            return vec![Consolidation::CandidatesEliminated {
+                technique,
+                cells: vec![removals[0].0],
+                digits: removals.iter().map(|(_, d)| *d).collect(),
+                justification,
+            }];
+        }
+
+        let mut digits: Vec<u8> = removals.iter().map(|(_, d)| *d).collect();
+        digits.sort();
+        digits.dedup();
+
+        digits
+            .into_iter()
+            .map(|digit| Consolidation::CandidatesEliminated {
+                technique,
+                cells: removals
+                    .iter()
+                    .filter(|(_, d)| *d == digit)
+                    .map(|(p, _)| *p)
+                    .collect(),
+                digits: vec![digit],
+                justification: justification.clone(),
+            })
+            .collect()
+    }
+
+    fn is_assignment(&self) -> bool {
+        !matches!(self, Consolidation::CandidatesEliminated { .. })
+    }
+
+    // The technique behind the consolidation, or `None` for a guess.
+    pub fn technique(&self) -> Option<Technique> {
+        match self {
+            Consolidation::SingleCandidateForCell(_) => Some(Technique::NakedSingle),
+            Consolidation::OnlyOnePossibleCandidateForBlock(_)
+            | Consolidation::OnlyOnePossibleCandidateForRow(_)
+            | Consolidation::OnlyOnePossibleCandidateForColumn(_)
+            | Consolidation::OnlyOnePossibleCandidateForDiagonal(_) => {
+                Some(Technique::HiddenSingle)
+            }
+            Consolidation::Guess(_) => None,
+            Consolidation::CandidatesEliminated { technique, .. } => Some(*technique),
+        }
+    }
+
+    // The grid position and number of an assignment.
+    pub fn assignment(&self) -> Option<((usize, usize), u8)> {
+        match self {
+            Consolidation::SingleCandidateForCell(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForBlock(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForRow(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForColumn(assignment)
+            | Consolidation::OnlyOnePossibleCandidateForDiagonal(assignment)
+            | Consolidation::Guess(assignment) => {
+                Some(((assignment.row, assignment.col), assignment.number))
+            }
+            Consolidation::CandidatesEliminated { .. } => None,
+        }
+    }
+}
+
+impl Puzzle {
+    fn with_grid(shape: Shape, grid: Grid) -> Puzzle {
+        Puzzle {
+            iteration: 0,
+            shape,
+            grid,
+            geometry: Geometry::of(shape),
+            clues: Arc::new(Clues::default()),
+            queue: vec![],
+            propagating: false,
+            last_consolidation: vec![],
+            config: SolverConfig::default(),
+            steps: vec![],
+            undone: vec![],
+            guesses_by_depth: vec![],
+            elapsed: Duration::ZERO,
+            technique_times: vec![],
+            deadline: None,
+        }
+    }
+
+    // Start over on a blank grid of the shape, without a variant's clues, as if just made, keeping the config and the buffers the steps and the rest were held in. For solving puzzle after puzzle without allocating afresh for each.
+    fn start_over(&mut self, shape: Shape) {
+        self.iteration = 0;
+        self.shape = shape;
+        self.geometry = Geometry::of(shape);
+        self.clues = Arc::new(Clues::default());
+        for row in self.grid.iter_mut() {
+            row.fill(Cell {
+                number: None,
+                given: false,
+                candidates: Candidates::none(),
+            });
+        }
+        self.queue.clear();
+        self.propagating = false;
+        self.last_consolidation.clear();
+        self.steps.clear();
+        self.undone.clear();
+        self.guesses_by_depth.clear();
+        self.elapsed = Duration::ZERO;
+        self.technique_times.clear();
+        self.deadline = None;
+    }
+
+    // The size of the grid and of its blocks.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    // Make the two main diagonals units too, each digit in them once, as in X-Sudoku, or plain units again. Candidates already noted are kept, so set it before solving.
+    pub fn set_diagonals(&mut self, diagonals: bool) {
+        self.shape = self.shape.with_diagonals(diagonals);
+        self.geometry = Geometry::of(self.shape);
+    }
+
+    // Give sums for the digits between the 1 and 9 of rows and columns, as in sandwich sudoku, or none. Like the diagonals, set them before solving.
+    pub fn set_sandwiches(&mut self, sandwiches: Sandwiches) {
+        Arc::make_mut(&mut self.clues).sandwiches = sandwiches;
+    }
+
+    pub fn sandwiches(&self) -> &Sandwiches {
+        &self.clues.sandwiches
+    }
+
+    // Give arrows whose digits add up to their circles', as in arrow sudoku, or none. Like the diagonals, set them before solving.
+    pub fn set_arrows(&mut self, arrows: Arrows) {
+        Arc::make_mut(&mut self.clues).arrows = arrows;
+    }
+
+    pub fn arrows(&self) -> &Arrows {
+        &self.clues.arrows
+    }
+
+    // Give X and V marks between cells side by side, as in XV sudoku, or none. Like the diagonals, set them before solving.
+    pub fn set_xv(&mut self, edges: Edges) {
+        Arc::make_mut(&mut self.clues).xv = edges;
+    }
+
+    pub fn xv(&self) -> &Edges {
+        &self.clues.xv
+    }
+
+    // Give renban and whisper lines, or none. Like the diagonals, set them before solving.
+    pub fn set_lines(&mut self, lines: Lines) {
+        Arc::make_mut(&mut self.clues).lines = lines;
+    }
+
+    pub fn lines(&self) -> &Lines {
+        &self.clues.lines
+    }
+
+    // Where the puzzle's clues are, row by row.
+    pub fn givens(&self) -> Vec<Coord> {
+        self.shape
+            .coords()
+            .filter(|&coord| self[coord].given)
+            .collect()
+    }
+
+    // Every reason the puzzle is ill-defined, dead cells first, then the units repeating a number, by row, column, and block, and diagonal in X-Sudoku, then cells that can only be the same number, then numbers with nowhere left to go, then the variant's clues and house rules broken. The units and clues are checked as `Constraint`s, so say what's broken as the search sees it. Empty if it's fine. Unlike `status()`, which stops at the first, this shows all there is to fix in a badly copied puzzle at once. Candidates need assigning first, or every blank cell looks dead.
+    pub fn diagnose(&self) -> Vec<IllDefinedReason> {
+        let mut reasons = vec![];
+
+        let size = self.shape.size();
+        let digits = 1..=size as u8;
+
+        // Bad if any cell has no number assigned and has no possible candidates
+        for row in 0..size {
+            for col in 0..size {
+                let cell = self.grid[row][col];
+
+                if cell.number.is_none() && cell.candidates.is_empty() {
+                    reasons.push(IllDefinedReason::NoPossibleSolutionForCell((row, col)));
+                }
+            }
+        }
+
+        // Bad if any row, column, block, or diagonal repeats a number
+        for unit in self.shape.units() {
+            reasons.extend(unit.check(self));
+        }
+
+        // Bad if two cells of a unit can only be the same number. Cells sharing a row and a block are only reported once.
+        for cells in units(self.shape) {
+            let singles: Vec<((usize, usize), u8)> = cells
+                .iter()
+                .filter(|&&(row, col)| self.grid[row][col].number.is_none())
+                .filter_map(|&(row, col)| {
+                    self.grid[row][col]
+                        .candidates
+                        .single()
+                        .map(|n| ((row, col), n))
+                })
+                .collect();
+
+            for (i, &(a, n)) in singles.iter().enumerate() {
+                for &(b, m) in &singles[i + 1..] {
+                    let reason = IllDefinedReason::SameOnlyCandidate(n, a, b);
+                    if n == m && !reasons.contains(&reason) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+        }
+
+        // Bad if a unit is missing a number no cell can take
+        for unit in self.shape.units() {
+            let cells = unit.cells(self.shape);
+            for needle in digits.clone() {
+                let placed = cells
+                    .iter()
+                    .any(|&(row, col)| self.grid[row][col].number == Some(needle));
+                let placeable = cells.iter().any(|&(row, col)| {
+                    let cell = self.grid[row][col];
+                    cell.number.is_none() && cell.candidates.contains(needle)
+                });
+
+                if !placed && !placeable {
+                    reasons.push(IllDefinedReason::NumberHasNoPlace(needle, unit));
+                }
+            }
+        }
+
+        // Bad if the numbers placed break a variant's clues, a sandwich, an arrow, an X or V, or a line, or a house rule
+        reasons.extend(
+            self.constraints()
+                .filter_map(|constraint| constraint.check(self)),
+        );
+
+        reasons
+    }
+
+    pub fn status(&self) -> PuzzleStatus {
+        if let Some(&reason) = self.diagnose().first() {
+            return PuzzleStatus::IllDefined(reason);
+        }
+
+        // Solved if every cell has an assigned number
+        for coord in self.shape.coords() {
+            match self[coord].number {
+                Some(_) => {}
+                None => return PuzzleStatus::Unsolved,
+            }
+        }
+
+        PuzzleStatus::Solved
+    }
+
+    // Solve as far as the techniques can, within any limits set in the config.
+    pub fn solve(&mut self) -> Result<(), SolveError> {
+        let started = Instant::now();
+        let solved = self.solve_within(&Budget::new(&self.config));
+        self.elapsed += started.elapsed();
+        solved
+    }
+
+    fn solve_within(&mut self, budget: &Budget) -> Result<(), SolveError> {
+        self.deadline = budget.deadline();
+        let solved = self.take_steps(budget);
+        self.deadline = None;
+        solved
+    }
+
+    fn take_steps(&mut self, budget: &Budget) -> Result<(), SolveError> {
+        loop {
+            budget.check_step(self)?;
+            let progress = self.step();
+
+            // The techniques stop short once time's up, so what they found may not be all there is
+            budget.check_clock(self)?;
+
+            // Eliminations are made until a number's assigned, so a step without assignments is the last
+            if !progress.iter().any(Consolidation::is_assignment) {
+                break;
+            }
+
+            let status = self.status();
+            if status == PuzzleStatus::Solved {
+                break;
+            }
+            if let PuzzleStatus::IllDefined(_) = status {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Whether a solve's wall clock timeout has passed, for the longer searches to give up on what they've found so far.
+    fn out_of_time(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    fn step(&mut self) -> Vec<Consolidation> {
+        self.iteration += 1;
+
+        log!(self, "Starting step #{}", self.iteration);
+        self.propagate();
+        let before = self.grid;
+
+        self.last_consolidation = self.run_pipeline();
+
+        self.record_step(before);
+        self.notify_step();
+        if self.config.verbose {
+            self.config.transcript.flush();
+        }
+        self.last_consolidation.clone()
+    }
+
+    // Try a number in a cell, recording the guess as a step of its own.
+    fn guess(&mut self, row: usize, col: usize, number: u8) {
+        self.iteration += 1;
+        let before = self.grid;
+
+        let placed = self.set_number(Coord::new(row, col), number);
+        assert!(placed, "Can't guess at the given at ({}, {})", row, col);
+        self.last_consolidation = vec![Consolidation::Guess(CellAssignment {
+            number,
+            block: self.shape.block_of(Coord::new(row, col)),
+            row,
+            col,
+            justification: Justification::Guess,
+        })];
+
+        self.record_step(before);
+        self.notify_guess(Coord::new(row, col), number);
+    }
+
+    // Snapshot the puzzle to tmp/, with what changed since the earlier state if one's given.
+    fn write_iteration(&self, filename: String, since: Option<&Puzzle>) -> std::io::Result<()> {
+        std::fs::create_dir_all("tmp")?;
+
+        let mut contents = format!(
+            "{}\n\nLast consolidation: {:?}",
+            self.display(),
+            self.last_consolidation
+        );
+        if let Some(earlier) = since {
+            contents.push_str(&format!("\n\nChanged:\n{}", earlier.diff(self).describe()));
+        }
+
+        std::fs::write(format!("tmp/{}", filename), contents)
+    }
+
+    // Bring the candidates up to date with the numbers placed since the last step, taking each from its peers' candidates and leaving the rest of the grid be. Eliminations made in earlier steps are kept, so the techniques needn't find them all again. The first time, or after the grid's been changed other than by the solver, every cell's candidates are worked out afresh.
+    fn propagate(&mut self) {
+        if !self.propagating {
+            self.assign_candidates();
+            self.propagating = true;
+            return;
+        }
+
+        let geometry = Arc::clone(&self.geometry);
+        while let Some(coord) = self.queue.pop() {
+            let number = match self[coord].number {
+                Some(number) => number,
+                None => continue,
+            };
+            for &peer in geometry.peers(coord) {
+                self[peer].remove_candidate(number);
+            }
+        }
+    }
+
+    // Have the next step work out every cell's candidates afresh, after the grid's been changed other than by the solver.
+    fn invalidate_candidates(&mut self) {
+        self.propagating = false;
+    }
+
+    /// Review every cell and assign the possible candidates by eliminating the obvious invalid ones.
+    fn assign_candidates(&mut self) {
+        self.queue.clear();
+        let geometry = Arc::clone(&self.geometry);
+
+        // The numbers placed in each unit, in the order the geometry lists them
+        let mut placed = [Candidates::none(); 3 * MAX_SIZE + 2];
+        for (numbers, &unit) in placed.iter_mut().zip(geometry.units()) {
+            *numbers = self.numbers_in(unit);
+        }
+
+        for coord in self.shape.coords() {
+            if self[coord].number.is_some() {
+                continue;
+            }
+
+            // Narrow candidates by block, row, and column, and any diagonal the cell's on
+            let forbidden = geometry
+                .units_of(coord)
+                .iter()
+                .fold(Candidates::none(), |forbidden, &unit| {
+                    forbidden.union(placed[geometry.index_of(unit)])
+                });
+
+            self[coord].candidates = self.shape.digits().difference(forbidden);
+        }
+    }
+
+    // Sara flex: combine the following rules to reduce potential candidates:
+    //
+    //    * Each row and column and block must have 9 unique digits
+    //    * Cells "pinned" to certain values force further reductions in other blocks
+    //
+    // These two rules yield incredible results, especially as each reduction can trigger further reductions.
+    //
+    // Returns the eliminations made. Should be called repeatedly until no further simplifcations can be made.
+    fn reduce_candidates_with_sara_flex(&mut self) -> Vec<Consolidation> {
+        let mut eliminations: Vec<Consolidation> = Vec::new();
+
+        // Rows, then columns, then blocks
+        for unit in self.shape.units() {
+            eliminations.extend(unit.prune(self));
+        }
+
+        eliminations
+    }
+
+    // Within a block, find 2 or 3 numbers that are on the same row or column. Use these to line up the sights of the water cannon. Water is projected at other blocks to clobber any matching candidates on that row or column.
+    fn reduce_candidates_using_water_cannon(&mut self) -> Vec<Consolidation> {
+        let mut eliminations: Vec<Consolidation> = Vec::new();
+
+        let geometry = Arc::clone(&self.geometry);
+        let size = self.shape.size();
+        for b in 0..size {
+            for number in 1..=size as u8 {
+                // The block's cells holding the number, all lined up
+                let mut sights = [(0, 0); MAX_SIZE];
+                let mut len = 0;
+                for &coord in geometry.cells(Unit::Block(b)) {
+                    if self[coord].candidates.contains(number) {
+                        sights[len] = coord.into();
+                        len += 1;
+                    }
+                }
+                let sights = &sights[..len];
+                let mut hits: Vec<((usize, usize), u8)> = Vec::new();
+
+                match line_up_water_cannon(sights) {
+                    WaterCannonSights::Row(row) => {
+                        // Nuke everyone else on this row outside of this block
+                        for &coord in geometry.cells(Unit::Row(row)) {
+                            if self.shape.block_of(coord) != b
+                                && self[coord].remove_candidate(number)
+                            {
+                                log!(self, "🔫🔫🔫🔫🔫 Water cannon shot from block {} eliminated candidate {} in same row at grid position ({}, {})", b, number, row, coord.col);
+                                hits.push((coord.into(), number));
+                            }
+                        }
+                    }
+                    WaterCannonSights::Column(col) => {
+                        // Nuke everyone else on this column outside of this block
+                        for &coord in geometry.cells(Unit::Column(col)) {
+                            if self.shape.block_of(coord) != b
+                                && self[coord].remove_candidate(number)
+                            {
+                                log!(self, "🔫🔫🔫🔫🔫 Water cannon shot from block {} eliminated candidate {} in same column at grid position ({}, {})", b, number, coord.row, col);
+                                hits.push((coord.into(), number));
+                            }
+                        }
+                    }
+                    WaterCannonSights::None => {}
+                }
+
+                if !hits.is_empty() {
+                    eliminations.append(&mut Consolidation::eliminations(
+                        Technique::WaterCannon,
+                        Justification::LockedCells(Unit::Block(b), sights.to_vec()),
+                        &hits,
+                    ));
+                }
+            }
+        }
+
+        eliminations.extend(self.reduce_candidates_using_water_cannon_on_diagonals());
+        eliminations
+    }
+
+    // In X-Sudoku the water cannon fires along the diagonals too. A block's cells holding the number may all be on a diagonal, clobbering it on the rest of the diagonal, or a diagonal's all be in one block, clobbering it on the rest of the block.
+    fn reduce_candidates_using_water_cannon_on_diagonals(&mut self) -> Vec<Consolidation> {
+        let mut eliminations: Vec<Consolidation> = Vec::new();
+        if !self.shape.has_diagonals() {
+            return eliminations;
+        }
+
+        let geometry = Arc::clone(&self.geometry);
+        let size = self.shape.size();
+        for d in 0..2 {
+            let diagonal = geometry.cells(Unit::Diagonal(d));
+            for b in 0..size {
+                let block = geometry.cells(Unit::Block(b));
+                for number in 1..=size as u8 {
+                    // Sights all in the other unit clobber the rest of it
+                    for (from, cells, other) in [
+                        (Unit::Block(b), block, diagonal),
+                        (Unit::Diagonal(d), diagonal, block),
+                    ] {
+                        let sights: Vec<Coord> = cells
+                            .iter()
+                            .copied()
+                            .filter(|&coord| self[coord].candidates.contains(number))
+                            .collect();
+                        if sights.len() < 2 || !sights.iter().all(|sight| other.contains(sight)) {
+                            continue;
+                        }
+
+                        let mut hits: Vec<((usize, usize), u8)> = Vec::new();
+                        for &coord in other.iter().filter(|cell| !cells.contains(cell)) {
+                            if self[coord].remove_candidate(number) {
+                                log!(self, "🔫🔫🔫🔫🔫 Water cannon shot from {:?} eliminated candidate {} at grid position ({}, {})", from, number, coord.row, coord.col);
+                                hits.push((coord.into(), number));
+                            }
+                        }
+
+                        eliminations.append(&mut Consolidation::eliminations(
+                            Technique::WaterCannon,
+                            Justification::LockedCells(
+                                from,
+                                sights.into_iter().map(Into::into).collect(),
+                            ),
+                            &hits,
+                        ));
+                    }
+                }
+            }
+        }
+
+        eliminations
+    }
+
+    /// Start with the trivial: resolve any cell with only one candidate. Returns the assignments made.
+    fn assign_naked_singles(&mut self) -> Vec<Consolidation> {
+        let mut progress: Vec<Consolidation> = Vec::new();
+
+        for block_num in 0..self.shape.size() {
+            for coord in self.shape.block_coords(block_num) {
+                let number = match self[coord].candidates.single() {
+                    Some(number) => number,
+                    None => continue,
+                };
+
+                if self.set_number(coord, number) {
+                    let updated = Consolidation::SingleCandidateForCell(CellAssignment {
+                        block: block_num,
+                        row: coord.row,
+                        col: coord.col,
+                        number,
+                        justification: Justification::Peers(
+                            self.peers_ruling_out(coord.row, coord.col, number),
+                        ),
+                    });
+                    progress.push(updated);
+                }
+            }
+        }
+
+        progress
+    }
+
+    /// Assign cells which are the only place for a number in their block, row, or column: all of them, or just the first, depending on the consolidation mode. Returns the assignments made.
+    fn assign_hidden_singles(&mut self) -> Vec<Consolidation> {
+        // Review all candidates within a _block_ and infer reductions based on uniqueness. For example, a block with only candidates [3, 5], [1, 3], and [2, 3, 5] remaining would require that the last cell be 2 since it's the only valid place for it.
+        let mut findings: Vec<Consolidation> = Vec::new();
+        for b in 0..self.shape.size() {
+            let counts = candidate_counts(self.block_cells(b));
+            for coord in self.shape.block_coords(b) {
+                for candidate in self[coord].candidates.iter() {
+                    if counts[candidate as usize] == 1 {
+                        log!(
+                            self,
+                            "➡️➡️➡️➡️ Inferred that block {}'s cell at row {} @ column {} must be {}",
+                            b,
+                            coord.row,
+                            coord.col,
+                            candidate
+                        );
+
+                        findings.push(Consolidation::OnlyOnePossibleCandidateForBlock(
+                            CellAssignment {
+                                number: candidate,
+                                row: coord.row,
+                                col: coord.col,
+                                block: b,
+                                justification: Justification::OnlyPlaceInUnit(Unit::Block(b)),
+                            },
+                        ));
+                        if self.config.consolidation_mode == ConsolidationMode::FirstFinding {
+                            return self.apply_findings(findings);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Same uniqueness logic as above, but for rows
+        for row_num in 0..self.shape.size() {
+            let row = self.row(row_num);
+            let counts = candidate_counts(row);
+            for (col_num, cell) in row.iter().enumerate() {
+                for candidate in cell.candidates.iter() {
+                    if counts[candidate as usize] == 1 {
+                        log!(self,
+                            "➡️➡️➡️➡️ Inferred that row {} @ column {} must be {} because it's the only one available in the ROW",
+                            row_num, col_num, candidate
+                        );
+
+                        findings.push(Consolidation::OnlyOnePossibleCandidateForRow(
+                            CellAssignment {
+                                number: candidate,
+                                row: row_num,
+                                col: col_num,
+                                block: self.shape.block_of(Coord::new(row_num, col_num)),
+                                justification: Justification::OnlyPlaceInUnit(Unit::Row(row_num)),
+                            },
+                        ));
+                        if self.config.consolidation_mode == ConsolidationMode::FirstFinding {
+                            return self.apply_findings(findings);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Same uniqueness logic as above, but for columns
+        for col_num in 0..self.shape.size() {
+            let counts = candidate_counts(self.column(col_num));
+            for row_num in 0..self.shape.size() {
+                for candidate in self.grid[row_num][col_num].candidates.iter() {
+                    if counts[candidate as usize] == 1 {
+                        log!(self,
+                            "➡️➡️➡️➡️ Inferred that row {} @ column {} must be {} because it's the only one in the COLUMN",
+                            row_num, col_num, candidate
+                        );
+
+                        findings.push(Consolidation::OnlyOnePossibleCandidateForColumn(
+                            CellAssignment {
+                                number: candidate,
+                                row: row_num,
+                                col: col_num,
+                                block: self.shape.block_of(Coord::new(row_num, col_num)),
+                                justification: Justification::OnlyPlaceInUnit(Unit::Column(
+                                    col_num,
+                                )),
+                            },
+                        ));
+                        if self.config.consolidation_mode == ConsolidationMode::FirstFinding {
+                            return self.apply_findings(findings);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Same again for the diagonals, in X-Sudoku
+        for unit in self.shape.units() {
+            let d = match unit {
+                Unit::Diagonal(d) => d,
+                _ => continue,
+            };
+            let counts = candidate_counts(self.shape.diagonal(d).map(|coord| &self[coord]));
+            for coord in self.shape.diagonal(d) {
+                for candidate in self[coord].candidates.iter() {
+                    if counts[candidate as usize] == 1 {
+                        log!(self,
+                            "➡️➡️➡️➡️ Inferred that row {} @ column {} must be {} because it's the only one in the DIAGONAL",
+                            coord.row, coord.col, candidate
+                        );
+
+                        findings.push(Consolidation::OnlyOnePossibleCandidateForDiagonal(
+                            CellAssignment {
+                                number: candidate,
+                                row: coord.row,
+                                col: coord.col,
+                                block: self.shape.block_of(coord),
+                                justification: Justification::OnlyPlaceInUnit(unit),
+                            },
+                        ));
+                        if self.config.consolidation_mode == ConsolidationMode::FirstFinding {
+                            return self.apply_findings(findings);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.apply_findings(findings)
+    }
+
+    // Assign the numbers found in one pass over the unchanged grid. The same cell is often found through more than one unit, so only its first finding is kept. A finding clashing with one already applied (a different number for the same cell, or the same number for a peer) means the puzzle is broken; it's skipped and left for `status()` to report.
+    fn apply_findings(&mut self, findings: Vec<Consolidation>) -> Vec<Consolidation> {
+        let mut applied: Vec<Consolidation> = Vec::new();
+        let mut placed: Vec<((usize, usize), u8)> = Vec::new();
+
+        for finding in findings {
+            let (position, number) = match finding.assignment() {
+                Some(assignment) => assignment,
+                None => continue,
+            };
+
+            let clashes = placed.iter().any(|&(p, n)| {
+                p == position || (n == number && cells_see_each_other(self.shape, p, position))
+            });
+            if clashes || !self.set_number(position.into(), number) {
+                continue;
+            }
+
+            placed.push((position, number));
+            applied.push(finding);
+        }
+
+        applied
+    }
+
+    // For each digit other than the given number, the first solved peer of the cell holding it.
+    fn peers_ruling_out(&self, row: usize, col: usize, number: u8) -> Vec<(usize, usize)> {
+        let mut peers: Vec<(usize, usize)> = Vec::new();
+
+        for digit in self.shape.digits().iter().filter(|&d| d != number) {
+            let peer = self
+                .geometry
+                .peers(Coord::new(row, col))
+                .iter()
+                .find(|&&peer| self[peer].number == Some(digit));
+            if let Some(&peer) = peer {
+                peers.push(peer.into());
+            }
+        }
+
+        peers
+    }
+
+    /// The cells of the corresponding block in our grid. Blocks are numbered from 0, starting in top left, and their cells are read left-to-right, top-to-bottom.
+    fn block_cells(&self, b: usize) -> impl Iterator<Item = &Cell> {
+        self.shape.block_coords(b).map(move |coord| &self[coord])
+    }
+
+    /// The corresponding row in our grid.
+    fn row(&self, r: usize) -> &[Cell] {
+        assert!(r < self.shape.size(), "Invalid row number: {}", r);
+
+        &self.grid[r][..self.shape.size()]
+    }
+
+    /// Every row of our grid, top to bottom, each as long as the grid is wide.
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        let size = self.shape.size();
+        self.grid[..size].iter().map(move |row| &row[..size])
+    }
+
+    /// The cells of the corresponding column in our grid, top to bottom.
+    fn column(&self, c: usize) -> impl Iterator<Item = &Cell> {
+        assert!(c < self.shape.size(), "Invalid column number: {}", c);
+
+        self.grid[..self.shape.size()]
+            .iter()
+            .map(move |row| &row[c])
+    }
+
+    // Place the number in the cell. Givens are never overwritten, so returns false, leaving the cell be, if it's one.
+    fn set_number(&mut self, coord: Coord, number: u8) -> bool {
+        if self[coord].given {
+            return false;
+        }
+
+        self[coord].number = Some(number);
+        self[coord].candidates = Candidates::none();
+        self.queue.push(coord);
+        true
+    }
+
+    // Updated cell candidates. Returns true if an update took place, which it never does for a given
+    fn update_candidates(&mut self, coord: Coord, candidates: Candidates) -> bool {
+        if !self[coord].given && self[coord].candidates != candidates {
+            self[coord].candidates = candidates;
+            return true;
+        }
+
+        false
+    }
+
+    fn numbers_in(&self, unit: Unit) -> Candidates {
+        self.geometry
+            .cells(unit)
+            .iter()
+            .filter_map(|&coord| self[coord].number)
+            .collect()
+    }
+
+    #[allow(dead_code)]
+    fn internals(&self) -> String {
+        let mut r = String::new();
+
+        for b in 0..self.shape.size() {
+            r.push_str(format!("Block {}:\n", b).as_str());
+
+            for Coord { row, col } in self.shape.block_coords(b) {
+                let cell = self.grid[row][col];
+                r.push_str(format!("    ({},{}) → ", row, col).as_str());
+
+                match cell.number {
+                    Some(n) => r.push_str(n.to_string().as_str()),
+                    None => r.push_str(format!("{:?}", cell.candidates).as_str()),
+                }
+                r.push('\n');
+            }
+            r.push('\n');
+        }
+
+        r
+    }
+
+    pub fn display(&self) -> String {
+        self.display_with(&DisplayOptions::default())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display_with(&DisplayOptions::compact()))
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut display = "".to_string();
+
+        match self.number {
+            Some(num) => {
+                display.push_str(&num.to_string());
+            }
+            None => display.push_str("unknown"),
+        }
+
+        write!(f, "{}", display)
+    }
+}
+
+// Given a row, column, or block's worth of candidate sets, look for numbers that are "pinned" to a particular set of sets. Then use this fact to eliminate those numbers from all other sets.
+//
+// For example, given:
+//
+//      [2,7], [2,5,7,8], 1, 3, 9, 4, 6, [5,8], [5,8]
+//
+// We know that 5 and 8 must be in the last two sets, and therefore cannot be anywhere else. This allows us to reduce [2,5,7,8] to [2,7].
+//
+// Sets need not be exact duplicates for this trick to work. For example, given:
+//
+//     [2,7], [2,5,7,8], 1, 9, 4, 6, [5,8], [3,8], [5,3]
+//
+// We can make a super set with the last three sets to form [3,5,8]. Since there are exactly 3 numbers possible for each of these 3 sets, the numbers within this super set are "pinned" and can be excluded from the rest of the line. In this example it would result in the 5 & 8 in the second set should be removed.
+//
+// Returns the consolidated sets in the same order they were provided.
+pub fn reduce_candidates_by_uniqueness(candidates: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    assert!(
+        candidates.len() <= MAX_SIZE,
+        "More sets than a unit has cells: {}",
+        candidates.len()
+    );
+    let sets: Vec<Candidates> = candidates.iter().map(|c| c.iter().collect()).collect();
+    reduce_by_uniqueness(&sets)[..sets.len()]
+        .iter()
+        .map(Candidates::to_vec)
+        .collect()
+}
+
+fn reduce_by_uniqueness(candidates: &[Candidates]) -> [Candidates; MAX_SIZE] {
+    // Pinned pair, triplet, quadruplet, etc.
+    let mut pinned = [Candidates::none(); MAX_SIZE];
+    let mut len = 0;
+    for &set in candidates {
+        if set.count() > 1 && is_pinned(candidates, set) {
+            pinned[len] = set;
+            len += 1;
+        }
+    }
+
+    // TODO: Figure out how to find pinned supersets from N sets that contain N numbers. E.g. [5,8], [3,8], [5,3] => [3,5,8].
+
+    // Remove contents of each pinned set from all _other_ sets.
+    let mut reduced = [Candidates::none(); MAX_SIZE];
+    for (reduced, &set) in reduced.iter_mut().zip(candidates) {
+        *reduced = pinned[..len]
+            .iter()
+            .filter(|&&pinned_numbers| pinned_numbers != set)
+            .fold(set, |reduced, &pinned_numbers| {
+                reduced.difference(pinned_numbers)
+            });
+    }
+    reduced
+}
+
+// Whether exactly as many of the sets match the set as it has numbers.
+fn is_pinned(candidates: &[Candidates], set: Candidates) -> bool {
+    candidates.iter().filter(|&&other| other == set).count() == set.count()
+}
+
+// The sara flex elimination for the unit's j-th cell, whose candidates were reduced to the given ones. The cells responsible are those of the unit's pinned sets holding an eliminated digit.
+fn flexed(
+    shape: Shape,
+    unit: Unit,
+    j: usize,
+    candidates: &[Candidates],
+    reduced: Candidates,
+) -> Consolidation {
+    let digits = candidates[j].difference(reduced);
+
+    let positions = unit.cells(shape);
+    let locked: Vec<(usize, usize)> = (0..positions.len())
+        .filter(|&k| {
+            let set = candidates[k];
+            k != j
+                && set.count() > 1
+                && is_pinned(candidates, set)
+                && !set.intersection(digits).is_empty()
+        })
+        .map(|k| positions[k])
+        .collect();
+
+    Consolidation::CandidatesEliminated {
+        technique: Technique::SaraFlex,
+        cells: vec![positions[j]],
+        digits: digits.to_vec(),
+        justification: Justification::LockedCells(unit, locked),
+    }
+}
+
+// How many of the unit's blank cells hold each digit as a candidate, indexed by digit.
+fn candidate_counts<'a>(cells: impl IntoIterator<Item = &'a Cell>) -> [u8; MAX_SIZE + 1] {
+    let mut bits = [0; MAX_SIZE];
+    let mut len = 0;
+    for cell in cells {
+        if cell.number.is_none() {
+            bits[len] = cell.candidates.bits();
+            len += 1;
+        }
+    }
+    census::digit_counts(&bits[..len])
+}
+
+// Whether two distinct grid positions share a row, column, or block of a grid of the shape.
+fn cells_see_each_other(shape: Shape, a: (usize, usize), b: (usize, usize)) -> bool {
+    shape.sees(Coord::from(a), Coord::from(b))
+}
+
+// Every row, column, and block of a grid of the shape as a list of grid positions, in that order.
+fn units(shape: Shape) -> Vec<Vec<(usize, usize)>> {
+    shape
+        .units()
+        .into_iter()
+        .map(|unit| unit.cells(shape))
+        .collect()
+}
+
+// All ways of picking `size` indexes out of `0..n`, in ascending order.
+fn combinations(n: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    if n < size {
+        return vec![];
+    }
+
+    let mut result = combinations(n - 1, size);
+    for mut c in combinations(n - 1, size - 1) {
+        c.push(n - 1);
+        result.push(c);
+    }
+
+    result
+}
+
+fn line_up_water_cannon(sights: &[(usize, usize)]) -> WaterCannonSights {
+    // Sights can only line up if there are at least 2 of them.
+    if sights.len() < 2 {
+        return WaterCannonSights::None;
+    }
+
+    let (row, column) = sights[0];
+    if sights.iter().all(|sight| sight.0 == row) {
+        WaterCannonSights::Row(row)
+    } else if sights.iter().all(|sight| sight.1 == column) {
+        WaterCannonSights::Column(column)
+    } else {
+        WaterCannonSights::None
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Guess {
+    row: usize,
+    column: usize,
+    number: u8,
+}
+
+// Return a solved puzzle or `None` if none of the given guesses are able to yield a solved puzzle. `None` would indicate an erroneous guess was taken earlier and the caller needs to discard this "branch". Gives up with an error once any limit set in the config is hit, counting the guesses of every branch.
+//
+// With `parallel_guesses` configured each of the first guesses is explored on a thread of its own, and the rest give up once one finds a solution. With `logic_only` no guesses are made at all, so `None` is returned straight away. With `max_guess_depth` guesses aren't made on top of that many others, those branches being abandoned like wrong guesses.
+//
+// A solved puzzle's `guess_report()` tells how the search went.
+pub fn solve_with_guesses(given_puzzle: Puzzle) -> Result<Option<Puzzle>, SolveError> {
+    if given_puzzle.config.logic_only {
+        return Ok(None);
+    }
+
+    let started = Instant::now();
+    let elapsed = given_puzzle.elapsed;
+    let technique_times = given_puzzle.technique_times.clone();
+    let budget = Budget::new(&given_puzzle.config);
+    let transcript = given_puzzle.config.transcript.clone();
+    let solved = if given_puzzle.config.parallel_guesses {
+        search_in_parallel(&given_puzzle, &budget)
+    } else {
+        search_guesses(given_puzzle, &budget, 1)
+    };
+    transcript.flush();
+    let solved = solved?;
+
+    Ok(solved.map(|mut puzzle| {
+        puzzle.guesses_by_depth = budget.guesses_by_depth();
+        puzzle.elapsed = elapsed + started.elapsed();
+        puzzle.technique_times = technique_times;
+        for &(technique, time) in budget.technique_times().iter() {
+            add_time(&mut puzzle.technique_times, technique, time);
+        }
+        puzzle
+    }))
+}
+
+// Explore each of the first guesses on a thread of its own.
+fn search_in_parallel(
+    given_puzzle: &Puzzle,
+    budget: &Budget,
+) -> Result<Option<Puzzle>, SolveError> {
+    let guesses = guesses(given_puzzle);
+    let state = given_puzzle.snapshot();
+    std::thread::scope(|scope| {
+        let branches: Vec<_> = guesses
+            .iter()
+            .map(|guess| {
+                let frame = Frame {
+                    state: state.clone(),
+                    guesses: vec![*guess],
+                    depth: 1,
+                };
+                let working = given_puzzle.clone();
+                scope.spawn(move || search(working, vec![frame], budget))
+            })
+            .collect();
+
+        // Join every branch, so none outlives the search
+        let results: Vec<Result<Option<Puzzle>, SolveError>> = branches
+            .into_iter()
+            .map(|branch| branch.join().unwrap())
+            .collect();
+
+        let mut outcome = Ok(None);
+        for result in results {
+            match result {
+                Ok(Some(puzzle)) => return Ok(Some(puzzle)),
+                Ok(None) => {}
+                Err(error) => outcome = Err(error),
+            }
+        }
+        outcome
+    })
+}
+
+// The numbers that could go in the last cell with candidates, to guess between, in the configured order.
+fn guesses(given_puzzle: &Puzzle) -> Vec<Guess> {
+    let mut guesses: Vec<Guess> = Vec::new();
+    for (row_num, row) in given_puzzle.grid.iter().enumerate() {
+        for (col_num, cell) in row.iter().enumerate() {
+            // Cells past the grid's size have no candidates, so are never guessed at
+            let mut silly_for_test: Vec<Guess> = Vec::new();
+            for c in cell.candidates.iter() {
+                let guess = Guess {
+                    row: row_num,
+                    column: col_num,
+                    number: c,
+                };
+                silly_for_test.push(guess);
+            }
+            if !silly_for_test.is_empty() {
+                guesses = silly_for_test;
+            }
+        }
+    }
+
+    match given_puzzle.config.guess_order {
+        GuessOrder::Ascending => {}
+        GuessOrder::Descending => guesses.reverse(),
+        GuessOrder::Random(seed) => {
+            // Seeded by where the search is too, so each set of guesses is shuffled differently but reproducibly, whichever thread it's on
+            let mut state = seed ^ ((given_puzzle.iteration as u64) << 32);
+            if let Some(guess) = guesses.first() {
+                state ^= (guess.row * given_puzzle.shape.size() + guess.column) as u64;
+            }
+
+            Rng::new(state).shuffle(&mut guesses);
+        }
+    }
+    guesses
+}
+
+// A puzzle being guessed on during a search, as a snapshot to restore before each guess, with the guesses left to try on it, the next last so it can be popped. The depth is how many guesses those are on top of, counting themselves.
+struct Frame {
+    state: GridState,
+    guesses: Vec<Guess>,
+    depth: usize,
+}
+
+// The outcome of a guess: solved, wrong, or needing more guesses on top.
+enum Trial {
+    Solved,
+    Wrong,
+    Inconclusive,
+}
+
+// Search the guesses that could be made on the puzzle. See `search()`.
+fn search_guesses(
+    given_puzzle: Puzzle,
+    budget: &Budget,
+    depth: usize,
+) -> Result<Option<Puzzle>, SolveError> {
+    let stack = frame(&given_puzzle, budget, depth).into_iter().collect();
+    search(given_puzzle, stack, budget)
+}
+
+// The guesses to try on the puzzle, or `None` if it's too deep to guess on.
+fn frame(given_puzzle: &Puzzle, budget: &Budget, depth: usize) -> Option<Frame> {
+    log!(
+        given_puzzle,
+        "🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶"
+    );
+    if !budget.within_depth(depth) {
+        log!(
+            given_puzzle,
+            "🧶 Too deep to guess again, at depth {}. Abandoning this branch.",
+            depth
+        );
+        return None;
+    }
+
+    let mut guesses = guesses(given_puzzle);
+
+    log!(
+        given_puzzle,
+        "🧶 solve_with_guesses – {} possible candidates to guess from: {:?}",
+        guesses.len(),
+        guesses
+    );
+    guesses.reverse();
+
+    Some(Frame {
+        state: given_puzzle.snapshot(),
+        guesses,
+        depth,
+    })
+}
+
+// Depth first, try the guesses on the puzzle at the top of the stack, pushing an inconclusive guess's puzzle to guess on in turn and popping puzzles out of guesses. Stops once a guess yields a solved puzzle or another branch calls the search off. An explicit stack rather than recursion, as there's no telling how deep a search can go.
+//
+// There's just the one puzzle to work on, restored from the top of the stack before each guess, rather than a copy of the puzzle for every guess. The steps taken since the snapshot are dropped on restoring, so those left lead to the guess being tried.
+//
+// Once solved, or out of budget, any other branches being searched are called off.
+fn search(
+    working: Puzzle,
+    stack: Vec<Frame>,
+    budget: &Budget,
+) -> Result<Option<Puzzle>, SolveError> {
+    let result = search_stack(working, stack, budget);
+    if !matches!(result, Ok(None)) {
+        budget.stop.store(true, Ordering::Relaxed);
+    }
+    result
+}
+
+fn search_stack(
+    mut working: Puzzle,
+    mut stack: Vec<Frame>,
+    budget: &Budget,
+) -> Result<Option<Puzzle>, SolveError> {
+    while let Some(top) = stack.last_mut() {
+        if budget.stop.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let guess = match top.guesses.pop() {
+            Some(guess) => guess,
+            None => {
+                stack.pop();
+                continue;
+            }
+        };
+
+        let depth = top.depth;
+        working.restore(&top.state);
+        match try_guess(&mut working, &guess, budget, depth)? {
+            Trial::Solved => return Ok(Some(working)),
+            Trial::Wrong => {}
+            Trial::Inconclusive => stack.extend(frame(&working, budget, depth + 1)),
+        }
+    }
+
+    Ok(None)
+}
+
+// Solve the puzzle with the guess made, in place.
+fn try_guess(
+    trial: &mut Puzzle,
+    guess: &Guess,
+    budget: &Budget,
+    depth: usize,
+) -> Result<Trial, SolveError> {
+    budget.check_guess(trial, depth)?;
+
+    log!(trial, "Taking a guess! {:?}", guess);
+    trial.guess(guess.row, guess.column, guess.number);
+
+    // Only the time spent on this guess is the budget's to add up
+    trial.technique_times.clear();
+    let solved = trial.solve_within(budget);
+    budget.add_technique_times(&trial.technique_times);
+    solved?;
+
+    Ok(match trial.status() {
+        PuzzleStatus::Solved => {
+            log!(
+                trial,
+                "🙌 🙌 🙌 🙌 🙌 SOLVED! Our guess of {:?} yielded a solved puzzle! ✅\n{}",
+                guess,
+                trial.display()
+            );
+            Trial::Solved
+        }
+        PuzzleStatus::IllDefined(_) => {
+            log!(
+                trial,
+                "🧶 🧶 🧶 YIKES! Our guess of {:?} was wrong. ❌",
+                guess
+            );
+            Trial::Wrong
+        }
+        PuzzleStatus::Unsolved => {
+            log!(trial, "INCONCLUSIVE! Our guess of {:?} was inconslusive. Going deeper, into the next set of guesses.", guess);
+            Trial::Inconclusive
+        }
+    })
+}
+mod test {
+    #[allow(unused_imports)] // wtf?
+    use super::*;
+    use std::collections::HashSet;
+
+    #[allow(dead_code)] // wtf?
+    const SAMPLE: &str = r#"
+.4.5.2...
+76....1.2
+9...18.64
+..429...8
+.8.3.6.7.
+6...754..
+21.68...3
+4.6....27
+...4.9.1.
+    "#;
+
+    #[allow(dead_code)]
+    fn assert_eq_set(a: &Candidates, b: &[u8]) {
+        let a: HashSet<_> = a.iter().collect();
+        let b: HashSet<_> = b.iter().copied().collect();
+
+        assert!(a == b, "Sets do not match. Expected {:?}, found {:?}", b, a);
+    }
+
+    #[allow(dead_code)]
+    fn eq_slice(a: &[u8], b: &[u8]) -> bool {
+        let mut a: HashSet<&u8> = a.iter().collect();
+        let mut b: HashSet<&u8> = b.iter().collect();
+
+        a.remove(&0);
+        b.remove(&0);
+
+        if a != b {
+            println!("Slices do not match. Expected {:?}, found {:?}.", b, a);
+        }
+
+        a == b
+    }
+
+    #[test]
+    fn helpers() {
+        // Where in the block the number could go
+        let sights = |block: &[[Cell; 3]; 3], number: u8| -> Vec<(usize, usize)> {
+            (0..9)
+                .map(|i| (i / 3, i % 3))
+                .filter(|&(row, col)| block[row][col].candidates.contains(number))
+                .collect()
+        };
+
+        let mut block = [
+            [
+                Cell::with_number(7),
+                Cell::with_candidates(vec![3, 9]),
+                Cell::with_number(5),
+            ],
+            [
+                Cell::with_number(6),
+                Cell::with_candidates(vec![4, 9]),
+                Cell::with_number(1),
+            ],
+            [
+                Cell::with_candidates(vec![2, 3, 9]),
+                Cell::with_candidates(vec![2, 3, 4, 9]),
+                Cell::with_number(8),
+            ],
+        ];
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 4)),
+            WaterCannonSights::Column(1)
+        );
+
+        block = [
+            [
+                Cell::with_candidates(vec![3, 7, 9]),
+                Cell::with_number(6),
+                Cell::with_candidates(vec![1, 3, 7]),
+            ],
+            [
+                Cell::with_number(2),
+                Cell::with_number(8),
+                Cell::with_candidates(vec![1, 3]),
+            ],
+            [
+                Cell::with_candidates(vec![3, 9]),
+                Cell::with_number(4),
+                Cell::with_number(5),
+            ],
+        ];
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 9)),
+            WaterCannonSights::Column(0)
+        );
+
+        block = [
+            [
+                Cell::with_candidates(vec![1, 3, 9]),
+                Cell::with_number(2),
+                Cell::with_number(5),
+            ],
+            [
+                Cell::with_candidates(vec![1, 3, 9]),
+                Cell::with_number(8),
+                Cell::with_number(6),
+            ],
+            [
+                Cell::with_number(7),
+                Cell::with_candidates(vec![1, 4]),
+                Cell::with_candidates(vec![4, 9]),
+            ],
+        ];
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 1)),
+            WaterCannonSights::None
+        );
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 3)),
+            WaterCannonSights::Column(0)
+        );
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 4)),
+            WaterCannonSights::Row(2)
+        );
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 5)),
+            WaterCannonSights::None
+        );
+        assert_eq!(
+            line_up_water_cannon(&sights(&block, 9)),
+            WaterCannonSights::None
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let puzzle = super::Puzzle::parse(SAMPLE);
+        let numbers = |cells: Vec<&Cell>| -> Vec<u8> {
+            cells.iter().map(|cell| cell.number.unwrap_or(0)).collect()
+        };
+
+        assert_eq!(
+            vec![0, 4, 0, 5, 0, 2, 0, 0, 0],
+            numbers(puzzle.row(0).iter().collect())
+        );
+        assert_eq!(
+            vec![0, 7, 9, 0, 0, 6, 2, 4, 0],
+            numbers(puzzle.column(0).collect())
+        );
+        assert_eq!(
+            vec![0, 0, 8, 0, 7, 0, 4, 0, 0],
+            numbers(puzzle.block_cells(5).collect())
+        );
+        assert_eq!(Some(4), puzzle.block_cells(5).nth(6).unwrap().number);
+    }
+
+    #[test]
+    fn givens() {
+        let mut puzzle = super::Puzzle::parse(SAMPLE);
+        let givens = puzzle.givens();
+        assert_eq!(36, givens.len());
+        assert_eq!(Coord::new(0, 1), givens[0]);
+
+        // Clues stand, whatever's placed on them
+        assert!(!puzzle.set_number(Coord::new(0, 1), 9));
+        assert_eq!(Some(4), puzzle.grid[0][1].number);
+        assert!(!puzzle.update_candidates(Coord::new(0, 1), puzzle.shape().digits()));
+        assert!(puzzle.grid[0][1].candidates.is_empty());
+
+        assert!(puzzle.set_number(Coord::new(0, 0), 3));
+        assert_eq!(Some(3), puzzle.grid[0][0].number);
+        assert_eq!(36, puzzle.givens().len());
+    }
+
+    #[test]
+    fn diagnose() {
+        let mut puzzle = super::Puzzle::parse(SAMPLE);
+        puzzle.assign_candidates();
+        assert!(puzzle.diagnose().is_empty());
+
+        // A 4 copied into the wrong cell, and two cells worn down to nothing
+        puzzle.grid[0][0].number = Some(4);
+        puzzle.grid[0][2].candidates = Candidates::none();
+        puzzle.grid[8][8].candidates = Candidates::none();
+        assert_eq!(
+            vec![
+                IllDefinedReason::NoPossibleSolutionForCell((0, 2)),
+                IllDefinedReason::NoPossibleSolutionForCell((8, 8)),
+                IllDefinedReason::NumberRepeatsInRow(4, 0),
+                IllDefinedReason::NumberRepeatsInColumn(4, 0),
+                IllDefinedReason::NumberRepeatsInBlock(4, 0),
+                IllDefinedReason::NumberHasNoPlace(1, Unit::Row(0)),
+                IllDefinedReason::NumberHasNoPlace(6, Unit::Column(8)),
+                IllDefinedReason::NumberHasNoPlace(1, Unit::Block(0)),
+            ],
+            puzzle.diagnose()
+        );
+        assert_eq!(
+            PuzzleStatus::IllDefined(IllDefinedReason::NoPossibleSolutionForCell((0, 2))),
+            puzzle.status()
+        );
+    }
+
+    #[test]
+    fn peer_contradictions() {
+        let mut puzzle = super::Puzzle::parse(SAMPLE);
+        puzzle.assign_candidates();
+
+        // The cells see each other along the row and in the block, but are only reported the once. Neither can be 1 now, which leaves 1 nowhere to go in either.
+        puzzle.grid[0][0].candidates = [3].into_iter().collect();
+        puzzle.grid[0][2].candidates = [3].into_iter().collect();
+        assert_eq!(
+            vec![
+                IllDefinedReason::SameOnlyCandidate(3, (0, 0), (0, 2)),
+                IllDefinedReason::NumberHasNoPlace(1, Unit::Row(0)),
+                IllDefinedReason::NumberHasNoPlace(1, Unit::Block(0)),
+            ],
+            puzzle.diagnose()
+        );
+    }
+
+    #[test]
+    fn baby_steps() {
+        let mut puzzle = super::Puzzle::parse(SAMPLE);
+
+        println!("Parsed puzzle:\n{}", puzzle);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(0)), &[4, 5, 2]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(1)), &[7, 6, 1, 2]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(2)), &[9, 1, 8, 6, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(3)), &[4, 2, 9, 8]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(4)), &[8, 3, 6, 7]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(5)), &[6, 7, 5, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(6)), &[2, 1, 6, 8, 3]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(7)), &[4, 6, 2, 7]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Row(8)), &[4, 9, 1]);
+
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(0)), &[7, 9, 6, 2, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(1)), &[4, 6, 8, 1]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(2)), &[4, 6]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(3)), &[5, 2, 3, 6, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(4)), &[1, 9, 7, 8]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(5)), &[2, 8, 6, 5, 9]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(6)), &[1, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(7)), &[6, 7, 2, 1]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Column(8)), &[2, 4, 8, 3, 7]);
+
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(0)), &[4, 7, 6, 9]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(1)), &[5, 2, 1, 8]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(2)), &[1, 2, 6, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(3)), &[4, 8, 6]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(4)), &[2, 9, 3, 6, 7, 5]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(5)), &[8, 7, 4]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(6)), &[2, 1, 4, 6]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(7)), &[6, 8, 4, 9]);
+        assert_eq_set(&puzzle.numbers_in(Unit::Block(8)), &[3, 2, 7, 1]);
+
+        // Candidates, narrowed down by the basic reductions
+        puzzle.assign_candidates();
+        puzzle.set_config(SolverConfig::with_techniques(vec![
+            Technique::SaraFlex,
+            Technique::WaterCannon,
+        ]));
+        puzzle.run_pipeline();
+
+        // Block 0
+        assert!(eq_slice(&puzzle.grid[0][0].candidates.to_vec(), &[1, 3, 8]));
+        assert!(eq_slice(&puzzle.grid[0][2].candidates.to_vec(), &[1, 3, 8]));
+        assert!(eq_slice(&puzzle.grid[1][2].candidates.to_vec(), &[5, 8]));
+        assert!(eq_slice(&puzzle.grid[2][1].candidates.to_vec(), &[2, 3, 5]));
+        assert!(eq_slice(&puzzle.grid[2][2].candidates.to_vec(), &[2, 3, 5]));
+
+        println!("Internals:\n{}", puzzle.internals());
+    }
+
+    #[test]
+    fn reduce_candidates_by_uniqueness() {
+        let pinned_pair: Vec<Vec<u8>> = vec![
+            vec![2, 7],
+            vec![2, 5, 7, 8],
+            vec![1],
+            vec![3],
+            vec![9],
+            vec![4],
+            vec![6],
+            vec![5, 8],
+            vec![5, 8],
+        ];
+
+        let mut reduced = super::reduce_candidates_by_uniqueness(pinned_pair);
+
+        assert_eq!(reduced[0], vec![2, 7]);
+        assert_eq!(reduced[1], vec![2, 7]);
+        assert_eq!(reduced[2], vec![1]);
+        assert_eq!(reduced[3], vec![3]);
+        assert_eq!(reduced[4], vec![9]);
+        assert_eq!(reduced[5], vec![4]);
+        assert_eq!(reduced[6], vec![6]);
+        assert_eq!(reduced[7], vec![5, 8]);
+        assert_eq!(reduced[8], vec![5, 8]);
+
+        let pinned_triplet: Vec<Vec<u8>> = vec![
+            vec![6, 3, 8],
+            vec![3, 4, 8],
+            vec![3, 4, 8],
+            vec![1],
+            vec![2, 4],
+            vec![5],
+            vec![4, 8, 9],
+            vec![7],
+            vec![3, 4, 8],
+        ];
+
+        reduced = super::reduce_candidates_by_uniqueness(pinned_triplet);
+
+        assert_eq!(reduced[0], vec![6]);
+        assert_eq!(reduced[1], vec![3, 4, 8]);
+        assert_eq!(reduced[2], vec![3, 4, 8]);
+        assert_eq!(reduced[3], vec![1]);
+        assert_eq!(reduced[4], vec![2]);
+        assert_eq!(reduced[5], vec![5]);
+        assert_eq!(reduced[6], vec![9]);
+        assert_eq!(reduced[7], vec![7]);
+        assert_eq!(reduced[8], vec![3, 4, 8]);
+    }
+
+    #[test]
+    fn eliminations() {
+        let pattern = Justification::Pattern(vec![(0, 0), (0, 1)]);
+        assert_eq!(
+            0,
+            Consolidation::eliminations(Technique::WaterCannon, pattern.clone(), &[]).len()
+        );
+
+        // Removals from one cell stay together
+        assert_eq!(
+            vec![Consolidation::CandidatesEliminated {
+                technique: Technique::SaraFlex,
+                cells: vec![(4, 5)],
+                digits: vec![3, 7],
+                justification: pattern.clone(),
+            }],
+            Consolidation::eliminations(
+                Technique::SaraFlex,
+                pattern.clone(),
+                &[((4, 5), 3), ((4, 5), 7)]
+            )
+        );
+
+        // Otherwise they're grouped by digit
+        let removals = [((0, 3), 6), ((0, 7), 6), ((0, 7), 2)];
+        let grouped =
+            Consolidation::eliminations(Technique::WaterCannon, pattern.clone(), &removals);
+        assert_eq!(
+            vec![
+                Consolidation::CandidatesEliminated {
+                    technique: Technique::WaterCannon,
+                    cells: vec![(0, 7)],
+                    digits: vec![2],
+                    justification: pattern.clone(),
+                },
+                Consolidation::CandidatesEliminated {
+                    technique: Technique::WaterCannon,
+                    cells: vec![(0, 3), (0, 7)],
+                    digits: vec![6],
+                    justification: pattern,
+                },
+            ],
+            grouped
+        );
+        assert!(!grouped[0].is_assignment());
+    }
+
+    #[test]
+    fn justifications() {
+        // A pinned pair in row 0 removes its digits from the rest of the row
+        let mut puzzle = super::Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][8] = Cell::with_candidates(vec![2, 5]);
+
+        let flexed = puzzle.reduce_candidates_with_sara_flex();
+        assert_eq!(
+            Consolidation::CandidatesEliminated {
+                technique: Technique::SaraFlex,
+                cells: vec![(0, 8)],
+                digits: vec![2],
+                justification: Justification::LockedCells(Unit::Row(0), vec![(0, 0), (0, 4)]),
+            },
+            flexed[0]
+        );
+
+        // Every other digit is already placed among the cell's peers
+        let mut puzzle = super::Puzzle::parse(SAMPLE);
+        puzzle.assign_candidates();
+        for consolidation in puzzle.assign_naked_singles() {
+            if let Consolidation::SingleCandidateForCell(assignment) = consolidation {
+                let Justification::Peers(peers) = assignment.justification else {
+                    panic!("Expected peers, found {:?}", assignment.justification);
+                };
+                assert!(!peers.is_empty());
+                for (r, c) in peers {
+                    assert_ne!(Some(assignment.number), puzzle.grid[r][c].number);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn consolidation_modes() {
+        let mut puzzle = super::Puzzle::parse("");
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][1] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[0][2] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[4][4] = Cell::with_candidates(vec![5, 6]);
+        puzzle.grid[4][5] = Cell::with_candidates(vec![6, 7]);
+
+        let mut first = puzzle.clone();
+        first.config.consolidation_mode = ConsolidationMode::FirstFinding;
+        assert_eq!(1, first.assign_hidden_singles().len());
+        assert_eq!(Some(1), first.grid[0][0].number);
+        assert_eq!(None, first.grid[4][4].number);
+
+        // Most digits have only one place left in some unit of such an empty grid, so findings clash. (0, 0) is the only place for 1 in its block, row, and column, but is only assigned once.
+        let all = puzzle.assign_hidden_singles();
+        assert!(all.len() > 1);
+        assert_eq!(Some(1), puzzle.grid[0][0].number);
+
+        let mut positions: Vec<(usize, usize)> = all
+            .iter()
+            .map(|consolidation| consolidation.assignment().unwrap().0)
+            .collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(all.len(), positions.len());
+        for &a in positions.iter() {
+            for &b in positions.iter() {
+                if cells_see_each_other(puzzle.shape, a, b) {
+                    assert_ne!(puzzle.grid[a.0][a.1].number, puzzle.grid[b.0][b.1].number);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_guesses() {
+        let mut puzzle = super::Puzzle::parse(include_str!("../samples/evil.txt"));
+        puzzle.config = SolverConfig {
+            techniques: vec![Technique::NakedSingle, Technique::HiddenSingle],
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Unsolved, puzzle.status());
+
+        let sequential = solve_with_guesses(puzzle.clone()).unwrap().unwrap();
+        puzzle.config.parallel_guesses = true;
+        let parallel = solve_with_guesses(puzzle).unwrap().unwrap();
+
+        assert_eq!(PuzzleStatus::Solved, parallel.status());
+        assert_eq!(sequential.grid, parallel.grid);
+    }
+
+    #[test]
+    fn logic_only() {
+        let mut puzzle = super::Puzzle::parse(include_str!("../samples/evil.txt"));
+        puzzle.config = SolverConfig {
+            techniques: vec![Technique::NakedSingle, Technique::HiddenSingle],
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+
+        assert_eq!(PuzzleStatus::Unsolved, puzzle.status());
+        assert!(solve_with_guesses(puzzle).unwrap().is_none());
+    }
+
+    #[test]
+    fn guess_depth() {
+        let mut puzzle = super::Puzzle::parse(include_str!("../samples/evil.txt"));
+        puzzle.config = SolverConfig {
+            techniques: vec![Technique::NakedSingle, Technique::HiddenSingle],
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!("No guesses needed.", puzzle.guess_report().describe());
+
+        let solved = solve_with_guesses(puzzle.clone()).unwrap().unwrap();
+        let report = solved.guess_report();
+        let depth = report.path().len();
+        assert!(depth > 1);
+        assert_eq!(depth, report.by_depth().len());
+        assert!(report.guesses() >= depth);
+        for ((row, col), number) in report.path() {
+            assert_eq!(Some(*number), solved.grid[*row][*col].number);
+        }
+
+        // Too shallow to reach the solution
+        puzzle.config.max_guess_depth = Some(depth - 1);
+        assert!(solve_with_guesses(puzzle.clone()).unwrap().is_none());
+        puzzle.config.max_guess_depth = Some(depth);
+        assert!(solve_with_guesses(puzzle).unwrap().is_some());
+    }
+    #[test]
+    fn deep_search() {
+        // Nothing to go on but guesses, dozens deep
+        let mut puzzle = super::Puzzle::parse("");
+        puzzle.config = SolverConfig {
+            techniques: vec![Technique::NakedSingle],
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+
+        let solved = solve_with_guesses(puzzle).unwrap().unwrap();
+        assert_eq!(PuzzleStatus::Solved, solved.status());
+        assert!(solved.guess_report().path().len() > 9);
+    }
+    #[test]
+    fn guess_orders() {
+        // Every number could go in the last cell
+        let mut puzzle = super::Puzzle::parse("");
+        puzzle.assign_candidates();
+        let numbers = |puzzle: &super::Puzzle| -> Vec<u8> {
+            guesses(puzzle).iter().map(|guess| guess.number).collect()
+        };
+
+        assert_eq!(vec![9, 8, 7, 6, 5, 4, 3, 2, 1], numbers(&puzzle));
+        puzzle.config.guess_order = GuessOrder::Ascending;
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], numbers(&puzzle));
+
+        puzzle.config.guess_order = GuessOrder::Random(42);
+        let shuffled = numbers(&puzzle);
+        assert_eq!(shuffled, numbers(&puzzle));
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 9], sorted);
+        puzzle.config.guess_order = GuessOrder::Random(43);
+        assert_ne!(shuffled, numbers(&puzzle));
+    }
+
+    #[test]
+    fn hexadoku() {
+        let mut puzzle = super::Puzzle::parse(include_str!("../samples/hexadoku.txt"));
+        assert_eq!(super::Shape::HEXADOKU, puzzle.shape());
+        assert_eq!(130, puzzle.givens().len());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert!(puzzle.ss().contains("|462F|D5BG|EA83|91C7|\n|D5BG|"));
+    }
+
+    #[test]
+    fn minis() {
+        let mini = super::Puzzle::parse(include_str!("../samples/mini.txt"));
+        let six = super::Puzzle::parse(include_str!("../samples/six.txt"));
+        assert_eq!(super::Shape::MINI, mini.shape());
+        assert_eq!(super::Shape::SIX, six.shape());
+        assert_eq!(
+            super::Shape::MINI,
+            super::Puzzle::parse(".2....1...31....").shape()
+        );
+
+        for mut puzzle in [mini, six] {
+            puzzle.config = SolverConfig {
+                logic_only: true,
+                ..SolverConfig::default()
+            };
+            puzzle.solve().unwrap();
+            assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        }
+
+        // Blocks of six span two rows and three columns
+        let mut six = super::Puzzle::parse(include_str!("../samples/six.txt"));
+        six.solve().unwrap();
+        assert!(six
+            .ss()
+            .starts_with("*-------*\n|631|425|\n|425|631|\n|---+---|\n"));
+    }
+
+    #[test]
+    fn any_size() {
+        let logic_only = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+
+        // 12x12, of 3x4 blocks
+        let mut twelve = super::Puzzle::parse(include_str!("../samples/twelve.txt"));
+        assert_eq!(super::Shape::new(12, 3, 4), Ok(twelve.shape()));
+        twelve.config = logic_only.clone();
+        twelve.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, twelve.status());
+        assert!(twelve.ss().contains("|4913|A7B8|2C65|"));
+
+        // 25x25 with every third cell blanked, written out on one line
+        let position: String = (0..625)
+            .map(|i| match (i / 25, i % 25) {
+                (row, col) if (row + col) % 3 == 0 => '.',
+                (row, col) => super::symbol(((row * 5 + row / 5 + col) % 25 + 1) as u8),
+            })
+            .collect();
+        let mut big = super::Puzzle::parse(&position);
+        assert_eq!(25, big.shape().size());
+        big.config = logic_only;
+        big.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, big.status());
+        assert_eq!(Some(25), big.grid[0][24].number);
+    }
+
+    #[test]
+    fn x_sudoku() {
+        let input = include_str!("../samples/x-sudoku.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert!(puzzle.shape().has_diagonals());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // Without the header the diagonals aren't units, and the givens are too few
+        let plain = super::Puzzle::parse(&input.replace("# X-Sudoku\n", ""));
+        assert!(!plain.shape().has_diagonals());
+        assert_eq!(2, plain.count_solutions(2));
+        let mut flagged = plain.clone();
+        flagged.set_diagonals(true);
+        assert_eq!(puzzle.shape(), flagged.shape());
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        for d in 0..2 {
+            let digits: HashSet<Option<u8>> = puzzle
+                .shape()
+                .diagonal(d)
+                .map(|coord| puzzle[coord].number)
+                .collect();
+            assert_eq!(9, digits.len());
+        }
+        assert!(puzzle
+            .solution()
+            .steps()
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .any(|c| matches!(c, Consolidation::OnlyOnePossibleCandidateForDiagonal(_))));
+
+        // A number repeated along a diagonal is only a problem when it's a unit
+        let empty = ".........\n".repeat(3);
+        let repeated = format!("# diagonals\n1........\n{0}....1....\n{0}.........", empty);
+        let mut x = super::Puzzle::parse(&repeated);
+        x.assign_candidates();
+        assert_eq!(
+            PuzzleStatus::IllDefined(IllDefinedReason::NumberRepeatsInDiagonal(1, 0)),
+            x.status()
+        );
+        let mut plain = super::Puzzle::parse(&repeated.replace("# diagonals\n", ""));
+        plain.assign_candidates();
+        assert_eq!(PuzzleStatus::Unsolved, plain.status());
+
+        // The water cannon fires from a block along a diagonal, and from a diagonal into a block
+        let mut cannon = super::Puzzle::parse("# diagonals");
+        cannon.assign_candidates();
+        for (row, col) in Unit::Block(0).cells(cannon.shape()) {
+            if row != col {
+                cannon.grid[row][col].remove_candidate(5);
+            }
+        }
+        for coord in cannon.shape().diagonal(1).filter(|coord| coord.row < 6) {
+            cannon[coord].remove_candidate(7);
+        }
+        let found = cannon.reduce_candidates_using_water_cannon();
+        assert!(!cannon.grid[4][4].candidates.contains(5));
+        assert!(cannon.grid[1][1].candidates.contains(5));
+        assert!(!cannon.grid[6][0].candidates.contains(7));
+        assert!(cannon.grid[7][1].candidates.contains(7));
+        assert!(found.iter().any(|c| matches!(
+            c,
+            Consolidation::CandidatesEliminated {
+                justification: Justification::LockedCells(Unit::Diagonal(1), _),
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn jigsaw() {
+        let input = include_str!("../samples/jigsaw.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert!(puzzle.shape().is_jigsaw());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // Without the region map the blocks are as usual, and the givens can't all fit them
+        let (grid, map) = input.split_once("# Regions\n").unwrap();
+        let plain = super::Puzzle::parse(grid);
+        assert!(!plain.shape().is_jigsaw());
+        assert_eq!(0, plain.count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        for block in 0..9 {
+            let digits: HashSet<Option<u8>> =
+                puzzle.block_cells(block).map(|cell| cell.number).collect();
+            assert_eq!(9, digits.len());
+        }
+
+        // r2c4 is in the first region rather than the second block
+        let repeated = format!(
+            "1........\n...1.....\n{}# Regions\n{}",
+            ".........\n".repeat(7),
+            map
+        );
+        let mut jigsaw = super::Puzzle::parse(&repeated);
+        jigsaw.assign_candidates();
+        assert_eq!(
+            PuzzleStatus::IllDefined(IllDefinedReason::NumberRepeatsInBlock(1, 0)),
+            jigsaw.status()
+        );
+        let mut plain = super::Puzzle::parse(repeated.split("# Regions").next().unwrap());
+        plain.assign_candidates();
+        assert_eq!(PuzzleStatus::Unsolved, plain.status());
+    }
+
+    #[test]
+    fn sandwich() {
+        let input = include_str!("../samples/sandwich.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert_eq!(Some(28), puzzle.sandwiches().rows[0]);
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // The few givens are nowhere near enough without the sums
+        let (grid, _) = input.split_once("# Sandwiches\n").unwrap();
+        assert_eq!(2, super::Puzzle::parse(grid).count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert!(puzzle.wrong_sandwiches().is_empty());
+        assert!(puzzle
+            .solution()
+            .steps()
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .any(|c| c.technique() == Some(Technique::Sandwich)));
+
+        // Filled in, the first row must add up to its sum
+        let mut wrong = puzzle.clone();
+        let mut sandwiches = *wrong.sandwiches();
+        sandwiches.rows[0] = Some(27);
+        wrong.set_sandwiches(sandwiches);
+        assert_eq!(
+            PuzzleStatus::IllDefined(IllDefinedReason::SandwichDoesNotAddUp(Unit::Row(0))),
+            wrong.status()
+        );
+    }
+
+    #[test]
+    fn arrow() {
+        let input = include_str!("../samples/arrow.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert_eq!(9, puzzle.arrows().len());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // The few givens are nowhere near enough without the arrows
+        let (grid, _) = input.split_once("# Arrows\n").unwrap();
+        assert_eq!(2, super::Puzzle::parse(grid).count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert!(puzzle.wrong_arrows().is_empty());
+        assert!(puzzle
+            .solution()
+            .steps()
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .any(|c| c.technique() == Some(Technique::Arrow)));
+
+        // Filled in, an arrow must add up to its circle
+        let mut wrong = puzzle.clone();
+        wrong.grid[3][3].number = Some(9);
+        assert!(wrong
+            .diagnose()
+            .contains(&IllDefinedReason::ArrowDoesNotAddUp((3, 3))));
+    }
+
+    #[test]
+    fn xv() {
+        let input = include_str!("../samples/xv.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert_eq!(28, puzzle.xv().len());
+        assert!(puzzle.xv().is_negative());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // Two givens are nothing like enough without the marks
+        let (grid, _) = input.split_once("# XV\n").unwrap();
+        assert_eq!(2, super::Puzzle::parse(grid).count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert!(puzzle.wrong_xv().is_empty());
+        assert!(puzzle
+            .solution()
+            .steps()
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .any(|c| c.technique() == Some(Technique::Xv)));
+
+        // Filled in, the cells either side of a V must add up to 5
+        let mut wrong = puzzle.clone();
+        wrong.grid[0][0].number = Some(9);
+        assert!(wrong
+            .diagnose()
+            .contains(&IllDefinedReason::XvBroken((0, 0), (0, 1))));
+    }
+
+    #[test]
+    fn lines() {
+        let input = include_str!("../samples/lines.txt");
+        let mut puzzle = super::Puzzle::parse(input);
+        assert_eq!(10, puzzle.lines().len());
+        assert_eq!(1, puzzle.count_solutions(2));
+
+        // Twelve givens aren't enough without the lines
+        let (grid, _) = input.split_once("# lines\n").unwrap();
+        assert_eq!(2, super::Puzzle::parse(grid).count_solutions(2));
+
+        puzzle.config = SolverConfig {
+            logic_only: true,
+            ..SolverConfig::default()
+        };
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert!(puzzle.wrong_lines().is_empty());
+        let techniques: Vec<Technique> = puzzle
+            .solution()
+            .steps()
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .filter_map(|c| c.technique())
+            .collect();
+        assert!(techniques.contains(&Technique::Renban));
+        assert!(techniques.contains(&Technique::Whisper));
+
+        // Filled in, a renban's digits must run on from each other
+        let mut wrong = puzzle.clone();
+        wrong.grid[2][5].number = Some(9);
+        assert!(wrong
+            .diagnose()
+            .contains(&IllDefinedReason::LineBroken((2, 5))));
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::{Duration, Instant};
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use super::batch::{solve_fully, techniques_used};
+use super::engine::position;
+use super::{Difficulty, Puzzle, SolverConfig};
+
+/// A collection of puzzles kept in an SQLite database, each with its rating and how solving it went, to search for puzzles to practise on.
+pub struct Library {
+    connection: Connection,
+}
+
+// A puzzle in the library.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub id: i64,
+    // The puzzle as added, 81 characters with `.` for blanks, or as many as the cells of a grid of another shape
+    pub puzzle: String,
+    // As `Puzzle::canonical()` writes it, so the same puzzle isn't added twice however it's turned or relabelled
+    pub canonical: String,
+    pub difficulty: Difficulty,
+    // Each technique the solver used, in the order first used, with any guessing as `guess`
+    pub techniques: Vec<String>,
+    pub steps: usize,
+    pub solve_time: Duration,
+}
+
+// What adding a puzzle to the library did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Added {
+    New(i64),
+    // The puzzle, or one equivalent to it, was in the library already, with this id
+    Known(i64),
+}
+
+impl Library {
+    // Open the library at the path, creating it if there's none. `:memory:` makes one that's gone once closed.
+    pub fn open(path: &str) -> Result<Library, String> {
+        let connection = Connection::open(path).map_err(|e| e.to_string())?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS puzzles (
+                    id INTEGER PRIMARY KEY,
+                    puzzle TEXT NOT NULL,
+                    canonical TEXT NOT NULL UNIQUE,
+                    difficulty TEXT NOT NULL,
+                    techniques TEXT NOT NULL,
+                    steps INTEGER NOT NULL,
+                    solve_micros INTEGER NOT NULL
+                )",
+            )
+            .map_err(|e| e.to_string())?;
+
+        Ok(Library { connection })
+    }
+
+    // Rate and solve the puzzle, quietly whatever its config says, and keep it with how that went. Only puzzles with just the one solution are taken.
+    pub fn add(&self, puzzle: &Puzzle) -> Result<Added, String> {
+        let validation = puzzle.validate();
+        if !validation.is_valid() {
+            return Err(format!("The puzzle is {}", validation.verdict()));
+        }
+
+        let canonical = puzzle.canonical();
+        if let Some(id) = self
+            .connection
+            .query_row(
+                "SELECT id FROM puzzles WHERE canonical = ?1",
+                [&canonical],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+        {
+            return Ok(Added::Known(id));
+        }
+
+        let difficulty = puzzle.difficulty();
+        let mut solving = puzzle.clone();
+        solving.set_config(SolverConfig {
+            verbose: false,
+            ..puzzle.config.clone()
+        });
+        let started = Instant::now();
+        let (solved, _) = solve_fully(solving);
+        let solve_time = started.elapsed();
+
+        self.connection
+            .execute(
+                "INSERT INTO puzzles (puzzle, canonical, difficulty, techniques, steps, solve_micros)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    position(puzzle),
+                    canonical,
+                    difficulty.name(),
+                    techniques_used(&solved).join(" "),
+                    solved.steps.len() as i64,
+                    solve_time.as_micros() as i64,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+
+        Ok(Added::New(self.connection.last_insert_rowid()))
+    }
+
+    // Every puzzle in the library, in the order they were added.
+    pub fn list(&self) -> Result<Vec<Entry>, String> {
+        self.search(None, None)
+    }
+
+    // The puzzles of the difficulty, if one's given, whose solve used the technique, if one's given, by its name as `Technique::name()` has it or `guess`.
+    pub fn search(
+        &self,
+        difficulty: Option<Difficulty>,
+        technique: Option<&str>,
+    ) -> Result<Vec<Entry>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT id, puzzle, canonical, difficulty, techniques, steps, solve_micros
+                FROM puzzles
+                WHERE (?1 IS NULL OR difficulty = ?1)
+                AND (?2 IS NULL OR instr(' ' || techniques || ' ', ' ' || ?2 || ' ') > 0)
+                ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let entries = statement
+            .query_map(
+                params![difficulty.map(|difficulty| difficulty.name()), technique],
+                entry,
+            )
+            .map_err(|e| e.to_string())?;
+        entries
+            .map(|entry| entry.map_err(|e| e.to_string()))
+            .collect()
+    }
+}
+
+fn entry(row: &Row) -> rusqlite::Result<Entry> {
+    let difficulty: String = row.get(3)?;
+    let techniques: String = row.get(4)?;
+    let steps: i64 = row.get(5)?;
+    let solve_micros: i64 = row.get(6)?;
+
+    Ok(Entry {
+        id: row.get(0)?,
+        puzzle: row.get(1)?,
+        canonical: row.get(2)?,
+        difficulty: Difficulty::parse(&difficulty).unwrap_or(Difficulty::Evil),
+        techniques: techniques.split_whitespace().map(String::from).collect(),
+        steps: steps as usize,
+        solve_time: Duration::from_micros(solve_micros as u64),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Difficulty, Puzzle};
+    use super::{Added, Library};
+
+    #[test]
+    fn library() {
+        let library = Library::open(":memory:").unwrap();
+        let easy = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let evil = Puzzle::parse(include_str!("../samples/evil.txt"));
+
+        assert_eq!(Ok(Added::New(1)), library.add(&easy));
+        assert_eq!(Ok(Added::New(2)), library.add(&evil));
+        assert_eq!(Ok(Added::Known(1)), library.add(&easy));
+        assert!(library.add(&Puzzle::parse("")).is_err());
+
+        let entries = library.list().unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!(Difficulty::Easy, entries[0].difficulty);
+        assert_eq!(easy.canonical(), entries[0].canonical);
+        assert!(entries[0].techniques.contains(&"naked-singles".to_string()));
+        assert!(entries[0].steps > 0);
+
+        let easy_ones = library.search(Some(Difficulty::Easy), None).unwrap();
+        assert_eq!(vec![1], easy_ones.iter().map(|e| e.id).collect::<Vec<_>>());
+        let singles = library.search(None, Some("naked-singles")).unwrap();
+        assert_eq!(2, singles.len());
+        let naked = library.search(None, Some("naked")).unwrap();
+        assert!(naked.is_empty());
+    }
+}
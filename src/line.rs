@@ -0,0 +1,425 @@
+use std::sync::Arc;
+
+use super::xv;
+use super::{
+    Candidates, Consolidation, Constraint, Coord, IllDefinedReason, Justification, Puzzle, Shape,
+    Technique,
+};
+
+/// What a line asks of the digits along it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    // A renban line's digits are a set of consecutive digits, in any order, with none repeated
+    Renban,
+    // A German whisper's digits side by side along it differ by at least 5, or half the digits of a grid of another size, rounding up
+    Whisper,
+}
+
+impl LineKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineKind::Renban => "renban",
+            LineKind::Whisper => "whisper",
+        }
+    }
+
+    // The kind of line by name, in either case.
+    pub fn parse(name: &str) -> Result<LineKind, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "renban" => Ok(LineKind::Renban),
+            "whisper" => Ok(LineKind::Whisper),
+            _ => Err(format!("Expected a renban or whisper line, not '{}'", name)),
+        }
+    }
+}
+
+// How far apart the digits side by side along a whisper must be in a grid this size: 5 of 1 thru 9.
+pub(super) fn whisper_gap(size: usize) -> u8 {
+    size.div_ceil(2) as u8
+}
+
+/// A line drawn through cells, each next to the one before it, diagonally or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    cells: Vec<Coord>,
+    kind: LineKind,
+}
+
+impl Line {
+    // The line through the cells, in order. There must be at least two, each touching the one before, none of them twice.
+    pub fn new(kind: LineKind, cells: &[Coord]) -> Result<Line, String> {
+        let start = match cells {
+            [] | [_] => {
+                return Err(format!(
+                    "A {} line runs through two cells or more, not {}",
+                    kind.name(),
+                    cells.len()
+                ))
+            }
+            [start, ..] => *start,
+        };
+
+        let mut line = Line {
+            cells: Vec::with_capacity(cells.len()),
+            kind,
+        };
+        for &coord in cells {
+            if line.cells.contains(&coord) {
+                return Err(format!(
+                    "The {} line from {} crosses {} twice",
+                    kind.name(),
+                    start,
+                    coord
+                ));
+            }
+            if let Some(&last) = line.cells.last() {
+                if last.row.abs_diff(coord.row) > 1 || last.col.abs_diff(coord.col) > 1 {
+                    return Err(format!(
+                        "The {} line from {} jumps from {} to {}",
+                        kind.name(),
+                        start,
+                        last,
+                        coord
+                    ));
+                }
+            }
+            line.cells.push(coord);
+        }
+        Ok(line)
+    }
+
+    pub fn kind(&self) -> LineKind {
+        self.kind
+    }
+
+    // The cells along the line, from its start.
+    pub fn cells(&self) -> Vec<Coord> {
+        self.cells.clone()
+    }
+}
+
+/// Renban and whisper lines, in the order given, as many as there are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lines {
+    lines: Vec<Line>,
+}
+
+impl Lines {
+    pub const NONE: Lines = Lines { lines: Vec::new() };
+
+    pub fn push(&mut self, line: Line) {
+        self.lines.push(line);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Line> {
+        self.lines.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+impl Default for Lines {
+    fn default() -> Lines {
+        Lines::NONE
+    }
+}
+
+// What each cell of a line could hold in some arrangement keeping to it. Cells are given as the digits they could be, a number as itself alone. A cell left empty means nothing does.
+pub(super) fn fits(kind: LineKind, size: usize, cells: &[Candidates]) -> Vec<Candidates> {
+    match kind {
+        LineKind::Renban => renban_fits(size, cells),
+        LineKind::Whisper => whisper_fits(size, cells),
+    }
+}
+
+// A renban's cells hold a run of as many consecutive digits as there are cells. A run is only possible if every cell could hold one of its digits and every digit could go somewhere, so a cell keeps what it could hold of the possible runs. A digit a cell must hold can't be anywhere else on the line either.
+//
+// As with arrows, a little more may be kept than could really go, but nothing that could is lost.
+fn renban_fits(size: usize, cells: &[Candidates]) -> Vec<Candidates> {
+    let mut fits = vec![Candidates::none(); cells.len()];
+    for low in 1..=(size + 1).saturating_sub(cells.len()) {
+        let run: Candidates = (low..low + cells.len()).map(|digit| digit as u8).collect();
+        let within: Vec<Candidates> = cells.iter().map(|cell| cell.intersection(run)).collect();
+        let covered = within
+            .iter()
+            .fold(Candidates::none(), |covered, &cell| covered.union(cell));
+        if within.iter().any(Candidates::is_empty) || covered != run {
+            continue;
+        }
+
+        for (fit, cell) in fits.iter_mut().zip(within) {
+            *fit = fit.union(cell);
+        }
+    }
+
+    for (i, cell) in cells.iter().enumerate() {
+        if let Some(digit) = cell.single() {
+            for (_, fit) in fits.iter_mut().enumerate().filter(|&(j, _)| j != i) {
+                fit.remove(digit);
+            }
+        }
+    }
+    fits
+}
+
+// A whisper's cells side by side along it hold digits far enough apart. Each pair is fitted in turn, as XV marks are, and again until none changes, so a cell keeps only digits with some partner either side.
+fn whisper_fits(size: usize, cells: &[Candidates]) -> Vec<Candidates> {
+    let gap = whisper_gap(size);
+    let mut fits = cells.to_vec();
+    loop {
+        let mut changed = false;
+        for i in 1..fits.len() {
+            let (a, b) = xv::fits(fits[i - 1], fits[i], |x, y| x.abs_diff(y) >= gap);
+            changed |= (a, b) != (fits[i - 1], fits[i]);
+            (fits[i - 1], fits[i]) = (a, b);
+        }
+        if !changed {
+            return fits;
+        }
+    }
+}
+
+// Each line is a constraint of its own, its cells keeping only what some arrangement along it has a place for.
+impl Constraint for Line {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        let positions = self.cells();
+        let cells: Vec<Candidates> = positions
+            .iter()
+            .map(|&coord| match puzzle[coord].number {
+                Some(number) => [number].into_iter().collect(),
+                None => puzzle[coord].candidates,
+            })
+            .collect();
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for (&coord, fit) in positions
+            .iter()
+            .zip(fits(self.kind, puzzle.shape.size(), &cells))
+        {
+            if puzzle[coord].number.is_some() {
+                continue;
+            }
+
+            for digit in puzzle[coord].candidates.difference(fit).iter() {
+                puzzle[coord].remove_candidate(digit);
+                log!(puzzle, "〰️〰️〰️〰️〰️ The {} line from {} eliminated candidate {} at grid position ({}, {})", self.kind.name(), positions[0], digit, coord.row, coord.col);
+                removals.push((coord.into(), digit));
+            }
+        }
+
+        let technique = match self.kind {
+            LineKind::Renban => Technique::Renban,
+            LineKind::Whisper => Technique::Whisper,
+        };
+        Consolidation::eliminations(
+            technique,
+            Justification::Line(self.kind, positions.into_iter().map(Into::into).collect()),
+            &removals,
+        )
+    }
+
+    // Broken once filled in if a renban's digits aren't a run, or repeat, or a whisper's side by side are too close.
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        let numbers: Vec<u8> = self
+            .cells()
+            .into_iter()
+            .map(|coord| puzzle[coord].number)
+            .collect::<Option<_>>()?;
+
+        let broken = match self.kind {
+            LineKind::Renban => {
+                let digits: Candidates = numbers.iter().collect();
+                let (low, high) = (numbers.iter().min(), numbers.iter().max());
+                digits.count() != numbers.len()
+                    || low
+                        .zip(high)
+                        .is_some_and(|(low, high)| usize::from(high - low) + 1 != numbers.len())
+            }
+            LineKind::Whisper => {
+                let gap = whisper_gap(puzzle.shape.size());
+                numbers
+                    .windows(2)
+                    .any(|pair| pair[0].abs_diff(pair[1]) < gap)
+            }
+        };
+        broken.then(|| IllDefinedReason::LineBroken(self.cells[0].into()))
+    }
+
+    fn fits(&self, shape: Shape, cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        let cells: Vec<Candidates> = self.cells.iter().map(|&coord| cell(coord)).collect();
+        self.cells
+            .iter()
+            .copied()
+            .zip(fits(self.kind, shape.size(), &cells))
+            .collect()
+    }
+}
+
+impl Puzzle {
+    // Renban lines: each holds a run of consecutive digits, in any order. A cell can't hold a digit no possible run has a place for, nor one another cell on the line must.
+    //
+    // Returns the eliminations made, a line at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_renban(&mut self) -> Vec<Consolidation> {
+        self.reduce_candidates_along_lines(LineKind::Renban)
+    }
+
+    // German whispers: digits side by side along each differ by at least 5. A cell can't hold a digit that leaves a neighbour along the line nothing far enough away, so a 5 never goes on one, and a cell between two others soon loses the middling digits.
+    //
+    // Returns the eliminations made, a line at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_whispers(&mut self) -> Vec<Consolidation> {
+        self.reduce_candidates_along_lines(LineKind::Whisper)
+    }
+
+    fn reduce_candidates_along_lines(&mut self, kind: LineKind) -> Vec<Consolidation> {
+        let clues = Arc::clone(&self.clues);
+        let lines = &clues.lines;
+        if lines.is_empty() {
+            return vec![];
+        }
+
+        for line in lines.iter().filter(|line| line.kind() == kind) {
+            let found = line.prune(self);
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        vec![]
+    }
+
+    // The starts of lines filled in that break their rule.
+    pub(super) fn wrong_lines(&self) -> Vec<Coord> {
+        self.clues
+            .lines
+            .iter()
+            .filter(|line| line.check(self).is_some())
+            .map(|line| line.cells()[0])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Coord, Puzzle, PuzzleStatus, Shape, SolverConfig, Technique};
+    use super::{fits, Line, LineKind, Lines};
+
+    #[test]
+    fn lines() {
+        let coord = |row, col| Coord::new(row, col);
+        let line = Line::new(LineKind::Whisper, &[coord(0, 0), coord(1, 1), coord(1, 2)]).unwrap();
+        assert_eq!(LineKind::Whisper, line.kind());
+        assert_eq!(vec![coord(0, 0), coord(1, 1), coord(1, 2)], line.cells());
+
+        assert!(Line::new(LineKind::Renban, &[]).is_err());
+        assert!(Line::new(LineKind::Renban, &[coord(0, 0)]).is_err());
+        assert!(Line::new(LineKind::Renban, &[coord(0, 0), coord(0, 2)]).is_err());
+        assert!(Line::new(LineKind::Renban, &[coord(0, 0), coord(0, 1), coord(0, 0)]).is_err());
+        assert_eq!(Ok(LineKind::Renban), LineKind::parse("Renban"));
+        assert!(LineKind::parse("thermo").is_err());
+
+        // As many lines as are given, however many, and as long
+        let mut lines = Lines::NONE;
+        assert!(lines.is_empty());
+        for _ in 0..100 {
+            lines.push(line.clone());
+        }
+        assert_eq!(100, lines.len());
+        let snake: Vec<Coord> = (0..81)
+            .map(|i| coord(i / 9, if (i / 9) % 2 == 0 { i % 9 } else { 8 - i % 9 }))
+            .collect();
+        assert_eq!(
+            81,
+            Line::new(LineKind::Whisper, &snake).unwrap().cells().len()
+        );
+    }
+
+    #[test]
+    fn renban() {
+        let set = |digits: &[u8]| -> Candidates { digits.iter().collect() };
+        let all = Shape::STANDARD.digits();
+
+        // With a 2 on a line of three, the run is 1 thru 3 or 2 thru 4, less the 2 the others can't repeat
+        let found = fits(LineKind::Renban, 9, &[set(&[2]), all, all]);
+        assert_eq!(vec![set(&[2]), set(&[1, 3, 4]), set(&[1, 3, 4])], found);
+
+        // A cell of 8 or 9 on a line of five rules out 1 thru 3
+        let found = fits(LineKind::Renban, 9, &[set(&[8, 9]), all, all, all, all]);
+        assert_eq!(set(&[4, 5, 6, 7, 8, 9]), found[1]);
+
+        // Nine cells hold every digit, and ten can't fit
+        assert_eq!(all, fits(LineKind::Renban, 9, &[all; 9])[0]);
+        assert!(fits(LineKind::Renban, 9, &[all; 10])
+            .iter()
+            .all(Candidates::is_empty));
+
+        // A run needs every digit placed
+        let found = fits(LineKind::Renban, 9, &[set(&[1, 5]), set(&[1, 5])]);
+        assert!(found.iter().all(Candidates::is_empty));
+    }
+
+    #[test]
+    fn whisper() {
+        let set = |digits: &[u8]| -> Candidates { digits.iter().collect() };
+        let all = Shape::STANDARD.digits();
+
+        // No 5, as nothing's 5 away from it
+        let found = fits(LineKind::Whisper, 9, &[all, all]);
+        assert_eq!(set(&[1, 2, 3, 4, 6, 7, 8, 9]), found[0]);
+
+        // A 4 next door leaves 9, which leaves the cell past it 1 thru 4
+        let found = fits(LineKind::Whisper, 9, &[set(&[4]), all, all]);
+        assert_eq!(vec![set(&[4]), set(&[9]), set(&[1, 2, 3, 4])], found);
+
+        // Between two cells, the middle can only be at the ends
+        let found = fits(LineKind::Whisper, 9, &[set(&[3, 4]), all, set(&[1])]);
+        assert_eq!(set(&[8, 9]), found[1]);
+    }
+
+    #[test]
+    fn reduce() {
+        let coord = |row, col| Coord::new(row, col);
+        let mut lines = Lines::NONE;
+        lines.push(Line::new(LineKind::Renban, &[coord(0, 0), coord(1, 1), coord(2, 2)]).unwrap());
+        lines.push(Line::new(LineKind::Whisper, &[coord(4, 4), coord(4, 5)]).unwrap());
+
+        let mut puzzle = Puzzle::parse("");
+        puzzle.set_lines(lines);
+        puzzle.config = SolverConfig::with_techniques(vec![Technique::Renban, Technique::Whisper]);
+        puzzle.grid[0][0].number = Some(9);
+        puzzle.grid[4][4].number = Some(3);
+        puzzle.assign_candidates();
+
+        // With a 9, the run is 7 thru 9
+        let found = puzzle.reduce_candidates_with_renban();
+        assert_eq!(Some(Technique::Renban), found[0].technique());
+        assert_eq!(vec![7, 8], puzzle.grid[1][1].candidates.to_vec());
+        assert!(puzzle.reduce_candidates_with_renban().is_empty());
+
+        // A 3 whispers to an 8 or 9
+        let found = puzzle.reduce_candidates_with_whispers();
+        assert_eq!(Some(Technique::Whisper), found[0].technique());
+        assert_eq!(vec![8, 9], puzzle.grid[4][5].candidates.to_vec());
+
+        // Filled in, they must keep to their rules
+        let mut filled = puzzle.clone();
+        filled.grid[1][1].number = Some(7);
+        filled.grid[2][2].number = Some(8);
+        filled.grid[4][5].number = Some(8);
+        assert!(filled.wrong_lines().is_empty());
+        filled.grid[2][2].number = Some(6);
+        filled.grid[4][5].number = Some(7);
+        assert_eq!(vec![coord(0, 0), coord(4, 4)], filled.wrong_lines());
+
+        // A renban can't run through a 1 and a 9 three cells apart
+        let mut stuck = puzzle.clone();
+        stuck.grid[2][2].number = Some(1);
+        while !stuck.reduce_candidates_with_renban().is_empty() {}
+        assert!(matches!(stuck.status(), PuzzleStatus::IllDefined(_)));
+    }
+}
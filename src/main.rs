@@ -1,1525 +1,1706 @@
-#![feature(let_chains)]
-
-use std::{collections::HashSet, fmt, fs::File, io::Write};
-
-#[derive(Clone, Copy, Debug)]
-struct Cell {
-    number: Option<u8>,
-
-    #[allow(dead_code)]
-    given: bool,
-
-    candidates: [u8; 9],
-}
-#[derive(Clone)]
-struct Puzzle {
-    iteration: usize,
-    grid: [[Cell; 9]; 9],
-    last_consolidation: Vec<Consolidation>,
-}
-
-// The type of consolidation performed during a step towards the solution
-#[derive(Clone, Debug, PartialEq)]
-enum Consolidation {
-    SingleCandidateForCell(CellAssignment),
-    OnlyOnePossibleCandidateForBlock(CellAssignment),
-    OnlyOnePossibleCandidateForRow(CellAssignment),
-    OnlyOnePossibleCandidateForColumn(CellAssignment),
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum PuzzleStatus {
-    Solved,
-    Unsolved,
-
-    IllDefined(IllDefinedReason),
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum IllDefinedReason {
-    NoPossibleSolutionForCell((usize, usize)),
-    NumberRepeatsInRow(u8, usize),
-    NumberRepeatsInColumn(u8, usize),
-    NumberRepeatsInBlock(u8, usize),
-}
-
-#[derive(Debug, PartialEq)]
-enum WaterCannonSights {
-    Row(usize),
-    Column(usize),
-    None,
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use indicatif::{ProgressBar, ProgressStyle};
+
+use christopher::{
+    batch_with_progress, benchmark_with_progress, cross_check_with_progress, engine, race,
+    solve_with_guesses, stream, strip_emoji, symbol, worksheet, AntiKnight, CandidateStyle,
+    ColorChoice, Coord, Dataset, Debugger, DisplayOptions, Game, GeneratorOptions, GridStyle,
+    GuessOrder, Hint, HouseRule, Key, MultiPuzzle, PageSize, Pipeline, Progress, Puzzle,
+    PuzzleStatus, SolveError, SolverConfig, Symmetry, Technique, Theme, WorksheetOptions, DATASETS,
+};
+
+struct Options {
+    config: SolverConfig,
+    trace: Option<String>,
+    html: Option<String>,
+    png: Option<String>,
+    ss: Option<String>,
+    csv: Option<String>,
+    animate: Option<String>,
+    explain: bool,
+    stats: bool,
+    quiet: bool,
+    plain: bool,
+    json: bool,
+    diagonals: bool,
+    display: DisplayOptions,
+    theme: Theme,
+    watch: Option<String>,
+    batch: Option<String>,
+    output: Option<String>,
+    cross_check: bool,
+    stream: bool,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-struct CellAssignment {
-    number: u8,
-    block: usize,
-    row: usize,
-    col: usize,
-}
-
-impl Cell {
-    #[allow(dead_code)]
-    fn with_number(number: u8) -> Cell {
-        Cell {
-            number: Some(number),
-            given: true,
-            candidates: [0, 0, 0, 0, 0, 0, 0, 0, 0],
+fn main() -> Result<(), std::io::Error> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("print") => return print(&args[1..]),
+        Some("play") => return play(&args[1..]),
+        Some("debug") => return debug(&args[1..]),
+        Some("hint") => return hint(&args[1..]),
+        Some("why") => return why(&args[1..]),
+        Some("scan") => return scan(&args[1..]),
+        Some("check") => return check(&args[1..]),
+        Some("validate") => return validate(&args[1..]),
+        Some("solutions") => return solutions(&args[1..]),
+        Some("samurai") => return samurai(&args[1..]),
+        Some("compare") => return compare(&args[1..]),
+        Some("diff") => return diff(&args[1..]),
+        Some("bench") => return bench(&args[1..]),
+        Some("generate") => return generate(&args[1..]),
+        Some("library") => return library(&args[1..]),
+        // See `engine()` for the protocol
+        Some("engine") => return engine(std::io::stdin().lock(), std::io::stdout()).map(|_| ()),
+        // See `serve()` for what's sent back
+        Some("serve") => return serve(&args[1..]),
+        // Solving is what's done without a command, but can be asked for by name
+        Some("solve") => {
+            args.remove(0);
         }
+        _ => {}
     }
 
-    #[allow(dead_code)]
-    fn with_candidates(candidates: Vec<u8>) -> Cell {
-        let mut initial = Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        };
-
-        initial.set_candidates(candidates);
-        initial
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+    if let Some(path) = &options.watch {
+        return watch(&options, path);
+    }
+    if let Some(path) = &options.batch {
+        return solve_batch(&options, path);
     }
 
-    fn candidates_as_vec(&self) -> Vec<u8> {
-        let mut r: Vec<u8> = Vec::new();
-        for i in 0..9 {
-            if self.candidates[i] > 0 {
-                r.push(self.candidates[i]);
+    let mut puzzle = match Puzzle::parse_with_report(&read_stdin()?) {
+        Ok((puzzle, report)) => {
+            if !report.is_clean() && !options.json {
+                eprintln!("{}", report);
             }
+            puzzle
+        }
+        Err(report) => {
+            eprintln!("{}", report);
+            exit_with(
+                &options,
+                "unreadable",
+                EXIT_UNREADABLE,
+                Some(&report.to_string()),
+                None,
+            );
         }
-        r
+    };
+    if options.diagonals {
+        puzzle.set_diagonals(true);
     }
+    solve(&options, puzzle)
+}
 
-    fn remove_candidate(&mut self, number: u8) -> bool {
-        let mut candidates = self.candidates_as_vec();
-
-        let pos = candidates.iter().position(|c| *c == number);
-        match pos {
-            Some(i) => {
-                candidates.remove(i);
-                self.set_candidates(candidates);
-                return true;
-            }
-            None => return false,
+impl Options {
+    // Commentary on the solve, left out with `--quiet`, and without emoji with `--plain`.
+    fn say(&self, text: &str) {
+        if !self.quiet {
+            println!("{}", self.text(text));
         }
     }
 
-    fn set_candidates(&mut self, mut candidates: Vec<u8>) {
-        self.candidates = [0; 9];
-        candidates.sort();
-        for (i, candidate) in candidates.iter().enumerate() {
-            self.candidates[i] = *candidate;
+    // Something asked for on the command line, as `--stats` is, so shown even with `--quiet`. Still without emoji with `--plain`, and on stderr with `--format json`, to keep stdout for the JSON.
+    fn show(&self, text: &str) {
+        if self.json {
+            eprintln!("{}", self.text(text));
+        } else {
+            println!("{}", self.text(text));
         }
     }
-}
 
-impl Puzzle {
-    fn parse(input: &str) -> Puzzle {
-        // println!("Parsing <{}>", input);
-        let mut grid: [[Cell; 9]; 9] = [[Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        }; 9]; 9];
-
-        for (i, line_str) in input.trim().split("\n").enumerate() {
-            let trimmed = line_str.trim();
-            if trimmed.len() == 0 {
-                continue;
-            }
-
-            for (j, c) in trimmed.chars().enumerate() {
-                match c.to_digit(10) {
-                    Some(d) => {
-                        grid[i][j] = Cell {
-                            number: Some(d.try_into().unwrap()),
-                            given: true,
-                            candidates: [0; 9],
-                        }
-                    }
-                    None => {}
-                }
-            }
+    // The grid the solve ends with, left out with `--format json` as it's in the JSON.
+    fn grid(&self, puzzle: &Puzzle) {
+        if !self.json {
+            println!("{}", puzzle.display_with(&self.display));
         }
+    }
 
-        Puzzle {
-            iteration: 0,
-            grid,
-            last_consolidation: vec![],
+    // A banner heading part of the output, left out with either `--quiet` or `--plain`.
+    fn banner(&self, text: &str) {
+        if !self.quiet && !self.plain {
+            println!("{}", text);
         }
     }
 
-    fn status(&self) -> PuzzleStatus {
-        // Bad if any cell has no number assigned and has no possible candidates
-        for row in 0..9 {
-            for col in 0..9 {
-                let cell = self.grid[row][col];
-
-                if let None = cell.number && cell.candidates_as_vec().len() == 0 {
-                    return PuzzleStatus::IllDefined(IllDefinedReason::NoPossibleSolutionForCell(
-                        (row, col),
-                    ));
-                }
-            }
+    fn text(&self, text: &str) -> String {
+        if self.plain {
+            strip_emoji(text)
+        } else {
+            text.to_string()
         }
+    }
+}
 
-        // Bad if any row repeats a number
-        for i in 0..9 {
-            let row = self.row(i);
+// Solve the puzzle, guessing if logic alone can't, and report on it as asked on the command line.
+fn solve(options: &Options, mut puzzle: Puzzle) -> Result<(), std::io::Error> {
+    puzzle.set_config(options.config.clone());
+    if let Err(error) = puzzle.solve() {
+        return give_up(options, error);
+    }
 
-            for needle in 1..10 {
-                let mut count = 0;
+    options.banner("🎁 🎁 🎁 🎁 🎁    FINAL     🎁 🎁 🎁 🎁 🎁");
+    options.say(&puzzle.display_with(&options.display));
 
-                for entry in row {
-                    match entry.number {
-                        Some(number) => {
-                            if number == needle {
-                                count += 1;
-                            }
-                        }
-                        None => {}
-                    }
-                }
+    // TODO: if not solved, we need to pick one of the opposing candidate pairs (e.g. a block with candidates [2,3] and [2, 3]) and work out if a solution can be found. Clone the puzzle, make a guess, and try solving again. If a contradiction is found, throw it away.
 
-                if count > 1 {
-                    return PuzzleStatus::IllDefined(IllDefinedReason::NumberRepeatsInRow(
-                        needle, i,
-                    ));
-                }
-            }
-        }
+    // Create vector of all possible guesses
+    //  guesses = [[Block1, Row0, Col2 = 2], [Block1, Row0, Col2 = 5], ....]
 
-        // Bad if any col repeats a number
-        for i in 0..9 {
-            let row = self.column(i);
+    // solved_puzzle = solve_with_guess(puzzle, guesses)
+    // solved_puzzle.display()
 
-            for needle in 1..10 {
-                let mut count = 0;
+    // fn solve_with_guess(puzzle, mut guesses) -> Result<Puzzle, Error> {
+    //
+    //      let guess = guesses.pop()
+    //
+    //      let my_puzzle = puzzle.clone()
+    //
+    //      my_puzzle.grid[][] = guess
+    //
+    //
+    //      my_puzzle.solve()
+    //      if my_puzzle.is_solved() {
+    //          return Ok(my_puzzle)
+    //      }
+    //      else if my_puzzle.is_ill_defined() {
+    //          return Error(...)
+    //      }
+    //      else {
+    //          return solve_with_guess(my_puzzle, guesses)
+    //      }
+    //
+    // }
 
-                for entry in row {
-                    match entry.number {
-                        Some(number) => {
-                            if number == needle {
-                                count += 1;
-                            }
-                        }
-                        None => {}
-                    }
-                }
+    // for guess in v.iter() {
+    //      my_guess = puzzle.clone()
+    //      my_guess.set_block_num(Block1, Row0, Col2)
+    //      my_guess.solve()
+    //      look for solved, bad, or more guesses needed
+    //      Create NEW list of guesses. v2 = [...]
+    // }
 
-                if count > 1 {
-                    return PuzzleStatus::IllDefined(IllDefinedReason::NumberRepeatsInColumn(
-                        needle, i,
-                    ));
-                }
-            }
+    let status = puzzle.status();
+    match status {
+        PuzzleStatus::Solved => {
+            options.say("Solved! 🙌");
+            options.grid(&puzzle);
+            report(options, &puzzle)?;
+            conclude(options, &puzzle, None);
         }
-
-        // Bad if any block repeats a number
-        for b in 0..9 {
-            let block = self.block(b);
-
-            for needle in 1..10 {
-                let mut count = 0;
-
-                for row in block {
-                    for entry in row {
-                        match entry.number {
-                            Some(number) => {
-                                if number == needle {
-                                    count += 1;
-                                }
-                            }
-                            None => {}
-                        }
-                    }
-                }
-
-                if count > 1 {
-                    return PuzzleStatus::IllDefined(IllDefinedReason::NumberRepeatsInBlock(
-                        needle, b,
-                    ));
-                }
-            }
+        PuzzleStatus::IllDefined(_) => {
+            options.say(&format!("💥 Ill-defined puzzle: {}", ill_defined(&puzzle)));
+            report(options, &puzzle)?;
+            conclude(options, &puzzle, None);
         }
-
-        // Solved if every cell has an assigned number
-        for row in 0..9 {
-            for col in 0..9 {
-                match self.grid[row][col].number {
-                    Some(_) => {}
-                    None => return PuzzleStatus::Unsolved,
-                }
-            }
+        PuzzleStatus::Unsolved if options.config.logic_only => {
+            options.say(&format!(
+                "🧠 Logic alone couldn't solve it. The candidates left:\n{}",
+                puzzle.display_with(&options.display)
+            ));
+            report(options, &puzzle)?;
+            conclude(options, &puzzle, Some("logic alone couldn't solve it"));
+        }
+        PuzzleStatus::Unsolved => {
+            options.say("⁉️  Couldn't reduce any further. Need more smarts. Or, guess!");
         }
-
-        PuzzleStatus::Solved
     }
 
-    fn solve(&mut self) {
-        loop {
-            let progress = self.step();
+    options.banner("❓❓❓❓  G U E S S   T I M E ❓ ❓ ❓ ❓ ❓");
 
-            print!(
-                "Step {} progressed by {:?}. Current board layout:\n{}",
-                self.iteration,
-                progress,
-                self.display()
-            );
+    let trial_puzzle = match solve_with_guesses(puzzle.clone()) {
+        Ok(trial_puzzle) => trial_puzzle,
+        Err(error) => return give_up(options, error),
+    };
+    report(options, trial_puzzle.as_ref().unwrap_or(&puzzle))?;
 
-            if progress.len() == 0 {
-                break;
+    match trial_puzzle {
+        Some(puzzle) => match puzzle.status() {
+            PuzzleStatus::Solved => {
+                options.say("Solved! 🙌🙌🙌🙌🙌");
+                options.say(&format!("🧶 {}", puzzle.guess_report().describe()));
+                options.grid(&puzzle);
+                conclude(options, &puzzle, None);
             }
-
-            let status = self.status();
-            if status == PuzzleStatus::Solved {
-                break;
+            PuzzleStatus::IllDefined(_) => {
+                options.say(&format!(
+                    "💥💥💥💥💥 Ill-defined puzzle: {}",
+                    ill_defined(&puzzle)
+                ));
+                conclude(options, &puzzle, None);
             }
-            if let PuzzleStatus::IllDefined(_) = status {
-                break;
+            PuzzleStatus::Unsolved => {
+                options.say("⁉️⁉️⁉️⁉️⁉️  Couldn't reduce any further. Not even with guesses!!");
+                conclude(options, &puzzle, Some("not even guessing could solve it"));
             }
+        },
+        None => {
+            options.say("Failed to solve puzzle with guesses 🙁");
+            conclude(options, &puzzle, Some("no guess led to a solution"));
         }
     }
+}
 
-    fn step(&mut self) -> Vec<Consolidation> {
-        self.iteration += 1;
-
-        println!("Starting step #{}", self.iteration);
-        self.assign_candidates();
-        self.write_iteration(format!("s{}-candidates", self.iteration));
-
-        self.last_consolidation = self.consolidate_candidates();
-        self.write_iteration(format!("s{}-consolidated", self.iteration));
-
-        self.last_consolidation.clone()
-    }
-
-    fn write_iteration(&self, filename: String) {
-        std::fs::create_dir_all("tmp").unwrap();
-        let full_filename = format!("tmp/{}", filename);
+// Exit statuses of a solve, for scripts and CI to branch on. The other commands exit 0 when all's well and 1 when not, as each says, and likewise 3 when they can't read their puzzle or command line.
+const EXIT_SOLVED: i32 = 0;
+const EXIT_UNSOLVED: i32 = 1;
+const EXIT_ILL_DEFINED: i32 = 2;
+const EXIT_UNREADABLE: i32 = 3;
+const EXIT_BUDGET_EXCEEDED: i32 = 4;
+
+// Exit with the status the puzzle has come to, and with `--format json` print it first. The reason is why an unsolved puzzle is, or for an ill-defined one, everything wrong with it.
+fn conclude(options: &Options, puzzle: &Puzzle, reason: Option<&str>) -> ! {
+    let (status, code, reason) = match puzzle.status() {
+        PuzzleStatus::Solved => ("solved", EXIT_SOLVED, None),
+        PuzzleStatus::Unsolved => ("unsolved", EXIT_UNSOLVED, reason.map(str::to_string)),
+        PuzzleStatus::IllDefined(_) => ("ill-defined", EXIT_ILL_DEFINED, Some(ill_defined(puzzle))),
+    };
+    exit_with(options, status, code, reason.as_deref(), Some(puzzle))
+}
 
-        let contents = format!(
-            "{}\n\nLast consolidation: {:?}",
-            self.display(),
-            self.last_consolidation
+// With `--format json`, print the outcome as an object a program can read, before exiting:
+//
+//    {"status": "solved", "exit_code": 0, "reason": null, "grid": "341562789768934152..."}
+//
+// The status is `solved`, `unsolved`, `ill-defined`, `unreadable`, or `budget-exceeded`, and the grid is as far as the solve got, row by row, with `.` for blanks. It's null if the puzzle couldn't be read.
+fn exit_with(
+    options: &Options,
+    status: &str,
+    code: i32,
+    reason: Option<&str>,
+    puzzle: Option<&Puzzle>,
+) -> ! {
+    if options.json {
+        let grid = puzzle.map(|puzzle| {
+            puzzle
+                .shape()
+                .coords()
+                .map(|coord| match puzzle[coord].number() {
+                    Some(n) => symbol(n),
+                    None => '.',
+                })
+                .collect::<String>()
+        });
+        println!(
+            "{{\"status\": \"{}\", \"exit_code\": {}, \"reason\": {}, \"grid\": {}}}",
+            status,
+            code,
+            reason.map_or("null".to_string(), json_string),
+            grid.as_deref().map_or("null".to_string(), json_string)
         );
-
-        let mut ofile = File::create(full_filename).expect("unable to create file");
-        ofile
-            .write_all(contents.as_bytes())
-            .expect("unable to write");
     }
+    std::process::exit(code);
+}
 
-    /// Review every cell and assign the possible candidates by eliminating the obvious invalid ones.
-    fn assign_candidates(&mut self) {
-        for cell_index in 0..81 {
-            let col = cell_index % 9;
-            let row = (cell_index - col) / 9;
-            let cell = self.grid[row][col];
-            let block = col / 3 + (row / 3) * 3;
-
-            let debug = row == 0 && col == 2 && block == 0;
-
-            if debug {
-                println!("@assign_candidates DEBUGGING\n\n\n");
-                println!(
-                    "   looking at cell #{:02} ({},{}) in block {}: {:?}",
-                    cell_index, row, col, block, cell.number
-                );
-            }
-
-            if let Some(_) = cell.number {
-                continue;
-            }
-
-            let mut cset: HashSet<u8> = HashSet::new();
-            for p in 1..10 {
-                cset.insert(p);
-            }
-
-            // Narrow candidates by block
-            let mut forbidden = self.numbers_in_block(block);
-            if debug {
-                println!("Numbers in block #{}: {:?}", block, forbidden);
-            }
-            for f in forbidden.iter() {
-                cset.remove(f);
-            }
-
-            // Narrow candidates by row
-            forbidden = self.numbers_in_row(row);
-            if debug {
-                println!("Numbers in row #{}: {:?}", row, forbidden);
-            }
-            for f in forbidden.iter() {
-                cset.remove(f);
-            }
-
-            // Narrow candidates by column
-            forbidden = self.numbers_in_column(col);
-            if debug {
-                println!("Numbers in column #{}: {:?}", col, forbidden);
-            }
-            for f in forbidden.iter() {
-                cset.remove(f);
-            }
-
-            let mut candidates: [u8; 9] = [0; 9];
-            let mut sorted: Vec<u8> = cset.drain().collect();
-            sorted.sort();
-            for (c, canidate) in sorted.iter().enumerate() {
-                candidates[c] = *canidate;
-            }
-            self.grid[row][col].candidates = candidates;
-        }
-
-        loop {
-            let flex_count = self.reduce_candidates_with_sara_flex();
-            println!("Sara flex reduced candidates by {}", flex_count);
-
-            let hit_count = self.reduce_candidates_using_water_cannon();
-            println!("Rifle shots reduced candidate pool by {}", hit_count);
-
-            if flex_count + hit_count == 0 {
-                break;
-            }
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
         }
     }
+    quoted.push('"');
+    quoted
+}
 
-    // Sara flex: combine the following rules to reduce potential candidates:
-    //
-    //    * Each row and column and block must have 9 unique digits
-    //    * Cells "pinned" to certain values force further reductions in other blocks
-    //
-    // These two rules yield incredible results, especially as each reduction can trigger further reductions.
-    //
-    // Returns the number of reductions. Should be called repeatedly until no further simplifcations can be made.
-    fn reduce_candidates_with_sara_flex(&mut self) -> usize {
-        let mut reductions = 0;
-
-        // Rows
-        for i in 0..9 {
-            let mut candidates: Vec<Vec<u8>> = Vec::new();
-            for c in self.row(i) {
-                candidates.push(c.candidates_as_vec());
-            }
-
-            let reduced = reduce_candidates_by_uniqueness(candidates);
-            for j in 0..9 {
-                let mut reduced_candidates: [u8; 9] = [0; 9];
+// Show how far the solve got before it hit a limit, and give up.
+fn give_up(options: &Options, error: SolveError) -> Result<(), std::io::Error> {
+    let reason = error.to_string();
+    options.say(&format!("⏱️  {}", reason));
+    let SolveError::BudgetExceeded { progress, .. } = error;
+    options.say(&progress.display_with(&options.display));
+    report(options, &progress)?;
+    exit_with(
+        options,
+        "budget-exceeded",
+        EXIT_BUDGET_EXCEEDED,
+        Some(&reason),
+        Some(&progress),
+    );
+}
 
-                for (k, c) in reduced[j].iter().enumerate() {
-                    reduced_candidates[k] = *c;
-                }
+// Everything wrong with an ill-defined puzzle, so it can all be fixed at once.
+fn ill_defined(puzzle: &Puzzle) -> String {
+    let reasons: Vec<String> = puzzle
+        .diagnose()
+        .iter()
+        .map(|reason| format!("{:?}", reason))
+        .collect();
+    reasons.join(", ")
+}
 
-                if reduced_candidates != self.grid[i][j].candidates {
-                    self.grid[i][j].candidates = reduced_candidates;
-                    reductions += 1;
-                }
+// Parse the puzzle, warning of anything odd about the input on stderr. If it can't be read at all, list what's wrong with it and exit.
+fn parse_puzzle(input: &str) -> Puzzle {
+    match Puzzle::parse_with_report(input) {
+        Ok((puzzle, report)) => {
+            if !report.is_clean() {
+                eprintln!("{}", report);
             }
+            puzzle
         }
-
-        // Columns
-        for i in 0..9 {
-            let mut candidates: Vec<Vec<u8>> = Vec::new();
-            for c in self.column(i) {
-                candidates.push(c.candidates_as_vec());
-            }
-
-            let reduced = reduce_candidates_by_uniqueness(candidates);
-
-            for j in 0..9 {
-                let mut reduced_candidates: [u8; 9] = [0; 9];
-
-                for (k, c) in reduced[j].iter().enumerate() {
-                    reduced_candidates[k] = *c;
-                }
-
-                if reduced_candidates != self.grid[j][i].candidates {
-                    self.grid[j][i].candidates = reduced_candidates;
-                    reductions += 1;
-                }
-            }
+        Err(report) => {
+            eprintln!("{}", report);
+            std::process::exit(EXIT_UNREADABLE);
         }
+    }
+}
 
-        // Blocks
-        for block_num in 0..9 {
-            let block_cells = self.block_as_slice(block_num);
-            let mut candidates: Vec<Vec<u8>> = Vec::new();
-            for c in block_cells {
-                candidates.push(c.candidates_as_vec());
-            }
-
-            let reduced = reduce_candidates_by_uniqueness(candidates);
-
-            for j in 0..9 {
-                let mut reduced_candidates: [u8; 9] = [0; 9];
+fn read_stdin() -> Result<String, std::io::Error> {
+    let mut buf = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
+    Ok(buf)
+}
 
-                for (k, c) in reduced[j].iter().enumerate() {
-                    reduced_candidates[k] = *c;
-                }
+// Explain the steps taken, write them out as JSON or HTML, and draw or animate the grid, as asked for on the command line.
+fn report(options: &Options, puzzle: &Puzzle) -> Result<(), std::io::Error> {
+    let solution = puzzle.solution();
 
-                let modified =
-                    self.update_block_candidates(block_num, j / 3, j % 3, reduced_candidates);
-                if modified {
-                    reductions += 1;
-                }
-            }
+    if options.explain {
+        options.banner("📖📖📖📖📖  E X P L A N A T I O N 📖 📖 📖 📖 📖");
+        for step in solution.steps() {
+            options.show(&format!("Step {}:\n{}", step.iteration(), step.describe()));
         }
-
-        reductions
     }
 
-    // Within a block, find 2 or 3 numbers that are on the same row or column. Use these to line up the sights of the water cannon. Water is projected at other blocks to clobber any matching candidates on that row or column.
-    fn reduce_candidates_using_water_cannon(&mut self) -> usize {
-        let mut reductions = 0;
-
-        for b in 0..9 {
-            let block = self.block(b);
-
-            for number in 1..10 {
-                let sights = line_up_water_cannon(block, number);
-
-                match sights {
-                    WaterCannonSights::Row(row_in_block) => {
-                        // Nuke everyone else on this row outside of this block
-                        let (origin_row, _) = grid_origin_offset_for_block(b);
-
-                        for i in 0..9 {
-                            if i / 3 == b % 3 {
-                                // This column is in the same block as our sights. Skip.
-                            } else {
-                                if self.grid[origin_row + row_in_block][i].remove_candidate(number)
-                                {
-                                    println!("🔫🔫🔫🔫🔫 Water cannon shot from block {} eliminated candidate {} in same row at grid position ({}, {})", b, number, origin_row + row_in_block, i);
-                                    reductions += 1;
-                                }
-                            }
-                        }
-                    }
-                    WaterCannonSights::Column(column_in_block) => {
-                        // Nuke everyone else on this column outside of this block
-                        let (_, origin_col) = grid_origin_offset_for_block(b);
-
-                        for i in 0..9 {
-                            if i / 3 == b / 3 {
-                                // This row is in the same block as our sights. Skip.
-                            } else {
-                                if self.grid[i][origin_col + column_in_block]
-                                    .remove_candidate(number)
-                                {
-                                    println!("🔫🔫🔫🔫🔫 Water cannon shot from block {} eliminated candidate {} in same column at grid position ({}, {})", b, number, i, origin_col + column_in_block);
-                                    reductions += 1;
-                                }
-                            }
-                        }
-                    }
-                    WaterCannonSights::None => {}
-                }
-            }
-        }
+    if options.stats {
+        options.show(&format!("📊 {}", solution.stats().describe()));
+    }
 
-        reductions
+    if let Some(path) = &options.trace {
+        std::fs::write(path, solution.trace())?;
     }
 
-    /// Review the candidates for each cell and infer ways to reduce them or assign a number to the cell. Returns the number of consolidation steps performed.
-    fn consolidate_candidates(&mut self) -> Vec<Consolidation> {
-        let mut progress: Vec<Consolidation> = Vec::new();
+    if let Some(path) = &options.html {
+        std::fs::write(path, solution.html_with(options.theme))?;
+    }
 
-        // Start with the trivial: resolve any cell with only one candidate
-        for block_num in 0..9 {
-            let block = self.block(block_num);
-            for row in 0..3 {
-                for col in 0..3 {
-                    let cell = block[row][col];
-                    let candidates = cell.candidates_as_vec();
+    if let Some(path) = &options.png {
+        std::fs::write(path, puzzle.png())?;
+    }
 
-                    if candidates.len() == 1 {
-                        self.update_block(block_num, row, col, candidates[0]);
+    if let Some(path) = &options.animate {
+        std::fs::write(path, puzzle.apng())?;
+    }
 
-                        let updated = Consolidation::SingleCandidateForCell(CellAssignment {
-                            block: block_num,
-                            row,
-                            col,
-                            number: candidates[0],
-                        });
-                        progress.push(updated);
-                    }
-                }
-            }
-        }
+    if let Some(path) = &options.ss {
+        std::fs::write(path, puzzle.ss())?;
+    }
 
-        // Save higher order logic for when we need it
-        if progress.len() > 0 {
-            return progress;
-        }
-
-        // Review all candidates within a _block_ and infer reductions based on uniqueness. For example, a block with only candidates [3, 5], [1, 3], and [2, 3, 5] remaining would require that the last cell be 2 since it's the only valid place for it.
-        for b in 0..9 {
-            let block = self.block(b);
-            for row in 0..3 {
-                for col in 0..3 {
-                    let candidates = block[row][col].candidates_as_vec();
-
-                    for candidate in candidates {
-                        let count = self.count_candidates_in_block_for(b, candidate);
-                        if count == 1 {
-                            println!(
-                                "➡️➡️➡️➡️ Inferred that block {}'s row {} @ column {} must be {}",
-                                b, row, col, candidate
-                            );
-                            self.update_block(b, row, col, candidate);
-
-                            return vec![Consolidation::OnlyOnePossibleCandidateForBlock(
-                                CellAssignment {
-                                    number: candidate,
-                                    row,
-                                    col,
-                                    block: b,
-                                },
-                            )];
-                        }
-                    }
-                }
-            }
-        }
+    if let Some(path) = &options.csv {
+        std::fs::write(path, puzzle.csv())?;
+    }
 
-        // Same uniqueness logic as above, but for rows
-        for row_num in 0..9 {
-            let row = self.row(row_num);
-            for (col_num, cell) in row.iter().enumerate() {
-                let candidates = cell.candidates_as_vec();
+    Ok(())
+}
 
-                for candidate in candidates {
-                    let count = self.count_candidates_in_row(row_num, candidate);
-                    if count == 1 {
+// Solve the puzzle in the file whenever its contents change, until interrupted, showing just the outcome and the grid. Handy while typing a puzzle in, as mistypes show up as an ill-defined puzzle straight away.
+fn watch(options: &Options, path: &str) -> Result<(), std::io::Error> {
+    // The solver's workings would bury the outcome, so clear them away on a terminal
+    let clear = if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+        "\x1b[H\x1b[2J"
+    } else {
+        ""
+    };
+    let display = DisplayOptions {
+        colors: options.display.colors.clone(),
+        ..DisplayOptions::compact()
+    };
+
+    let mut last: Option<String> = None;
+    loop {
+        // The file may be missing for a moment while an editor saves it
+        if let Ok(input) = std::fs::read_to_string(path) {
+            if last.as_ref() != Some(&input) {
+                let mut puzzle = match Puzzle::parse_with_report(&input) {
+                    Ok((puzzle, _)) => puzzle,
+                    Err(report) => {
                         println!(
-                            "➡️➡️➡️➡️ Inferred that row {} @ column {} must be {} because it's the only one available in the ROW",
-                            row_num, col_num, candidate
+                            "{}👀 Watching {} for changes\n\n💥 Can't read the puzzle:\n{}",
+                            clear, path, report
                         );
-                        self.set_number(row_num, col_num, candidate);
-
-                        return vec![Consolidation::OnlyOnePossibleCandidateForRow(
-                            CellAssignment {
-                                number: candidate,
-                                row: row_num,
-                                col: col_num,
-                                block: block_num_for_row_col(row_num, col_num),
-                            },
-                        )];
+                        last = Some(input);
+                        continue;
                     }
+                };
+                puzzle.set_config(options.config.clone());
+                if options.diagonals {
+                    puzzle.set_diagonals(true);
                 }
-            }
-        }
-
-        // Same uniqueness logic as above, but for columns
-        for col_num in 0..9 {
-            let col = self.column(col_num);
-
-            for (row_num, cell) in col.iter().enumerate() {
-                let candidates = cell.candidates_as_vec();
-
-                for candidate in candidates {
-                    let count = self.count_candidates_in_col(col_num, candidate);
-
-                    if count == 1 {
-                        println!(
-                            "➡️➡️➡️➡️ Inferred that row {} @ column {} must be {} because it's the only one in the COLUMN",
-                            row_num, col_num, candidate
-                        );
-                        self.set_number(row_num, col_num, candidate);
-
-                        return vec![Consolidation::OnlyOnePossibleCandidateForColumn(
-                            CellAssignment {
-                                number: candidate,
-                                row: row_num,
-                                col: col_num,
-                                block: block_num_for_row_col(row_num, col_num),
-                            },
-                        )];
+                let solved = match puzzle.solve() {
+                    Ok(()) if puzzle.status() == PuzzleStatus::Unsolved => {
+                        solve_with_guesses(puzzle.clone()).map(|solved| solved.unwrap_or(puzzle))
                     }
-                }
-            }
-        }
-
-        vec![]
-    }
-
-    /// The corresponding block in our grid. 0 thru 8, starting in top left.
-    fn block(&self, b: usize) -> [[Cell; 3]; 3] {
-        assert!(b < 9, "Invalid block number: {}", b);
-
-        let origin_x = b % 3;
-        let origin_y = (b - origin_x) / 3;
+                    Ok(()) => Ok(puzzle),
+                    Err(error) => Err(error),
+                };
 
-        let mut result: [[Cell; 3]; 3] = [[Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        }; 3]; 3];
+                let (puzzle, outcome) = match solved {
+                    Ok(puzzle) => {
+                        let outcome = match puzzle.status() {
+                            PuzzleStatus::Solved => "Solved! 🙌".to_string(),
+                            PuzzleStatus::IllDefined(_) => {
+                                format!("💥 Ill-defined puzzle: {}", ill_defined(&puzzle))
+                            }
+                            PuzzleStatus::Unsolved if options.config.logic_only => {
+                                "🧠 Logic alone couldn't solve it".to_string()
+                            }
+                            PuzzleStatus::Unsolved => {
+                                "⁉️  Couldn't solve it, not even with guesses".to_string()
+                            }
+                        };
+                        (puzzle, outcome)
+                    }
+                    Err(error) => {
+                        let outcome = format!("⏱️  {}", error);
+                        let SolveError::BudgetExceeded { progress, .. } = error;
+                        (*progress, outcome)
+                    }
+                };
+                println!(
+                    "{}👀 Watching {} for changes\n\n{}\n\n{}",
+                    clear,
+                    path,
+                    outcome,
+                    puzzle.display_with(&display)
+                );
 
-        for i in 0..3 {
-            for j in 0..3 {
-                result[i][j] = self.grid[origin_y * 3 + i][origin_x * 3 + j];
+                last = Some(input);
             }
         }
 
-        result
+        std::thread::sleep(std::time::Duration::from_millis(500));
     }
+}
 
-    /// The corresponding block in our grid as a single slice of cells. Blocks are numbered 0 thru 8, starting in top left, proceeding left-to-right, top-to-bottom.
-    fn block_as_slice(&self, b: usize) -> [Cell; 9] {
-        assert!(b < 9, "Invalid block number: {}", b);
-
-        let origin_x = b % 3;
-        let origin_y = (b - origin_x) / 3;
-
-        let mut result: [Cell; 9] = [Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        }; 9];
+// Solve each puzzle in the file, writing how each went as CSV to the output file, or stdout, and a summary to stdout, or stderr if the results went there.
+fn solve_batch(options: &Options, path: &str) -> Result<(), std::io::Error> {
+    let bar = if options.quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0)
+    };
 
-        for i in 0..3 {
-            for j in 0..3 {
-                result[i * 3 + j] = self.grid[origin_y * 3 + i][origin_x * 3 + j];
-            }
+    if options.stream {
+        let input: Box<dyn BufRead> = match path {
+            "-" => Box::new(std::io::stdin().lock()),
+            path => Box::new(BufReader::new(File::open(path)?)),
+        };
+        let output: Box<dyn Write> = match &options.output {
+            Some(output) => Box::new(BufWriter::new(File::create(output)?)),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        let tally = stream(input, output, &options.config)?;
+        if !options.quiet {
+            eprintln!("{}", options.text(&format!("🧮 {}", tally.summary())));
         }
-
-        result
+        return Ok(());
     }
+    if options.cross_check {
+        let checked = cross_check_with_progress(
+            &std::fs::read_to_string(path)?,
+            &options.config,
+            show_progress(&bar, "puzzles"),
+        );
+        bar.finish_and_clear();
 
-    /// The corresponding row in our grid.
-    fn row(&self, r: usize) -> [Cell; 9] {
-        assert!(r < 9, "Invalid row number: {}", r);
-
-        let mut result: [Cell; 9] = [Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        }; 9];
-
-        for i in 0..9 {
-            result[i] = self.grid[r][i];
+        for (puzzle, disagreement) in checked.disagreements() {
+            println!(
+                "{}",
+                options.text(&format!("❗ {}: {}", puzzle, disagreement.describe()))
+            );
         }
-
-        result
-    }
-
-    /// The corresponding column in our grid.
-    fn column(&self, c: usize) -> [Cell; 9] {
-        assert!(c < 9, "Invalid column number: {}", c);
-
-        let mut result: [Cell; 9] = [Cell {
-            number: None,
-            given: false,
-            candidates: [0; 9],
-        }; 9];
-
-        for i in 0..9 {
-            result[i] = self.grid[i][c];
+        options.say(&format!("🔍 {}", checked.summary()));
+        if !checked.disagreements().is_empty() {
+            std::process::exit(1);
         }
-
-        result
+        return Ok(());
     }
+    let batch = batch_with_progress(
+        &std::fs::read_to_string(path)?,
+        &options.config,
+        show_progress(&bar, "puzzles"),
+    );
+    bar.finish_and_clear();
 
-    fn update_block(&mut self, block_num: usize, row: usize, col: usize, number: u8) {
-        let origin_row = block_num / 3;
-        let origin_col = block_num % 3;
-
-        self.grid[origin_row * 3 + row][origin_col * 3 + col].number = Some(number);
-        self.grid[origin_row * 3 + row][origin_col * 3 + col].candidates = [0; 9];
+    match &options.output {
+        Some(output) => {
+            std::fs::write(output, batch.csv())?;
+            options.say(&format!("🧮 {}. Wrote {}", batch.summary(), output));
+        }
+        None => {
+            print!("{}", batch.csv());
+            if !options.quiet {
+                eprintln!("{}", options.text(&format!("🧮 {}", batch.summary())));
+            }
+        }
     }
 
-    fn set_number(&mut self, row: usize, col: usize, number: u8) {
-        self.grid[row][col].number = Some(number);
-        self.grid[row][col].candidates = [0; 9];
+    if options.stats {
+        let mut times = batch.technique_times();
+        times.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (technique, time) in times {
+            eprintln!(
+                "{}",
+                options.text(&format!("📊 {}: {:.1?}", technique.name(), time))
+            );
+        }
     }
 
-    // Updated cell candidates in block. Returns true if an update took place
-    fn update_block_candidates(
-        &mut self,
-        block_num: usize,
-        row: usize,
-        col: usize,
-        candidates: [u8; 9],
-    ) -> bool {
-        let origin_row = block_num / 3;
-        let origin_col = block_num % 3;
-
-        if self.grid[origin_row * 3 + row][origin_col * 3 + col].candidates != candidates {
-            self.grid[origin_row * 3 + row][origin_col * 3 + col].candidates = candidates;
-            return true;
-        }
+    Ok(())
+}
 
-        false
+// Show progress through a long job on a bar on stderr, which is only drawn on a terminal.
+fn show_progress<'a>(bar: &'a ProgressBar, unit: &'a str) -> impl FnMut(Progress) + 'a {
+    bar.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+    move |progress| {
+        bar.set_length(progress.total as u64);
+        bar.set_position(progress.done as u64);
+        let eta = progress
+            .eta()
+            .map_or(String::new(), |eta| format!(", {}s left", eta.as_secs()));
+        bar.set_message(format!("{} at {:.1}/s{}", unit, progress.rate(), eta));
     }
+}
 
-    fn numbers_in_block(&self, b: usize) -> HashSet<u8> {
-        let mut r: HashSet<u8> = HashSet::new();
-        let block = self.block(b);
+// Command line options:
+//
+//    * `--techniques singles,pointing,naked-subsets,xwing`: the techniques to use, in order
+//    * `--trace out.json`: write every step of the solve to the given file as JSON
+//    * `--html report.html`: write a walkthrough of the solve to the given file as a self-contained web page
+//    * `--png grid.png`: draw the grid as it stands at the end to the given file
+//    * `--animate solve.png`: animate the solve, a frame per step, to the given file as an animated PNG
+//    * `--ss grid.ss`: write the grid as it stands at the end to the given file in Simple Sudoku's format
+//    * `--csv grid.csv`: write the grid as it stands at the end to the given file as CSV, for spreadsheets
+//    * `--quiet`: print nothing but the solved grid, or nothing at all if it can't be solved, leaving the exit status to tell. What's asked for, as by `--explain`, is still printed
+//    * `--format json`: print nothing but the outcome as JSON once done, for programs to read. See `exit_with()`
+//    * `--plain`: no emoji or banners, in the solver's workings or anywhere else, for scripts and logs
+//    * `--explain`: explain each step of the solve in words once done
+//    * `--stats`: sum up the solve once done: time taken, in all and in each technique, iterations, consolidations, eliminations, and guesses. With `--batch`, the time in each technique over the whole batch
+//    * `--diagonals`: solve as X-Sudoku, each digit once in each of the two main diagonals too, as a `# X-Sudoku` line in the puzzle also asks
+//    * `--anti-knight`: solve with the anti-knight house rule, no digit a knight's move from the same digit
+//    * `--logic-only`: never guess, leaving a puzzle logic alone can't solve unsolved
+//    * `--max-guess-depth 3`: don't guess on top of this many guesses, trying other guesses instead
+//    * `--guess-order random:42`: the order to guess a cell's candidates in, `ascending`, `descending` (the default), or shuffled by the given seed
+//    * `--parallel-guesses`: when it comes to guessing, explore each of the first guesses on a thread of its own
+//    * `--max-iterations 1000`, `--max-guesses 10000`, `--timeout 2.5`: give up once the solve has taken this many steps, made this many guesses, or run for this many seconds
+//    * `--grid unicode`: draw the grid with box-drawing characters rather than dashes and pipes, or `--grid compact` for no lines at all
+//    * `--pencil-marks`: show candidates at fixed places within each cell rather than as a list
+//    * `--cell-width 9`: give each cell of the grid at least this many columns
+//    * `--highlight r4c7,b5p3`: pick out the given cells when coloring the grid
+//    * `--watch puzzle.txt`: solve the puzzle in the given file afresh every time it changes, rather than once from stdin
+//    * `--batch puzzles.txt`: solve each puzzle in the given file, one to a line, writing a line of CSV for each rather than showing the solve
+//    * `--output results.txt`: where `--batch` writes its results, rather than stdout
+//    * `--stream`: with `--batch`, read the puzzles and write the CSV a chunk at a time, solving on every core, for collections too big to hold in memory. `--batch -` reads them from stdin
+//    * `--cross-check`: with `--batch`, solve each puzzle with the techniques alone and by brute force search, and list those they disagree on rather than writing CSV. Exits 1 if there are any
+//    * `--color`: color the grid when writing to a terminal, or `--color=always` or `--color=never`
+//    * `--theme deuteranopia`: the colors for `--color` and `--html`, `default`, `deuteranopia` for red-green colorblindness, `high-contrast`, or `mono` for none at all
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        // The workings are shown unless asked to keep quiet
+        config: SolverConfig {
+            verbose: true,
+            ..SolverConfig::default()
+        },
+        trace: None,
+        html: None,
+        png: None,
+        ss: None,
+        csv: None,
+        animate: None,
+        explain: false,
+        stats: false,
+        quiet: false,
+        plain: false,
+        json: false,
+        diagonals: false,
+        display: DisplayOptions::default(),
+        theme: Theme::Default,
+        watch: None,
+        batch: None,
+        output: None,
+        cross_check: false,
+        stream: false,
+    };
+    let mut color = ColorChoice::Never;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
 
-        for i in 0..3 {
-            for j in 0..3 {
-                match block[i][j].number {
-                    Some(n) => {
-                        r.insert(n);
-                    }
-                    None => {}
+        match flag.as_str() {
+            "--techniques" => {
+                let list = value.or_else(|| args.next()).unwrap_or_default();
+                options.config.techniques = Technique::parse_list(&list)?;
+            }
+            "--trace" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.trace = Some(path),
+                _ => return Err("--trace needs a file to write to".to_string()),
+            },
+            "--html" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.html = Some(path),
+                _ => return Err("--html needs a file to write to".to_string()),
+            },
+            "--png" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.png = Some(path),
+                _ => return Err("--png needs a file to write to".to_string()),
+            },
+            "--animate" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.animate = Some(path),
+                _ => return Err("--animate needs a file to write to".to_string()),
+            },
+            "--ss" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.ss = Some(path),
+                _ => return Err("--ss needs a file to write to".to_string()),
+            },
+            "--csv" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.csv = Some(path),
+                _ => return Err("--csv needs a file to write to".to_string()),
+            },
+            "--explain" => options.explain = true,
+            "--stats" => options.stats = true,
+            "--quiet" => {
+                options.quiet = true;
+                options.config.verbose = false;
+            }
+            "--format" => match value.or_else(|| args.next()).as_deref() {
+                Some("text") => options.json = false,
+                Some("json") => {
+                    options.json = true;
+                    options.quiet = true;
+                    options.config.verbose = false;
                 }
+                _ => return Err("--format needs text or json".to_string()),
+            },
+            "--plain" => {
+                options.plain = true;
+                options.config.plain = true;
             }
-        }
-
-        r
-    }
-
-    fn numbers_in_row(&self, row: usize) -> HashSet<u8> {
-        let mut r: HashSet<u8> = HashSet::new();
-
-        for i in 0..9 {
-            match self.grid[row][i].number {
-                Some(n) => {
-                    r.insert(n);
+            "--parallel-guesses" => options.config.parallel_guesses = true,
+            "--logic-only" => options.config.logic_only = true,
+            "--diagonals" => options.diagonals = true,
+            "--anti-knight" => options.config.house_rules.push(HouseRule::new(AntiKnight)),
+            "--guess-order" => {
+                options.config.guess_order =
+                    GuessOrder::parse(&value.or_else(|| args.next()).unwrap_or_default())?
+            }
+            "--max-guess-depth" => {
+                match value.or_else(|| args.next()).map(|n| n.parse::<usize>()) {
+                    Some(Ok(max)) => options.config.max_guess_depth = Some(max),
+                    _ => return Err("--max-guess-depth needs a number of guesses".to_string()),
                 }
-                None => {}
             }
-        }
-
-        r
-    }
-
-    fn numbers_in_column(&self, col: usize) -> HashSet<u8> {
-        let mut r: HashSet<u8> = HashSet::new();
-
-        for i in 0..9 {
-            match self.grid[i][col].number {
-                Some(n) => {
-                    r.insert(n);
+            "--max-iterations" => match value.or_else(|| args.next()).map(|n| n.parse::<usize>()) {
+                Some(Ok(max)) => options.config.max_iterations = Some(max),
+                _ => return Err("--max-iterations needs a number of steps".to_string()),
+            },
+            "--max-guesses" => match value.or_else(|| args.next()).map(|n| n.parse::<usize>()) {
+                Some(Ok(max)) => options.config.max_guess_nodes = Some(max),
+                _ => return Err("--max-guesses needs a number of guesses".to_string()),
+            },
+            "--timeout" => match value.or_else(|| args.next()).map(|s| s.parse::<f64>()) {
+                // Negative, infinite, or too many seconds to count are as unreadable as none at all
+                Some(Ok(seconds)) => match std::time::Duration::try_from_secs_f64(seconds) {
+                    Ok(timeout) => options.config.wall_clock_timeout = Some(timeout),
+                    Err(_) => return Err("--timeout needs a number of seconds".to_string()),
+                },
+                _ => return Err("--timeout needs a number of seconds".to_string()),
+            },
+            "--watch" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.watch = Some(path),
+                _ => return Err("--watch needs a puzzle file to watch".to_string()),
+            },
+            "--batch" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.batch = Some(path),
+                _ => return Err("--batch needs a file of puzzles to solve".to_string()),
+            },
+            "--cross-check" => options.cross_check = true,
+            "--stream" => options.stream = true,
+            "--output" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => options.output = Some(path),
+                _ => return Err("--output needs a file to write to".to_string()),
+            },
+            "--pencil-marks" => options.display.candidates = CandidateStyle::PencilMarks,
+            "--grid" => {
+                options.display.style =
+                    GridStyle::parse(&value.or_else(|| args.next()).unwrap_or_default())?
+            }
+            "--cell-width" => match value.or_else(|| args.next()).map(|w| w.parse::<usize>()) {
+                Some(Ok(width)) => options.display.cell_width = Some(width),
+                _ => return Err("--cell-width needs a number of columns".to_string()),
+            },
+            "--highlight" => {
+                for name in value.or_else(|| args.next()).unwrap_or_default().split(',') {
+                    let coord: Coord = name.trim().parse()?;
+                    options.display.highlight.push(coord.into());
+                }
+            }
+            "--color" => {
+                color = match value {
+                    Some(choice) => ColorChoice::parse(&choice)?,
+                    None => ColorChoice::Auto,
                 }
-                None => {}
             }
+            "--theme" => {
+                options.theme = Theme::parse(&value.or_else(|| args.next()).unwrap_or_default())?
+            }
+            _ => return Err(format!("Unknown option '{}'", flag)),
         }
+    }
 
-        r
+    if color.enabled() {
+        options.display.colors = Some(options.theme.colors());
     }
 
-    fn count_candidates_in_block_for(&self, block_num: usize, needle: u8) -> usize {
-        let block = self.block(block_num);
-        let mut count = 0;
+    Ok(options)
+}
 
-        for i in 0..3 {
-            for j in 0..3 {
-                match block[i][j].number {
-                    Some(_) => {}
-                    None => {
-                        let candidates = block[i][j].candidates;
+// `print [--page a4|a5|letter|WIDTHxHEIGHT] [--candidates] [--out worksheet.pdf] [puzzle.txt ...]`: lay the puzzles out on a PDF worksheet, reading a single puzzle from stdin if no files are given.
+fn print(args: &[String]) -> Result<(), std::io::Error> {
+    let mut options = WorksheetOptions::default();
+    let mut out = "worksheet.pdf".to_string();
+    let mut files: Vec<String> = Vec::new();
+
+    let mut args = args.iter().cloned();
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
 
-                        for candidate in candidates {
-                            if candidate == needle {
-                                count += 1;
-                            }
-                        }
-                    }
+        let result = match flag.as_str() {
+            "--page" => PageSize::parse(&value.or_else(|| args.next()).unwrap_or_default())
+                .map(|page_size| options.page_size = page_size),
+            "--candidates" => {
+                options.candidates = true;
+                Ok(())
+            }
+            "--out" => match value.or_else(|| args.next()) {
+                Some(path) if !path.is_empty() => {
+                    out = path;
+                    Ok(())
                 }
+                _ => Err("--out needs a file to write to".to_string()),
+            },
+            _ if flag.starts_with("--") => Err(format!("Unknown option '{}'", flag)),
+            _ => {
+                files.push(flag);
+                Ok(())
             }
-        }
+        };
 
-        count
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
     }
 
-    fn count_candidates_in_row(&self, row_num: usize, needle: u8) -> usize {
-        let row = self.row(row_num);
-        let mut count = 0;
+    let puzzles: Vec<Puzzle> = if files.is_empty() {
+        vec![parse_puzzle(&read_stdin()?)]
+    } else {
+        let mut puzzles = Vec::new();
+        for file in files.iter() {
+            puzzles.push(parse_puzzle(&std::fs::read_to_string(file)?));
+        }
+        puzzles
+    };
 
-        for i in 0..9 {
-            match row[i].number {
-                Some(_) => {}
-                None => {
-                    let candidates = row[i].candidates;
+    std::fs::write(&out, worksheet(&puzzles, &options))?;
+    println!(
+        "🖨️  Wrote {} puzzle{} to {}",
+        puzzles.len(),
+        if puzzles.len() == 1 { "" } else { "s" },
+        out
+    );
 
-                    for candidate in candidates {
-                        if candidate == needle {
-                            count += 1;
-                        }
-                    }
-                }
-            }
-        }
+    Ok(())
+}
 
-        count
+// `play puzzle.txt`: play the puzzle in the terminal, with mistakes shown against the solution and hints on demand. The puzzle is read from a file as the keyboard needs stdin.
+fn play(args: &[String]) -> Result<(), std::io::Error> {
+    let puzzle = match args {
+        [path] => parse_puzzle(&std::fs::read_to_string(path)?),
+        _ => {
+            eprintln!("play needs a puzzle file to play");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+
+    println!("🧮 Working out the solution first...");
+    let mut solved = puzzle.clone();
+    let solution = match solved.solve().map(|()| solved.status()) {
+        Ok(PuzzleStatus::Solved) => Some(solved),
+        Ok(PuzzleStatus::Unsolved) => solve_with_guesses(solved).unwrap_or(None),
+        Ok(PuzzleStatus::IllDefined(_)) | Err(_) => None,
+    };
+    if solution.is_none() {
+        println!("⁉️  No solution found, so mistakes won't be pointed out");
     }
 
-    fn count_candidates_in_col(&self, col_num: usize, needle: u8) -> usize {
-        let col = self.column(col_num);
-        let mut count = 0;
+    let mut game = Game::new(puzzle, solution);
+    let result = {
+        let _terminal = RawTerminal::enter()?;
+        play_game(&mut game)
+    };
+    println!("{}", game.puzzle());
 
-        for i in 0..9 {
-            match col[i].number {
-                Some(_) => {}
-                None => {
-                    let candidates = col[i].candidates;
+    result
+}
 
-                    for candidate in candidates {
-                        if candidate == needle {
-                            count += 1;
-                        }
-                    }
+// Draw the game and act on each key pressed until the player quits.
+fn play_game(game: &mut Game) -> Result<(), std::io::Error> {
+    let mut stdout = std::io::stdout();
+    loop {
+        queue!(stdout, Clear(ClearType::All))?;
+        for (line, text) in game.render().lines().enumerate() {
+            queue!(stdout, MoveTo(0, line as u16), Print(text))?;
+        }
+        let (line, column) = game.cursor_on_screen();
+        queue!(stdout, MoveTo(column as u16, line as u16))?;
+        stdout.flush()?;
+
+        if let Event::Key(event) = event::read()? {
+            if let Some(key) = key(event) {
+                if !game.handle(key) {
+                    return Ok(());
                 }
             }
         }
+    }
+}
 
-        count
+// The game's key for a key pressed, if it's one the game uses. Ctrl-C is read as a key like any other in raw mode, so it quits the game too.
+fn key(event: KeyEvent) -> Option<Key> {
+    if event.kind == KeyEventKind::Release {
+        return None;
     }
 
-    #[allow(dead_code)]
-    fn internals(&self) -> String {
-        let mut r = String::new();
+    match event.code {
+        KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => Some(Key::Quit),
+        KeyCode::Char(c) => Key::from_char(c),
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Backspace | KeyCode::Delete => Some(Key::Erase),
+        KeyCode::Esc => Some(Key::Quit),
+        _ => None,
+    }
+}
 
-        for b in 0..9 {
-            let block = self.block(b);
-            r.push_str(format!("Block {}:\n", b).as_str());
+// The terminal in raw mode on the alternate screen, so each key's read as it's pressed, without echoing it, and the scrollback's left as it was. Put back as it was when dropped, however the game ends.
+struct RawTerminal;
 
-            for i in 0..3 {
-                for j in 0..3 {
-                    let cell = block[i][j];
+impl RawTerminal {
+    fn enter() -> Result<RawTerminal, std::io::Error> {
+        terminal::enable_raw_mode()?;
+        let terminal = RawTerminal;
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        Ok(terminal)
+    }
+}
 
-                    r.push_str(format!("    ({},{}) → ", i, j).as_str());
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
 
-                    match cell.number {
-                        Some(n) => r.push_str(n.to_string().as_str()),
-                        None => {
-                            let mut candidates: Vec<u8> = Vec::new();
-                            for c in 0..9 {
-                                if cell.candidates[c] > 0 {
-                                    candidates.push(cell.candidates[c]);
-                                }
-                            }
-                            r.push_str(format!("{:?}", candidates).as_str())
-                        }
-                    }
-                    r.push_str("\n");
-                }
-            }
-            r.push_str("\n");
+// `debug puzzle.txt`: step through solving the puzzle a command at a time, reading commands from stdin. See `Debugger::help()`.
+fn debug(args: &[String]) -> Result<(), std::io::Error> {
+    let mut puzzle = match args {
+        [path] => parse_puzzle(&std::fs::read_to_string(path)?),
+        _ => {
+            eprintln!("debug needs a puzzle file to debug");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+    // Each step's workings are shown as it's taken, as when solving
+    puzzle.set_config(SolverConfig {
+        verbose: true,
+        ..SolverConfig::default()
+    });
+
+    let mut debugger = Debugger::new(puzzle);
+    println!("{}\n{}", Debugger::help(), debugger.puzzle().display());
+
+    let mut line = String::new();
+    loop {
+        print!("🐞 ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        line.clear();
+        if std::io::stdin().read_line(&mut line)? == 0 || line.trim() == "quit" {
+            break;
         }
 
-        r
+        match debugger.run(&line) {
+            Ok(output) => println!("{}", output),
+            Err(message) => eprintln!("{}", message),
+        }
     }
 
-    fn display(&self) -> String {
-        let mut r = String::new();
-
-        r.push_str(
-            "\n-------------------------------------------------------------------------------------------------------------------------\n"
-        );
-        for row in 0..9 {
-            r.push_str("|");
-
-            for col in 0..9 {
-                let cell = self.grid[row][col];
-
-                if let Some(n) = cell.number {
-                    let string = n.to_string();
-                    let display = string.as_str();
-                    r.push_str(format!("{: <13}", display.to_string()).as_str());
-                } else {
-                    let mut candidates: Vec<u8> = Vec::new();
-                    for c in 0..9 {
-                        if cell.candidates[c] > 0 {
-                            candidates.push(cell.candidates[c]);
-                        }
-                    }
-                    let mut display = String::new();
-                    let mut iter = candidates.iter().peekable();
-                    display.push_str("[");
-                    loop {
-                        let c = iter.next();
-
-                        match c {
-                            Some(c) => {
-                                display.push_str(c.to_string().as_str());
-                            }
-                            None => {}
-                        }
-
-                        match &iter.peek() {
-                            Some(_) => display.push_str(","),
-                            None => {
-                                display.push_str("]");
-                                break;
-                            }
-                        }
-                    }
-                    r.push_str(format!("{: <13}", display).as_str());
-                }
+    Ok(())
+}
 
-                if (col + 1) % 3 == 0 {
-                    r.push_str("|");
+// `hint [--all] [--level N] [puzzle.txt]`: explain the next step, reading the puzzle from stdin if no file is given. Pencil marks given with the puzzle are followed on from. With `--all`, list every move each technique could make instead. `--level 1` only says where to look and `--level 2` what to look for, as `Hint::reveal()` does, where the default of 3 tells it all.
+fn hint(args: &[String]) -> Result<(), std::io::Error> {
+    let mut all = false;
+    let mut level = Hint::LEVELS;
+    let mut paths = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--level" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if (1..=Hint::LEVELS).contains(&n) => level = n,
+                _ => {
+                    eprintln!("--level needs a number from 1 to {}", Hint::LEVELS);
+                    std::process::exit(EXIT_UNREADABLE);
                 }
-            }
+            },
+            _ => paths.push(arg),
+        }
+    }
+    let puzzle = match paths.as_slice() {
+        [] => parse_puzzle(&read_stdin()?),
+        [path] => parse_puzzle(&std::fs::read_to_string(path)?),
+        _ => {
+            eprintln!("hint takes at most one puzzle file");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
 
-            if (row + 1) % 3 == 0 {
-                r.push_str(
-                    "\n-------------------------------------------------------------------------------------------------------------------------\n"
+    if all {
+        let analysis = puzzle.analyze();
+        if analysis.is_empty() {
+            println!("🤷 The techniques are stuck");
+            std::process::exit(1);
+        }
+        for (technique, found) in analysis {
+            println!("💡 {} ({}):", technique, found.len());
+            for consolidation in found {
+                println!(
+                    "  {}",
+                    Hint::new(puzzle.shape(), consolidation).reveal(level)
                 );
-            } else {
-                r.push_str("\n");
             }
         }
-
-        r
+        return Ok(());
     }
-}
 
-impl fmt::Display for Puzzle {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut display = "".to_string();
-
-        for (i, row) in self.grid.iter().enumerate() {
-            for (j, cell) in row.iter().enumerate() {
-                match cell.number {
-                    Some(num) => {
-                        display.push_str(&num.to_string());
-                    }
-                    None => display.push_str("·"),
-                }
+    match puzzle.tiered_hint() {
+        Some(hint) => println!("💡 {}", hint.reveal(level)),
+        None => {
+            println!("🤷 The techniques are stuck");
+            std::process::exit(1);
+        }
+    }
 
-                if j != 0 && (j + 1) % 3 == 0 {
-                    display.push_str("  ");
-                }
-            }
-            display.push_str("\n");
+    Ok(())
+}
 
-            if i != 0 && (i + 1) % 3 == 0 {
-                display.push_str("\n");
-            }
+// `why r4c7 2 [puzzle.txt]`: explain why the digit can't go in the cell, reading the puzzle from stdin if no file is given, with pencil marks followed on from as for `hint`. Exits 1 if it can.
+fn why(args: &[String]) -> Result<(), std::io::Error> {
+    let (cell, digit, path) = match args {
+        [cell, digit] => (cell, digit, None),
+        [cell, digit, path] => (cell, digit, Some(path)),
+        _ => {
+            eprintln!("why needs a cell and a digit, e.g. why r4c7 2, and at most one puzzle file");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+    let puzzle = match path {
+        None => parse_puzzle(&read_stdin()?),
+        Some(path) => parse_puzzle(&std::fs::read_to_string(path)?),
+    };
+
+    let size = puzzle.shape().size();
+    let coord = puzzle.shape().parse_cell(cell);
+    let digit = digit
+        .parse::<u8>()
+        .ok()
+        .filter(|d| (1..=size as u8).contains(d));
+    let (coord, digit) = match (coord, digit) {
+        (Ok(coord), Some(digit)) => (coord, digit),
+        (Err(message), _) => {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
         }
+        (_, None) => {
+            eprintln!("The digit needs to be from 1 to {}", size);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
 
-        write!(f, "{}", display)
+    let refutation = puzzle.refute(coord, digit);
+    println!(
+        "🤔 Why can't {} be {}?",
+        coord.to_string().to_uppercase(),
+        digit
+    );
+    for line in refutation.describe().lines() {
+        println!("💡 {}", line);
+    }
+    if !refutation.is_refuted() {
+        std::process::exit(1);
     }
+    Ok(())
 }
 
-impl fmt::Display for Cell {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut display = "".to_string();
-
-        match self.number {
-            Some(num) => {
-                display.push_str(&num.to_string());
+// `check --puzzle p.txt --solution s.txt`: mark a solution handed in for a puzzle, listing everything wrong with it. Exits 1 if anything is.
+fn check(args: &[String]) -> Result<(), std::io::Error> {
+    let (mut puzzle, mut solution) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--puzzle", Some(path)) => puzzle = Some(path),
+            ("--solution", Some(path)) => solution = Some(path),
+            _ => {
+                eprintln!("check needs --puzzle and --solution files, not {}", arg);
+                std::process::exit(EXIT_UNREADABLE);
             }
-            None => display.push_str("unknown"),
         }
+    }
+    let (Some(puzzle), Some(solution)) = (puzzle, solution) else {
+        eprintln!("check needs both --puzzle and --solution files");
+        std::process::exit(EXIT_UNREADABLE);
+    };
+
+    let puzzle = parse_puzzle(&std::fs::read_to_string(puzzle)?);
+    let solution = parse_puzzle(&std::fs::read_to_string(solution)?);
+    let mistakes = solution.verify(&puzzle);
+    if mistakes.is_empty() {
+        println!("✅ Solved, with every given kept");
+        return Ok(());
+    }
 
-        write!(f, "{}", display)
+    for mistake in &mistakes {
+        println!("❌ {}", mistake);
     }
+    std::process::exit(1);
 }
 
-// Given 9 sets of candidate sets (from either a row, line, or block), look for numbers that are "pinned" to a particular set of sets. Then use this fact to eliminate those numbers from all other sets.
-//
-// For example, given:
-//
-//      [2,7], [2,5,7,8], 1, 3, 9, 4, 6, [5,8], [5,8]
-//
-// We know that 5 and 8 must be in the last two sets, and therefore cannot be anywhere else. This allows us to reduce [2,5,7,8] to [2,7].
-//
-// Sets need not be exact duplicates for this trick to work. For example, given:
-//
-//     [2,7], [2,5,7,8], 1, 9, 4, 6, [5,8], [3,8], [5,3]
-//
-// We can make a super set with the last three sets to form [3,5,8]. Since there are exactly 3 numbers possible for each of these 3 sets, the numbers within this super set are "pinned" and can be excluded from the rest of the line. In this example it would result in the 5 & 8 in the second set should be removed.
-//
-// Returns the consolidated sets in the same order they were provided.
-//pub fn reduce_candidates_by_uniqueness(candidates: [[u8; 9]; 9]) -> [[u8; 9]; 9] {
-pub fn reduce_candidates_by_uniqueness(candidates: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
-    use hashbag::HashBag;
-
-    let mut reduced: Vec<HashSet<u8>> = Vec::new(); // maybe `residual` instead?
-    let mut bag: HashBag<Vec<u8>> = HashBag::new();
-
-    for i in 0..9 {
-        bag.insert(candidates[i].clone());
-
-        let mut r: HashSet<u8> = HashSet::new();
-        for c in candidates[i].iter() {
-            r.insert(*c);
+// `validate [puzzle.txt]`: check the puzzle is sound and has just the one solution, without solving it, reading it from stdin if no file is given. The verdict is printed a line to each fact, `verdict` first, for scripts to read. Exits 0 if it's valid, 1 if not, and 3 if the puzzle can't be read.
+fn validate(args: &[String]) -> Result<(), std::io::Error> {
+    let input = match args {
+        [] => read_stdin()?,
+        [path] => std::fs::read_to_string(path)?,
+        _ => {
+            eprintln!("validate takes at most one puzzle file");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+
+    let (puzzle, report) = match Puzzle::parse_with_report(&input) {
+        Ok(parsed) => parsed,
+        Err(report) => {
+            println!("verdict unreadable");
+            for error in &report.errors {
+                println!("error {}", error);
+            }
+            std::process::exit(EXIT_UNREADABLE);
         }
-        reduced.push(r);
+    };
+
+    let validation = puzzle.validate();
+    println!("verdict {}", validation.verdict());
+    println!("givens {}", validation.givens);
+    if let Some(solutions) = validation.solutions {
+        println!("solutions {}", solutions);
+    }
+    for problem in &validation.problems {
+        println!("problem {:?}", problem);
+    }
+    for warning in &report.warnings {
+        println!("warning {}", warning);
     }
 
-    let mut pinned: Vec<Vec<u8>> = Vec::new();
-    for (candidate, count) in bag.set_iter() {
-        // println!("{} instance of {:?}", count, candidate);
+    if !validation.is_valid() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
 
-        if count > 1 && count == candidate.len() {
-            // Pinned pair, triplet, quadruplet, etc.
-            pinned.push(candidate.clone());
+// `solutions [puzzle.txt] [--limit N]`: show up to N different solutions of the puzzle, 10 unless told otherwise, reading it from stdin if no file is given.
+fn solutions(args: &[String]) -> Result<(), std::io::Error> {
+    let mut path = None;
+    let mut limit = 10;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--limit" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => limit = n,
+                _ => {
+                    eprintln!("--limit needs a number of solutions");
+                    std::process::exit(EXIT_UNREADABLE);
+                }
+            },
+            _ if path.is_none() => path = Some(arg),
+            _ => {
+                eprintln!("solutions takes at most one puzzle file");
+                std::process::exit(EXIT_UNREADABLE);
+            }
         }
     }
 
-    // TODO: Figure out how to find pinned supersets from N sets that contain N numbers. E.g. [5,8], [3,8], [5,3] => [3,5,8].
+    let puzzle = match path {
+        Some(path) => parse_puzzle(&std::fs::read_to_string(path)?),
+        None => parse_puzzle(&read_stdin()?),
+    };
 
-    // println!("Pinned pairs/triplets/quadruplets/etc: {:?}", pinned);
+    let solutions = puzzle.solutions(limit);
+    for (i, solution) in solutions.iter().enumerate() {
+        println!("🧩 Solution {}:\n{}", i + 1, solution);
+    }
+    match solutions.len() {
+        0 => println!("💥 No solutions"),
+        1 => println!("Just the one solution 🙌"),
+        n if n == limit => println!("Found {} solutions, and stopped looking for more", n),
+        n => println!("Found all {} solutions", n),
+    }
 
-    // Remove contents of each pinned set from all _other_ sets.
-    for pinned_numbers in pinned.iter() {
-        for i in 0..9 {
-            if *pinned_numbers == candidates[i] {
-                // println!("Pinned set {:?} matched itself; skipping", pinned_numbers);
-                continue;
-            }
+    Ok(())
+}
 
-            for pinned_number in pinned_numbers {
-                // Changing the
-                reduced[i].remove(pinned_number);
+// `samurai [puzzle.txt] [--logic-only]`: solve a samurai sudoku, its five grids laid out as usual in 21 lines, reading it from stdin if no file is given. Exits as solving does.
+fn samurai(args: &[String]) -> Result<(), std::io::Error> {
+    let mut path = None;
+    let mut logic_only = false;
+    for arg in args {
+        match arg.as_str() {
+            "--logic-only" => logic_only = true,
+            _ if path.is_none() => path = Some(arg),
+            _ => {
+                eprintln!("samurai takes at most one puzzle file");
+                std::process::exit(EXIT_UNREADABLE);
             }
         }
     }
 
-    let mut result: Vec<Vec<u8>> = Vec::new();
-    for i in 0..9 {
-        let mut entries: Vec<u8> = reduced[i].iter().map(|c| *c).collect();
-        entries.sort();
-        result.push(entries);
-    }
-
-    return result;
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => read_stdin()?,
+    };
+    let mut multi = match MultiPuzzle::parse_samurai(&input) {
+        Ok(multi) => multi,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+
+    multi.set_config(SolverConfig {
+        logic_only,
+        ..SolverConfig::default()
+    });
+    multi.solve();
+    print!("{}", multi);
+    let code = match multi.status() {
+        PuzzleStatus::Solved => EXIT_SOLVED,
+        PuzzleStatus::Unsolved => EXIT_UNSOLVED,
+        PuzzleStatus::IllDefined(_) => EXIT_ILL_DEFINED,
+    };
+    std::process::exit(code);
 }
 
-fn read_stdin() -> Result<String, std::io::Error> {
-    let mut buf = String::new();
-    std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
-    Ok(buf)
-}
+// `compare a.txt b.txt`: whether the two are the same puzzle, but for relabelled digits, rows and columns shuffled within their bands and stacks, or the grid turned or reflected. Exits 1 if they aren't.
+//
+// `compare --pipelines a.toml b.toml puzzles.sdm`: solve the puzzles, a line to each, with each pipeline in turn and compare how they fared. See `Pipeline::parse()` for what goes in a pipeline file. Reads the puzzles from stdin if every file is a pipeline.
+fn compare(args: &[String]) -> Result<(), std::io::Error> {
+    if args.first().map(String::as_str) == Some("--pipelines") {
+        return compare_pipelines(&args[1..]);
+    }
 
-fn block_num_for_row_col(row: usize, col: usize) -> usize {
-    (row / 3) * 3 + col / 3
+    let (a, b) = match args {
+        [a, b] => (
+            parse_puzzle(&std::fs::read_to_string(a)?),
+            parse_puzzle(&std::fs::read_to_string(b)?),
+        ),
+        _ => {
+            eprintln!("compare needs two puzzle files");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+
+    if a.is_equivalent_to(&b) {
+        println!("🪞 The same puzzle, up to symmetry");
+        Ok(())
+    } else {
+        println!("🙅 Different puzzles");
+        std::process::exit(1);
+    }
 }
 
-fn line_up_water_cannon(block: [[Cell; 3]; 3], number: u8) -> WaterCannonSights {
-    let mut sights: Vec<(usize, usize)> = Vec::new();
-    for row in 0..3 {
-        for col in 0..3 {
-            if block[row][col].candidates_as_vec().contains(&number) {
-                sights.push((row, col));
-            }
+// `diff a.txt b.txt`: where two transcriptions of a puzzle differ, and whether either carries on from the other. Exits 1 if they differ at all.
+fn diff(args: &[String]) -> Result<(), std::io::Error> {
+    let ((a, first), (b, second)) = match args {
+        [a, b] => (
+            (a, parse_puzzle(&std::fs::read_to_string(a)?)),
+            (b, parse_puzzle(&std::fs::read_to_string(b)?)),
+        ),
+        _ => {
+            eprintln!("diff needs two puzzle files");
+            std::process::exit(EXIT_UNREADABLE);
         }
+    };
+    if first.shape() != second.shape() {
+        eprintln!(
+            "{} is a {} puzzle and {} a {} one",
+            a,
+            first.shape().name(),
+            b,
+            second.shape().name()
+        );
+        std::process::exit(EXIT_UNREADABLE);
     }
 
-    // Sights can only line up if there are exactly 2 or 3 of them.
-    if sights.len() != 2 && sights.len() != 3 {
-        return WaterCannonSights::None;
+    let discrepancies = first.discrepancies(&second);
+    if discrepancies.is_empty() {
+        println!("🪞 The same grid");
+        return Ok(());
     }
 
-    let row_offset = sights[0].0;
-    let column_offset = sights[0].1;
-    let mut row_aligned = true;
-    let mut column_aligned = true;
-    for sight in sights.iter().skip(1) {
-        if sight.0 != row_offset {
-            row_aligned = false;
-        }
-        if sight.1 != column_offset {
-            column_aligned = false;
-        }
+    if !discrepancies.disagreements.is_empty() {
+        let count = discrepancies.disagreements.len();
+        println!(
+            "🙅 {} cell{} disagree{}",
+            count,
+            if count == 1 { "" } else { "s" },
+            if count == 1 { "s" } else { "" }
+        );
+    }
+    if !discrepancies.added.is_empty() {
+        let count = discrepancies.added.len();
+        println!(
+            "➕ {} given{} only in {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            b
+        );
+    }
+    if !discrepancies.removed.is_empty() {
+        let count = discrepancies.removed.len();
+        println!(
+            "➖ {} given{} only in {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            a
+        );
     }
+    println!("{}", discrepancies.describe());
 
-    if row_aligned {
-        WaterCannonSights::Row(row_offset)
-    } else if column_aligned {
-        WaterCannonSights::Column(column_offset)
+    if second.continues(&first) {
+        println!("✅ {} carries on consistently from {}", b, a);
+    } else if first.continues(&second) {
+        println!("✅ {} carries on consistently from {}", a, b);
     } else {
-        WaterCannonSights::None
+        println!("❌ Neither carries on consistently from the other");
     }
+    std::process::exit(1);
 }
 
-// Determine the origin offset for indexing into the full 9x9 grid from the given block.
-//
-// Recall that blocks are counted as follows:
-//     0 1 2
-//     3 4 5
-//     6 7 8
-fn grid_origin_offset_for_block(b: usize) -> (usize, usize) {
-    let origin_row = (b / 3) * 3;
-    let origin_col = (b % 3) * 3;
-
-    (origin_row, origin_col)
-}
+fn compare_pipelines(files: &[String]) -> Result<(), std::io::Error> {
+    let (pipelines, input) = match files.split_last() {
+        Some((last, rest)) if !last.ends_with(".toml") => (rest, std::fs::read_to_string(last)?),
+        _ => (files, read_stdin()?),
+    };
+    if pipelines.is_empty() {
+        eprintln!("compare --pipelines needs a pipeline file or more to compare");
+        std::process::exit(EXIT_UNREADABLE);
+    }
+
+    let mut parsed: Vec<Pipeline> = Vec::new();
+    for path in pipelines {
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map_or(path.clone(), |stem| stem.to_string_lossy().to_string());
+        match Pipeline::parse(&name, &std::fs::read_to_string(path)?) {
+            Ok(pipeline) => parsed.push(pipeline),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                std::process::exit(EXIT_UNREADABLE);
+            }
+        }
+    }
 
-#[derive(Debug, Clone, Copy)]
-struct Guess {
-    row: usize,
-    column: usize,
-    number: u8,
+    print!("{}", race(&input, &parsed).table());
+    Ok(())
 }
 
-// Return a solved puzzle or `None` if none of the given guesses are able to yield a solved puzzle. `None` would indicate an erroneous guess was taken earlier and the caller needs to discard this "branch".
-fn solve_with_guesses(given_puzzle: Puzzle) -> Option<Puzzle> {
-    println!("🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶🧶");
-    let mut guesses: Vec<Guess> = Vec::new();
-    for (row_num, row) in given_puzzle.grid.iter().enumerate() {
-        for (col_num, cell) in row.iter().enumerate() {
-            let candidates = cell.candidates_as_vec();
-
-            let mut silly_for_test: Vec<Guess> = Vec::new();
-            for c in candidates.iter() {
-                let guess = Guess {
-                    row: row_num,
-                    column: col_num,
-                    number: *c,
-                };
-                silly_for_test.push(guess);
-            }
-            if silly_for_test.len() > 0 {
-                guesses = silly_for_test;
+// `bench [--techniques T] [--logic-only] [--datasets DIR] [top95|hardest|puzzles.txt ...]`: solve the classic public puzzle lists, or files of puzzles one to a line, and report how many the techniques solve without guessing, the average time, and the guesses made, to quantify an improvement. Both lists are benched if none are named. They're downloaded with `curl` the first time, into `datasets/` unless `--datasets` says otherwise.
+fn bench(args: &[String]) -> Result<(), std::io::Error> {
+    let mut config = SolverConfig::default();
+    let mut dir = "datasets".to_string();
+    let mut names = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--techniques" => match Technique::parse_list(args.next().map_or("", String::as_str)) {
+                Ok(techniques) => config.techniques = techniques,
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(EXIT_UNREADABLE);
+                }
+            },
+            "--logic-only" => config.logic_only = true,
+            "--datasets" => match args.next() {
+                Some(path) => dir = path.clone(),
+                None => {
+                    eprintln!("--datasets needs a directory");
+                    std::process::exit(EXIT_UNREADABLE);
+                }
+            },
+            _ => names.push(arg.clone()),
+        }
+    }
+    if names.is_empty() {
+        names = DATASETS.iter().map(|d| d.name.to_string()).collect();
+    }
+
+    let mut lists = vec![];
+    for name in names {
+        match Dataset::find(&name) {
+            Some(dataset) => lists.push((name, fetch_dataset(&dataset, &dir)?)),
+            None => {
+                let input = std::fs::read_to_string(&name)?;
+                let stem = std::path::Path::new(&name)
+                    .file_stem()
+                    .map_or(name.clone(), |stem| stem.to_string_lossy().to_string());
+                lists.push((stem, input));
             }
         }
     }
+    let lists: Vec<(&str, &str)> = lists
+        .iter()
+        .map(|(name, input)| (name.as_str(), input.as_str()))
+        .collect();
 
-    println!(
-        "🧶 solve_with_guesses – {} possible candidates to guess from: {:?}",
-        guesses.len(),
-        guesses
-    );
+    let bar = ProgressBar::new(0);
+    let mut show = show_progress(&bar, "puzzles");
+    let benchmark = benchmark_with_progress(&lists, &config, |_, progress| show(progress));
+    bar.finish_and_clear();
 
-    let mut result: Option<Puzzle> = None;
+    print!("{}", benchmark.table());
+    Ok(())
+}
 
-    // TODO: remove rev() –– it's here simply because sample/expert3.txt worked well backwards
-    for guess in guesses.iter().rev() {
-        println!("Taking a guess! {:?}", guess);
-        let mut trial = given_puzzle.clone();
-        trial.grid[guess.row][guess.column].number = Some(guess.number);
-        trial.grid[guess.row][guess.column].candidates = [0; 9];
-        trial.solve();
+// The dataset's puzzles, downloaded into the directory the first time they're wanted and read from there after.
+fn fetch_dataset(dataset: &Dataset, dir: &str) -> Result<String, std::io::Error> {
+    let path = std::path::Path::new(dir).join(dataset.file_name());
+    if !path.exists() {
+        std::fs::create_dir_all(dir)?;
+        eprintln!("⬇️ Downloading {} from {}", dataset.name, dataset.url);
+        let status = std::process::Command::new("curl")
+            .args([
+                "--fail",
+                "--silent",
+                "--show-error",
+                "--location",
+                "--output",
+            ])
+            .arg(&path)
+            .arg(dataset.url)
+            .status();
+        if !matches!(status, Ok(status) if status.success()) {
+            let _ = std::fs::remove_file(&path);
+            eprintln!(
+                "Couldn't download {}. Save {} as {} to bench it offline",
+                dataset.name,
+                dataset.url,
+                path.display()
+            );
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    }
+    std::fs::read_to_string(path)
+}
 
-        result = match trial.status() {
-            PuzzleStatus::Solved => {
-                println!("SOLVED! Our guess of {:?} was correct. ✅", guess);
-                Some(trial)
+// `generate [--seed N] [--symmetry S] [--requires T] [--diagonals] [--anti-knight]`: make a new puzzle, printed as an .ss file headed by its seed. The same seed always makes the same puzzle, and without one it's seeded by the time. The symmetry, `none` unless asked for, is one `Symmetry::parse()` takes. With a technique to require, seeds are tried from the one given until a puzzle needs it. `--diagonals` makes an X-Sudoku, printed with its `# X-Sudoku` line, and `--anti-knight` one for the anti-knight rule, to solve with `--anti-knight` too.
+// Puzzles tried for one requiring a technique before giving up. The rarer techniques can take a thousand or so.
+const GENERATE_ATTEMPTS: usize = 10_000;
+
+fn generate(args: &[String]) -> Result<(), std::io::Error> {
+    let mut options = GeneratorOptions {
+        seed: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+        symmetry: Symmetry::None,
+        diagonals: false,
+        anti_knight: false,
+    };
+    let mut requires = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--diagonals" => {
+                options.diagonals = true;
+                continue;
             }
-            PuzzleStatus::IllDefined(_) => {
-                println!("🧶 🧶 🧶 YIKES! Our guess of {:?} was wrong. ❌", guess);
-                None
+            "--anti-knight" => {
+                options.anti_knight = true;
+                continue;
             }
-            PuzzleStatus::Unsolved => {
-                println!("INCONCLUSIVE! Our guess of {:?} was inconslusive. RECURSING into the next set of guesses.", guess);
-
-                solve_with_guesses(trial)
+            _ => {}
+        }
+        let parsed = match (arg.as_str(), args.next()) {
+            ("--seed", Some(seed)) => seed
+                .parse::<u64>()
+                .map(|seed| options.seed = seed)
+                .map_err(|_| "--seed needs a number".to_string()),
+            ("--symmetry", Some(name)) => {
+                Symmetry::parse(name).map(|symmetry| options.symmetry = symmetry)
             }
+            ("--requires", Some(name)) => match Technique::parse_list(name).as_deref() {
+                Ok([technique]) => {
+                    requires = Some(*technique);
+                    Ok(())
+                }
+                Ok(_) => Err("--requires needs a single technique".to_string()),
+                Err(message) => Err(message.clone()),
+            },
+            _ => Err(format!("Unknown option for generate: {}", arg)),
         };
+        if let Err(message) = parsed {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    }
 
-        if let Some(puzzle) = &result {
-            println!(
-                "🙌 🙌 🙌 🙌 🙌 Our guess of {:?} yielded a solved puzzle!\n{}",
-                guess,
-                puzzle.display()
+    let mut header = String::new();
+    let puzzle = match requires {
+        None => Puzzle::generate_with(&options),
+        Some(technique) => {
+            eprintln!("🔎 Looking for a puzzle that requires {}", technique.name());
+            let bar = ProgressBar::new(0);
+            let found = Puzzle::generate_requiring_with_progress(
+                &options,
+                technique,
+                GENERATE_ATTEMPTS,
+                show_progress(&bar, "puzzles tried"),
             );
-            break;
+            bar.finish_and_clear();
+            match found {
+                Some((seed, puzzle)) => {
+                    options.seed = seed;
+                    header = format!(", requiring {}", technique.name());
+                    puzzle
+                }
+                None => {
+                    eprintln!(
+                        "🤷 None of the {} puzzles tried required {}",
+                        GENERATE_ATTEMPTS,
+                        technique.name()
+                    );
+                    std::process::exit(1);
+                }
+            }
         }
+    };
+    if options.symmetry != Symmetry::None {
+        header = format!(", {} symmetry{}", options.symmetry.name(), header);
     }
-
-    result
+    if options.anti_knight {
+        header = format!(", anti-knight{}", header);
+    }
+    println!("# Puzzle #{}{}", options.seed, header);
+    print!("{}", puzzle.ss());
+    if options.diagonals {
+        println!("# X-Sudoku");
+    }
+    Ok(())
 }
-fn main() -> Result<(), std::io::Error> {
-    let input = &read_stdin()?;
-    let mut puzzle = Puzzle::parse(input);
-    puzzle.solve();
 
-    println!(
-        "🎁 🎁 🎁 🎁 🎁    FINAL     🎁 🎁 🎁 🎁 🎁\n{}",
-        puzzle.display()
-    );
-
-    // TODO: if not solved, we need to pick one of the opposing candidate pairs (e.g. a block with candidates [2,3] and [2, 3]) and work out if a solution can be found. Clone the puzzle, make a guess, and try solving again. If a contradiction is found, throw it away.
-
-    // Create vector of all possible guesses
-    //  guesses = [[Block1, Row0, Col2 = 2], [Block1, Row0, Col2 = 5], ....]
+// `scan photo.jpg [options]`: make out the puzzle in a photo and, once it's confirmed as read right, solve it with the options as without a command.
+#[cfg(feature = "ocr")]
+fn scan(args: &[String]) -> Result<(), std::io::Error> {
+    let (path, args) = match args.split_first() {
+        Some((path, args)) => (path, args),
+        None => {
+            eprintln!("scan needs a photo of the puzzle");
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+    let options = match parse_args(args.to_vec()) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
 
-    // solved_puzzle = solve_with_guess(puzzle, guesses)
-    // solved_puzzle.display()
+    let puzzle = match Puzzle::scan(&std::fs::read(path)?) {
+        Ok(puzzle) => puzzle,
+        Err(message) => {
+            eprintln!("💥 {}", message);
+            std::process::exit(EXIT_UNREADABLE);
+        }
+    };
+
+    // Printed as an .ss file, so it can be saved and corrected if anything was misread
+    println!("📷 Made out this puzzle:\n{}", puzzle.ss());
+    print!("Solve it? [Y/n] ");
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if answer.trim().to_lowercase().starts_with('n') {
+        println!("Save it to a file, correct any misread digits, and solve it with `cargo run < puzzle.ss`");
+        return Ok(());
+    }
 
-    // fn solve_with_guess(puzzle, mut guesses) -> Result<Puzzle, Error> {
-    //
-    //      let guess = guesses.pop()
-    //
-    //      let my_puzzle = puzzle.clone()
-    //
-    //      my_puzzle.grid[][] = guess
-    //
-    //
-    //      my_puzzle.solve()
-    //      if my_puzzle.is_solved() {
-    //          return Ok(my_puzzle)
-    //      }
-    //      else if my_puzzle.is_ill_defined() {
-    //          return Error(...)
-    //      }
-    //      else {
-    //          return solve_with_guess(my_puzzle, guesses)
-    //      }
-    //
-    // }
+    solve(&options, puzzle)
+}
 
-    // for guess in v.iter() {
-    //      my_guess = puzzle.clone()
-    //      my_guess.set_block_num(Block1, Row0, Col2)
-    //      my_guess.solve()
-    //      look for solved, bad, or more guesses needed
-    //      Create NEW list of guesses. v2 = [...]
-    // }
+#[cfg(not(feature = "ocr"))]
+fn scan(_args: &[String]) -> Result<(), std::io::Error> {
+    eprintln!("scan needs christopher built with `--features ocr`");
+    std::process::exit(EXIT_UNREADABLE);
+}
 
-    let status = puzzle.status();
-    match status {
-        PuzzleStatus::Solved => {
-            println!("Solved! 🙌");
-            println!("{}", puzzle.display());
-            std::process::exit(0);
+// `library [--db FILE] add [puzzle.txt ...]`, `library [--db FILE] list`, or `library [--db FILE] search [--difficulty D] [--technique T]`: keep puzzles in a library, rated and solved as they're added, and find them again. The library is `library.db` unless told otherwise. Puzzles to add are read from stdin if no files are given.
+#[cfg(feature = "library")]
+fn library(args: &[String]) -> Result<(), std::io::Error> {
+    use christopher::{Added, Difficulty, Library};
+
+    let fail = |message: String| -> ! {
+        eprintln!("💥 {}", message);
+        std::process::exit(EXIT_UNREADABLE);
+    };
+
+    let mut path = "library.db".to_string();
+    let mut command = None;
+    let mut rest = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => match args.next() {
+                Some(db) => path = db.clone(),
+                None => fail("--db needs the library's file".to_string()),
+            },
+            _ if command.is_none() => command = Some(arg.as_str()),
+            _ => rest.push(arg.clone()),
         }
-        PuzzleStatus::IllDefined(reason) => {
-            println!("💥 Ill-defined puzzle: {:?}", reason);
-            std::process::exit(-1);
+    }
+
+    let library = Library::open(&path).unwrap_or_else(|message| fail(message));
+    let entries = match command {
+        Some("add") => {
+            let inputs = match rest.as_slice() {
+                [] => vec![read_stdin()?],
+                paths => paths
+                    .iter()
+                    .map(std::fs::read_to_string)
+                    .collect::<Result<Vec<String>, std::io::Error>>()?,
+            };
+            for input in inputs {
+                match library.add(&parse_puzzle(&input)) {
+                    Ok(Added::New(id)) => println!("📚 Added puzzle {}", id),
+                    Ok(Added::Known(id)) => println!("🪞 Already in the library as puzzle {}", id),
+                    Err(message) => fail(message),
+                }
+            }
+            return Ok(());
         }
-        PuzzleStatus::Unsolved => {
-            println!("⁉️  Couldn't reduce any further. Need more smarts. Or, guess!");
+        Some("list") if rest.is_empty() => library.list(),
+        Some("search") => {
+            let mut difficulty = None;
+            let mut technique = None;
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match (arg.as_str(), rest.next()) {
+                    ("--difficulty", Some(name)) => {
+                        difficulty = Some(Difficulty::parse(name).unwrap_or_else(|m| fail(m)))
+                    }
+                    ("--technique", Some(name)) if name == "guess" => {
+                        technique = Some(name.clone())
+                    }
+                    ("--technique", Some(name)) => match Technique::parse_list(name).as_deref() {
+                        Ok([t]) => technique = Some(t.name().to_string()),
+                        Ok(_) => fail("--technique needs a single technique".to_string()),
+                        Err(message) => fail(message.clone()),
+                    },
+                    _ => fail(format!("Unknown option for library search: {}", arg)),
+                }
+            }
+            library.search(difficulty, technique.as_deref())
         }
+        _ => fail("library needs one of add, list, or search".to_string()),
+    };
+
+    let entries = entries.unwrap_or_else(|message| fail(message));
+    for entry in &entries {
+        println!(
+            "#{} {} {} ({} steps in {:?}: {})",
+            entry.id,
+            entry.puzzle,
+            entry.difficulty.name(),
+            entry.steps,
+            entry.solve_time,
+            entry.techniques.join(" ")
+        );
     }
+    if entries.is_empty() {
+        println!("🤷 No puzzles found");
+    }
+    Ok(())
+}
 
-    println!("❓❓❓❓  G U E S S   T I M E ❓ ❓ ❓ ❓ ❓");
-
-    let trial_puzzle = solve_with_guesses(puzzle);
+#[cfg(not(feature = "library"))]
+fn library(_args: &[String]) -> Result<(), std::io::Error> {
+    eprintln!("library needs christopher built with `--features library`");
+    std::process::exit(EXIT_UNREADABLE);
+}
 
-    match trial_puzzle {
-        Some(puzzle) => match puzzle.status() {
-            PuzzleStatus::Solved => {
-                println!("Solved! 🙌🙌🙌🙌🙌");
-                println!("{}", puzzle.display());
-            }
-            PuzzleStatus::IllDefined(reason) => {
-                println!("💥💥💥💥💥 Ill-defined puzzle: {:?}", reason);
-            }
-            PuzzleStatus::Unsolved => {
-                println!("⁉️⁉️⁉️⁉️⁉️  Couldn't reduce any further. Not even with guesses!!");
+// `serve [--port N]`: stream solves to browsers over WebSocket on the port, 8080 unless told otherwise, until interrupted.
+#[cfg(feature = "server")]
+fn serve(args: &[String]) -> Result<(), std::io::Error> {
+    let port = match args {
+        [] => 8080,
+        [flag, port] if flag == "--port" => match port.parse::<u16>() {
+            Ok(port) => port,
+            Err(_) => {
+                eprintln!("Expected a port number for --port, not '{}'", port);
+                std::process::exit(EXIT_UNREADABLE);
             }
         },
-        None => {
-            println!("Failed to solve puzzle with guesses 🙁");
+        _ => {
+            eprintln!("serve takes at most --port N");
+            std::process::exit(EXIT_UNREADABLE);
         }
-    }
+    };
 
-    Ok(())
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+    println!("🔌 Serving solves on ws://127.0.0.1:{}", port);
+    christopher::serve(listener)
 }
 
-mod test {
-    #[allow(unused_imports)] // wtf?
-    use super::*;
-    use std::collections::HashSet;
-
-    #[allow(dead_code)] // wtf?
-    const SAMPLE: &str = r#"
-.4.5.2...
-76....1.2
-9...18.64
-..429...8
-.8.3.6.7.
-6...754..
-21.68...3
-4.6....27
-...4.9.1.
-    "#;
-
-    #[allow(dead_code)]
-    fn assert_eq_set(a: &HashSet<u8>, b: &[u8]) {
-        let a: HashSet<_> = a.iter().collect();
-        let b: HashSet<_> = b.iter().collect();
-
-        assert!(a == b, "Sets do not match. Expected {:?}, found {:?}", b, a);
-    }
-
-    #[allow(dead_code)]
-    fn eq_slice(a: &[u8], b: &[u8]) -> bool {
-        let mut a: HashSet<&u8> = a.iter().collect();
-        let mut b: HashSet<&u8> = b.iter().collect();
-
-        a.remove(&0);
-        b.remove(&0);
-
-        if a != b {
-            println!("Slices do not match. Expected {:?}, found {:?}.", b, a);
-        }
-
-        a == b
-    }
-
-    #[test]
-    fn helpers() {
-        assert_eq!(0, block_num_for_row_col(0, 0));
-        assert_eq!(0, block_num_for_row_col(2, 2));
-        assert_eq!(2, block_num_for_row_col(1, 6));
-        assert_eq!(6, block_num_for_row_col(7, 2));
-        assert_eq!(8, block_num_for_row_col(8, 8));
-
-        assert_eq!((0, 0), grid_origin_offset_for_block(0));
-        assert_eq!((0, 3), grid_origin_offset_for_block(1));
-        assert_eq!((3, 0), grid_origin_offset_for_block(3));
-        assert_eq!((3, 3), grid_origin_offset_for_block(4));
-        assert_eq!((6, 6), grid_origin_offset_for_block(8));
-
-        let mut block = [
-            [
-                Cell::with_number(7),
-                Cell::with_candidates(vec![3, 9]),
-                Cell::with_number(5),
-            ],
-            [
-                Cell::with_number(6),
-                Cell::with_candidates(vec![4, 9]),
-                Cell::with_number(1),
-            ],
-            [
-                Cell::with_candidates(vec![2, 3, 9]),
-                Cell::with_candidates(vec![2, 3, 4, 9]),
-                Cell::with_number(8),
-            ],
-        ];
-        assert_eq!(line_up_water_cannon(block, 4), WaterCannonSights::Column(1));
-
-        block = [
-            [
-                Cell::with_candidates(vec![3, 7, 9]),
-                Cell::with_number(6),
-                Cell::with_candidates(vec![1, 3, 7]),
-            ],
-            [
-                Cell::with_number(2),
-                Cell::with_number(8),
-                Cell::with_candidates(vec![1, 3]),
-            ],
-            [
-                Cell::with_candidates(vec![3, 9]),
-                Cell::with_number(4),
-                Cell::with_number(5),
-            ],
-        ];
-        assert_eq!(line_up_water_cannon(block, 9), WaterCannonSights::Column(0));
-
-        block = [
-            [
-                Cell::with_candidates(vec![1, 3, 9]),
-                Cell::with_number(2),
-                Cell::with_number(5),
-            ],
-            [
-                Cell::with_candidates(vec![1, 3, 9]),
-                Cell::with_number(8),
-                Cell::with_number(6),
-            ],
-            [
-                Cell::with_number(7),
-                Cell::with_candidates(vec![1, 4]),
-                Cell::with_candidates(vec![4, 9]),
-            ],
-        ];
-        assert_eq!(line_up_water_cannon(block, 1), WaterCannonSights::None);
-        assert_eq!(line_up_water_cannon(block, 3), WaterCannonSights::Column(0));
-        assert_eq!(line_up_water_cannon(block, 4), WaterCannonSights::Row(2));
-        assert_eq!(line_up_water_cannon(block, 5), WaterCannonSights::None);
-        assert_eq!(line_up_water_cannon(block, 9), WaterCannonSights::None);
-    }
-
-    #[test]
-    fn baby_steps() {
-        let mut puzzle = super::Puzzle::parse(SAMPLE);
-
-        println!("Parsed puzzle:\n{}", puzzle);
-        assert_eq_set(&puzzle.numbers_in_row(0), &[4, 5, 2]);
-        assert_eq_set(&puzzle.numbers_in_row(1), &[7, 6, 1, 2]);
-        assert_eq_set(&puzzle.numbers_in_row(2), &[9, 1, 8, 6, 4]);
-        assert_eq_set(&puzzle.numbers_in_row(3), &[4, 2, 9, 8]);
-        assert_eq_set(&puzzle.numbers_in_row(4), &[8, 3, 6, 7]);
-        assert_eq_set(&puzzle.numbers_in_row(5), &[6, 7, 5, 4]);
-        assert_eq_set(&puzzle.numbers_in_row(6), &[2, 1, 6, 8, 3]);
-        assert_eq_set(&puzzle.numbers_in_row(7), &[4, 6, 2, 7]);
-        assert_eq_set(&puzzle.numbers_in_row(8), &[4, 9, 1]);
-
-        assert_eq_set(&puzzle.numbers_in_column(0), &[7, 9, 6, 2, 4]);
-        assert_eq_set(&puzzle.numbers_in_column(1), &[4, 6, 8, 1]);
-        assert_eq_set(&puzzle.numbers_in_column(2), &[4, 6]);
-        assert_eq_set(&puzzle.numbers_in_column(3), &[5, 2, 3, 6, 4]);
-        assert_eq_set(&puzzle.numbers_in_column(4), &[1, 9, 7, 8]);
-        assert_eq_set(&puzzle.numbers_in_column(5), &[2, 8, 6, 5, 9]);
-        assert_eq_set(&puzzle.numbers_in_column(6), &[1, 4]);
-        assert_eq_set(&puzzle.numbers_in_column(7), &[6, 7, 2, 1]);
-        assert_eq_set(&puzzle.numbers_in_column(8), &[2, 4, 8, 3, 7]);
-
-        assert_eq_set(&puzzle.numbers_in_block(0), &[4, 7, 6, 9]);
-        assert_eq_set(&puzzle.numbers_in_block(1), &[5, 2, 1, 8]);
-        assert_eq_set(&puzzle.numbers_in_block(2), &[1, 2, 6, 4]);
-        assert_eq_set(&puzzle.numbers_in_block(3), &[4, 8, 6]);
-        assert_eq_set(&puzzle.numbers_in_block(4), &[2, 9, 3, 6, 7, 5]);
-        assert_eq_set(&puzzle.numbers_in_block(5), &[8, 7, 4]);
-        assert_eq_set(&puzzle.numbers_in_block(6), &[2, 1, 4, 6]);
-        assert_eq_set(&puzzle.numbers_in_block(7), &[6, 8, 4, 9]);
-        assert_eq_set(&puzzle.numbers_in_block(8), &[3, 2, 7, 1]);
-
-        puzzle.assign_candidates();
-
-        // Block 0
-        assert!(eq_slice(&puzzle.grid[0][0].candidates, &[1, 3, 8]));
-        assert!(eq_slice(&puzzle.grid[0][2].candidates, &[1, 3, 8]));
-        assert!(eq_slice(&puzzle.grid[1][2].candidates, &[5, 8]));
-        assert!(eq_slice(&puzzle.grid[2][1].candidates, &[2, 3, 5]));
-        assert!(eq_slice(&puzzle.grid[2][2].candidates, &[2, 3, 5]));
-
-        println!("Internals:\n{}", puzzle.internals());
-    }
-
-    #[test]
-    fn reduce_candidates_by_uniqueness() {
-        let pinned_pair: Vec<Vec<u8>> = vec![
-            vec![2, 7],
-            vec![2, 5, 7, 8],
-            vec![1],
-            vec![3],
-            vec![9],
-            vec![4],
-            vec![6],
-            vec![5, 8],
-            vec![5, 8],
-        ];
-
-        let mut reduced = super::reduce_candidates_by_uniqueness(pinned_pair);
-
-        assert_eq!(reduced[0], vec![2, 7]);
-        assert_eq!(reduced[1], vec![2, 7]);
-        assert_eq!(reduced[2], vec![1]);
-        assert_eq!(reduced[3], vec![3]);
-        assert_eq!(reduced[4], vec![9]);
-        assert_eq!(reduced[5], vec![4]);
-        assert_eq!(reduced[6], vec![6]);
-        assert_eq!(reduced[7], vec![5, 8]);
-        assert_eq!(reduced[8], vec![5, 8]);
-
-        let pinned_triplet: Vec<Vec<u8>> = vec![
-            vec![6, 3, 8],
-            vec![3, 4, 8],
-            vec![3, 4, 8],
-            vec![1],
-            vec![2, 4],
-            vec![5],
-            vec![4, 8, 9],
-            vec![7],
-            vec![3, 4, 8],
-        ];
-
-        reduced = super::reduce_candidates_by_uniqueness(pinned_triplet);
-
-        assert_eq!(reduced[0], vec![6]);
-        assert_eq!(reduced[1], vec![3, 4, 8]);
-        assert_eq!(reduced[2], vec![3, 4, 8]);
-        assert_eq!(reduced[3], vec![1]);
-        assert_eq!(reduced[4], vec![2]);
-        assert_eq!(reduced[5], vec![5]);
-        assert_eq!(reduced[6], vec![9]);
-        assert_eq!(reduced[7], vec![7]);
-        assert_eq!(reduced[8], vec![3, 4, 8]);
-    }
+#[cfg(not(feature = "server"))]
+fn serve(_args: &[String]) -> Result<(), std::io::Error> {
+    eprintln!("serve needs christopher built with `--features server`");
+    std::process::exit(EXIT_UNREADABLE);
 }
@@ -0,0 +1,467 @@
+use std::fmt;
+
+use super::shape::digit;
+use super::{Consolidation, Coord, GridState, Puzzle, PuzzleStatus, SolverConfig, Technique};
+
+// Where the five grids of a samurai sudoku sit in its 21x21 layout: a grid in each corner, overlapping one in the middle by a block apiece.
+pub const SAMURAI: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+// The techniques that take the puzzle to have just the one solution
+const UNIQUENESS: [Technique; 3] = [
+    Technique::UniqueRectangle,
+    Technique::UniqueLoop,
+    Technique::BugLite,
+];
+
+/// Puzzles laid out overlapping, as the five 9x9 grids of a samurai sudoku are. Where two overlap they share whole blocks, the cells the same cells in both, so a number placed in one is placed in the other too. Each grid is solved as a puzzle of its own, with what it learns passed on to the grids it overlaps.
+#[derive(Clone, Debug)]
+pub struct MultiPuzzle {
+    // Each grid, and the row and column of the layout its top left cell sits at
+    grids: Vec<(Coord, Puzzle)>,
+}
+
+impl MultiPuzzle {
+    // The grids laid out with their top left cells where given. Each must be the same shape, with blocks rather than a jigsaw's regions, and any overlap whole blocks of both. Where they overlap, the grids must agree on any numbers, each taking those of the others.
+    pub fn new(grids: Vec<(Coord, Puzzle)>) -> Result<MultiPuzzle, String> {
+        let Some(shape) = grids.first().map(|(_, puzzle)| puzzle.shape) else {
+            return Err("Expected at least one grid".to_string());
+        };
+
+        for (at, puzzle) in &grids {
+            if puzzle.shape.name() != shape.name() || puzzle.shape.is_jigsaw() {
+                return Err(format!(
+                    "The grid at {} should be {} like the first, with blocks",
+                    at,
+                    shape.name()
+                ));
+            }
+            if at.row % shape.block_rows() != 0 || at.col % shape.block_cols() != 0 {
+                return Err(format!(
+                    "The grid at {} doesn't line up with the blocks of the first",
+                    at
+                ));
+            }
+        }
+
+        let mut multi = MultiPuzzle { grids };
+        for (a, b, in_a, in_b) in multi.overlaps() {
+            let (first, second) = (multi.grids[a].1[in_a], multi.grids[b].1[in_b]);
+            if let (Some(x), Some(y)) = (first.number, second.number) {
+                if x != y {
+                    let at = multi.grids[a].0;
+                    return Err(format!(
+                        "The grids overlapping at {} disagree, with {} and {}",
+                        Coord::new(at.row + in_a.row, at.col + in_a.col),
+                        x,
+                        y
+                    ));
+                }
+            }
+        }
+
+        multi.share();
+        for (_, puzzle) in &mut multi.grids {
+            puzzle.assign_candidates();
+        }
+        Ok(multi)
+    }
+
+    // Read a samurai sudoku as it's usually written: 21 lines of 21 characters, digits for the givens and any of `0 . * _ ? X` for blanks, with anything off the five grids, usually spaces, left out. Lines starting with `#` are comments.
+    pub fn parse_samurai(input: &str) -> Result<MultiPuzzle, String> {
+        let size = 21;
+        let lines: Vec<(usize, &str)> = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        if lines.len() != size {
+            return Err(format!(
+                "Expected {} lines of a samurai sudoku, not {}",
+                size,
+                lines.len()
+            ));
+        }
+
+        let on_grid = |row: usize, col: usize| {
+            SAMURAI
+                .iter()
+                .any(|&(r, c)| (r..r + 9).contains(&row) && (c..c + 9).contains(&col))
+        };
+        let mut cells = [[None; 21]; 21];
+        for (row, &(line_number, line)) in lines.iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            for col in (0..size).filter(|&col| on_grid(row, col)) {
+                let c = chars.get(col).copied().unwrap_or(' ');
+                cells[row][col] = match c {
+                    '0' | '.' | '*' | '_' | '?' | 'X' => None,
+                    _ => match digit(c, 9) {
+                        Some(d) => Some(d),
+                        None => {
+                            return Err(format!(
+                                "Line {}, column {}: '{}' is neither a digit nor a blank",
+                                line_number,
+                                col + 1,
+                                c
+                            ))
+                        }
+                    },
+                };
+            }
+        }
+
+        let grids = SAMURAI
+            .iter()
+            .map(|&(r, c)| {
+                let text: String = (r..r + 9)
+                    .flat_map(|row| {
+                        (c..c + 9)
+                            .map(move |col| cells[row][col].map_or('.', |d| char::from(b'0' + d)))
+                            .chain(['\n'])
+                    })
+                    .collect();
+                (Coord::new(r, c), Puzzle::parse(&text))
+            })
+            .collect();
+        MultiPuzzle::new(grids)
+    }
+
+    // Each grid, with where its top left cell sits.
+    pub fn grids(&self) -> impl Iterator<Item = (Coord, &Puzzle)> {
+        self.grids.iter().map(|(at, puzzle)| (*at, puzzle))
+    }
+
+    // The rows and columns the layout spans.
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.grids
+            .iter()
+            .fold((0, 0), |(rows, cols), (at, puzzle)| {
+                let size = puzzle.shape.size();
+                (rows.max(at.row + size), cols.max(at.col + size))
+            })
+    }
+
+    // The number in a cell of the layout, if there's a grid there and it has one.
+    pub fn number(&self, at: Coord) -> Option<u8> {
+        self.holding(at)
+            .find_map(|(i, coord)| self.grids[i].1[coord].number)
+    }
+
+    // Use the config for every grid, but for the techniques that rely on a grid having just the one solution. Alone, a grid of a samurai sudoku may well have more, those of the layout being told apart by the grids it overlaps, so a deadly pattern within it can be real.
+    pub fn set_config(&mut self, mut config: SolverConfig) {
+        config
+            .techniques
+            .retain(|technique| !UNIQUENESS.contains(technique));
+        for (_, puzzle) in &mut self.grids {
+            puzzle.set_config(config.clone());
+        }
+    }
+
+    // Ill-defined if any grid is, and solved once every grid is.
+    pub fn status(&self) -> PuzzleStatus {
+        let mut status = PuzzleStatus::Solved;
+        for (_, puzzle) in &self.grids {
+            match puzzle.status() {
+                PuzzleStatus::IllDefined(reason) => return PuzzleStatus::IllDefined(reason),
+                PuzzleStatus::Unsolved => status = PuzzleStatus::Unsolved,
+                PuzzleStatus::Solved => {}
+            }
+        }
+        status
+    }
+
+    // Solve each grid a step at a time, in turn, passing the numbers each places on to the grids it overlaps, until none can go further. Then, unless the config is for logic alone, guess in the cell with the fewest candidates, solving on from each guess until one works out. Left as it was if no guess does.
+    pub fn solve(&mut self) {
+        self.solve_logically();
+        if self.status() != PuzzleStatus::Unsolved || self.grids[0].1.config.logic_only {
+            return;
+        }
+
+        // Depth first, with snapshots to go back to rather than a copy of every grid for each guess, as the single puzzle search does
+        let start = self.snapshot();
+        let mut stack = vec![(start.clone(), self.guesses())];
+        while let Some((state, guesses)) = stack.last_mut() {
+            let Some((at, number)) = guesses.pop() else {
+                stack.pop();
+                continue;
+            };
+
+            self.restore(state);
+            self.place(at, number);
+            self.solve_logically();
+            match self.status() {
+                PuzzleStatus::Solved => return,
+                PuzzleStatus::IllDefined(_) => {}
+                PuzzleStatus::Unsolved => stack.push((self.snapshot(), self.guesses())),
+            }
+        }
+        self.restore(&start);
+    }
+
+    fn solve_logically(&mut self) {
+        loop {
+            let mut progress = false;
+            for (_, puzzle) in &mut self.grids {
+                if puzzle.status() == PuzzleStatus::Unsolved {
+                    progress |= puzzle.step().iter().any(Consolidation::is_assignment);
+                }
+            }
+            progress |= self.share();
+
+            if !progress || self.status() != PuzzleStatus::Unsolved {
+                break;
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<GridState> {
+        self.grids
+            .iter()
+            .map(|(_, puzzle)| puzzle.snapshot())
+            .collect()
+    }
+
+    fn restore(&mut self, states: &[GridState]) {
+        for ((_, puzzle), state) in self.grids.iter_mut().zip(states) {
+            puzzle.restore(state);
+        }
+    }
+
+    // Every pair of grids' cells that are the same cell of the layout: the two grids, then the cell in each.
+    fn overlaps(&self) -> Vec<(usize, usize, Coord, Coord)> {
+        let mut overlaps = vec![];
+        for (a, (at_a, first)) in self.grids.iter().enumerate() {
+            for (b, (at_b, second)) in self.grids.iter().enumerate().skip(a + 1) {
+                for coord in first.shape.coords() {
+                    let (row, col) = (at_a.row + coord.row, at_a.col + coord.col);
+                    let size = second.shape.size();
+                    if (at_b.row..at_b.row + size).contains(&row)
+                        && (at_b.col..at_b.col + size).contains(&col)
+                    {
+                        overlaps.push((a, b, coord, Coord::new(row - at_b.row, col - at_b.col)));
+                    }
+                }
+            }
+        }
+        overlaps
+    }
+
+    // The grids holding a cell of the layout, and where it is in each.
+    fn holding(&self, at: Coord) -> impl Iterator<Item = (usize, Coord)> + '_ {
+        self.grids
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, (corner, puzzle))| {
+                let size = puzzle.shape.size();
+                let inside = (corner.row..corner.row + size).contains(&at.row)
+                    && (corner.col..corner.col + size).contains(&at.col);
+                inside.then(|| (i, Coord::new(at.row - corner.row, at.col - corner.col)))
+            })
+    }
+
+    // Copy the numbers in each overlap over to the grid without them, keeping givens given, and rule them out of the cells they see there. Returns whether any were.
+    fn share(&mut self) -> bool {
+        let mut changed = vec![false; self.grids.len()];
+        for (a, b, in_a, in_b) in self.overlaps() {
+            let (first, second) = (self.grids[a].1[in_a], self.grids[b].1[in_b]);
+            match (first.number, second.number) {
+                (Some(_), None) => {
+                    self.grids[b].1[in_b] = first;
+                    changed[b] = true;
+                }
+                (None, Some(_)) => {
+                    self.grids[a].1[in_a] = second;
+                    changed[a] = true;
+                }
+                _ => {}
+            }
+        }
+
+        for (i, (_, puzzle)) in self.grids.iter_mut().enumerate() {
+            if changed[i] {
+                puzzle.assign_candidates();
+            }
+        }
+        changed.contains(&true)
+    }
+
+    // Place a number in a cell of the layout, in every grid holding it.
+    fn place(&mut self, at: Coord, number: u8) {
+        let holding: Vec<(usize, Coord)> = self.holding(at).collect();
+        for (i, coord) in holding {
+            self.grids[i].1.set_number(coord, number);
+            self.grids[i].1.assign_candidates();
+        }
+    }
+
+    // The numbers to guess between in the blank cell of the layout with the fewest candidates, those of every grid holding it, the first last so it can be popped.
+    fn guesses(&self) -> Vec<(Coord, u8)> {
+        let (rows, cols) = self.dimensions();
+        let mut fewest: Option<(Coord, Vec<u8>)> = None;
+        for at in (0..rows).flat_map(|row| (0..cols).map(move |col| Coord::new(row, col))) {
+            let holding: Vec<(usize, Coord)> = self.holding(at).collect();
+            let Some(&(first, _)) = holding.first() else {
+                continue;
+            };
+            if self.number(at).is_some() {
+                continue;
+            }
+
+            let candidates = holding.iter().fold(
+                self.grids[first].1.shape.digits(),
+                |candidates, &(i, coord)| {
+                    candidates.intersection(self.grids[i].1[coord].candidates)
+                },
+            );
+            if fewest
+                .as_ref()
+                .is_none_or(|(_, most)| candidates.count() < most.len())
+            {
+                fewest = Some((at, candidates.to_vec()));
+            }
+        }
+        fewest.map_or(vec![], |(at, candidates)| {
+            candidates
+                .into_iter()
+                .rev()
+                .map(|number| (at, number))
+                .collect()
+        })
+    }
+}
+
+// The layout as `parse_samurai()` reads it: a line for each row, `.` for blanks, and spaces off the grids.
+impl fmt::Display for MultiPuzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.dimensions();
+        for row in 0..rows {
+            let line: String = (0..cols)
+                .map(|col| {
+                    let at = Coord::new(row, col);
+                    match (self.holding(at).next(), self.number(at)) {
+                        (None, _) => ' ',
+                        (Some(_), None) => '.',
+                        (Some(_), Some(number)) => super::symbol(number),
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Coord, Puzzle, PuzzleStatus, SolverConfig, Technique};
+    use super::MultiPuzzle;
+
+    const SAMURAI: &str = include_str!("../samples/samurai.txt");
+
+    // The quicker techniques, enough for the sample, as the chains take a while over grids this bare
+    fn quiet(multi: &mut MultiPuzzle, logic_only: bool) {
+        multi.set_config(SolverConfig {
+            logic_only,
+            ..SolverConfig::with_techniques(vec![
+                Technique::SaraFlex,
+                Technique::WaterCannon,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+                Technique::XWing,
+            ])
+        });
+    }
+
+    #[test]
+    fn samurai() {
+        let mut multi = MultiPuzzle::parse_samurai(SAMURAI).unwrap();
+        assert_eq!((21, 21), multi.dimensions());
+        assert_eq!(5, multi.grids().count());
+        let text: String = SAMURAI
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect();
+        assert_eq!(text, multi.to_string());
+
+        // Givens in a shared block are given in both grids
+        let centre = multi.grids().nth(2).unwrap().1;
+        for (at, puzzle) in multi.grids() {
+            for coord in puzzle.shape().coords() {
+                let shared = (coord.row + at.row)
+                    .checked_sub(6)
+                    .zip((coord.col + at.col).checked_sub(6))
+                    .filter(|&(row, col)| row < 9 && col < 9);
+                if let Some((row, col)) = shared {
+                    let (theirs, ours) = (centre[(row, col)], puzzle[coord]);
+                    assert_eq!(theirs.number(), ours.number());
+                    assert_eq!(theirs.is_given(), ours.is_given());
+                }
+            }
+        }
+
+        // Alone, the corner grids are too short of givens, but each passes the others what it places
+        let alone = multi.grids().next().unwrap().1;
+        assert!(alone.count_solutions(2) > 1);
+        quiet(&mut multi, true);
+        multi.solve();
+        assert_eq!(PuzzleStatus::Solved, multi.status());
+        for (_, puzzle) in multi.grids() {
+            assert!(puzzle.verify(puzzle).is_empty());
+        }
+        assert!(!multi.to_string().contains('.'));
+    }
+
+    #[test]
+    fn guesses() {
+        // An empty samurai solves by guessing, the overlaps kept in step
+        let empty: String = MultiPuzzle::parse_samurai(SAMURAI)
+            .unwrap()
+            .to_string()
+            .chars()
+            .map(|c| if c.is_ascii_digit() { '.' } else { c })
+            .collect();
+        let mut multi = MultiPuzzle::parse_samurai(&empty).unwrap();
+        quiet(&mut multi, true);
+        multi.solve();
+        assert_eq!(PuzzleStatus::Unsolved, multi.status());
+        quiet(&mut multi, false);
+        multi.solve();
+        assert_eq!(PuzzleStatus::Solved, multi.status());
+        let (corner, centre) = (
+            multi.grids().next().unwrap().1,
+            multi.grids().nth(2).unwrap().1,
+        );
+        assert_eq!(corner[(6, 6)], centre[(0, 0)]);
+        assert_eq!(corner[(8, 8)], centre[(2, 2)]);
+    }
+
+    #[test]
+    fn errors() {
+        let lines: Vec<&str> = SAMURAI.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(
+            Err("Expected 21 lines of a samurai sudoku, not 20".to_string()),
+            MultiPuzzle::parse_samurai(&lines[1..].join("\n")).map(|_| ())
+        );
+        let mut broken = lines.clone();
+        broken[0] = "x";
+        assert_eq!(
+            Err("Line 1, column 1: 'x' is neither a digit nor a blank".to_string()),
+            MultiPuzzle::parse_samurai(&broken.join("\n")).map(|_| ())
+        );
+
+        // Grids must line up with each other's blocks, and agree where they overlap
+        let blank = ".........\n".repeat(8);
+        let grid = Puzzle::parse(&format!("..1......\n{}", blank));
+        let other = Puzzle::parse(&format!("........2\n{}", blank));
+        assert!(MultiPuzzle::new(vec![
+            (Coord::new(0, 0), grid.clone()),
+            (Coord::new(4, 4), grid.clone())
+        ])
+        .is_err());
+        assert_eq!(
+            Err("The grids overlapping at r1c9 disagree, with 2 and 1".to_string()),
+            MultiPuzzle::new(vec![(Coord::new(0, 0), other), (Coord::new(0, 6), grid)]).map(|_| ())
+        );
+    }
+}
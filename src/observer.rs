@@ -0,0 +1,196 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::{Consolidation, Coord, Puzzle, Step, Technique};
+
+/// Told what the solver does as it does it, to show it some other way, gather figures on it, or record it. Every callback does nothing unless overridden. With `parallel_guesses` configured the callbacks come from whichever thread is solving, so take `&self`.
+pub trait SolveObserver: Send + Sync {
+    // A number was put in a cell by the technique, before the step it's part of is over.
+    fn on_assignment(&self, _puzzle: &Puzzle, _coord: Coord, _number: u8, _technique: Technique) {}
+
+    // The digits were removed from the candidates of the cells by the technique.
+    fn on_elimination(
+        &self,
+        _puzzle: &Puzzle,
+        _technique: Technique,
+        _cells: &[(usize, usize)],
+        _digits: &[u8],
+    ) {
+    }
+
+    // A step of the techniques is over, and recorded as the puzzle's last. Guesses are steps of their own, but told with `on_guess()` instead.
+    fn on_iteration_end(&self, _puzzle: &Puzzle, _step: &Step) {}
+
+    // Nothing else worked, so the number was tried in the cell.
+    fn on_guess(&self, _puzzle: &Puzzle, _coord: Coord, _number: u8) {}
+}
+
+/// A `SolveObserver` to set in `SolverConfig::observer`, shared by every copy of the config, including those of any guesses.
+#[derive(Clone)]
+pub struct Observer(Arc<dyn SolveObserver>);
+
+impl Observer {
+    pub fn new(observer: impl SolveObserver + 'static) -> Observer {
+        Observer(Arc::new(observer))
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observer")
+    }
+}
+
+// Configs are the same only if they share the one observer.
+impl PartialEq for Observer {
+    fn eq(&self, other: &Observer) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// Shared, so whoever set the observer can keep hold of it to see what it was told.
+impl<T: SolveObserver> SolveObserver for Arc<T> {
+    fn on_assignment(&self, puzzle: &Puzzle, coord: Coord, number: u8, technique: Technique) {
+        self.as_ref()
+            .on_assignment(puzzle, coord, number, technique)
+    }
+
+    fn on_elimination(
+        &self,
+        puzzle: &Puzzle,
+        technique: Technique,
+        cells: &[(usize, usize)],
+        digits: &[u8],
+    ) {
+        self.as_ref()
+            .on_elimination(puzzle, technique, cells, digits)
+    }
+
+    fn on_iteration_end(&self, puzzle: &Puzzle, step: &Step) {
+        self.as_ref().on_iteration_end(puzzle, step)
+    }
+
+    fn on_guess(&self, puzzle: &Puzzle, coord: Coord, number: u8) {
+        self.as_ref().on_guess(puzzle, coord, number)
+    }
+}
+
+// The workings printed to the transcript when the config is verbose: the grid after each step, also snapshot to tmp/ with the candidates going into it and what the step changed. Quiet solves touch neither stdout nor the filesystem.
+struct Workings;
+
+impl SolveObserver for Workings {
+    fn on_iteration_end(&self, puzzle: &Puzzle, step: &Step) {
+        puzzle.config.transcript.write(&format!(
+            "Step {} progressed by {:?}. Current board layout:\n{}",
+            step.iteration,
+            step.consolidations,
+            puzzle.display()
+        ));
+
+        let mut candidates = puzzle.clone();
+        candidates.grid = step.before;
+        candidates.last_consolidation = vec![];
+        // Snapshots are only ever a help, so one that can't be written, as on a read-only filesystem, is said so rather than failing the solve
+        let written = candidates
+            .write_iteration(format!("s{}-candidates", step.iteration), None)
+            .and_then(|()| {
+                puzzle.write_iteration(
+                    format!("s{}-consolidated", step.iteration),
+                    Some(&candidates),
+                )
+            });
+        if let Err(error) = written {
+            puzzle.config.say(&format!(
+                "⚠️  Couldn't snapshot step {} to tmp/: {}",
+                step.iteration, error
+            ));
+        }
+    }
+}
+
+impl Puzzle {
+    // Tell the workings, if verbose, then any observer configured.
+    fn notify(&self, tell: impl Fn(&dyn SolveObserver)) {
+        if self.config.verbose {
+            tell(&Workings);
+        }
+        if let Some(Observer(observer)) = &self.config.observer {
+            tell(observer.as_ref());
+        }
+    }
+
+    // Tell of each assignment and elimination the last step made, then that it's over.
+    pub(super) fn notify_step(&self) {
+        let step = match self.steps.last() {
+            Some(step) => step,
+            None => return,
+        };
+
+        for consolidation in &step.consolidations {
+            if let Consolidation::CandidatesEliminated {
+                technique,
+                cells,
+                digits,
+                ..
+            } = consolidation
+            {
+                self.notify(|observer| observer.on_elimination(self, *technique, cells, digits));
+            } else if let (Some(((row, col), number)), Some(technique)) =
+                (consolidation.assignment(), consolidation.technique())
+            {
+                let coord = Coord::new(row, col);
+                self.notify(|observer| observer.on_assignment(self, coord, number, technique));
+            }
+        }
+
+        self.notify(|observer| observer.on_iteration_end(self, step));
+    }
+
+    pub(super) fn notify_guess(&self, coord: Coord, number: u8) {
+        self.notify(|observer| observer.on_guess(self, coord, number));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::super::{Coord, Puzzle, PuzzleStatus, SolverConfig, Step, Technique};
+    use super::{Observer, SolveObserver};
+
+    #[derive(Default)]
+    struct Tally {
+        assignments: Mutex<usize>,
+        iterations: Mutex<usize>,
+    }
+
+    impl SolveObserver for Tally {
+        fn on_assignment(&self, puzzle: &Puzzle, coord: Coord, number: u8, _: Technique) {
+            assert_eq!(Some(number), puzzle[coord].number);
+            *self.assignments.lock().unwrap() += 1;
+        }
+
+        fn on_iteration_end(&self, _: &Puzzle, _: &Step) {
+            *self.iterations.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn observer() {
+        let tally = Arc::new(Tally::default());
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let blanks = puzzle.shape().cell_count() - puzzle.givens().len();
+        puzzle.set_config(SolverConfig {
+            observer: Some(Observer::new(tally.clone())),
+            ..SolverConfig::default()
+        });
+        puzzle.solve().unwrap();
+
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+        assert_eq!(blanks, *tally.assignments.lock().unwrap());
+        assert_eq!(
+            puzzle.solution().steps().len(),
+            *tally.iterations.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,1525 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::json::Json;
+use super::shape::digit;
+use super::{
+    symbol, Arrow, Arrows, Candidates, Cell, Clues, Coord, Edge, Edges, Grid, Line, LineKind,
+    Lines, Mark, Puzzle, Sandwiches, Shape, MAX_SIZE,
+};
+
+// Fewer givens than this and a puzzle can't have just the one solution
+const MIN_GIVENS: usize = 17;
+
+/// Everything wrong with a puzzle's input. Errors mean it isn't a puzzle at all, where warnings are about input that was read but looks off.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseReport {
+    pub errors: Vec<ParseIssue>,
+    pub warnings: Vec<ParseIssue>,
+}
+
+/// A problem with the input, and where it is: the line and column, counting from 1, or `None` for a problem with the puzzle as a whole.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseIssue {
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ParseReport {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    fn error(&mut self, location: Option<(usize, usize)>, message: String) {
+        self.errors.push(ParseIssue { location, message });
+    }
+
+    fn warning(&mut self, location: Option<(usize, usize)>, message: String) {
+        self.warnings.push(ParseIssue { location, message });
+    }
+}
+
+// One issue to a line, errors first, e.g. "error: line 3, column 9: ..."
+impl fmt::Display for ParseReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let issues = self
+            .errors
+            .iter()
+            .map(|issue| ("error", issue))
+            .chain(self.warnings.iter().map(|issue| ("warning", issue)));
+
+        for (i, (kind, issue)) in issues.enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", kind, issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "line {}, column {}: {}", line, column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Puzzle {
+    // Parse the puzzle as written in samples/, or with pencil marks as `display()` draws it. The former is 9 lines of 9 cells, digits for the givens and any of `0 . * _ ? X` for blanks, or 16 lines of 16 for a hexadoku, its digits past 9 written A thru G. Spaces, commas, or pipes may come between cells, with empty fields between commas blank as in CSV, lines of `-` between rows, and lines starting with `#` are comments, though a `# X-Sudoku` or `# diagonals` comment makes the two main diagonals units too. A jigsaw's regions follow the grid after a `# regions` comment, as a second grid with a label for each cell, e.g. `111222333`, the cells labelled alike making up a region. Sandwich sudoku's sums follow a `# sandwiches` comment, a line of them for the `rows` and another for the `columns`, e.g. `columns 10 . 35 0 . . . . 2`, with `.` where there's none. Arrow sudoku's arrows follow an `# arrows` comment, one to a line, each its circle then the cells along it, e.g. `r1c1 r1c2 r2c3`. XV sudoku's marks follow an `# xv` comment, one to a line, each its letter then the cells either side, e.g. `X r1c1 r1c2`, with a line of `negative` if no other cells side by side add up to 5 or 10. Renban and whisper lines follow a `# lines` comment, one to a line, each its kind then the cells along it, e.g. `renban r1c1 r2c2 r2c3`. The whole puzzle may be on one line of 81 cells instead, or given as a grid of pencil marks as sudokuwiki.org copies them out. Anything amiss is ignored as best it can be; see `parse_with_report()` to hear about it.
+    pub fn parse(input: &str) -> Puzzle {
+        let (shape, clues, grid, _) = read(input);
+        Puzzle {
+            clues: Arc::new(clues),
+            ..Puzzle::with_grid(shape, grid)
+        }
+    }
+
+    // Parse the puzzle, reporting every problem with the input. A puzzle is only returned if there were no errors, but may come with warnings.
+    pub fn parse_with_report(input: &str) -> Result<(Puzzle, ParseReport), ParseReport> {
+        let (shape, clues, grid, mut report) = read(input);
+        let puzzle = Puzzle {
+            clues: Arc::new(clues),
+            ..Puzzle::with_grid(shape, grid)
+        };
+
+        // Bigger grids take more givens, and a variant's clues can stand in for them, so only plain 9x9 ones are known to need this many
+        if report.errors.is_empty() && shape == Shape::STANDARD && puzzle.clues.is_empty() {
+            let givens = puzzle.givens().len();
+            if givens < MIN_GIVENS {
+                report.warning(
+                    None,
+                    format!(
+                        "Only {} givens, too few for the puzzle to have just the one solution",
+                        givens
+                    ),
+                );
+            }
+        }
+
+        if report.errors.is_empty() {
+            Ok((puzzle, report))
+        } else {
+            Err(report)
+        }
+    }
+}
+
+// The characters taken as a blank, as puzzles found around the web write them
+const BLANKS: [char; 6] = ['0', '.', '*', '_', '?', 'X'];
+
+// The grid as far as it can be made out of the input, and its shape and any variant's clues, along with everything wrong with it. Cells past the last of a row and rows past the last are left out.
+fn read(input: &str) -> (Shape, Clues, Grid, ParseReport) {
+    if input.trim_start().starts_with('{') {
+        return read_fpuzzles(input);
+    }
+
+    let (input, sections) = split_sections(input);
+    let marked = input
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| !line.starts_with('#'))
+        .any(|line| line.contains('['));
+
+    let (shape, grid, mut report) = if marked {
+        read_marked(input)
+    } else if let Some((shape, grid)) = read_pencil_grid(input) {
+        (shape, grid, ParseReport::default())
+    } else {
+        read_plain(input)
+    };
+    let mut shape = shape.with_diagonals(is_x_sudoku(input));
+    let mut clues = Clues::default();
+
+    for (header, line_number, text) in sections {
+        match header {
+            REGIONS_HEADER => match read_regions(shape, text) {
+                Ok(jigsaw) => shape = jigsaw,
+                Err(message) => report.error(Some((line_number, 1)), message),
+            },
+            SANDWICHES_HEADER => read_sandwiches(shape, &mut clues, line_number, text, &mut report),
+            ARROWS_HEADER => read_arrows(shape, &mut clues, line_number, text, &mut report),
+            XV_HEADER => read_xv(shape, &mut clues, line_number, text, &mut report),
+            _ => read_lines(shape, &mut clues, line_number, text, &mut report),
+        }
+    }
+
+    (shape, clues, grid, report)
+}
+
+// The comments starting a section after the grid, in any case: a jigsaw's region map, sandwich sudoku's sums, arrow sudoku's arrows, XV sudoku's marks, and renban and whisper lines
+const REGIONS_HEADER: &str = "regions";
+const SANDWICHES_HEADER: &str = "sandwiches";
+const ARROWS_HEADER: &str = "arrows";
+const XV_HEADER: &str = "xv";
+const LINES_HEADER: &str = "lines";
+
+// A section of the input after the grid: its header, the header's line, and the text up to the next section
+type Section<'a> = (&'static str, usize, &'a str);
+
+// The input up to the first section, such as the region map after a `# regions` comment, and each section after it.
+fn split_sections(input: &str) -> (&str, Vec<Section<'_>>) {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut grid = input;
+    let mut start = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let header = line.trim().strip_prefix('#').and_then(|comment| {
+            [
+                REGIONS_HEADER,
+                SANDWICHES_HEADER,
+                ARROWS_HEADER,
+                XV_HEADER,
+                LINES_HEADER,
+            ]
+            .into_iter()
+            .find(|header| comment.trim().eq_ignore_ascii_case(header))
+        });
+
+        if let Some(header) = header {
+            // The section before, or the grid, ends here
+            match sections.last_mut() {
+                Some((_, _, text)) => *text = &text[..text.len() - (input.len() - start)],
+                None => grid = &input[..start],
+            }
+            sections.push((header, i + 1, &input[start + line.len()..]));
+        }
+        start += line.len();
+    }
+    (grid, sections)
+}
+
+// Add the sandwich sums for the rows and columns listed to the clues, a line of each, e.g. `rows 10 . 0 35 ...` and `columns: ...`, with a `.` or `-` for those without. Sums are separated by spaces or commas, and there must be one for every row or column.
+fn read_sandwiches(
+    shape: Shape,
+    clues: &mut Clues,
+    header: usize,
+    text: &str,
+    report: &mut ParseReport,
+) {
+    let size = shape.size();
+    // What the 2 thru the second largest digit add up to
+    let most = (2..size as u16).sum::<u16>();
+    let sandwiches = &mut clues.sandwiches;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = header + i + 1;
+        let fields = fields(line);
+        let (lines, sums) = match fields.split_first() {
+            None => continue,
+            Some(((_, first), _)) if first.starts_with('#') => continue,
+            Some(((_, name), sums)) => match name.trim_end_matches(':').to_lowercase().as_str() {
+                "rows" => (&mut sandwiches.rows, sums),
+                "columns" => (&mut sandwiches.columns, sums),
+                _ => {
+                    report.error(
+                        Some((line_number, 1)),
+                        format!("Expected sums for the rows or columns, not '{}'", name),
+                    );
+                    continue;
+                }
+            },
+        };
+
+        if sums.len() != size {
+            report.error(
+                Some((line_number, 1)),
+                format!(
+                    "Expected a sum for each of the {} lines, not {}",
+                    size,
+                    sums.len()
+                ),
+            );
+            continue;
+        }
+
+        for (line_sum, &(column, field)) in lines.iter_mut().zip(sums) {
+            *line_sum = match field {
+                "." | "-" => None,
+                _ => match field.parse::<u16>() {
+                    Ok(sum) if sum <= most => Some(sum),
+                    Ok(sum) => {
+                        report.error(
+                            Some((line_number, column)),
+                            format!("A sandwich adds up to at most {}, not {}", most, sum),
+                        );
+                        None
+                    }
+                    Err(_) => {
+                        report.error(
+                            Some((line_number, column)),
+                            format!("'{}' is neither a sum nor a blank (. -)", field),
+                        );
+                        None
+                    }
+                },
+            };
+        }
+    }
+}
+
+// Add the arrows listed to the clues, one to a line, each its circle then the cells along it in order, e.g. `r1c1 r1c2 r2c3`. Cells are separated by spaces or commas, and named as in sudoku books.
+fn read_arrows(
+    shape: Shape,
+    clues: &mut Clues,
+    header: usize,
+    text: &str,
+    report: &mut ParseReport,
+) {
+    for (i, line) in text.lines().enumerate() {
+        let line_number = header + i + 1;
+        let fields = fields(line);
+        if fields
+            .first()
+            .is_none_or(|(_, first)| first.starts_with('#'))
+        {
+            continue;
+        }
+
+        let mut cells: Vec<Coord> = Vec::new();
+        for &(column, field) in &fields {
+            match shape.parse_cell(field) {
+                Ok(coord) => cells.push(coord),
+                Err(message) => report.error(Some((line_number, column)), message),
+            }
+        }
+        if cells.len() < fields.len() {
+            continue;
+        }
+
+        let added = Arrow::new(cells[0], &cells[1..]).map(|arrow| clues.arrows.push(arrow));
+        if let Err(message) = added {
+            report.error(Some((line_number, 1)), message);
+        }
+    }
+}
+
+// Add the X and V marks listed to the clues, one to a line, each its letter then the two cells side by side it sits between, e.g. `X r1c1 r1c2`. A line of `negative` says they're all the marks there are, so no other cells side by side add up to 5 or 10.
+fn read_xv(shape: Shape, clues: &mut Clues, header: usize, text: &str, report: &mut ParseReport) {
+    let xv = &mut clues.xv;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = header + i + 1;
+        let fields = fields(line);
+        let (mark, cells) = match fields.split_first() {
+            None => continue,
+            Some(((_, first), _)) if first.starts_with('#') => continue,
+            Some(((_, first), [])) if first.eq_ignore_ascii_case("negative") => {
+                *xv = std::mem::take(xv).with_negative(true);
+                continue;
+            }
+            Some(((column, mark), cells)) => match Mark::parse(mark) {
+                Ok(mark) => (mark, cells),
+                Err(message) => {
+                    report.error(Some((line_number, *column)), message);
+                    continue;
+                }
+            },
+        };
+
+        let mut coords: Vec<Coord> = Vec::new();
+        for &(column, field) in cells {
+            match shape.parse_cell(field) {
+                Ok(coord) => coords.push(coord),
+                Err(message) => report.error(Some((line_number, column)), message),
+            }
+        }
+        if coords.len() < cells.len() {
+            continue;
+        }
+
+        let added = match coords[..] {
+            [a, b] => Edge::new(a, b, mark).and_then(|edge| xv.push(edge)),
+            _ => Err(format!(
+                "{} marks go between two cells, not {}",
+                mark.letter(),
+                coords.len()
+            )),
+        };
+        if let Err(message) = added {
+            report.error(Some((line_number, 1)), message);
+        }
+    }
+}
+
+// Add the renban and whisper lines listed to the clues, one to a line, each its kind then the cells along it in order, e.g. `whisper r1c1 r2c2 r2c3`.
+fn read_lines(
+    shape: Shape,
+    clues: &mut Clues,
+    header: usize,
+    text: &str,
+    report: &mut ParseReport,
+) {
+    for (i, line) in text.lines().enumerate() {
+        let line_number = header + i + 1;
+        let fields = fields(line);
+        let (kind, cells) = match fields.split_first() {
+            None => continue,
+            Some(((_, first), _)) if first.starts_with('#') => continue,
+            Some(((column, kind), cells)) => match LineKind::parse(kind) {
+                Ok(kind) => (kind, cells),
+                Err(message) => {
+                    report.error(Some((line_number, *column)), message);
+                    continue;
+                }
+            },
+        };
+
+        let mut coords: Vec<Coord> = Vec::new();
+        for &(column, field) in cells {
+            match shape.parse_cell(field) {
+                Ok(coord) => coords.push(coord),
+                Err(message) => report.error(Some((line_number, column)), message),
+            }
+        }
+        if coords.len() < cells.len() {
+            continue;
+        }
+
+        let added = Line::new(kind, &coords).map(|line| clues.lines.push(line));
+        if let Err(message) = added {
+            report.error(Some((line_number, 1)), message);
+        }
+    }
+}
+
+// Each field of a line and the column it starts in, the fields separated by spaces or commas.
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields: Vec<(usize, &str)> = Vec::new();
+    let mut start = None;
+    for (at, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        if c.is_whitespace() || c == ',' {
+            if let Some(from) = start.take() {
+                fields.push((line[..from].chars().count() + 1, &line[from..at]));
+            }
+        } else if start.is_none() {
+            start = Some(at);
+        }
+    }
+    fields
+}
+
+// The shape with the regions the map labels, read as the grid's cells are: a line to a row or all on the one, skipping spaces, commas, pipes, rules, and comments. Any character but those can be a label.
+fn read_regions(shape: Shape, map: &str) -> Result<Shape, String> {
+    let regions: Vec<usize> = map
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !is_rule(line))
+        .flat_map(str::chars)
+        .filter(|&c| !c.is_whitespace() && c != '|' && c != ',')
+        .map(|c| c as usize)
+        .collect();
+    shape.with_regions(&regions)
+}
+
+// The comments that make a puzzle X-Sudoku, its diagonals units too, in any case
+const X_SUDOKU_HEADERS: [&str; 3] = ["x-sudoku", "x sudoku", "diagonals"];
+
+// Whether a comment says the puzzle's X-Sudoku, e.g. `# X-Sudoku` above the grid.
+fn is_x_sudoku(input: &str) -> bool {
+    input
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('#'))
+        .any(|comment| X_SUDOKU_HEADERS.contains(&comment.trim().to_lowercase().as_str()))
+}
+
+// Whether the line is a rule between rows, e.g. `|---+---+---|` or `.-------.-------.`
+fn is_rule(line: &str) -> bool {
+    line.contains('-') && line.chars().all(|c| "-+*|.:'".contains(c))
+}
+
+// The shape of a grid of this many lines whose first has this many cells: as many rows as cells to a row, or the whole puzzle on the one line. A 9x9 grid otherwise, for the errors to say what's wrong with one.
+fn shape_of(cells: usize, lines: usize) -> Shape {
+    let shape = if lines == 1 {
+        Shape::of_cell_count(cells)
+    } else if cells == lines {
+        Shape::of_size(cells).ok()
+    } else {
+        None
+    };
+    shape.unwrap_or(Shape::STANDARD)
+}
+
+// A line of 81 cells is taken as the whole puzzle, as is one of 256 for a 16x16 grid or any other grid's worth. Blank lines and comments (lines starting with `#`) are skipped, as are spaces, commas, and pipes between cells, though an empty field between commas is a blank. So are rules such as `|---+---+---|`, making Simple Sudoku's .ss files and the grid as `display()` draws it without candidates readable too. The first row tells the grid's shape.
+fn read_plain(input: &str) -> (Shape, Grid, ParseReport) {
+    let mut report = ParseReport::default();
+
+    // Each line of cells, each cell along with its column
+    let mut lines: Vec<(usize, Vec<(usize, char)>)> = Vec::new();
+    let mut last_line = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || is_rule(trimmed) {
+            continue;
+        }
+        last_line = line_number;
+
+        // Where there are commas, as in a spreadsheet's CSV, a field left empty is a blank.
+        let mut cells: Vec<(usize, char)> = Vec::new();
+        let mut start = 0;
+        for field in line.split(',') {
+            let before = cells.len();
+            cells.extend(
+                field
+                    .chars()
+                    .enumerate()
+                    .filter(|&(_, c)| !c.is_whitespace() && c != '|')
+                    .map(|(i, c)| (start + i + 1, c)),
+            );
+            if cells.len() == before && line.contains(',') {
+                cells.push((start + 1, '.'));
+            }
+            start += field.chars().count() + 1;
+        }
+        lines.push((line_number, cells));
+    }
+
+    let shape = shape_of(
+        lines.first().map_or(0, |(_, cells)| cells.len()),
+        lines.len(),
+    );
+    let size = shape.size();
+    let mut grid = [[blank(Candidates::none()); MAX_SIZE]; MAX_SIZE];
+    let mut rows = 0;
+    for (line_number, cells) in lines {
+        // The whole puzzle on one line, as sudokuwiki.org and Sudoku Exchange share them
+        let line_rows: Vec<&[(usize, char)]> = if cells.len() == shape.cell_count() {
+            cells.chunks(size).collect()
+        } else {
+            vec![&cells]
+        };
+
+        for cells in line_rows {
+            let row = rows;
+            rows += 1;
+            if row >= size {
+                report.error(
+                    Some((line_number, cells.first().map_or(1, |&(column, _)| column))),
+                    format!(
+                        "Row {} is past the last. Puzzles have {} rows",
+                        row + 1,
+                        size
+                    ),
+                );
+                continue;
+            }
+
+            if cells.len() != size {
+                let column = match cells.get(size) {
+                    Some(&(column, _)) => column,
+                    None => cells.last().map_or(1, |&(column, _)| column + 1),
+                };
+                report.error(
+                    Some((line_number, column)),
+                    format!(
+                        "Row {} has {} cells rather than {}",
+                        row + 1,
+                        cells.len(),
+                        size
+                    ),
+                );
+            }
+
+            for (col, &(column, c)) in cells.iter().enumerate().take(size) {
+                match digit(c, size) {
+                    Some(number) => grid[row][col] = given(number),
+                    None if BLANKS.contains(&c) => {}
+                    None => report.error(
+                        Some((line_number, column)),
+                        format!("'{}' is neither a digit nor a blank (0 . * _ ? X)", c),
+                    ),
+                }
+            }
+        }
+    }
+
+    if rows < size {
+        report.error(
+            Some((last_line + 1, 1)),
+            format!("Only {} of the {} rows", rows, size),
+        );
+    }
+
+    (shape, grid, report)
+}
+
+// A row of the grid with pencil marks: its line, the line's length, and each cell along with its column
+type MarkedRow = (usize, usize, Vec<(usize, Cell)>);
+
+// The grid with pencil marks: each cell a number or its candidates in brackets, e.g. `4 [36] 1 | [37] 5 2 | ...`, as `display()` draws it and so as the snapshots in tmp/ are written. Lines starting with anything but a pipe, a digit, or a bracket, such as the rules between blocks and anything after the grid, are skipped. Numbers placed aren't told apart from givens, so every number is taken as given. The first row tells the grid's shape.
+fn read_marked(input: &str) -> (Shape, Grid, ParseReport) {
+    let mut report = ParseReport::default();
+
+    let mut lines: Vec<MarkedRow> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let is_row = line
+            .trim_start()
+            .starts_with(|c: char| c == '|' || c == '[' || c.is_ascii_digit());
+        if !is_row {
+            continue;
+        }
+
+        let mut cells: Vec<(usize, Cell)> = Vec::new();
+        let mut chars = line.chars().enumerate();
+        while let Some((i, c)) = chars.next() {
+            let column = i + 1;
+            match c {
+                '[' => {
+                    let mut candidates = Candidates::none();
+                    let mut closed = false;
+                    for (j, c) in chars.by_ref() {
+                        match (c, digit(c, MAX_SIZE)) {
+                            (']', _) => {
+                                closed = true;
+                                break;
+                            }
+                            (_, Some(candidate)) => candidates.insert(candidate),
+                            (',', _) => {}
+                            _ => report.error(
+                                Some((line_number, j + 1)),
+                                format!("'{}' is neither a candidate nor a comma", c),
+                            ),
+                        }
+                    }
+                    if !closed {
+                        report.error(
+                            Some((line_number, column)),
+                            "Candidates are missing their closing ]".to_string(),
+                        );
+                    }
+                    cells.push((column, blank(candidates)));
+                }
+                '|' | ',' => {}
+                c if c.is_whitespace() => {}
+                c => match digit(c, MAX_SIZE) {
+                    Some(number) => cells.push((column, given(number))),
+                    None => report.error(
+                        Some((line_number, column)),
+                        format!("'{}' is neither a number nor candidates in brackets", c),
+                    ),
+                },
+            }
+        }
+        lines.push((line_number, line.chars().count(), cells));
+    }
+
+    let shape = shape_of(
+        lines.first().map_or(0, |(_, _, cells)| cells.len()),
+        lines.len(),
+    );
+    let size = shape.size();
+    let mut grid = [[blank(Candidates::none()); MAX_SIZE]; MAX_SIZE];
+    let last_line = lines.last().map_or(0, |&(line_number, _, _)| line_number);
+    for (row, (line_number, length, cells)) in lines.iter().enumerate() {
+        if row >= size {
+            report.error(
+                Some((*line_number, 1)),
+                format!(
+                    "Row {} is past the last. Puzzles have {} rows",
+                    row + 1,
+                    size
+                ),
+            );
+            continue;
+        }
+
+        if cells.len() != size {
+            let column = match cells.get(size) {
+                Some(&(column, _)) => column,
+                None => length + 1,
+            };
+            report.error(
+                Some((*line_number, column)),
+                format!(
+                    "Row {} has {} cells rather than {}",
+                    row + 1,
+                    cells.len(),
+                    size
+                ),
+            );
+        }
+        for (col, &(column, cell)) in cells.iter().enumerate().take(size) {
+            if cell
+                .candidates
+                .iter()
+                .chain(cell.number)
+                .any(|d| d as usize > size)
+            {
+                report.error(
+                    Some((*line_number, column)),
+                    format!(
+                        "Digits of a {} grid go no higher than {}",
+                        shape.name(),
+                        symbol(size as u8)
+                    ),
+                );
+            }
+            grid[row][col] = cell;
+        }
+    }
+
+    if lines.len() < size {
+        report.error(
+            Some((last_line + 1, 1)),
+            format!("Only {} of the {} rows", lines.len(), size),
+        );
+    }
+
+    (shape, grid, report)
+}
+
+// The grid of pencil marks sudokuwiki.org, Sudoku Exchange, and HoDoKu copy out: 81 groups of digits between spaces or pipes, or as many as the cells of another shape, a lone digit for a number and several for a cell's candidates, with rules between the blocks. `None` unless the input is just that and has a cell with candidates, as otherwise it reads as well as any other grid. Every number is taken as given.
+fn read_pencil_grid(input: &str) -> Option<(Shape, Grid)> {
+    let groups: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !is_rule(line))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == '|'))
+        .filter(|group| !group.is_empty())
+        .collect();
+
+    let shape = Shape::of_cell_count(groups.len())?;
+    let digits = |group: &str| -> Vec<Option<u8>> {
+        group.chars().map(|c| digit(c, shape.size())).collect()
+    };
+    if !groups.iter().all(|g| digits(g).iter().all(Option::is_some))
+        || groups.iter().all(|g| g.chars().count() == 1)
+    {
+        return None;
+    }
+
+    let mut grid = [[blank(Candidates::none()); MAX_SIZE]; MAX_SIZE];
+    for (coord, group) in shape.coords().zip(groups) {
+        let mut digits = digits(group).into_iter().flatten();
+        grid[coord.row][coord.col] = match group.chars().count() {
+            1 => given(digits.next().unwrap()),
+            _ => blank(digits.collect()),
+        };
+    }
+    Some((shape, grid))
+}
+
+// Entries of an f-puzzles file that aren't constraints
+const FPUZZLES_SETTINGS: [&str; 8] = [
+    "size",
+    "grid",
+    "title",
+    "author",
+    "ruleset",
+    "solution",
+    "disabledlogic",
+    "truecandidatesoptions",
+];
+
+// The JSON f-puzzles.com saves, as shared in links to hand-made puzzles: a `size` and a `grid` of rows of cells, each with its `value` if any, `given` set for the givens, and `centerPencilMarks` for its candidates. Values without `given` are numbers placed, and read as such. Both `diagonal+` and `diagonal-` set make it X-Sudoku, and cells' `region`s, counting from 0, make it a jigsaw, those without one left in their block. A `sandwichsum` is a sandwich sudoku's sum, its `cell` just outside the grid, `R0C4` above the fourth column or `R2C0` left of the second row. An `arrow` has its circle's one cell as its `cells`, and `lines` of cells out of it, each an arrow of its own. An `xv` mark has the two `cells` it's between and its letter as its `value`, and `xv` among the `negative` constraints makes them all there are. A `renban` or `whispers` clue has `lines` of cells, each a line of its own. Other constraints can't be kept yet, so each kind is warned of and left out, as is a lone diagonal.
+fn read_fpuzzles(input: &str) -> (Shape, Clues, Grid, ParseReport) {
+    let mut shape = Shape::STANDARD;
+    let mut grid = [[blank(Candidates::none()); MAX_SIZE]; MAX_SIZE];
+    let mut report = ParseReport::default();
+
+    let json = match Json::parse(input) {
+        Ok(json) => json,
+        Err((location, message)) => {
+            report.error(Some(location), message);
+            return (shape, Clues::default(), grid, report);
+        }
+    };
+
+    match json.get("size").and_then(Json::as_f64) {
+        Some(size) if size.fract() != 0.0 || size < 0.0 => {
+            report.error(
+                None,
+                format!("The size should be a whole number, not {}", size),
+            );
+            return (shape, Clues::default(), grid, report);
+        }
+        Some(size) => match Shape::of_size(size as usize) {
+            Ok(sized) => shape = sized,
+            Err(message) => {
+                report.error(None, message);
+                return (shape, Clues::default(), grid, report);
+            }
+        },
+        None => report.error(None, "No size given for the grid".to_string()),
+    }
+
+    let size = shape.size();
+    let mut regions: Vec<usize> = shape.coords().map(|coord| shape.block_of(coord)).collect();
+    let mut jigsaw = false;
+    let rows = json.get("grid").and_then(Json::as_array).unwrap_or(&[]);
+    if rows.len() != size {
+        report.error(
+            None,
+            format!("The grid has {} rows rather than {}", rows.len(), size),
+        );
+    }
+    for (row, cells) in rows.iter().enumerate().take(size) {
+        let cells = cells.as_array().unwrap_or(&[]);
+        if cells.len() != size {
+            report.error(
+                None,
+                format!(
+                    "Row {} has {} cells rather than {}",
+                    row + 1,
+                    cells.len(),
+                    size
+                ),
+            );
+        }
+
+        for (col, cell) in cells.iter().enumerate().take(size) {
+            let digit = |value: &Json| {
+                value
+                    .as_f64()
+                    .filter(|&d| d.fract() == 0.0 && (1.0..=size as f64).contains(&d))
+                    .map(|d| d as u8)
+            };
+
+            if let Some(region) = cell.get("region").and_then(Json::as_f64) {
+                regions[row * size + col] = region as usize;
+                jigsaw = true;
+            }
+
+            grid[row][col] = match cell.get("value") {
+                Some(value) => match digit(value) {
+                    Some(number) => Cell {
+                        number: Some(number),
+                        given: cell.get("given").and_then(Json::as_bool) == Some(true),
+                        candidates: Candidates::none(),
+                    },
+                    None => {
+                        report.error(
+                            None,
+                            format!("{} holds a value other than a digit", Coord::new(row, col)),
+                        );
+                        continue;
+                    }
+                },
+                None => {
+                    let marks = cell.get("centerPencilMarks").and_then(Json::as_array);
+                    blank(marks.unwrap_or(&[]).iter().filter_map(digit).collect())
+                }
+            };
+        }
+    }
+
+    let diagonals = ["diagonal+", "diagonal-"]
+        .iter()
+        .all(|&key| json.get(key).and_then(Json::as_bool) == Some(true));
+    shape = shape.with_diagonals(diagonals);
+
+    if jigsaw {
+        match shape.with_regions(&regions) {
+            Ok(regioned) => shape = regioned,
+            Err(message) => report.error(None, message),
+        }
+    }
+
+    let sums = json
+        .get("sandwichsum")
+        .and_then(Json::as_array)
+        .unwrap_or(&[]);
+    let mut sandwiches = Sandwiches::NONE;
+    for clue in sums {
+        let cell = clue.get("cell").and_then(Json::as_str).unwrap_or("");
+        let place = cell
+            .strip_prefix('R')
+            .and_then(|rest| rest.split_once('C'))
+            .and_then(|(row, col)| Some((row.parse::<usize>().ok()?, col.parse::<usize>().ok()?)));
+        let sum = match clue.get("value") {
+            Some(Json::String(value)) => value.parse::<u16>().ok(),
+            Some(value) => value
+                .as_f64()
+                .filter(|&v| v.fract() == 0.0 && v >= 0.0)
+                .map(|v| v as u16),
+            None => None,
+        };
+
+        match (place, sum) {
+            (Some((0, col)), Some(sum)) if (1..=size).contains(&col) => {
+                sandwiches.columns[col - 1] = Some(sum)
+            }
+            (Some((row, 0)), Some(sum)) if (1..=size).contains(&row) => {
+                sandwiches.rows[row - 1] = Some(sum)
+            }
+            _ => report.error(
+                None,
+                format!(
+                    "The sandwich sum at '{}' isn't a sum outside the grid",
+                    cell
+                ),
+            ),
+        }
+    }
+
+    let clues = json.get("arrow").and_then(Json::as_array).unwrap_or(&[]);
+    let mut arrows = Arrows::NONE;
+    for clue in clues {
+        let names = |key: &str| -> Vec<&str> {
+            clue.get(key)
+                .and_then(Json::as_array)
+                .unwrap_or(&[])
+                .iter()
+                .filter_map(Json::as_str)
+                .collect()
+        };
+        let added = match names("cells")[..] {
+            [bulb] => shape.parse_cell(bulb).and_then(|circle| {
+                let lines = clue.get("lines").and_then(Json::as_array).unwrap_or(&[]);
+                for line in lines {
+                    let cells: Vec<&str> = line
+                        .as_array()
+                        .unwrap_or(&[])
+                        .iter()
+                        .filter_map(Json::as_str)
+                        .collect();
+                    // Lines start in the circle
+                    let path = cells
+                        .iter()
+                        .map(|name| shape.parse_cell(name))
+                        .filter(|cell| cell.as_ref() != Ok(&circle))
+                        .collect::<Result<Vec<Coord>, String>>()?;
+                    arrows.push(Arrow::new(circle, &path)?);
+                }
+                Ok(())
+            }),
+            ref bulb => Err(format!(
+                "Arrows need a circle of one cell, not {}",
+                bulb.len()
+            )),
+        };
+        if let Err(message) = added {
+            report.error(None, message);
+        }
+    }
+
+    let clues = json.get("xv").and_then(Json::as_array).unwrap_or(&[]);
+    let mut xv = Edges::NONE;
+    for clue in clues {
+        let cells: Vec<&str> = clue
+            .get("cells")
+            .and_then(Json::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(Json::as_str)
+            .collect();
+        let mark = clue.get("value").and_then(Json::as_str).unwrap_or("");
+        let added = match cells[..] {
+            [a, b] => Mark::parse(mark).and_then(|mark| {
+                xv.push(Edge::new(shape.parse_cell(a)?, shape.parse_cell(b)?, mark)?)
+            }),
+            _ => Err(format!(
+                "XV marks go between two cells, not {}",
+                cells.len()
+            )),
+        };
+        if let Err(message) = added {
+            report.error(None, message);
+        }
+    }
+    // The negative constraint is listed by the kind of mark it's for
+    let negative: Vec<&str> = json
+        .get("negative")
+        .and_then(Json::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(Json::as_str)
+        .collect();
+    let xv = xv.with_negative(negative.contains(&"xv"));
+
+    let mut lines = Lines::NONE;
+    for (key, kind) in [
+        ("renban", LineKind::Renban),
+        ("whispers", LineKind::Whisper),
+    ] {
+        let clues = json.get(key).and_then(Json::as_array).unwrap_or(&[]);
+        for clue in clues.iter().flat_map(|clue| {
+            clue.get("lines")
+                .and_then(Json::as_array)
+                .unwrap_or(&[])
+                .iter()
+        }) {
+            let added = clue
+                .as_array()
+                .unwrap_or(&[])
+                .iter()
+                .filter_map(Json::as_str)
+                .map(|name| shape.parse_cell(name))
+                .collect::<Result<Vec<Coord>, String>>()
+                .and_then(|cells| Line::new(kind, &cells))
+                .map(|line| lines.push(line));
+            if let Err(message) = added {
+                report.error(None, message);
+            }
+        }
+    }
+
+    if let Json::Object(entries) = &json {
+        for (key, value) in entries {
+            let constraint = match value {
+                Json::Array(items) => !items.is_empty(),
+                Json::Bool(set) => *set,
+                _ => false,
+            };
+            let kept = (diagonals && key.starts_with("diagonal"))
+                || key == "sandwichsum"
+                || key == "arrow"
+                || key == "xv"
+                || key == "renban"
+                || key == "whispers"
+                || (key == "negative" && negative.iter().all(|&kind| kind == "xv"));
+            if constraint && !kept && !FPUZZLES_SETTINGS.contains(&key.as_str()) {
+                report.warning(
+                    None,
+                    format!("The '{}' constraint isn't supported, so was left out", key),
+                );
+            }
+        }
+    }
+
+    let clues = Clues {
+        sandwiches,
+        arrows,
+        xv,
+        lines,
+    };
+    (shape, clues, grid, report)
+}
+
+fn given(number: u8) -> Cell {
+    Cell {
+        number: Some(number),
+        given: true,
+        candidates: Candidates::none(),
+    }
+}
+
+fn blank(candidates: Candidates) -> Cell {
+    Cell {
+        number: None,
+        given: false,
+        candidates,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Coord, LineKind, Mark, Puzzle, Shape};
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn clean() {
+        let (puzzle, report) = Puzzle::parse_with_report(EASY).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+    }
+
+    #[test]
+    fn errors() {
+        // A 10th row, a short row, and a stray letter
+        let input = format!("{}\n.........\n", EASY.replacen("76....1.2", "76...1.2", 1)).replacen(
+            "9...18.64",
+            "9..x18.64",
+            1,
+        );
+        let report = Puzzle::parse_with_report(&input).unwrap_err();
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "line 2, column 9: Row 2 has 8 cells rather than 9",
+                "line 3, column 4: 'x' is neither a digit nor a blank (0 . * _ ? X)",
+                "line 10, column 1: Row 10 is past the last. Puzzles have 9 rows",
+            ],
+            messages
+        );
+
+        // The lenient parse makes what it can of it
+        assert_eq!(Some(2), Puzzle::parse(&input).grid[1][7].number);
+
+        let report = Puzzle::parse_with_report("  123456789\n\n").unwrap_err();
+        assert_eq!(
+            "error: line 2, column 1: Only 1 of the 9 rows",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn flexible() {
+        let input = format!(
+            "# Copied from the web\n\n{}",
+            EASY.lines()
+                .map(|line| {
+                    let cells: Vec<String> = line.chars().map(|c| c.to_string()).collect();
+                    cells.join(", ")
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+                .replacen('.', "0", 3)
+                .replacen('.', "*", 3)
+                .replacen('.', "?", 3)
+        );
+        let (puzzle, report) = Puzzle::parse_with_report(&input).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+        assert!(!puzzle.shape().has_diagonals());
+
+        // A comment can say it's X-Sudoku
+        for header in ["# X-Sudoku", "#diagonals", "  # x sudoku "] {
+            let puzzle = Puzzle::parse(&format!("{}\n{}", header, EASY));
+            assert!(puzzle.shape().has_diagonals(), "{}", header);
+        }
+    }
+
+    #[test]
+    fn regions() {
+        let grid = "1...\n....\n....\n...4\n";
+        let (puzzle, report) =
+            Puzzle::parse_with_report(&format!("{}\n# Regions\n1112\n1222\n3334\n3444\n", grid))
+                .unwrap();
+        assert!(report.is_clean());
+        assert!(puzzle.shape().is_jigsaw());
+        assert_eq!(1, puzzle.shape().block_of(Coord::new(1, 1)));
+        assert_eq!(Some(1), puzzle.grid[0][0].number);
+
+        // All on one line, with any labels, and X-Sudoku too
+        let puzzle = Puzzle::parse(&format!(
+            "# X-Sudoku\n{}# regions\naaab abbb cccd cddd",
+            grid
+        ));
+        assert_eq!(
+            Shape::MINI.with_regions(&[0, 0, 0, 1, 0, 1, 1, 1, 2, 2, 2, 3, 2, 3, 3, 3]),
+            Ok(puzzle.shape().with_diagonals(false))
+        );
+        assert!(puzzle.shape().has_diagonals());
+
+        let report =
+            Puzzle::parse_with_report(&format!("{}# regions\n1212\n1212\n3434\n3434", grid))
+                .unwrap_err();
+        assert_eq!(
+            "error: line 5, column 1: The region holding r1c1 isn't all in one piece",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn sandwiches() {
+        let input = format!(
+            "{}\n# Sandwiches\nrows: 0 . . . 35 . . . 2\ncolumns 10, -, -, -, -, -, -, -, 7\n",
+            EASY
+        );
+        let (puzzle, report) = Puzzle::parse_with_report(&input).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+        let sandwiches = puzzle.sandwiches();
+        assert_eq!([Some(0), None, None, None, Some(35)], sandwiches.rows[..5]);
+        assert_eq!(Some(2), sandwiches.rows[8]);
+        assert_eq!([Some(10), None], sandwiches.columns[..2]);
+        assert_eq!(Some(7), sandwiches.columns[8]);
+
+        // Alongside a jigsaw's regions, in either order
+        let mini = "1...\n....\n....\n...4\n";
+        let puzzle = Puzzle::parse(&format!(
+            "{}# sandwiches\nrows 2 . . 0\n# regions\n1112\n1222\n3334\n3444\n",
+            mini
+        ));
+        assert!(puzzle.shape().is_jigsaw());
+        assert_eq!(Some(2), puzzle.sandwiches().rows[0]);
+        assert_eq!(Some(1), puzzle.grid[0][0].number);
+
+        let report = Puzzle::parse_with_report(&format!(
+            "{}# sandwiches\nrows 2 . 0\ncolumns . 6 x .\ndiagonals 1 2 3 4",
+            mini
+        ))
+        .unwrap_err();
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "line 6, column 1: Expected a sum for each of the 4 lines, not 3",
+                "line 7, column 11: A sandwich adds up to at most 5, not 6",
+                "line 7, column 13: 'x' is neither a sum nor a blank (. -)",
+                "line 8, column 1: Expected sums for the rows or columns, not 'diagonals'",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn xv() {
+        let input = format!(
+            "{}\n# XV\nX r1c1 r1c2\n# a comment\n\nv, r5c5, r4c5\nNegative\n",
+            EASY
+        );
+        let (puzzle, report) = Puzzle::parse_with_report(&input).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+        let xv = puzzle.xv();
+        let marks: Vec<([Coord; 2], Mark)> = xv.iter().map(|e| (e.cells(), e.mark())).collect();
+        assert_eq!(
+            vec![
+                ([Coord::new(0, 0), Coord::new(0, 1)], Mark::X),
+                ([Coord::new(3, 4), Coord::new(4, 4)], Mark::V)
+            ],
+            marks
+        );
+        assert!(xv.is_negative());
+
+        let mini = "1...\n....\n....\n...4\n";
+        let report = Puzzle::parse_with_report(&format!(
+            "{}# xv\nX r1c2 r5c1\nV r1c1 r2c2\nW r1c1 r1c2\nX r1c1\nV r1c1 r1c2\nX r1c2 r1c1",
+            mini
+        ))
+        .unwrap_err();
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "line 6, column 8: Expected a cell such as r4c7 or b5p3, not 'r5c1'",
+                "line 7, column 1: V marks go between cells side by side, not r1c1 and r2c2",
+                "line 8, column 1: Expected an X or V, not 'W'",
+                "line 9, column 1: X marks go between two cells, not 1",
+                "line 11, column 1: r1c1 and r1c2 already have a mark between them",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let input = format!(
+            "{}\n# Lines\nrenban r1c1 r2c2 r2c3\n# a comment\n\nWhisper, r9c9, r9c8\n",
+            EASY
+        );
+        let (puzzle, report) = Puzzle::parse_with_report(&input).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+        let lines: Vec<(LineKind, Vec<Coord>)> = puzzle
+            .lines()
+            .iter()
+            .map(|line| (line.kind(), line.cells()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    LineKind::Renban,
+                    vec![Coord::new(0, 0), Coord::new(1, 1), Coord::new(1, 2)]
+                ),
+                (LineKind::Whisper, vec![Coord::new(8, 8), Coord::new(8, 7)])
+            ],
+            lines
+        );
+
+        let mini = "1...\n....\n....\n...4\n";
+        let report = Puzzle::parse_with_report(&format!(
+            "{}# lines\nrenban r1c1 r5c1\nthermo r1c1 r1c2\nwhisper r1c1\nrenban r1c1 r1c3\nrenban r1c1 r1c2 r1c1",
+            mini
+        ))
+        .unwrap_err();
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "line 6, column 13: Expected a cell such as r4c7 or b5p3, not 'r5c1'",
+                "line 7, column 1: Expected a renban or whisper line, not 'thermo'",
+                "line 8, column 1: A whisper line runs through two cells or more, not 1",
+                "line 9, column 1: The renban line from r1c1 jumps from r1c1 to r1c3",
+                "line 10, column 1: The renban line from r1c1 crosses r1c1 twice",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn arrows() {
+        let input = format!(
+            "{}\n# Arrows\nr1c1 r1c2 r2c3\n# a comment\n\nb5p5, r4c4\n",
+            EASY
+        );
+        let (puzzle, report) = Puzzle::parse_with_report(&input).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(Puzzle::parse(EASY).grid, puzzle.grid);
+        let arrows: Vec<Vec<Coord>> = puzzle.arrows().iter().map(|a| a.cells()).collect();
+        assert_eq!(
+            vec![
+                vec![Coord::new(0, 0), Coord::new(0, 1), Coord::new(1, 2)],
+                vec![Coord::new(4, 4), Coord::new(3, 3)]
+            ],
+            arrows
+        );
+
+        let mini = "1...\n....\n....\n...4\n";
+        let report = Puzzle::parse_with_report(&format!(
+            "{}# arrows\nr1c2 r5c1\nr2c2\nr1c1 r1c2 r1c1",
+            mini
+        ))
+        .unwrap_err();
+        let messages: Vec<String> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "line 6, column 6: Expected a cell such as r4c7 or b5p3, not 'r5c1'",
+                "line 7, column 1: The arrow from r2c2 has no cells along it",
+                "line 8, column 1: The arrow from r1c1 crosses r1c1 twice",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.step();
+        puzzle.step();
+
+        // As a snapshot in tmp/ is written
+        let snapshot = format!(
+            "{}\n\nLast consolidation: {:?}",
+            puzzle.display(),
+            puzzle.last_consolidation
+        );
+        let (parsed, report) = Puzzle::parse_with_report(&snapshot).unwrap();
+        assert!(report.is_clean());
+        for coord in puzzle.shape.coords() {
+            assert_eq!(puzzle[coord].number(), parsed[coord].number());
+            assert_eq!(puzzle[coord].candidates(), parsed[coord].candidates());
+        }
+        assert_eq!(puzzle.display(), parsed.display());
+
+        // Solving picks up where it left off
+        let mut solved = puzzle.clone();
+        solved.solve().unwrap();
+        let mut resumed = parsed;
+        resumed.solve().unwrap();
+        assert_eq!(solved.to_string(), resumed.to_string());
+
+        let report = Puzzle::parse_with_report(&snapshot.replacen("[", "[x", 1)).unwrap_err();
+        assert_eq!(
+            "'x' is neither a candidate nor a comma",
+            report.errors[0].message
+        );
+    }
+
+    #[test]
+    fn exchange_strings() {
+        let puzzle = Puzzle::parse(EASY);
+
+        // As sudokuwiki.org's and Sudoku Exchange's puzzle strings, or the engine's `position`
+        let line: String = EASY.lines().collect();
+        let (parsed, report) = Puzzle::parse_with_report(&line).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(puzzle.grid, parsed.grid);
+        let (parsed, _) = Puzzle::parse_with_report(&line.replace('.', "0")).unwrap();
+        assert_eq!(puzzle.grid, parsed.grid);
+
+        // With candidates, as the sites copy out the grid of pencil marks
+        let mut marked = puzzle.clone();
+        marked.assign_candidates();
+        let mut grid = String::from(".-------------.-------------.\n");
+        for row in 0..9 {
+            for col in 0..9 {
+                let cell = &marked.grid[row][col];
+                let group: String = match cell.number {
+                    Some(number) => number.to_string(),
+                    None => cell.candidates.iter().map(|d| d.to_string()).collect(),
+                };
+                grid.push_str(&format!("{:<10}", group));
+                if col % 3 == 2 {
+                    grid.push_str("| ");
+                }
+            }
+            grid.push('\n');
+            if row % 3 == 2 {
+                grid.push_str(":------+------:------+------:\n");
+            }
+        }
+        let (parsed, report) = Puzzle::parse_with_report(&grid).unwrap();
+        assert!(report.is_clean());
+
+        // A cell down to one candidate can't be told from a number
+        for cell in marked.grid.iter_mut().flatten() {
+            if let Some(digit) = cell.candidates.single() {
+                *cell = super::given(digit);
+            }
+        }
+        assert_eq!(marked.grid, parsed.grid);
+    }
+
+    #[test]
+    fn fpuzzles() {
+        let puzzle = Puzzle::parse(EASY);
+        let rows: Vec<String> = puzzle
+            .rows()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| match cell.number {
+                        Some(n) => format!("{{\"value\": {}, \"given\": true}}", n),
+                        None => "{}".to_string(),
+                    })
+                    .collect();
+                format!("[{}]", cells.join(", "))
+            })
+            .collect();
+        let json = format!(
+            "{{\"size\": 9, \"title\": \"Easy\", \"grid\": [\n{}\n], \"thermometer\": [{{\"lines\": [[\"R1C1\", \"R1C2\"]]}}], \"diagonal+\": false}}",
+            rows.join(",\n")
+        );
+
+        let (parsed, report) = Puzzle::parse_with_report(&json).unwrap();
+        assert_eq!(puzzle.grid, parsed.grid);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            "The 'thermometer' constraint isn't supported, so was left out",
+            report.warnings[0].to_string()
+        );
+        assert_eq!(1, report.warnings.len());
+
+        // Numbers placed and pencil marks come along too
+        let json = json.replacen(
+            "{}, {}",
+            "{\"value\": 3}, {\"centerPencilMarks\": [1, 8]}",
+            1,
+        );
+        let parsed = Puzzle::parse(&json);
+        assert_eq!(
+            (Some(3), false),
+            (parsed.grid[0][6].number, parsed.grid[0][6].given)
+        );
+        assert_eq!(vec![1, 8], parsed.grid[0][7].candidates.to_vec());
+
+        // Both diagonals make it X-Sudoku, where one alone is left out
+        let x = json.replace("\"diagonal+\": false", "\"diagonal+\": true");
+        let (parsed, report) = Puzzle::parse_with_report(&x).unwrap();
+        assert!(!parsed.shape().has_diagonals());
+        assert_eq!(
+            "The 'diagonal+' constraint isn't supported, so was left out",
+            report.warnings[1].to_string()
+        );
+        let x = x.replace("\"diagonal+\"", "\"diagonal-\": true, \"diagonal+\"");
+        let (parsed, report) = Puzzle::parse_with_report(&x).unwrap();
+        assert!(parsed.shape().has_diagonals());
+        assert_eq!(1, report.warnings.len());
+
+        // Cells' regions make it a jigsaw, the rest staying in their blocks
+        let jigsaw = "{\"size\": 4, \"grid\": [[{}, {}, {\"region\": 0}, {}], [{}, {\"region\": 1}, {}, {}], [{}, {}, {\"region\": 2}, {}], [{}, {\"region\": 3}, {}, {}]]}";
+        let parsed = Puzzle::parse(jigsaw);
+        assert!(parsed.shape().is_jigsaw());
+        assert_eq!(0, parsed.shape().block_of(Coord::new(0, 2)));
+        assert_eq!(1, parsed.shape().block_of(Coord::new(0, 3)));
+        let report =
+            Puzzle::parse_with_report(&jigsaw.replacen("{\"region\": 3}", "{}", 1)).unwrap_err();
+        assert_eq!(
+            "The region holding r3c1 has 5 cells rather than 4",
+            report.errors[0].message
+        );
+
+        // Sandwich sums sit just outside the grid, as strings or numbers
+        let sandwich = json.replace(
+            "\"diagonal+\": false",
+            "\"sandwichsum\": [{\"cell\": \"R0C4\", \"value\": \"10\"}, {\"cell\": \"R9C0\", \"value\": 0}]",
+        );
+        let (parsed, report) = Puzzle::parse_with_report(&sandwich).unwrap();
+        assert_eq!(Some(10), parsed.sandwiches().columns[3]);
+        assert_eq!(Some(0), parsed.sandwiches().rows[8]);
+        assert_eq!(1, report.warnings.len());
+        let report = Puzzle::parse_with_report(&sandwich.replace("R9C0", "R3C3")).unwrap_err();
+        assert_eq!(
+            "The sandwich sum at 'R3C3' isn't a sum outside the grid",
+            report.errors[0].message
+        );
+
+        // Arrows come out of a circle of one cell, each line an arrow of its own
+        let arrow = json.replace(
+            "\"diagonal+\": false",
+            "\"arrow\": [{\"lines\": [[\"R1C1\", \"R1C2\", \"R1C3\"], [\"R1C1\", \"R2C1\"]], \"cells\": [\"R1C1\"]}]",
+        );
+        let (parsed, report) = Puzzle::parse_with_report(&arrow).unwrap();
+        let arrows: Vec<Vec<Coord>> = parsed.arrows().iter().map(|a| a.cells()).collect();
+        assert_eq!(
+            vec![
+                vec![Coord::new(0, 0), Coord::new(0, 1), Coord::new(0, 2)],
+                vec![Coord::new(0, 0), Coord::new(1, 0)]
+            ],
+            arrows
+        );
+        assert_eq!(1, report.warnings.len());
+        let report = Puzzle::parse_with_report(
+            &arrow.replace("\"cells\": [\"R1C1\"]", "\"cells\": [\"R1C1\", \"R2C2\"]"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "Arrows need a circle of one cell, not 2",
+            report.errors[0].message
+        );
+
+        // XV marks go between two cells, and the negative constraint makes them all there are
+        let xv = json.replace(
+            "\"diagonal+\": false",
+            "\"xv\": [{\"cells\": [\"R1C1\", \"R2C1\"], \"value\": \"V\"}], \"negative\": [\"xv\"]",
+        );
+        let (parsed, report) = Puzzle::parse_with_report(&xv).unwrap();
+        let marks = parsed.xv();
+        assert_eq!(
+            Some(Mark::V),
+            marks.between(Coord::new(0, 0), Coord::new(1, 0))
+        );
+        assert!(marks.is_negative());
+        assert_eq!(1, report.warnings.len());
+        let (_, report) =
+            Puzzle::parse_with_report(&xv.replace("[\"xv\"]", "[\"xv\", \"ratio\"]")).unwrap();
+        assert_eq!(
+            "The 'negative' constraint isn't supported, so was left out",
+            report.warnings[1].to_string()
+        );
+        let report = Puzzle::parse_with_report(&xv.replace("\"value\": \"V\"", "\"value\": \"L\""))
+            .unwrap_err();
+        assert_eq!("Expected an X or V, not 'L'", report.errors[0].message);
+
+        // Renban and whisper clues have lines of cells, each a line of its own
+        let lines = json.replace(
+            "\"diagonal+\": false",
+            "\"renban\": [{\"lines\": [[\"R1C1\", \"R1C2\"], [\"R9C9\", \"R8C8\", \"R8C7\"]]}], \"whispers\": [{\"lines\": [[\"R5C5\", \"R5C6\"]]}]",
+        );
+        let (parsed, report) = Puzzle::parse_with_report(&lines).unwrap();
+        let kinds: Vec<(LineKind, usize)> = parsed
+            .lines()
+            .iter()
+            .map(|line| (line.kind(), line.cells().len()))
+            .collect();
+        assert_eq!(
+            vec![
+                (LineKind::Renban, 2),
+                (LineKind::Renban, 3),
+                (LineKind::Whisper, 2)
+            ],
+            kinds
+        );
+        assert_eq!(1, report.warnings.len());
+        let report = Puzzle::parse_with_report(&lines.replace("R5C6", "R5C7")).unwrap_err();
+        assert_eq!(
+            "The whisper line from r5c5 jumps from r5c5 to r5c7",
+            report.errors[0].message
+        );
+
+        let report = Puzzle::parse_with_report(&json.replacen("9", "7", 1)).unwrap_err();
+
+        assert_eq!(
+            "A 7x7 grid can't be divided into blocks",
+            report.errors[0].message
+        );
+        let report = Puzzle::parse_with_report("{\"size\": 9,\n\"grid\": [}").unwrap_err();
+        assert_eq!(
+            "error: line 2, column 10: Expected a value, not '}'",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let (_, report) = Puzzle::parse_with_report(&"1........\n".repeat(9)).unwrap();
+        assert_eq!(
+            "Only 9 givens, too few for the puzzle to have just the one solution",
+            report.warnings[0].to_string()
+        );
+    }
+}
@@ -0,0 +1,560 @@
+use super::{symbol, Coord, LineKind, Puzzle};
+
+// PDF measures in points, 72 to the inch
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+const PAGE_MARGIN: f64 = 36.0;
+const PUZZLE_GAP: f64 = 24.0;
+
+// Every digit of Helvetica is this wide, as a fraction of the font size
+const DIGIT_WIDTH: f64 = 0.556;
+// Roughly the height of a digit above the baseline, as a fraction of the font size
+const DIGIT_HEIGHT: f64 = 0.7;
+
+/// The size of the paper to print worksheets on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageSize {
+    A4,
+    A5,
+    Letter,
+
+    // Width and height in millimetres
+    Custom(f64, f64),
+}
+
+impl PageSize {
+    // Parse `a4`, `a5`, `letter`, or a width and height in millimetres such as `210x297`.
+    pub fn parse(name: &str) -> Result<PageSize, String> {
+        match name.to_lowercase().as_str() {
+            "a4" => Ok(PageSize::A4),
+            "a5" => Ok(PageSize::A5),
+            "letter" => Ok(PageSize::Letter),
+            custom => {
+                let size = custom.split_once('x').and_then(|(width, height)| {
+                    Some((width.parse::<f64>().ok()?, height.parse::<f64>().ok()?))
+                });
+
+                match size {
+                    Some((width, height)) if width >= 50.0 && height >= 50.0 => {
+                        Ok(PageSize::Custom(width, height))
+                    }
+                    _ => Err(format!(
+                        "Unknown page size '{}'. Expected a4, a5, letter, or a width and height in millimetres of at least 50x50",
+                        name
+                    )),
+                }
+            }
+        }
+    }
+
+    // Width and height in points
+    fn dimensions(&self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::A5 => (419.53, 595.28),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Custom(width, height) => (width * POINTS_PER_MM, height * POINTS_PER_MM),
+        }
+    }
+}
+
+/// How to lay out a worksheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorksheetOptions {
+    pub page_size: PageSize,
+
+    // Pencil in the candidates of each unsolved cell
+    pub candidates: bool,
+}
+
+impl Default for WorksheetOptions {
+    fn default() -> WorksheetOptions {
+        WorksheetOptions {
+            page_size: PageSize::A4,
+            candidates: false,
+        }
+    }
+}
+
+// A PDF of the puzzles ready to print: one to a page on its own, two stacked, or otherwise four to a page. Givens are printed in bold.
+pub fn worksheet(puzzles: &[Puzzle], options: &WorksheetOptions) -> Vec<u8> {
+    let (page_width, page_height) = options.page_size.dimensions();
+    let (across, down) = match puzzles.len() {
+        0 | 1 => (1, 1),
+        2 => (1, 2),
+        _ => (2, 2),
+    };
+
+    let slot_width =
+        (page_width - 2.0 * PAGE_MARGIN - (across - 1) as f64 * PUZZLE_GAP) / across as f64;
+    let slot_height =
+        (page_height - 2.0 * PAGE_MARGIN - (down - 1) as f64 * PUZZLE_GAP) / down as f64;
+    let side = slot_width.min(slot_height);
+
+    let mut pages: Vec<String> = Vec::new();
+    for page in puzzles.chunks(across * down) {
+        let mut content = String::new();
+
+        for (i, puzzle) in page.iter().enumerate() {
+            let (column, row) = (i % across, i / across);
+            // Centre the grid within its slot. PDF counts y from the bottom of the page.
+            let x =
+                PAGE_MARGIN + column as f64 * (slot_width + PUZZLE_GAP) + (slot_width - side) / 2.0;
+            let y = page_height
+                - PAGE_MARGIN
+                - row as f64 * (slot_height + PUZZLE_GAP)
+                - (slot_height - side) / 2.0
+                - side;
+
+            let mut puzzle = puzzle.clone();
+            if options.candidates {
+                puzzle.assign_candidates();
+            }
+            content.push_str(&draw_puzzle(&puzzle, x, y, side, options.candidates));
+        }
+
+        pages.push(content);
+    }
+    if pages.is_empty() {
+        pages.push(String::new());
+    }
+
+    write_pdf(&pages, page_width, page_height)
+}
+
+// Drawing operators for a grid whose bottom left corner is at (x, y).
+fn draw_puzzle(puzzle: &Puzzle, x: f64, y: f64, side: f64, candidates: bool) -> String {
+    let shape = puzzle.shape();
+    let sandwiches = puzzle.sandwiches();
+    // Sandwich sums take a cell's room above and to the left of the grid
+    let (x, side) = match sandwiches.is_empty() {
+        true => (x, side),
+        false => {
+            let outside = side / (shape.size() + 1) as f64;
+            (x + outside, side - outside)
+        }
+    };
+    let cell = side / shape.size() as f64;
+    // Pencil marks are laid out as the cells of a block
+    let (across, down) = (shape.block_cols() as f64, shape.block_rows() as f64);
+    // Square line caps, so the borders meet at the corners
+    let mut content = String::from("2 J\n");
+
+    // Cells are numbered from the top, PDF from the bottom
+    let centre = |row: usize, col: usize| {
+        (
+            x + (col as f64 + 0.5) * cell,
+            y + side - (row as f64 + 0.5) * cell,
+        )
+    };
+
+    // Renban lines in purple and whispers in green, thick and rounded, under the digits
+    let lines = puzzle.lines();
+    if !lines.is_empty() {
+        content.push_str(&format!("1 J 1 j {:.2} w\n", cell * 0.3));
+        for line in lines.iter() {
+            let colour = match line.kind() {
+                LineKind::Renban => "0.80 0.60 0.90",
+                LineKind::Whisper => "0.55 0.85 0.55",
+            };
+            let mut path = format!("{} RG", colour);
+            for (i, coord) in line.cells().iter().enumerate() {
+                let (px, py) = centre(coord.row, coord.col);
+                let op = if i == 0 { "m" } else { "l" };
+                path.push_str(&format!(" {:.2} {:.2} {}", px, py, op));
+            }
+            content.push_str(&path);
+            content.push_str(" S\n");
+        }
+        content.push_str("0 G 2 J 0 j\n");
+    }
+
+    for (row, cells) in puzzle.rows().enumerate() {
+        for (col, c) in cells.iter().enumerate() {
+            let (cx, cy) = centre(row, col);
+
+            match c.number {
+                Some(number) => {
+                    let font = if c.given { "F2" } else { "F1" };
+                    content.push_str(&draw_digit(font, cell * 0.6, cx, cy, number));
+                }
+                None if candidates => {
+                    for digit in c.candidates.iter() {
+                        let i = (digit - 1) as f64;
+                        let dx = ((i % across) - (across - 1.0) / 2.0) * cell / across;
+                        let dy = ((down - 1.0) / 2.0 - (i / across).floor()) * cell / down;
+                        let size = cell * 0.66 / across;
+                        content.push_str(&draw_digit("F1", size, cx + dx, cy + dy, digit));
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    for i in 0..shape.size() {
+        let size = cell * 0.45;
+        let middle = (i as f64 + 0.5) * cell;
+        if let Some(sum) = sandwiches.columns[i] {
+            content.push_str(&draw_text(
+                "F1",
+                size,
+                x + middle,
+                y + side + cell / 2.0,
+                &sum.to_string(),
+            ));
+        }
+        if let Some(sum) = sandwiches.rows[i] {
+            content.push_str(&draw_text(
+                "F1",
+                size,
+                x - cell / 2.0,
+                y + side - middle,
+                &sum.to_string(),
+            ));
+        }
+    }
+
+    // Arrows in grey: a circle, and a line from its edge through the cells along, ending in a head
+    let arrows = puzzle.arrows();
+    if !arrows.is_empty() {
+        let radius = cell * 0.4;
+        content.push_str("0.60 G 1.00 w\n");
+        for arrow in arrows.iter() {
+            let (cx, cy) = centre(arrow.circle().row, arrow.circle().col);
+            let points: Vec<(f64, f64)> = arrow
+                .path()
+                .iter()
+                .map(|coord| centre(coord.row, coord.col))
+                .collect();
+            let (fx, fy) = points[0];
+            let towards = (fy - cy).atan2(fx - cx);
+            let mut line = format!(
+                "{:.2} {:.2} m",
+                cx + radius * towards.cos(),
+                cy + radius * towards.sin()
+            );
+            for (px, py) in &points {
+                line.push_str(&format!(" {:.2} {:.2} l", px, py));
+            }
+
+            let (ex, ey) = points[points.len() - 1];
+            let (bx, by) = match points.len() {
+                1 => (cx, cy),
+                n => points[n - 2],
+            };
+            let back = (by - ey).atan2(bx - ex);
+            for side in [-0.5, 0.5] {
+                line.push_str(&format!(
+                    " {:.2} {:.2} m {:.2} {:.2} l",
+                    ex + cell * 0.25 * (back + side).cos(),
+                    ey + cell * 0.25 * (back + side).sin(),
+                    ex,
+                    ey
+                ));
+            }
+            content.push_str(&draw_circle(cx, cy, radius));
+            content.push_str(&line);
+            content.push_str(" S\n");
+        }
+        content.push_str("0 G\n");
+    }
+
+    // Thick lines between the blocks, the columns' then the rows', with a jigsaw's regions bordered piece by piece after
+    let width = |i: usize, block: usize| {
+        let border = if shape.is_jigsaw() {
+            i.is_multiple_of(shape.size())
+        } else {
+            i.is_multiple_of(block)
+        };
+        if border {
+            2.0
+        } else {
+            0.5
+        }
+    };
+    for i in 0..=shape.size() {
+        let offset = i as f64 * cell;
+        content.push_str(&format!(
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S {:.2} w {:.2} {:.2} m {:.2} {:.2} l S\n",
+            width(i, shape.block_cols()),
+            x + offset,
+            y,
+            x + offset,
+            y + side,
+            width(i, shape.block_rows()),
+            x,
+            y + side - offset,
+            x + side,
+            y + side - offset
+        ));
+    }
+
+    if shape.is_jigsaw() {
+        content.push_str("2.00 w\n");
+        for row in 0..shape.size() {
+            for col in 0..shape.size() {
+                let coord = Coord::new(row, col);
+                let (left, top) = (x + col as f64 * cell, y + side - row as f64 * cell);
+                if shape.border_right(coord) {
+                    content.push_str(&format!(
+                        "{:.2} {:.2} m {:.2} {:.2} l S\n",
+                        left + cell,
+                        top,
+                        left + cell,
+                        top - cell
+                    ));
+                }
+                if shape.border_below(coord) {
+                    content.push_str(&format!(
+                        "{:.2} {:.2} m {:.2} {:.2} l S\n",
+                        left,
+                        top - cell,
+                        left + cell,
+                        top - cell
+                    ));
+                }
+            }
+        }
+    }
+
+    // XV sudoku's marks on the borders between their cells, each in a white box so the line doesn't run through it
+    for edge in puzzle.xv().iter() {
+        let [a, b] = edge.cells();
+        let ((ax, ay), (bx, by)) = (centre(a.row, a.col), centre(b.row, b.col));
+        let (mx, my) = ((ax + bx) / 2.0, (ay + by) / 2.0);
+        let half = cell * 0.18;
+        content.push_str(&format!(
+            "1 g {:.2} {:.2} {:.2} {:.2} re f 0 g\n",
+            mx - half,
+            my - half,
+            half * 2.0,
+            half * 2.0
+        ));
+        content.push_str(&draw_text(
+            "F2",
+            cell * 0.3,
+            mx,
+            my,
+            &edge.mark().letter().to_string(),
+        ));
+    }
+
+    content
+}
+
+// A circle centred on (x, y), as four curves
+fn draw_circle(x: f64, y: f64, radius: f64) -> String {
+    // How far the control points stand off, for curves as round as a quarter circle
+    let k = radius * 0.5523;
+    // Each quarter's control points and end, anticlockwise from the right
+    let quarters = [
+        [(radius, k), (k, radius), (0.0, radius)],
+        [(-k, radius), (-radius, k), (-radius, 0.0)],
+        [(-radius, -k), (-k, -radius), (0.0, -radius)],
+        [(k, -radius), (radius, -k), (radius, 0.0)],
+    ];
+
+    let mut path = format!("{:.2} {:.2} m", x + radius, y);
+    for points in quarters {
+        for (dx, dy) in points {
+            path.push_str(&format!(" {:.2} {:.2}", x + dx, y + dy));
+        }
+        path.push_str(" c");
+    }
+    path + " S\n"
+}
+
+// A digit centred on (x, y)
+fn draw_digit(font: &str, size: f64, x: f64, y: f64, digit: u8) -> String {
+    draw_text(font, size, x, y, &symbol(digit).to_string())
+}
+
+// Digits centred on (x, y), every one as wide as the rest
+fn draw_text(font: &str, size: f64, x: f64, y: f64, text: &str) -> String {
+    format!(
+        "BT /{} {:.2} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+        font,
+        size,
+        x - size * DIGIT_WIDTH * text.len() as f64 / 2.0,
+        y - size * DIGIT_HEIGHT / 2.0,
+        text
+    )
+}
+
+// A minimal PDF: the catalog, the page tree, the two fonts, then a page object and content stream per page.
+fn write_pdf(pages: &[String], width: f64, height: f64) -> Vec<u8> {
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + 2 * i).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>".to_string(),
+    ];
+    for (content, id) in pages.iter().zip(page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            width,
+            height,
+            id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets: Vec<usize> = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    ));
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{LineKind, Puzzle};
+    use super::{worksheet, PageSize, WorksheetOptions};
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+    const EVIL: &str = include_str!("../samples/evil.txt");
+
+    #[test]
+    fn page_sizes() {
+        assert_eq!(Ok(PageSize::A4), PageSize::parse("a4"));
+        assert_eq!(Ok(PageSize::Letter), PageSize::parse("Letter"));
+        assert_eq!(
+            Ok(PageSize::Custom(100.0, 150.5)),
+            PageSize::parse("100x150.5")
+        );
+        assert!(PageSize::parse("b5").is_err());
+        assert!(PageSize::parse("10x10").is_err());
+    }
+
+    #[test]
+    fn layout() {
+        let puzzles: Vec<Puzzle> = [EASY, EVIL, EASY, EVIL, EASY]
+            .iter()
+            .map(|p| Puzzle::parse(p))
+            .collect();
+        let pdf = String::from_utf8(worksheet(&puzzles, &WorksheetOptions::default())).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("/MediaBox [0 0 595.28 841.89]"));
+
+        // Every object is where the cross-reference table says
+        let xref = pdf.find("xref\n").unwrap();
+        let startxref: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert_eq!(xref, startxref);
+        for (i, line) in pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .enumerate()
+        {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+
+        // Givens in bold, nothing else
+        let givens: usize = puzzles.iter().map(|p| p.givens().len()).sum();
+        assert_eq!(givens, pdf.matches("/F2").count() - 2);
+        assert_eq!(0, pdf.matches("BT /F1").count());
+    }
+
+    #[test]
+    fn candidates() {
+        let puzzles = vec![Puzzle::parse(EASY)];
+        let options = WorksheetOptions {
+            candidates: true,
+            ..WorksheetOptions::default()
+        };
+        let pdf = String::from_utf8(worksheet(&puzzles, &options)).unwrap();
+
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.assign_candidates();
+        let candidates: usize = puzzle
+            .grid
+            .iter()
+            .flatten()
+            .map(|c| c.candidates.count())
+            .sum();
+        assert_eq!(candidates, pdf.matches("BT /F1").count());
+    }
+
+    #[test]
+    fn sandwiches() {
+        let puzzles = vec![Puzzle::parse(include_str!("../samples/sandwich.txt"))];
+        let pdf = String::from_utf8(worksheet(&puzzles, &WorksheetOptions::default())).unwrap();
+
+        // Each sum is written outside the grid, which shrinks to make room
+        assert_eq!(18, pdf.matches("BT /F1").count());
+        assert!(pdf.contains("(28) Tj"));
+        let plain = String::from_utf8(worksheet(
+            &[Puzzle::parse(EASY)],
+            &WorksheetOptions::default(),
+        ))
+        .unwrap();
+        assert!(!plain.contains("(28) Tj"));
+    }
+
+    #[test]
+    fn arrows() {
+        let puzzles = vec![Puzzle::parse(include_str!("../samples/arrow.txt"))];
+        let pdf = String::from_utf8(worksheet(&puzzles, &WorksheetOptions::default())).unwrap();
+
+        // Each arrow's circle, and its line with a head, in grey and back to black after
+        assert!(pdf.contains("0.60 G 1.00 w\n"));
+        assert_eq!(9, pdf.matches(" c S\n").count());
+        assert_eq!(1, pdf.matches("\n0 G\n").count());
+    }
+
+    #[test]
+    fn xv() {
+        let puzzles = vec![Puzzle::parse(include_str!("../samples/xv.txt"))];
+        let pdf = String::from_utf8(worksheet(&puzzles, &WorksheetOptions::default())).unwrap();
+
+        // Each mark in a white box on its border
+        assert_eq!(28, pdf.matches(" re f 0 g\n").count());
+        assert_eq!(13, pdf.matches("(V) Tj").count());
+        assert_eq!(15, pdf.matches("(X) Tj").count());
+    }
+
+    #[test]
+    fn lines() {
+        let puzzles = vec![Puzzle::parse(include_str!("../samples/lines.txt"))];
+        let pdf = String::from_utf8(worksheet(&puzzles, &WorksheetOptions::default())).unwrap();
+
+        // Each line in its colour, rounded, and back to square caps after
+        let lines = puzzles[0].lines();
+        let renban = lines.iter().filter(|line| line.kind() == LineKind::Renban);
+        assert_eq!(renban.count(), pdf.matches("0.80 0.60 0.90 RG").count());
+        assert_eq!(lines.len(), pdf.matches(" RG ").count());
+        assert_eq!(1, pdf.matches("\n0 G 2 J 0 j\n").count());
+    }
+}
@@ -0,0 +1,424 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::{
+    strip_emoji, Consolidation, ConsolidationMode, HouseRule, Instant, Observer, Puzzle, Transcript,
+};
+
+/// The techniques the solver can apply.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    SaraFlex,
+    WaterCannon,
+    XWing,
+    UniqueRectangle,
+    UniqueLoop,
+    BugLite,
+    AlternatingInferenceChain,
+    AlignedPairExclusion,
+    AlmostLockedSetChain,
+    ForcingChain,
+    Sandwich,
+    Arrow,
+    Xv,
+    Renban,
+    Whisper,
+    HouseRule,
+}
+
+impl Technique {
+    // Every technique, in the default order: the cheap eliminations, sandwich sums, arrows, XV marks, lines, and house rules first as there may be little else to go on, then singles, then the heavier eliminations, cheapest first.
+    pub const ALL: [Technique; 18] = [
+        Technique::Sandwich,
+        Technique::Arrow,
+        Technique::Xv,
+        Technique::Renban,
+        Technique::Whisper,
+        Technique::HouseRule,
+        Technique::SaraFlex,
+        Technique::WaterCannon,
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::XWing,
+        Technique::UniqueRectangle,
+        Technique::UniqueLoop,
+        Technique::BugLite,
+        Technique::AlternatingInferenceChain,
+        Technique::AlignedPairExclusion,
+        Technique::AlmostLockedSetChain,
+        Technique::ForcingChain,
+    ];
+
+    // The name used on the command line, e.g. `--techniques singles,pointing,naked-subsets,xwing`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked-singles",
+            Technique::HiddenSingle => "hidden-singles",
+            Technique::SaraFlex => "naked-subsets",
+            Technique::WaterCannon => "pointing",
+            Technique::XWing => "xwing",
+            Technique::UniqueRectangle => "unique-rectangles",
+            Technique::UniqueLoop => "unique-loops",
+            Technique::BugLite => "bug-lite",
+            Technique::AlternatingInferenceChain => "aic",
+            Technique::AlignedPairExclusion => "aligned-pairs",
+            Technique::AlmostLockedSetChain => "als-chains",
+            Technique::ForcingChain => "forcing-chains",
+            Technique::Sandwich => "sandwiches",
+            Technique::Arrow => "arrows",
+            Technique::Xv => "xv",
+            Technique::Renban => "renban",
+            Technique::Whisper => "whispers",
+            Technique::HouseRule => "house-rules",
+        }
+    }
+
+    // Parse a comma separated list of technique names, keeping their order. `singles` stands for both naked and hidden singles.
+    pub fn parse_list(list: &str) -> Result<Vec<Technique>, String> {
+        let mut techniques: Vec<Technique> = Vec::new();
+
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let named: Vec<Technique> = if name == "singles" {
+                vec![Technique::NakedSingle, Technique::HiddenSingle]
+            } else {
+                match Technique::ALL.iter().find(|t| t.name() == name) {
+                    Some(technique) => vec![*technique],
+                    None => {
+                        let known: Vec<&str> = Technique::ALL.iter().map(|t| t.name()).collect();
+                        return Err(format!(
+                            "Unknown technique '{}'. Expected singles or one of: {}",
+                            name,
+                            known.join(", ")
+                        ));
+                    }
+                }
+            };
+
+            for technique in named {
+                if !techniques.contains(&technique) {
+                    techniques.push(technique);
+                }
+            }
+        }
+
+        if techniques.is_empty() {
+            return Err("No techniques given".to_string());
+        }
+
+        Ok(techniques)
+    }
+
+    pub(super) fn apply(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        match self {
+            Technique::NakedSingle => puzzle.assign_naked_singles(),
+            Technique::HiddenSingle => puzzle.assign_hidden_singles(),
+            Technique::SaraFlex => puzzle.reduce_candidates_with_sara_flex(),
+            Technique::WaterCannon => puzzle.reduce_candidates_using_water_cannon(),
+            Technique::XWing => puzzle.reduce_candidates_with_x_wings(),
+            Technique::UniqueRectangle => puzzle.reduce_candidates_with_unique_rectangles(),
+            Technique::UniqueLoop => puzzle.reduce_candidates_with_unique_loops(),
+            Technique::BugLite => puzzle.reduce_candidates_with_bug_lite(),
+            Technique::AlternatingInferenceChain => puzzle.reduce_candidates_with_aics(),
+            Technique::AlignedPairExclusion => {
+                puzzle.reduce_candidates_with_aligned_pair_exclusion()
+            }
+            Technique::AlmostLockedSetChain => puzzle.reduce_candidates_with_als_chains(),
+            Technique::ForcingChain => puzzle.reduce_candidates_with_forcing_chains(),
+            Technique::Sandwich => puzzle.reduce_candidates_with_sandwiches(),
+            Technique::Arrow => puzzle.reduce_candidates_with_arrows(),
+            Technique::Xv => puzzle.reduce_candidates_with_xv(),
+            Technique::Renban => puzzle.reduce_candidates_with_renban(),
+            Technique::Whisper => puzzle.reduce_candidates_with_whispers(),
+            Technique::HouseRule => puzzle.reduce_candidates_with_house_rules(),
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The order the numbers a cell could be are guessed in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GuessOrder {
+    Ascending,
+    Descending,
+
+    // Shuffled, the same way every time for the same seed
+    Random(u64),
+}
+
+impl GuessOrder {
+    // `ascending`, `descending`, or `random:<seed>`, e.g. `random:42`.
+    pub fn parse(name: &str) -> Result<GuessOrder, String> {
+        match name.split_once(':') {
+            None if name == "ascending" => Ok(GuessOrder::Ascending),
+            None if name == "descending" => Ok(GuessOrder::Descending),
+            Some(("random", seed)) => seed
+                .parse::<u64>()
+                .map(GuessOrder::Random)
+                .map_err(|_| format!("Expected a number to seed the random order, not '{}'", seed)),
+            _ => Err(format!(
+                "Unknown guess order '{}'. Expected ascending, descending, or random:<seed>",
+                name
+            )),
+        }
+    }
+}
+
+/// Which techniques the solver may use and in what order, and how it applies them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolverConfig {
+    pub techniques: Vec<Technique>,
+    pub consolidation_mode: ConsolidationMode,
+
+    // Print the solver's workings as it goes, and snapshot each step to tmp/. Off by default, so the library keeps quiet unless asked; the command line turns it on unless given `--quiet`.
+    pub verbose: bool,
+
+    // Leave the emoji and banners out of the workings printed, for logs
+    pub plain: bool,
+
+    // Where the workings are printed, standard output by default. See `Transcript`.
+    pub transcript: Transcript,
+
+    // Explore each of the first guesses on a thread of its own when logic alone can't solve the puzzle. Puzzles with more than one solution may then be solved differently from run to run.
+    pub parallel_guesses: bool,
+
+    // The order to guess a cell's candidates in. Descending by default, as it happens to suit sample/expert3.txt.
+    pub guess_order: GuessOrder,
+
+    // Never guess, so a puzzle logic alone can't solve is left unsolved with its candidates as they stand. Tells whether a puzzle can be solved by hand.
+    pub logic_only: bool,
+
+    // Limits on the solve, so pathological puzzles can't run unbounded: the steps taken, counting from the start of the puzzle and along any guesses; the guesses made, across every branch of the search; and the time taken by each call of `solve()` or `solve_with_guesses()`
+    pub max_iterations: Option<usize>,
+    pub max_guess_nodes: Option<usize>,
+    pub wall_clock_timeout: Option<Duration>,
+
+    // Don't guess on top of this many guesses, abandoning those branches of the search instead. Unlike the limits above, other branches are still searched.
+    pub max_guess_depth: Option<usize>,
+
+    // Told of each assignment, elimination, step, and guess as the solver makes them. See `SolveObserver`.
+    pub observer: Option<Observer>,
+
+    // Rules of your own the digits must keep to, beyond the puzzle's. See `Constraint`.
+    pub house_rules: Vec<HouseRule>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> SolverConfig {
+        SolverConfig {
+            techniques: Technique::ALL.to_vec(),
+            consolidation_mode: ConsolidationMode::AllFindings,
+            verbose: false,
+            plain: false,
+            transcript: Transcript::stdout(),
+            parallel_guesses: false,
+            guess_order: GuessOrder::Descending,
+            logic_only: false,
+            max_iterations: None,
+            max_guess_nodes: None,
+            wall_clock_timeout: None,
+            max_guess_depth: None,
+            observer: None,
+            house_rules: vec![],
+        }
+    }
+}
+
+impl SolverConfig {
+    // Print a line of the solver's workings to the transcript, as plain as configured. A line of nothing but emoji is a banner, so is left out altogether.
+    pub(super) fn say(&self, line: &str) {
+        if !self.plain {
+            self.transcript.write(&format!("{}\n", line));
+            return;
+        }
+
+        let line = strip_emoji(line);
+        if !line.trim().is_empty() {
+            self.transcript.write(&format!("{}\n", line));
+        }
+    }
+
+    pub fn with_techniques(techniques: Vec<Technique>) -> SolverConfig {
+        SolverConfig {
+            techniques,
+            ..SolverConfig::default()
+        }
+    }
+}
+
+impl Puzzle {
+    pub fn set_config(&mut self, config: SolverConfig) {
+        self.config = config;
+    }
+
+    // Try each configured technique in turn. Eliminations can open up the techniques before them, so after any technique makes progress start again from the top. Stops as soon as a number is assigned, once no technique can make progress, or once a solve's time is up.
+    //
+    // Returns everything the techniques did, in order.
+    pub(super) fn run_pipeline(&mut self) -> Vec<Consolidation> {
+        let techniques = self.config.techniques.clone();
+        let mut progress: Vec<Consolidation> = Vec::new();
+
+        'pipeline: loop {
+            for technique in techniques.iter() {
+                if self.out_of_time() {
+                    break 'pipeline;
+                }
+
+                let started = Instant::now();
+                let mut found = technique.apply(self);
+                add_time(&mut self.technique_times, *technique, started.elapsed());
+                log!(self, "{} made {} consolidations", technique, found.len());
+
+                if found.is_empty() {
+                    continue;
+                }
+
+                let assigned = found.iter().any(Consolidation::is_assignment);
+                progress.append(&mut found);
+                if assigned {
+                    break 'pipeline;
+                }
+                continue 'pipeline;
+            }
+
+            break;
+        }
+
+        progress
+    }
+
+    // Apply the one technique, once, as a step of its own if it finds anything, whatever techniques are configured. Candidates are worked out as for `hint()`, so those eliminated by techniques applied before are still gone. Returns what the technique found.
+    pub fn apply_technique(&mut self, technique: Technique) -> Vec<Consolidation> {
+        self.grid = self.noted().grid;
+        self.invalidate_candidates();
+        let before = self.grid;
+
+        let started = Instant::now();
+        let found = technique.apply(self);
+        add_time(&mut self.technique_times, technique, started.elapsed());
+        if found.is_empty() {
+            return found;
+        }
+
+        self.iteration += 1;
+        self.last_consolidation = found.clone();
+        self.record_step(before);
+        self.notify_step();
+        found
+    }
+}
+
+// Add time spent in the technique to the times of those tried so far, listed in the order first tried.
+pub(super) fn add_time(
+    times: &mut Vec<(Technique, Duration)>,
+    technique: Technique,
+    elapsed: Duration,
+) {
+    match times.iter_mut().find(|(t, _)| *t == technique) {
+        Some((_, time)) => *time += elapsed,
+        None => times.push((technique, elapsed)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Consolidation, Puzzle, PuzzleStatus};
+    use super::{GuessOrder, SolverConfig, Technique};
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+    const EVIL: &str = include_str!("../samples/evil.txt");
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            Ok(vec![
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+                Technique::WaterCannon,
+                Technique::SaraFlex,
+                Technique::XWing,
+            ]),
+            Technique::parse_list("singles,pointing,naked-subsets,xwing")
+        );
+        assert_eq!(
+            Ok(vec![Technique::HiddenSingle, Technique::NakedSingle]),
+            Technique::parse_list("hidden-singles, singles")
+        );
+        assert!(Technique::parse_list("singles,swordfish").is_err());
+        assert!(Technique::parse_list("").is_err());
+    }
+
+    #[test]
+    fn apply_technique() {
+        let mut puzzle = Puzzle::parse(EASY);
+
+        // Pointing eliminates, and the eliminations stay for the technique after
+        let pointing = puzzle.apply_technique(Technique::WaterCannon);
+        assert!(!pointing.is_empty());
+        assert!(pointing.iter().all(|c| c.assignment().is_none()));
+        assert_eq!(1, puzzle.solution().steps().len());
+        let (row, col) = match &pointing[0] {
+            Consolidation::CandidatesEliminated { cells, .. } => cells[0],
+            _ => unreachable!(),
+        };
+        let candidates = puzzle.grid[row][col].candidates;
+
+        let singles = puzzle.apply_technique(Technique::NakedSingle);
+        assert!(singles.iter().all(|c| c.assignment().is_some()));
+        assert_eq!(2, puzzle.solution().steps().len());
+        if puzzle.grid[row][col].number.is_none() {
+            assert_eq!(candidates, puzzle.grid[row][col].candidates);
+        }
+
+        // Nothing found makes no step
+        let mut solved = puzzle.clone();
+        solved.solve().unwrap();
+        let steps = solved.solution().steps().len();
+        assert!(solved.apply_technique(Technique::XWing).is_empty());
+        assert_eq!(steps, solved.solution().steps().len());
+    }
+
+    // Like `solve()`, without the snapshots
+    fn solve(puzzle: &mut Puzzle) {
+        loop {
+            puzzle.assign_candidates();
+            if !puzzle
+                .run_pipeline()
+                .iter()
+                .any(Consolidation::is_assignment)
+            {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn parse_guess_order() {
+        assert_eq!(Ok(GuessOrder::Ascending), GuessOrder::parse("ascending"));
+        assert_eq!(Ok(GuessOrder::Random(42)), GuessOrder::parse("random:42"));
+        assert!(GuessOrder::parse("random").is_err());
+        assert!(GuessOrder::parse("random:x").is_err());
+        assert!(GuessOrder::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn singles_only() {
+        let singles = SolverConfig::with_techniques(Technique::parse_list("singles").unwrap());
+
+        let mut easy = Puzzle::parse(EASY);
+        easy.set_config(singles.clone());
+        solve(&mut easy);
+        assert_eq!(PuzzleStatus::Solved, easy.status());
+
+        let mut evil = Puzzle::parse(EVIL);
+        evil.set_config(singles);
+        solve(&mut evil);
+        assert_eq!(PuzzleStatus::Unsolved, evil.status());
+    }
+}
@@ -0,0 +1,287 @@
+use super::shape::digit;
+use super::{
+    symbol, CandidateStyle, Candidates, ColorScheme, DisplayOptions, GridStyle, Puzzle,
+    PuzzleStatus, MAX_SIZE,
+};
+
+/// A key pressed while playing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Digit(u8),
+    Erase,
+    Pencil,
+    Hint,
+    Quit,
+}
+
+impl Key {
+    // The key for a character typed, if it's one the game uses. The terminal reads arrows, backspace, escape, and Ctrl-C as keys of their own.
+    //
+    //    * `hjkl` to move
+    //    * `1` to `9`, then `a` on in bigger grids, to enter a number, or pencil it in. Past `g` the letters that are keys of their own are entered in capitals
+    //    * `0`, space, or `.` to erase
+    //    * `p` to switch between entering numbers and pencilling them in
+    //    * `?` for a hint
+    //    * `q` to quit
+    pub fn from_char(c: char) -> Option<Key> {
+        match c {
+            'k' => Some(Key::Up),
+            'j' => Some(Key::Down),
+            'l' => Some(Key::Right),
+            'h' => Some(Key::Left),
+            '0' | ' ' | '.' => Some(Key::Erase),
+            'p' => Some(Key::Pencil),
+            '?' => Some(Key::Hint),
+            'q' => Some(Key::Quit),
+            c => digit(c, MAX_SIZE).map(Key::Digit),
+        }
+    }
+}
+
+/// A puzzle being played by hand: the numbers entered so far, with any pencil marks as the candidates of the cells, and where the cursor is.
+pub struct Game {
+    puzzle: Puzzle,
+    solution: Option<Puzzle>,
+    cursor: (usize, usize),
+    pencil: bool,
+    message: String,
+}
+
+impl Game {
+    // Mistakes can only be pointed out when the solution is known.
+    pub fn new(puzzle: Puzzle, solution: Option<Puzzle>) -> Game {
+        Game {
+            puzzle,
+            solution,
+            cursor: (0, 0),
+            pencil: false,
+            message: String::new(),
+        }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    // Act on the key. Returns `false` once the player quits.
+    pub fn handle(&mut self, key: Key) -> bool {
+        let (row, col) = self.cursor;
+        let size = self.puzzle.shape.size();
+        self.message.clear();
+
+        match key {
+            Key::Up => self.cursor = ((row + size - 1) % size, col),
+            Key::Down => self.cursor = ((row + 1) % size, col),
+            Key::Left => self.cursor = (row, (col + size - 1) % size),
+            Key::Right => self.cursor = (row, (col + 1) % size),
+            Key::Digit(digit) if digit as usize > size => {
+                self.message = format!("Only 1 to {} go in this grid.", symbol(size as u8));
+            }
+            Key::Digit(_) | Key::Erase if self.puzzle.grid[row][col].given => {
+                self.message = "That's a given, it can't be changed.".to_string();
+            }
+            Key::Digit(digit) if self.pencil => {
+                self.puzzle.invalidate_candidates();
+                let cell = &mut self.puzzle.grid[row][col];
+                if cell.number.is_none() && !cell.remove_candidate(digit) {
+                    cell.candidates.insert(digit);
+                }
+            }
+            Key::Digit(digit) => {
+                self.puzzle.invalidate_candidates();
+                let cell = &mut self.puzzle.grid[row][col];
+                cell.number = Some(digit);
+                cell.candidates = Candidates::none();
+
+                if self.puzzle.status() == PuzzleStatus::Solved && self.mistakes().is_empty() {
+                    self.message = "Solved! 🙌".to_string();
+                }
+            }
+            Key::Erase => {
+                self.puzzle.invalidate_candidates();
+                let cell = &mut self.puzzle.grid[row][col];
+                cell.number = None;
+                cell.candidates = Candidates::none();
+            }
+            Key::Pencil => self.pencil = !self.pencil,
+            Key::Hint => self.hint(),
+            Key::Quit => return false,
+        }
+
+        true
+    }
+
+    // Numbers entered that don't match the solution.
+    fn mistakes(&self) -> Vec<(usize, usize)> {
+        let solution = match &self.solution {
+            Some(solution) => solution,
+            None => return vec![],
+        };
+
+        self.puzzle
+            .shape
+            .coords()
+            .map(|coord| (coord.row, coord.col))
+            .filter(|&(row, col)| {
+                let number = self.puzzle.grid[row][col].number;
+                number.is_some() && number != solution.grid[row][col].number
+            })
+            .collect()
+    }
+
+    // Point out a mistake if there is one, since the techniques can't be trusted on a grid with mistakes in it. Otherwise explain what to do next, moving the cursor to the cell to do it to.
+    fn hint(&mut self) {
+        if let Some(&(row, col)) = self.mistakes().first() {
+            self.cursor = (row, col);
+            self.message = format!("R{}C{} is wrong.", row + 1, col + 1);
+            return;
+        }
+
+        match self.puzzle.hint() {
+            Some(hint) => {
+                if let Some((position, _)) = hint.assignment() {
+                    self.cursor = position;
+                }
+                self.message = hint.describe();
+            }
+            None => self.message = "No hint: the techniques are stuck too.".to_string(),
+        }
+    }
+
+    // The whole screen: the grid with mistakes in red, what the keys do, and any message.
+    pub fn render(&self) -> String {
+        let options = DisplayOptions {
+            candidates: CandidateStyle::PencilMarks,
+            style: GridStyle::Unicode,
+            highlight: self.mistakes(),
+            colors: Some(ColorScheme {
+                highlight: "\x1b[41m".to_string(),
+                ..ColorScheme::default()
+            }),
+            ..DisplayOptions::default()
+        };
+
+        let mode = if self.pencil {
+            "pencilling in"
+        } else {
+            "entering numbers"
+        };
+
+        format!(
+            "{}\nArrows move · 1-{} {} · 0 erases · p switches · ? hints · q quits\n{}",
+            self.puzzle.display_with(&options),
+            symbol(self.puzzle.shape.size() as u8),
+            mode,
+            self.message
+        )
+    }
+
+    // Where on the screen `render()` draws the cell under the cursor, as the line and column counting from nought, for the terminal's cursor to sit there.
+    pub fn cursor_on_screen(&self) -> (usize, usize) {
+        // The grid's drawn from the second line down, its top border first. Each row of cells takes a line per row of pencil marks and one more for the border below, a number sitting on the middle line half way across its marks. Cells are eight columns apart in a 9x9 grid, wider once there are more marks to a row.
+        let shape = self.puzzle.shape;
+        let (row, col) = self.cursor;
+        let width = (2 * shape.block_cols()).max(7) + 1;
+        (
+            2 + shape.block_rows() / 2 + (shape.block_rows() + 1) * row,
+            shape.block_cols() + 1 + width * col,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+    use super::{Game, Key};
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+    const EASY_SOLVED: &str = "
+        341562789
+        768934152
+        952718364
+        574291638
+        189346275
+        623875491
+        215687943
+        496153827
+        837429516
+    ";
+
+    fn game() -> Game {
+        Game::new(Puzzle::parse(EASY), Some(Puzzle::parse(EASY_SOLVED)))
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(Some(Key::Up), Key::from_char('k'));
+        assert_eq!(Some(Key::Left), Key::from_char('h'));
+        assert_eq!(Some(Key::Digit(7)), Key::from_char('7'));
+        assert_eq!(Some(Key::Erase), Key::from_char('0'));
+        assert_eq!(Some(Key::Quit), Key::from_char('q'));
+        assert_eq!(None, Key::from_char('x'));
+    }
+
+    #[test]
+    fn entering_numbers() {
+        let mut game = game();
+
+        assert!(game.handle(Key::Left));
+        assert_eq!((0, 8), game.cursor);
+        game.handle(Key::Digit(5));
+        assert_eq!(vec![(0, 8)], game.mistakes());
+        assert!(game.render().contains("\x1b[41m"));
+        game.handle(Key::Erase);
+        assert!(game.mistakes().is_empty());
+
+        // Givens stay put
+        game.handle(Key::Right);
+        game.handle(Key::Right);
+        game.handle(Key::Digit(1));
+        assert_eq!(Some(4), game.puzzle.grid[0][1].number);
+
+        game.handle(Key::Pencil);
+        game.handle(Key::Left);
+        game.handle(Key::Digit(3));
+        game.handle(Key::Digit(1));
+        game.handle(Key::Digit(3));
+        assert_eq!(vec![1], game.puzzle.grid[0][0].candidates.to_vec());
+        assert_eq!(None, game.puzzle.grid[0][0].number);
+
+        assert!(!game.handle(Key::Quit));
+    }
+
+    #[test]
+    fn hints() {
+        let mut game = game();
+        game.handle(Key::Digit(9));
+        game.handle(Key::Down);
+        game.handle(Key::Hint);
+        assert_eq!((0, 0), game.cursor);
+        assert_eq!("R1C1 is wrong.", game.message);
+
+        game.handle(Key::Erase);
+        game.handle(Key::Hint);
+        assert!(game.message.contains(" must be "));
+        let (row, col) = game.cursor;
+        assert!(game.puzzle.grid[row][col].number.is_none());
+        assert_eq!((3 + 4 * row, 4 + 8 * col), game.cursor_on_screen());
+    }
+
+    #[test]
+    fn solving() {
+        let mut game = game();
+        let solved = Puzzle::parse(EASY_SOLVED);
+
+        for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            game.cursor = (row, col);
+            if !game.puzzle.grid[row][col].given {
+                game.handle(Key::Digit(solved.grid[row][col].number.unwrap()));
+            }
+        }
+        assert_eq!("Solved! 🙌", game.message);
+    }
+}
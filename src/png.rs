@@ -0,0 +1,568 @@
+use super::{Consolidation, Coord, Grid, Puzzle, Shape, MAX_SIZE};
+
+const CELL_SIZE: usize = 48;
+const MARGIN: usize = 8;
+
+// Each glyph pixel is drawn as a square this many pixels across
+const GLYPH_SCALE: usize = 4;
+
+const WHITE: [u8; 3] = [255, 255, 255];
+const BLACK: [u8; 3] = [34, 34, 34];
+const GREY: [u8; 3] = [170, 170, 170];
+const BLUE: [u8; 3] = [26, 79, 160];
+const GREEN: [u8; 3] = [39, 140, 60];
+const YELLOW: [u8; 3] = [253, 240, 181];
+
+// 5x7 bitmaps of the digits 1 thru 9, then A on
+#[rustfmt::skip]
+const GLYPHS: [[&str; 7]; MAX_SIZE] = [
+    ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+    ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+    ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+    ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+    ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+    ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+    [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+    [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+    [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."],
+    [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."],
+    ["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."],
+    ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+    ["#####", "#....", "#....", "####.", "#....", "#....", "#...."],
+    [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".###."],
+    ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+    ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"],
+    ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+    ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"],
+    ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"],
+    [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+    ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."],
+];
+
+// Pixels across and down a grid of the shape.
+fn side(shape: Shape) -> usize {
+    2 * MARGIN + shape.size() * CELL_SIZE
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![WHITE; width * height],
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, colour: [u8; 3]) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.pixels[py * self.width + px] = colour;
+            }
+        }
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Puzzle {
+    // The grid as a PNG image, givens in black and solved numbers in blue. Unsolved cells are left blank.
+    pub fn png(&self) -> Vec<u8> {
+        let canvas = render(self.shape, &self.grid, &[], &[]);
+
+        let mut png = start_png(&canvas);
+        push_chunk(&mut png, b"IDAT", &zlib(&scanlines(&canvas)));
+        push_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    // The solve so far as an animated PNG: the puzzle as given, then a frame for each step placing numbers, with the numbers it placed in green and the cells they follow from on yellow. The last frame lingers before the animation loops.
+    pub fn apng(&self) -> Vec<u8> {
+        let first = self.steps.first().map_or(self.grid, |step| step.before);
+        let mut frames: Vec<Canvas> = vec![render(self.shape, &first, &[], &[])];
+
+        for step in self.steps.iter() {
+            let placed: Vec<(usize, usize)> = self
+                .shape
+                .coords()
+                .map(|coord| (coord.row, coord.col))
+                .filter(|&(r, c)| {
+                    step.before[r][c].number.is_none() && step.after[r][c].number.is_some()
+                })
+                .collect();
+            if !placed.is_empty() {
+                let defining: Vec<(usize, usize)> = step
+                    .consolidations
+                    .iter()
+                    .flat_map(Consolidation::defining_cells)
+                    .collect();
+                frames.push(render(self.shape, &step.after, &placed, &defining));
+            }
+        }
+
+        let mut png = start_png(&frames[0]);
+
+        // Animation control: the number of frames, and to loop forever
+        let mut control: Vec<u8> = Vec::new();
+        control.extend((frames.len() as u32).to_be_bytes());
+        control.extend(0u32.to_be_bytes());
+        push_chunk(&mut png, b"acTL", &control);
+
+        // Frame controls and frame data share one sequence
+        let mut sequence = 0u32;
+        for (i, frame) in frames.iter().enumerate() {
+            let (delay, per_second) = if i == frames.len() - 1 {
+                (3u16, 1u16)
+            } else {
+                (1, 2)
+            };
+
+            let mut frame_control: Vec<u8> = Vec::new();
+            frame_control.extend(sequence.to_be_bytes());
+            frame_control.extend((frame.width as u32).to_be_bytes());
+            frame_control.extend((frame.height as u32).to_be_bytes());
+            frame_control.extend(0u32.to_be_bytes());
+            frame_control.extend(0u32.to_be_bytes());
+            frame_control.extend(delay.to_be_bytes());
+            frame_control.extend(per_second.to_be_bytes());
+            // Neither dispose of nor blend with the previous frame, each replaces it whole
+            frame_control.extend([0, 0]);
+            push_chunk(&mut png, b"fcTL", &frame_control);
+            sequence += 1;
+
+            let data = zlib(&scanlines(frame));
+            if i == 0 {
+                push_chunk(&mut png, b"IDAT", &data);
+            } else {
+                let mut frame_data = sequence.to_be_bytes().to_vec();
+                frame_data.extend(data);
+                push_chunk(&mut png, b"fdAT", &frame_data);
+                sequence += 1;
+            }
+        }
+
+        push_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+// The numbers of the grid, with those in the highlighted cells in green, and the defining cells on yellow.
+fn render(
+    shape: Shape,
+    grid: &Grid,
+    highlight: &[(usize, usize)],
+    defining: &[(usize, usize)],
+) -> Canvas {
+    let size = shape.size();
+    let mut canvas = Canvas::new(side(shape), side(shape));
+
+    for (row, cells) in grid[..size].iter().enumerate() {
+        for (col, cell) in cells[..size].iter().enumerate() {
+            if defining.contains(&(row, col)) {
+                let (x, y) = (MARGIN + col * CELL_SIZE, MARGIN + row * CELL_SIZE);
+                canvas.fill(x, y, CELL_SIZE, CELL_SIZE, YELLOW);
+            }
+            if let Some(number) = cell.number {
+                let colour = if highlight.contains(&(row, col)) {
+                    GREEN
+                } else if cell.given {
+                    BLACK
+                } else {
+                    BLUE
+                };
+                draw_digit(&mut canvas, row, col, number, colour);
+            }
+        }
+    }
+
+    // Thin lines first so the block borders are drawn over them
+    for thick in [false, true] {
+        for i in 0..=size {
+            // The width and colour of the line, if it's drawn this time round
+            let line = |block: usize| {
+                // A jigsaw's regions are bordered piece by piece below
+                let border = if shape.is_jigsaw() {
+                    i % size == 0
+                } else {
+                    i % block == 0
+                };
+                (border == thick).then_some(if border { (3, BLACK) } else { (1, GREY) })
+            };
+
+            let length = size * CELL_SIZE + 2;
+            if let Some((width, colour)) = line(shape.block_cols()) {
+                let offset = MARGIN + i * CELL_SIZE - width / 2;
+                canvas.fill(offset, MARGIN - 1, width, length, colour);
+            }
+            if let Some((width, colour)) = line(shape.block_rows()) {
+                let offset = MARGIN + i * CELL_SIZE - width / 2;
+                canvas.fill(MARGIN - 1, offset, length, width, colour);
+            }
+        }
+    }
+
+    // Each side of a cell on a region's border, long enough to meet the next at the corners
+    if shape.is_jigsaw() {
+        for row in 0..size {
+            for col in 0..size {
+                let coord = Coord::new(row, col);
+                let (x, y) = (MARGIN + col * CELL_SIZE, MARGIN + row * CELL_SIZE);
+                if shape.border_right(coord) {
+                    canvas.fill(x + CELL_SIZE - 1, y - 1, 3, CELL_SIZE + 3, BLACK);
+                }
+                if shape.border_below(coord) {
+                    canvas.fill(x - 1, y + CELL_SIZE - 1, CELL_SIZE + 3, 3, BLACK);
+                }
+            }
+        }
+    }
+
+    canvas
+}
+
+fn draw_digit(canvas: &mut Canvas, row: usize, col: usize, number: u8, colour: [u8; 3]) {
+    let glyph = GLYPHS[number as usize - 1];
+    let x = MARGIN + col * CELL_SIZE + (CELL_SIZE - 5 * GLYPH_SCALE) / 2;
+    let y = MARGIN + row * CELL_SIZE + (CELL_SIZE - 7 * GLYPH_SCALE) / 2;
+
+    for (gy, line) in glyph.iter().enumerate() {
+        for (gx, pixel) in line.chars().enumerate() {
+            if pixel == '#' {
+                canvas.fill(
+                    x + gx * GLYPH_SCALE,
+                    y + gy * GLYPH_SCALE,
+                    GLYPH_SCALE,
+                    GLYPH_SCALE,
+                    colour,
+                );
+            }
+        }
+    }
+}
+
+// The signature and header of an 8-bit RGB PNG the size of the canvas.
+fn start_png(canvas: &Canvas) -> Vec<u8> {
+    let mut header: Vec<u8> = Vec::new();
+    header.extend((canvas.width as u32).to_be_bytes());
+    header.extend((canvas.height as u32).to_be_bytes());
+    // Bit depth, colour type (RGB), compression, filter, and interlace methods
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png: Vec<u8> = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    push_chunk(&mut png, b"IHDR", &header);
+    png
+}
+
+// The image data, each scanline starting with its filter type, 0 for none.
+fn scanlines(canvas: &Canvas) -> Vec<u8> {
+    let mut raw: Vec<u8> = Vec::with_capacity(canvas.height * (1 + 3 * canvas.width));
+    for y in 0..canvas.height {
+        raw.push(0);
+        for x in 0..canvas.width {
+            raw.extend(canvas.pixel(x, y));
+        }
+    }
+    raw
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+
+    png.extend(crc.to_be_bytes());
+}
+
+// The base of each deflate length code from 257 up, and how many extra bits follow it
+const LENGTH_BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Likewise for each distance code
+const DISTANCE_BASES: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW: usize = 32768;
+const MAX_MATCH: usize = 258;
+
+// Deflate packs values from their least significant bit, except Huffman codes which start from their most significant.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        for i in 0..count {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 1 << self.used;
+            }
+            self.used = (self.used + 1) % 8;
+        }
+    }
+
+    fn write_code(&mut self, code: u32, length: u32) {
+        for bit in (0..length).rev() {
+            self.write((code >> bit) & 1, 1);
+        }
+    }
+
+    // A literal byte, a match length, or the end of the block, using the fixed Huffman codes
+    fn write_symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASES
+            .iter()
+            .rposition(|&base| base <= length)
+            .unwrap();
+        self.write_symbol(257 + code as u32);
+        self.write(
+            (length - LENGTH_BASES[code]) as u32,
+            LENGTH_EXTRA_BITS[code],
+        );
+
+        let code = DISTANCE_BASES
+            .iter()
+            .rposition(|&base| base <= distance)
+            .unwrap();
+        self.write_code(code as u32, 5);
+        self.write(
+            (distance - DISTANCE_BASES[code]) as u32,
+            DISTANCE_EXTRA_BITS[code],
+        );
+    }
+}
+
+// A zlib stream of the data as a single deflate block with the fixed Huffman codes. Repeats are found greedily from where each three bytes were last seen, which suits the long runs of a rendered grid well enough.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & 0x7fff
+    };
+    let mut last_seen: Vec<Option<usize>> = vec![None; 0x8000];
+
+    let mut bits = BitWriter::default();
+    // Final block, fixed Huffman codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+
+        if i + 3 <= data.len() {
+            let h = hash(i);
+            if let Some(previous) = last_seen[h].filter(|&p| i - p <= WINDOW) {
+                length = (0..(data.len() - i).min(MAX_MATCH))
+                    .take_while(|&k| data[previous + k] == data[i + k])
+                    .count();
+                distance = i - previous;
+            }
+            last_seen[h] = Some(i);
+        }
+
+        if length < 3 {
+            bits.write_symbol(data[i] as u32);
+            i += 1;
+            continue;
+        }
+
+        bits.write_match(length, distance);
+        for j in (i + 1)..(i + length) {
+            if j + 3 <= data.len() {
+                last_seen[hash(j)] = Some(j);
+            }
+        }
+        i += length;
+    }
+    bits.write_symbol(256);
+
+    let mut zlib: Vec<u8> = vec![0x78, 0x01];
+    zlib.extend(bits.bytes);
+    zlib.extend(adler32(data).to_be_bytes());
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Puzzle, Shape};
+    use super::{
+        adler32, crc32, render, side, zlib, BLACK, BLUE, CELL_SIZE, GREY, MARGIN, WHITE, YELLOW,
+    };
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+        assert_eq!(0xae426082, crc32(b"IEND"));
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn deflate() {
+        assert_eq!(vec![0x78, 1, 3, 0, 0, 0, 0, 1], zlib(b""));
+        assert_eq!(
+            vec![0x78, 1, 11, 46, 77, 201, 207, 46, 85, 64, 161, 20, 1, 87, 199, 7, 211],
+            zlib(b"Sudoku Sudoku Sudoku!")
+        );
+        assert_eq!(
+            vec![0x78, 1, 251, 63, 10, 70, 193, 40, 24, 246, 0, 0, 230, 233, 228, 70],
+            zlib(&[255; 1000])
+        );
+    }
+
+    #[test]
+    fn png() {
+        let png = Puzzle::parse(EASY).png();
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[0..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!((side(Shape::STANDARD) as u32).to_be_bytes(), png[16..20]);
+        assert_eq!((side(Shape::STANDARD) as u32).to_be_bytes(), png[20..24]);
+        assert_eq!(b"IEND\xae\x42\x60\x82", &png[png.len() - 8..]);
+    }
+
+    #[test]
+    fn colours() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.grid[0][0].number = Some(1);
+        let canvas = render(puzzle.shape, &puzzle.grid, &[], &[]);
+
+        let ink = |row: usize, col: usize| -> Vec<[u8; 3]> {
+            let (x, y) = (MARGIN + col * CELL_SIZE, MARGIN + row * CELL_SIZE);
+            let mut colours: Vec<[u8; 3]> = Vec::new();
+            for py in (y + 4)..(y + CELL_SIZE - 4) {
+                for px in (x + 4)..(x + CELL_SIZE - 4) {
+                    let colour = canvas.pixel(px, py);
+                    if !colours.contains(&colour) {
+                        colours.push(colour);
+                    }
+                }
+            }
+            colours
+        };
+
+        // Solved, a given, and blank
+        assert_eq!(vec![WHITE, BLUE], ink(0, 0));
+        assert_eq!(vec![WHITE, BLACK], ink(0, 1));
+        assert_eq!(vec![WHITE], ink(0, 2));
+
+        // The cells a step follows from on yellow, under their digits
+        let canvas = render(puzzle.shape, &puzzle.grid, &[], &[(0, 1), (0, 2)]);
+        assert_eq!(YELLOW, canvas.pixel(MARGIN + CELL_SIZE + 4, MARGIN + 4));
+        assert_eq!(YELLOW, canvas.pixel(MARGIN + 2 * CELL_SIZE + 4, MARGIN + 4));
+        assert_eq!(WHITE, canvas.pixel(MARGIN + 4, MARGIN + 4));
+
+        // A jigsaw's borders thick where its regions meet, thin inside them
+        let jigsaw = Puzzle::parse("1...\n....\n....\n...4\n# regions\n1112\n1222\n3334\n3444");
+        let canvas = render(jigsaw.shape, &jigsaw.grid, &[], &[]);
+        let middle = MARGIN + CELL_SIZE / 2;
+        assert_eq!(GREY, canvas.pixel(MARGIN + CELL_SIZE, middle));
+        assert_eq!(BLACK, canvas.pixel(MARGIN + 3 * CELL_SIZE + 1, middle));
+        assert_eq!(
+            BLACK,
+            canvas.pixel(MARGIN + CELL_SIZE + middle, MARGIN + CELL_SIZE - 1)
+        );
+        assert_eq!(GREY, canvas.pixel(middle, MARGIN + CELL_SIZE));
+    }
+
+    // The chunk types of a PNG, and the sequence numbers of its animation chunks
+    fn chunks(png: &[u8]) -> (Vec<String>, Vec<u32>) {
+        let (mut types, mut sequence) = (Vec::new(), Vec::new());
+
+        let mut i = 8;
+        while i < png.len() {
+            let length = u32::from_be_bytes(png[i..i + 4].try_into().unwrap()) as usize;
+            let kind = String::from_utf8(png[i + 4..i + 8].to_vec()).unwrap();
+            if kind == "fcTL" || kind == "fdAT" {
+                sequence.push(u32::from_be_bytes(png[i + 8..i + 12].try_into().unwrap()));
+            }
+            types.push(kind);
+            i += 12 + length;
+        }
+
+        (types, sequence)
+    }
+
+    #[test]
+    fn apng() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.assign_candidates();
+        puzzle.guess(0, 0, 3);
+        puzzle.guess(0, 2, 1);
+
+        let apng = puzzle.apng();
+        let (types, sequence) = chunks(&apng);
+        assert_eq!(
+            vec!["IHDR", "acTL", "fcTL", "IDAT", "fcTL", "fdAT", "fcTL", "fdAT", "IEND"],
+            types
+        );
+        assert_eq!(vec![0, 1, 2, 3, 4], sequence);
+
+        // Three frames, looping forever
+        // After the signature and the header chunk
+        let control = 8 + 25;
+        assert_eq!(b"acTL", &apng[control + 4..control + 8]);
+        assert_eq!([0, 0, 0, 3, 0, 0, 0, 0], apng[control + 8..control + 16]);
+    }
+}
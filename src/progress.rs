@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+/// How far a long job, solving a batch or generating puzzles, has got, as handed to a progress callback after each puzzle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    pub done: usize,
+    // Puzzles in the batch, or puzzles that may be tried while generating, as the most there will be
+    pub total: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    // Puzzles done a second so far.
+    pub fn rate(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.done as f64 / secs,
+            _ => 0.0,
+        }
+    }
+
+    // The time left to finish at the rate so far, or None until a puzzle is done to go by.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.done == 0 {
+            return None;
+        }
+        let left = self.total.saturating_sub(self.done) as u32;
+        Some(self.elapsed / self.done as u32 * left)
+    }
+}
+
+// Counts puzzles off towards the total, telling the callback after each.
+pub(crate) struct Tracker<F: FnMut(Progress)> {
+    progress: Progress,
+    started: Instant,
+    callback: F,
+}
+
+impl<F: FnMut(Progress)> Tracker<F> {
+    pub(crate) fn new(total: usize, callback: F) -> Tracker<F> {
+        Tracker {
+            progress: Progress {
+                done: 0,
+                total,
+                elapsed: Duration::ZERO,
+            },
+            started: Instant::now(),
+            callback,
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        self.progress.done += 1;
+        self.progress.elapsed = self.started.elapsed();
+        (self.callback)(self.progress);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::Progress;
+
+    #[test]
+    fn eta() {
+        let progress = Progress {
+            done: 10,
+            total: 40,
+            elapsed: Duration::from_secs(5),
+        };
+        assert_eq!(2.0, progress.rate());
+        assert_eq!(Some(Duration::from_secs(15)), progress.eta());
+        assert_eq!(
+            None,
+            Progress {
+                done: 0,
+                ..progress
+            }
+            .eta()
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use super::batch::{batch, Batch};
+use super::{GuessOrder, SolverConfig, Technique};
+
+/// A named configuration of the solver, its techniques and settings, to race against others on the same puzzles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    pub name: String,
+    pub config: SolverConfig,
+}
+
+impl Pipeline {
+    // Read a pipeline file, just enough TOML to set the solver up, one setting to a line:
+    //
+    //    name = "singles only"
+    //    techniques = ["naked-singles", "hidden-singles"]
+    //    logic-only = true
+    //
+    // The settings are named as the command line's options are, e.g. `guess-order`, `max-guess-depth`, `max-iterations`, `max-guesses`, and `timeout`, and the techniques may be a list or a string as `--techniques` takes them. Anything left out is as it is by default, and the name is the given one unless the file has its own.
+    pub fn parse(name: &str, text: &str) -> Result<Pipeline, String> {
+        let mut pipeline = Pipeline {
+            name: name.to_string(),
+            config: SolverConfig::default(),
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                format!("Expected a setting like `key = value` on line {}", i + 1)
+            })?;
+            let value = Value::parse(value.trim()).ok_or_else(|| {
+                format!("Can't read the value of `{}` on line {}", key.trim(), i + 1)
+            })?;
+
+            let config = &mut pipeline.config;
+            let wrong = || format!("`{}` can't be {} on line {}", key.trim(), value, i + 1);
+            match (key.trim(), &value) {
+                ("name", Value::Text(text)) => pipeline.name = text.clone(),
+                ("techniques", Value::Text(list)) => {
+                    config.techniques = Technique::parse_list(list)?
+                }
+                ("techniques", Value::List(names)) => {
+                    config.techniques = Technique::parse_list(&names.join(","))?
+                }
+                ("logic-only", Value::Bool(only)) => config.logic_only = *only,
+                ("parallel-guesses", Value::Bool(parallel)) => config.parallel_guesses = *parallel,
+                ("guess-order", Value::Text(order)) => {
+                    config.guess_order = GuessOrder::parse(order)?
+                }
+                ("max-guess-depth", &Value::Number(n)) if n >= 0.0 => {
+                    config.max_guess_depth = Some(n as usize)
+                }
+                ("max-iterations", &Value::Number(n)) if n >= 0.0 => {
+                    config.max_iterations = Some(n as usize)
+                }
+                ("max-guesses", &Value::Number(n)) if n >= 0.0 => {
+                    config.max_guess_nodes = Some(n as usize)
+                }
+                ("timeout", &Value::Number(seconds)) => {
+                    let timeout = Duration::try_from_secs_f64(seconds).map_err(|_| wrong())?;
+                    config.wall_clock_timeout = Some(timeout)
+                }
+                (
+                    "name" | "techniques" | "logic-only" | "parallel-guesses" | "guess-order"
+                    | "max-guess-depth" | "max-iterations" | "max-guesses" | "timeout",
+                    _,
+                ) => return Err(wrong()),
+                (key, _) => {
+                    return Err(format!("Unknown setting `{}` on line {}", key, i + 1));
+                }
+            }
+        }
+
+        Ok(pipeline)
+    }
+}
+
+// A value in a pipeline file: a quoted string, a list of them in square brackets, true or false, or a number.
+#[derive(Debug, PartialEq)]
+enum Value {
+    Text(String),
+    List(Vec<String>),
+    Bool(bool),
+    Number(f64),
+}
+
+impl Value {
+    fn parse(text: &str) -> Option<Value> {
+        // A comment may follow the value, though not within a string
+        let mut quoted = false;
+        let end = text
+            .char_indices()
+            .find(|&(_, c)| {
+                quoted ^= c == '"';
+                c == '#' && !quoted
+            })
+            .map_or(text.len(), |(i, _)| i);
+        let text = text[..end].trim_end();
+
+        if let Some(items) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            return items
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| match Value::parse(item)? {
+                    Value::Text(text) => Some(text),
+                    _ => None,
+                })
+                .collect::<Option<Vec<String>>>()
+                .map(Value::List);
+        }
+        if let Some(text) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+            return Some(Value::Text(text.to_string()));
+        }
+        match text {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => text.replace('_', "").parse().ok().map(Value::Number),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Text(text) => write!(f, "\"{}\"", text),
+            Value::List(_) => write!(f, "a list"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// How each pipeline fared on the same batch of puzzles, to tell whether a technique earns its time.
+pub struct Race {
+    standings: Vec<Standing>,
+}
+
+struct Standing {
+    name: String,
+    // The puzzles that could be read, those of them solved by the techniques alone, and those solved only by guessing
+    puzzles: usize,
+    solved: usize,
+    guessed: usize,
+    steps: usize,
+    elapsed: Duration,
+}
+
+impl Standing {
+    fn of(name: &str, batch: &Batch) -> Standing {
+        let results: Vec<_> = batch
+            .results
+            .iter()
+            .filter(|r| r.status != "invalid")
+            .collect();
+        let solved = |guessing: bool| {
+            results
+                .iter()
+                .filter(|r| {
+                    r.status == "solved" && r.techniques.iter().any(|t| t == "guess") == guessing
+                })
+                .count()
+        };
+
+        Standing {
+            name: name.to_string(),
+            puzzles: results.len(),
+            solved: solved(false),
+            guessed: solved(true),
+            steps: results.iter().map(|r| r.steps).sum(),
+            elapsed: results.iter().map(|r| r.elapsed).sum(),
+        }
+    }
+}
+
+// Solve every puzzle of the input, given a line to a puzzle as for `batch()`, with each pipeline in turn.
+pub fn race(input: &str, pipelines: &[Pipeline]) -> Race {
+    let standings = pipelines
+        .iter()
+        .map(|pipeline| Standing::of(&pipeline.name, &batch(input, &pipeline.config)))
+        .collect();
+
+    Race { standings }
+}
+
+impl Race {
+    // A line per pipeline under a header, lined up: the puzzles it solved without guessing and what share of them that is, those it had to guess, and the steps and time it took on average a puzzle. E.g.
+    //
+    //    pipeline   solved   rate  guessed  avg steps  avg time
+    //    singles      3/95   3.2%       92       41.5    2.1ms
+    pub fn table(&self) -> String {
+        let name_width = self
+            .standings
+            .iter()
+            .map(|s| s.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("pipeline".len());
+
+        let mut table = format!(
+            "{:<width$}  {:>9}  {:>6}  {:>7}  {:>9}  {:>9}\n",
+            "pipeline",
+            "solved",
+            "rate",
+            "guessed",
+            "avg steps",
+            "avg time",
+            width = name_width
+        );
+        for standing in self.standings.iter() {
+            let puzzles = standing.puzzles.max(1);
+            table.push_str(&format!(
+                "{:<width$}  {:>9}  {:>5.1}%  {:>7}  {:>9.1}  {:>9}\n",
+                standing.name,
+                format!("{}/{}", standing.solved, standing.puzzles),
+                100.0 * standing.solved as f64 / puzzles as f64,
+                standing.guessed,
+                standing.steps as f64 / puzzles as f64,
+                format!("{:.1?}", standing.elapsed / puzzles as u32),
+                width = name_width
+            ));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{GuessOrder, Technique};
+    use super::{race, Pipeline};
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+
+    #[test]
+    fn pipelines() {
+        let pipeline = Pipeline::parse(
+            "file",
+            "# Singles, then guess\nname = \"singles\"\ntechniques = [\"naked-singles\", \"hidden-singles\"] # cheap\nguess-order = \"ascending\"\nmax-guess-depth = 3\n",
+        )
+        .unwrap();
+        assert_eq!("singles", pipeline.name);
+        assert_eq!(
+            vec![Technique::NakedSingle, Technique::HiddenSingle],
+            pipeline.config.techniques
+        );
+        assert_eq!(GuessOrder::Ascending, pipeline.config.guess_order);
+        assert_eq!(Some(3), pipeline.config.max_guess_depth);
+
+        let pipeline = Pipeline::parse(
+            "pointing",
+            "techniques = \"singles,pointing\"\nlogic-only = true",
+        )
+        .unwrap();
+        assert_eq!("pointing", pipeline.name);
+        assert!(pipeline.config.logic_only);
+
+        assert!(Pipeline::parse("bad", "logic-only = 3").is_err());
+        assert!(Pipeline::parse("bad", "speed = 11").is_err());
+        assert!(Pipeline::parse("bad", "techniques").is_err());
+        assert!(Pipeline::parse("bad", "techniques = [\"telepathy\"]").is_err());
+        assert!(Pipeline::parse("bad", "timeout = -1").is_err());
+        assert!(Pipeline::parse("bad", "timeout = 1e300").is_err());
+        assert!(Pipeline::parse("bad", "timeout = inf").is_err());
+    }
+
+    #[test]
+    fn races() {
+        let hard = include_str!("../samples/expert3.txt");
+        let hard: String = hard
+            .lines()
+            .take(9)
+            .flat_map(|line| line.trim().chars())
+            .map(|c| if c == '_' { '.' } else { c })
+            .collect();
+        let input = format!("{}\n{}\nnot a puzzle\n", EASY, hard);
+
+        let all = Pipeline::parse("all", "").unwrap();
+        let singles = Pipeline::parse(
+            "naked singles",
+            "techniques = [\"naked-singles\"]\nlogic-only = true",
+        )
+        .unwrap();
+        let table = race(&input, &[all, singles]).table();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(3, lines.len());
+        // The line that isn't a puzzle isn't counted, and naked singles alone only solve the easy one
+        assert!(lines[0].starts_with("pipeline          solved    rate  guessed  avg steps"));
+        assert!(lines[0].ends_with("avg time"));
+        assert!(lines[1].starts_with("all                  2/2  100.0%        0"));
+        assert!(lines[2].starts_with("naked singles        1/2   50.0%        0"));
+    }
+}
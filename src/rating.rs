@@ -0,0 +1,172 @@
+use super::{LineKind, Puzzle, PuzzleStatus, SolverConfig, Technique};
+
+/// How hard a puzzle is to solve by hand, from the hardest technique it takes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    // The singles alone, with the puzzle's own clues and house rules
+    Easy,
+    // Naked subsets or pointing, along the diagonals too in X-Sudoku
+    Medium,
+    // X-Wings or the uniqueness techniques
+    Hard,
+    // Chains or aligned pairs
+    Expert,
+    // Beyond the techniques, so guessing
+    Evil,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Evil,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+            Difficulty::Evil => "evil",
+        }
+    }
+
+    // `easy`, `medium`, `hard`, `expert`, or `evil`.
+    pub fn parse(name: &str) -> Result<Difficulty, String> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown difficulty '{}'. Expected easy, medium, hard, expert, or evil",
+                    name
+                )
+            })
+    }
+
+    // The difficulty of a puzzle whose hardest technique is this one. A variant's clues are read as plainly as the singles, the sums and lines being what the variant's about.
+    pub fn of(technique: Technique) -> Difficulty {
+        match technique {
+            Technique::NakedSingle
+            | Technique::HiddenSingle
+            | Technique::Sandwich
+            | Technique::Arrow
+            | Technique::Xv
+            | Technique::Renban
+            | Technique::Whisper
+            | Technique::HouseRule => Difficulty::Easy,
+            Technique::SaraFlex | Technique::WaterCannon => Difficulty::Medium,
+            Technique::XWing
+            | Technique::UniqueRectangle
+            | Technique::UniqueLoop
+            | Technique::BugLite => Difficulty::Hard,
+            Technique::AlternatingInferenceChain
+            | Technique::AlignedPairExclusion
+            | Technique::AlmostLockedSetChain
+            | Technique::ForcingChain => Difficulty::Expert,
+        }
+    }
+}
+
+impl Puzzle {
+    // How hard the puzzle is to solve by hand. The singles and the variant's own techniques are tried alone, then with the other techniques added one at a time in the default order, and the first that solves it says how hard it is. Puzzles that can't be solved, or not just the one way, are rated evil without trying, as the techniques would get nowhere with them, and slowly on a grid near empty.
+    pub fn difficulty(&self) -> Difficulty {
+        if self.count_solutions(2) != 1 {
+            return Difficulty::Evil;
+        }
+
+        let singles = self.easiest_techniques();
+        let mut techniques = singles.clone();
+        if self.solves_with(techniques.clone()) {
+            return Difficulty::Easy;
+        }
+
+        for &technique in Technique::ALL.iter().filter(|t| !singles.contains(t)) {
+            techniques.push(technique);
+            if self.solves_with(techniques.clone()) {
+                return Difficulty::of(technique);
+            }
+        }
+
+        Difficulty::Evil
+    }
+
+    // The singles, then the techniques that read the puzzle's own clues and house rules, which any solver of the variant starts with. Those the puzzle has nothing for are left out.
+    pub(crate) fn easiest_techniques(&self) -> Vec<Technique> {
+        let lines = &self.clues.lines;
+        let has = |kind| lines.iter().any(|line| line.kind() == kind);
+        let variants = [
+            (Technique::Sandwich, !self.clues.sandwiches.is_empty()),
+            (Technique::Arrow, !self.clues.arrows.is_empty()),
+            (Technique::Xv, !self.clues.xv.is_none()),
+            (Technique::Renban, has(LineKind::Renban)),
+            (Technique::Whisper, has(LineKind::Whisper)),
+            (Technique::HouseRule, !self.config.house_rules.is_empty()),
+        ];
+
+        let mut techniques = vec![Technique::NakedSingle, Technique::HiddenSingle];
+        techniques.extend(variants.iter().filter(|(_, has)| *has).map(|&(t, _)| t));
+        techniques
+    }
+
+    // Whether the techniques alone solve the puzzle, without guessing, and quietly. The puzzle's house rules still hold.
+    pub(crate) fn solves_with(&self, techniques: Vec<Technique>) -> bool {
+        let mut puzzle = self.clone();
+        puzzle.set_config(SolverConfig {
+            logic_only: true,
+            house_rules: self.config.house_rules.clone(),
+            ..SolverConfig::with_techniques(techniques)
+        });
+        puzzle.solve().is_ok() && puzzle.status() == PuzzleStatus::Solved
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{AntiKnight, HouseRule, Puzzle, SolverConfig, Technique};
+    use super::Difficulty;
+
+    #[test]
+    fn difficulty() {
+        let easy = Puzzle::parse(include_str!("../samples/easy.txt"));
+        assert_eq!(Difficulty::Easy, easy.difficulty());
+        let evil = Puzzle::parse(include_str!("../samples/evil.txt"));
+        assert!(evil.difficulty() > Difficulty::Medium);
+
+        assert_eq!(Difficulty::Evil, Puzzle::parse("11").difficulty());
+        assert_eq!(Difficulty::Hard, Difficulty::of(Technique::XWing));
+        assert_eq!(Difficulty::Easy, Difficulty::of(Technique::Arrow));
+        assert_eq!(Ok(Difficulty::Expert), Difficulty::parse("expert"));
+        assert!(Difficulty::parse("fiendish").is_err());
+    }
+
+    #[test]
+    fn variants() {
+        // The singles and the anti-knight rule solve it, where the singles alone can't even pin it down
+        let grid =
+            ".54...2........1.........3..3...........2...5...61........9......8.4..7...2..1.4.";
+        let mut puzzle = Puzzle::parse(grid);
+        assert_eq!(2, puzzle.count_solutions(2));
+        puzzle.set_config(SolverConfig {
+            house_rules: vec![HouseRule::new(AntiKnight)],
+            ..SolverConfig::default()
+        });
+        assert_eq!(
+            vec![
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+                Technique::HouseRule
+            ],
+            puzzle.easiest_techniques()
+        );
+        assert_eq!(Difficulty::Easy, puzzle.difficulty());
+
+        let lines = Puzzle::parse(include_str!("../samples/lines.txt"));
+        let easiest = lines.easiest_techniques();
+        assert!(easiest.contains(&Technique::Renban) && easiest.contains(&Technique::Whisper));
+        assert!(!easiest.contains(&Technique::Arrow));
+    }
+}
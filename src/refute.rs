@@ -0,0 +1,208 @@
+use super::{cells_see_each_other, Consolidation, Coord, Difficulty, Puzzle};
+
+/// Why a digit can't go in a cell, as `Puzzle::refute()` argues it, the shortest way it can.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Refutation {
+    // The cell's already filled in, with this other number
+    Filled(u8),
+    // A cell the cell sees already holds the digit
+    Peer(Coord),
+    // The pencil marks noted in the cell already leave it out
+    Noted,
+    // The last of these moves rules it out, those before leading up to it
+    Steps(Vec<Consolidation>),
+    // The techniques can't rule it out, but no solution has it there
+    NoSolution,
+    // It isn't wrong: the digit's there in a solution
+    Possible,
+}
+
+impl Refutation {
+    // The argument in words, a line to a move for `Steps`, e.g. "R1C4 already holds it."
+    pub fn describe(&self) -> String {
+        match self {
+            Refutation::Filled(number) => format!("The cell already holds {}.", number),
+            Refutation::Peer(peer) => {
+                format!("{} already holds it.", peer.to_string().to_uppercase())
+            }
+            Refutation::Noted => "The pencil marks already rule it out.".to_string(),
+            Refutation::Steps(steps) => steps
+                .iter()
+                .map(Consolidation::describe)
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Refutation::NoSolution => {
+                "Nothing the techniques find rules it out, but no solution has it there."
+                    .to_string()
+            }
+            Refutation::Possible => "It can: a solution has it there.".to_string(),
+        }
+    }
+
+    // Whether the digit really can't go in the cell.
+    pub fn is_refuted(&self) -> bool {
+        *self != Refutation::Possible
+    }
+}
+
+impl Puzzle {
+    // Why the digit can't go in the cell, by the shortest argument to hand: a number already in the cell or a cell it sees, then a move of the configured techniques, the simplest first, that rules it out straight away. Failing those, the simplest move that gets anywhere is taken and the techniques tried again, until one rules it out, and the moves leading up to it are part of the argument. Candidates are worked out as for `hint()`, following on from any pencil marks. The puzzle is left as is.
+    pub fn refute(&self, coord: Coord, digit: u8) -> Refutation {
+        match self[coord].number {
+            Some(number) if number == digit => return Refutation::Possible,
+            Some(number) => return Refutation::Filled(number),
+            None => {}
+        }
+        if let Some(peer) = self.peer_holding(coord, digit) {
+            return Refutation::Peer(peer);
+        }
+
+        let mut puzzle = self.noted();
+        puzzle.config.verbose = false;
+        if !puzzle[coord].candidates.contains(digit) {
+            return Refutation::Noted;
+        }
+
+        let mut techniques = self.config.techniques.clone();
+        techniques.sort_by_key(|&technique| Difficulty::of(technique));
+        let mut steps = vec![];
+        loop {
+            for technique in techniques.iter() {
+                let mut trial = puzzle.clone();
+                let found = technique.apply(&mut trial);
+                if !trial.noted().rules_out(coord, digit) {
+                    continue;
+                }
+
+                let refuting = found.iter().rev().find(|c| self.refutes(c, coord, digit));
+                steps.extend(refuting.or(found.last()).cloned());
+                return Refutation::Steps(steps);
+            }
+
+            let progress = techniques.iter().find_map(|technique| {
+                let found = technique.apply(&mut puzzle);
+                (!found.is_empty()).then_some(found)
+            });
+            match progress {
+                Some(found) => steps.extend(found),
+                None => break,
+            }
+            puzzle = puzzle.noted();
+        }
+
+        let mut placed = self.clone();
+        placed[coord].number = Some(digit);
+        match placed.count_solutions(1) {
+            0 => Refutation::NoSolution,
+            _ => Refutation::Possible,
+        }
+    }
+
+    // A cell the cell sees that holds the digit.
+    fn peer_holding(&self, coord: Coord, digit: u8) -> Option<Coord> {
+        self.shape.coords().find(|&peer| {
+            peer != coord
+                && self[peer].number == Some(digit)
+                && cells_see_each_other(self.shape, coord.into(), peer.into())
+        })
+    }
+
+    // Whether the digit's gone from the cell, or the cell's been filled in with another.
+    fn rules_out(&self, coord: Coord, digit: u8) -> bool {
+        match self[coord].number {
+            Some(number) => number != digit,
+            None => !self[coord].candidates.contains(digit),
+        }
+    }
+
+    // Whether the move is the one that rules out the digit: taking it from the cell, filling in the cell with another, or putting it in a cell the cell sees.
+    fn refutes(&self, consolidation: &Consolidation, coord: Coord, digit: u8) -> bool {
+        let position = <(usize, usize)>::from(coord);
+        match (consolidation, consolidation.assignment()) {
+            (_, Some((at, number))) if at == position => number != digit,
+            (_, Some((at, number))) => {
+                number == digit && cells_see_each_other(self.shape, position, at)
+            }
+            (Consolidation::CandidatesEliminated { cells, digits, .. }, None) => {
+                cells.contains(&position) && digits.contains(&digit)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Consolidation, Coord, Puzzle, SolverConfig, Technique};
+    use super::Refutation;
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    #[test]
+    fn refute() {
+        let puzzle = Puzzle::parse(EASY);
+        let solution = puzzle.solutions(1).remove(0);
+        let given = puzzle.givens()[0];
+        let number = puzzle[given].number.unwrap();
+
+        // The cell's filled in, or a cell it sees holds the digit
+        assert_eq!(Refutation::Possible, puzzle.refute(given, number));
+        let other = number % 9 + 1;
+        assert_eq!(Refutation::Filled(number), puzzle.refute(given, other));
+        let blank = puzzle
+            .shape()
+            .coords()
+            .find(|&c| c.row == given.row && puzzle[c].number.is_none())
+            .unwrap();
+        assert_eq!(Refutation::Peer(given), puzzle.refute(blank, number));
+        assert!(puzzle
+            .refute(blank, number)
+            .describe()
+            .ends_with(" already holds it."));
+
+        // The solution's digit can't be refuted, where any other can, ending in the move that rules it out
+        let answer = solution[blank].number.unwrap();
+        assert_eq!(Refutation::Possible, puzzle.refute(blank, answer));
+        for digit in (1..=9).filter(|&d| d != answer) {
+            let refutation = puzzle.refute(blank, digit);
+            assert!(refutation.is_refuted());
+            if let Refutation::Steps(steps) = refutation {
+                let last = steps.last().unwrap();
+                let position = (blank.row, blank.col);
+                assert!(
+                    last.assignment().is_some()
+                        || matches!(last, Consolidation::CandidatesEliminated { cells, digits, .. }
+                            if cells.contains(&position) && digits.contains(&digit))
+                );
+            }
+        }
+
+        // Without the techniques, only a search can tell
+        let mut stuck = puzzle.clone();
+        stuck.set_config(SolverConfig::with_techniques(vec![]));
+        let (cell, candidates) = puzzle
+            .shape()
+            .coords()
+            .map(|c| (c, puzzle.noted()[c].candidates))
+            .find(|(_, candidates)| candidates.count() > 1)
+            .unwrap();
+        let answer = solution[cell].number.unwrap();
+        let wrong = candidates.iter().find(|&d| d != answer).unwrap();
+        assert_eq!(Refutation::NoSolution, stuck.refute(cell, wrong));
+        assert_eq!(Refutation::Possible, stuck.refute(cell, answer));
+
+        // A naked subset rules it out in one move
+        let mut marked = Puzzle::parse("");
+        marked.set_config(SolverConfig::with_techniques(vec![Technique::SaraFlex]));
+        for col in 0..2 {
+            marked.grid[0][col].candidates = [1, 2].iter().collect();
+        }
+        match marked.refute(Coord::new(0, 5), 1) {
+            Refutation::Steps(steps) => {
+                assert_eq!(1, steps.len());
+                assert_eq!(Some(Technique::SaraFlex), steps[0].technique());
+            }
+            refutation => panic!("Expected a naked subset, not {:?}", refutation),
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use super::{
+    symbol, Clues, Consolidation, Coord, Grid, PuzzleStatus, Shape, Solution, Step, Theme,
+};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+section { margin-bottom: 3em; }
+table.grid { border-collapse: collapse; border: 3px solid #222; }
+table.grid td { width: 3.2em; height: 3.2em; border: 1px solid #aaa; text-align: center; vertical-align: middle; padding: 0; }
+td.number { font-size: 1.6em; color: #1a4fa0; }
+td.given { color: #222; font-weight: bold; }
+td.assigned { background: #bfe8bf; }
+td.reason { background: #fdf0b5; }
+td.eliminated { background: #f8d4d4; }
+.candidates { display: grid; font-size: 0.7em; color: #555; }
+.candidates s { color: #c0392b; font-weight: bold; }
+.candidates b { color: #8a6d00; border-radius: 50%; box-shadow: inset 0 0 0 1px #8a6d00; }
+table.grid th { font-weight: normal; color: #555; padding: 0 0.5em; }
+table.grid.sandwiches { border: none; }
+table.grid.sandwiches tbody tr:first-child td { border-top: 3px solid #222; }
+table.grid.sandwiches td:first-of-type { border-left: 3px solid #222; }
+td.circle { background-image: radial-gradient(circle, transparent 56%, #888 58%, #888 62%, transparent 64%); }
+td.arrow { background-image: radial-gradient(circle, #aaa 10%, transparent 12%); }
+td.renban { box-shadow: inset 0 0 0 0.6em #e4d3f0; }
+td.whisper { box-shadow: inset 0 0 0 0.6em #cdeecd; }
+td[data-right], td[data-below] { position: relative; }
+td[data-right]::after, td[data-below]::before { position: absolute; z-index: 1; font-size: 0.9rem; font-weight: bold; color: #222; background: #fff; line-height: 1; }
+td[data-right]::after { content: attr(data-right); right: -0.35rem; top: calc(50% - 0.45rem); }
+td[data-below]::before { content: attr(data-below); bottom: -0.45rem; left: calc(50% - 0.35rem); }
+";
+
+// The theme's colors in place of the default's, for the numbers and the highlights that explain each step.
+fn theme_style(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Default => "",
+        Theme::Deuteranopia => {
+            "
+td.number { color: #0072b2; }
+td.assigned { background: #c4e3f6; }
+td.reason { background: #f7f0a0; }
+td.eliminated { background: #f5c7a1; }
+.candidates s { color: #d55e00; }
+.candidates b { color: #222; box-shadow: inset 0 0 0 1px #222; }
+"
+        }
+        Theme::HighContrast => {
+            "
+td.number, td.given, .candidates { color: #000; }
+td.assigned { background: #5fff5f; }
+td.reason { background: #ffff00; }
+td.eliminated { background: #ff7070; }
+.candidates s { color: #a00000; }
+.candidates b { color: #000; box-shadow: inset 0 0 0 2px #000; }
+"
+        }
+        Theme::Mono => {
+            "
+td.number, td.given, .candidates, .candidates s, .candidates b { color: #000; }
+td.assigned { background: #fff; box-shadow: inset 0 0 0 3px #000; }
+td.reason { background: repeating-linear-gradient(45deg, #fff 0 4px, #ddd 4px 8px); }
+td.eliminated { background: #ccc; }
+.candidates b { box-shadow: inset 0 0 0 1px #000; }
+"
+        }
+    }
+}
+
+// The borders between blocks, and the candidates laid out as the cells of a block, which depend on the shape of the grid.
+fn block_style(shape: Shape) -> String {
+    // A jigsaw's regions are bordered cell by cell instead
+    if shape.is_jigsaw() {
+        return format!(
+            ".candidates {{ grid-template-columns: repeat({}, 1fr); }}\n",
+            shape.block_cols()
+        );
+    }
+
+    format!(
+        "table.grid tr:nth-child({}n) td {{ border-bottom: 3px solid #222; }}
+table.grid td:nth-of-type({}n) {{ border-right: 3px solid #222; }}
+.candidates {{ grid-template-columns: repeat({}, 1fr); }}
+",
+        shape.block_rows(),
+        shape.block_cols(),
+        shape.block_cols()
+    )
+}
+
+impl Solution {
+    // A self-contained HTML page walking through the solve: the grid going into each step, with the cells acted on highlighted, the eliminated candidates struck through, and each consolidation explained.
+    //
+    //    * Green: cells assigned a number
+    //    * Red: cells losing candidates
+    //    * Yellow: the cells justifying it all, the digits they lock in ringed
+    pub fn html(&self) -> String {
+        self.html_with(Theme::Default)
+    }
+
+    // The walkthrough in the theme's colors, e.g. blue, orange, and yellow for `Theme::Deuteranopia`.
+    pub fn html_with(&self, theme: Theme) -> String {
+        let status = match self.status() {
+            PuzzleStatus::Solved => "Solved".to_string(),
+            PuzzleStatus::Unsolved => "Unsolved".to_string(),
+            PuzzleStatus::IllDefined(reason) => format!("Ill-defined: {:?}", reason),
+        };
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Sudoku walkthrough</title>\n");
+        html.push_str(&format!(
+            "<style>{}{}{}</style>\n",
+            STYLE,
+            theme_style(theme),
+            block_style(self.shape())
+        ));
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!(
+            "<h1>Sudoku walkthrough</h1>\n<p>{} in {} steps.</p>\n",
+            status,
+            self.steps().len()
+        ));
+
+        for step in self.steps() {
+            html.push_str(&html_step(self.shape(), self.clues(), step));
+        }
+
+        if let Some(last) = self.steps().last() {
+            html.push_str("<section>\n<h2>Final grid</h2>\n");
+            html.push_str(&html_grid(self.shape(), self.clues(), &last.after, &[]));
+            html.push_str("</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn html_step(shape: Shape, clues: &Clues, step: &Step) -> String {
+    let sentences: Vec<String> = step
+        .consolidations
+        .iter()
+        .map(|c| format!("<li>{}</li>", escape(&c.describe())))
+        .collect();
+
+    format!(
+        "<section>\n<h2>Step {}</h2>\n{}<ol>\n{}\n</ol>\n</section>\n",
+        step.iteration,
+        html_grid(shape, clues, &step.before, &step.consolidations),
+        sentences.join("\n")
+    )
+}
+
+fn html_grid(shape: Shape, clues: &Clues, grid: &Grid, consolidations: &[Consolidation]) -> String {
+    let mut assigned: Vec<((usize, usize), u8)> = Vec::new();
+    let mut eliminated: Vec<((usize, usize), u8)> = Vec::new();
+    let mut reasons: Vec<(usize, usize)> = Vec::new();
+    // The digits the pair, fish, or chain pins down, whose eliminations follow from them
+    let mut locked: Vec<((usize, usize), u8)> = Vec::new();
+
+    for consolidation in consolidations {
+        if let Some(assignment) = consolidation.assignment() {
+            assigned.push(assignment);
+        }
+
+        if let Consolidation::CandidatesEliminated { cells, digits, .. } = consolidation {
+            for &cell in cells {
+                for &digit in digits {
+                    eliminated.push((cell, digit));
+                }
+            }
+            for cell in consolidation.defining_cells() {
+                for &digit in digits {
+                    locked.push((cell, digit));
+                }
+            }
+        }
+        reasons.extend(consolidation.defining_cells());
+    }
+
+    // Arrow sudoku's circles are ringed, and the cells along their arrows dotted
+    let circles: Vec<(usize, usize)> = clues
+        .arrows
+        .iter()
+        .map(|arrow| arrow.circle().into())
+        .collect();
+    let arrows: Vec<(usize, usize)> = clues
+        .arrows
+        .iter()
+        .flat_map(|arrow| arrow.path())
+        .map(Into::into)
+        .collect();
+
+    // Cells along a renban or whisper line are edged in its colour
+    let lines: Vec<((usize, usize), &str)> = clues
+        .lines
+        .iter()
+        .flat_map(|line| {
+            line.cells()
+                .into_iter()
+                .map(move |coord| (coord.into(), line.kind().name()))
+        })
+        .collect();
+
+    // Sandwich sudoku's sums go outside the grid, in a row of headers above it and a column to its left
+    let sandwiches = clues.sandwiches;
+    let sum = |sum: Option<u16>| {
+        format!(
+            "<th>{}</th>",
+            sum.map_or(String::new(), |sum| sum.to_string())
+        )
+    };
+
+    let mut html = match sandwiches.is_empty() {
+        true => String::from("<table class=\"grid\">\n"),
+        false => {
+            let columns: String = sandwiches.columns[..shape.size()]
+                .iter()
+                .map(|&s| sum(s))
+                .collect();
+            format!(
+                "<table class=\"grid sandwiches\">\n<thead><tr><th></th>{}</tr></thead>\n<tbody>\n",
+                columns
+            )
+        }
+    };
+    for (row, cells) in grid[..shape.size()].iter().enumerate() {
+        html.push_str("<tr>");
+        if !sandwiches.is_empty() {
+            html.push_str(&sum(sandwiches.rows[row]));
+        }
+        for (col, cell) in cells[..shape.size()].iter().enumerate() {
+            let position = (row, col);
+
+            let assignment = assigned.iter().find(|(p, _)| *p == position);
+
+            let mut classes: Vec<&str> = Vec::new();
+            if cell.number.is_some() || assignment.is_some() {
+                classes.push("number");
+            }
+            if cell.given {
+                classes.push("given");
+            }
+            if circles.contains(&position) {
+                classes.push("circle");
+            } else if arrows.contains(&position) {
+                classes.push("arrow");
+            }
+            if let Some(&(_, kind)) = lines.iter().find(|(p, _)| *p == position) {
+                classes.push(kind);
+            }
+            if assignment.is_some() {
+                classes.push("assigned");
+            } else if eliminated.iter().any(|(p, _)| *p == position) {
+                classes.push("eliminated");
+            } else if reasons.contains(&position) {
+                classes.push("reason");
+            }
+
+            let contents = match (cell.number, assignment) {
+                (Some(number), _) | (None, Some(&(_, number))) => symbol(number).to_string(),
+                (None, None) => {
+                    let candidates: Vec<String> = (1..=shape.size() as u8)
+                        .map(|digit| {
+                            if !cell.candidates.contains(digit) {
+                                "<span></span>".to_string()
+                            } else if eliminated.contains(&(position, digit)) {
+                                format!("<s>{}</s>", symbol(digit))
+                            } else if locked.contains(&(position, digit)) {
+                                format!("<b>{}</b>", symbol(digit))
+                            } else {
+                                format!("<span>{}</span>", symbol(digit))
+                            }
+                        })
+                        .collect();
+                    format!("<div class=\"candidates\">{}</div>", candidates.join(""))
+                }
+            };
+
+            let mut attributes = String::new();
+            if !classes.is_empty() {
+                attributes.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+            }
+            if shape.is_jigsaw() {
+                let coord = Coord::new(row, col);
+                let mut borders = String::new();
+                if shape.border_right(coord) {
+                    borders.push_str("border-right: 3px solid #222; ");
+                }
+                if shape.border_below(coord) {
+                    borders.push_str("border-bottom: 3px solid #222; ");
+                }
+                if !borders.is_empty() {
+                    attributes.push_str(&format!(" style=\"{}\"", borders.trim_end()));
+                }
+            }
+
+            // XV sudoku's marks sit on the borders to the right of and below their first cells
+            let xv = &clues.xv;
+            let coord = Coord::new(row, col);
+            for (side, next) in [("right", (row, col + 1)), ("below", (row + 1, col))] {
+                if let Some(mark) = xv.between(coord, next.into()) {
+                    attributes.push_str(&format!(" data-{}=\"{}\"", side, mark.letter()));
+                }
+            }
+            html.push_str(&format!("<td{}>{}</td>", attributes, contents));
+        }
+        html.push_str("</tr>\n");
+    }
+    if !sandwiches.is_empty() {
+        html.push_str("</tbody>\n");
+    }
+    html.push_str("</table>\n");
+
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Consolidation, Justification, Puzzle, Technique, Theme, Unit};
+
+    #[test]
+    fn highlights_the_step() {
+        let mut puzzle = Puzzle::parse("");
+        puzzle.assign_candidates();
+        puzzle.guess(0, 1, 3);
+
+        puzzle.iteration += 1;
+        let before = puzzle.grid;
+        puzzle.grid[0][5].remove_candidate(4);
+        puzzle.last_consolidation = vec![Consolidation::CandidatesEliminated {
+            technique: Technique::WaterCannon,
+            cells: vec![(0, 5)],
+            digits: vec![4],
+            justification: Justification::LockedCells(Unit::Block(0), vec![(0, 0), (0, 2)]),
+        }];
+        puzzle.record_step(before);
+
+        let html = puzzle.solution().html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h2>Step 1</h2>"));
+        assert!(html.contains("<h2>Step 2</h2>"));
+        assert!(html.contains("<td class=\"number assigned\">3</td>"));
+        assert!(html
+            .contains("<li>Guess R1C2 is 3, to be abandoned if it leads to a contradiction.</li>"));
+        assert!(html.contains("<li>R1C6 can't be 4: in box 1, 4 must go in R1C1 or R1C3.</li>"));
+        assert_eq!(1, html.matches("<s>4</s>").count());
+        assert_eq!(2, html.matches("<b>4</b>").count());
+        assert_eq!(2, html.matches("<td class=\"reason\">").count());
+        assert_eq!(1, html.matches("<td class=\"eliminated\">").count());
+        assert!(html.contains("<h2>Final grid</h2>"));
+
+        // Themes restyle the highlights, leaving the grid itself as it is
+        let mono = puzzle.solution().html_with(Theme::Mono);
+        assert!(mono.contains("td.eliminated { background: #ccc; }"));
+        assert!(!html.contains("#ccc"));
+        assert_eq!(html.split("</style>").nth(1), mono.split("</style>").nth(1));
+
+        // A jigsaw's regions are bordered cell by cell
+        assert!(!html.contains(" style="));
+        let mut jigsaw = Puzzle::parse("1...\n....\n....\n...4\n# regions\n1112\n1222\n3334\n3444");
+        jigsaw.assign_candidates();
+        jigsaw.step();
+        let html = jigsaw.solution().html();
+        assert!(!html.contains("nth-"));
+        assert!(html.contains(" style=\"border-bottom: 3px solid #222;\">"));
+        assert!(html
+            .contains(" style=\"border-right: 3px solid #222; border-bottom: 3px solid #222;\">"));
+        assert!(!html.contains("<th>"));
+
+        // Sandwich sums head the columns and start the rows
+        let mut sandwich =
+            Puzzle::parse("1...\n....\n....\n...4\n# sandwiches\nrows 2 . . 0\ncolumns . 5 . .");
+        sandwich.assign_candidates();
+        sandwich.step();
+        let html = sandwich.solution().html();
+        assert!(html.contains(
+            "<table class=\"grid sandwiches\">\n<thead><tr><th></th><th></th><th>5</th><th></th><th></th></tr></thead>"
+        ));
+        assert!(html.contains("<tr><th>2</th><td"));
+        assert!(html.contains("<tr><th></th><td"));
+
+        // Arrows' circles are ringed, and the cells along them dotted
+        let mut arrow = Puzzle::parse("1...\n....\n....\n...4\n# arrows\nr1c2 r2c1\n");
+        arrow.assign_candidates();
+        arrow.step();
+        let html = arrow.solution().html();
+        assert!(html.contains("<tr><td class=\"number given\">1</td><td class=\"circle"));
+        assert!(html.contains("<tr><td class=\"arrow"));
+
+        // XV marks sit on the borders of the cells before them
+        let mut xv = Puzzle::parse("1...\n....\n....\n...4\n# xv\nV r1c1 r1c2\nV r1c3 r2c3\n");
+        xv.assign_candidates();
+        xv.step();
+        let html = xv.solution().html();
+        assert!(html.contains("<tr><td class=\"number given\" data-right=\"V\">1</td>"));
+        assert!(html.contains(" data-below=\"V\">"));
+
+        // Cells along lines are edged in their colour
+        let mut lines =
+            Puzzle::parse("1...\n....\n....\n...4\n# lines\nrenban r1c1 r2c2\nwhisper r4c3 r3c3\n");
+        lines.assign_candidates();
+        lines.step();
+        let html = lines.solution().html();
+        assert!(html.contains("<tr><td class=\"number given renban\">1</td>"));
+        assert!(html.contains("<td class=\"whisper eliminated\">"));
+    }
+}
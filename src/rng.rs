@@ -0,0 +1,56 @@
+// Random numbers drawn from a seed, the same every time for the same seed, on any machine. Guessing in a random order and generating puzzles both draw on it, so any of their results can be had again from the seed alone.
+//
+// It's splitmix64: small and quick, and plenty random enough for shuffling.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // A number from 0 up to but not including `n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // A Fisher-Yates shuffle.
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rng;
+
+    #[test]
+    fn seeded() {
+        let draw = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| rng.next_u64()).collect::<Vec<u64>>()
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+
+        // The first of splitmix64 from 0, as published
+        assert_eq!(0xe220a8397b1dcdaf, Rng::new(0).next_u64());
+
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        Rng::new(7).shuffle(&mut digits);
+        let mut sorted = digits;
+        sorted.sort();
+        assert_eq!([1, 2, 3, 4, 5, 6, 7, 8, 9], sorted);
+    }
+}
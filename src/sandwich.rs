@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use super::{
+    Candidates, Consolidation, Constraint, Coord, IllDefinedReason, Justification, Puzzle, Shape,
+    Technique, Unit, MAX_SIZE,
+};
+
+/// Sandwich sudoku's clues, written outside the grid: for each row and column, the sum of the digits between its 1 and its largest digit, the 9 of a 9x9 grid, where there's one given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sandwiches {
+    pub rows: [Option<u16>; MAX_SIZE],
+    pub columns: [Option<u16>; MAX_SIZE],
+}
+
+impl Sandwiches {
+    pub const NONE: Sandwiches = Sandwiches {
+        rows: [None; MAX_SIZE],
+        columns: [None; MAX_SIZE],
+    };
+
+    // The sum given for the row or column, if any. Other units have none.
+    pub fn of(&self, unit: Unit) -> Option<u16> {
+        match unit {
+            Unit::Row(row) => self.rows[row],
+            Unit::Column(col) => self.columns[col],
+            Unit::Block(_) | Unit::Diagonal(_) => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Sandwiches::NONE
+    }
+}
+
+impl Default for Sandwiches {
+    fn default() -> Sandwiches {
+        Sandwiches::NONE
+    }
+}
+
+// Every set of `count` different digits, none of them the 1 or the largest, adding up to the sum: what the cells between a line's 1 and largest digit could hold.
+fn fillings(size: usize, count: usize, sum: u16) -> Vec<Candidates> {
+    let mut found = vec![];
+    fill(
+        2,
+        size as u8 - 1,
+        count,
+        sum,
+        Candidates::none(),
+        &mut found,
+    );
+    found
+}
+
+fn fill(from: u8, to: u8, count: usize, sum: u16, chosen: Candidates, found: &mut Vec<Candidates>) {
+    if count == 0 {
+        if sum == 0 {
+            found.push(chosen);
+        }
+        return;
+    }
+
+    for digit in (from..=to).take_while(|&digit| u16::from(digit) <= sum) {
+        let mut next = chosen;
+        next.insert(digit);
+        fill(
+            digit + 1,
+            to,
+            count - 1,
+            sum - u16::from(digit),
+            next,
+            found,
+        );
+    }
+}
+
+// What each cell of a line could hold in some arrangement of its sandwich: the 1 and the largest digit in cells that allow them, digits adding up to the sum between them, and the rest outside. Cells are given as the digits they could be, a number as itself alone. Every cell comes back empty when nothing fits.
+//
+// Each cell need only allow some digit of its part of the arrangement, so a little more may be kept than could really go, but nothing that could is lost.
+pub(super) fn fits(size: usize, sum: u16, cells: &[Candidates]) -> Vec<Candidates> {
+    let (low, high) = (1, size as u8);
+    let crusts: Candidates = [low, high].into_iter().collect();
+    let digits: Candidates = (1..=high).collect();
+    let by_count: Vec<Vec<Candidates>> = (0..size.saturating_sub(1))
+        .map(|count| fillings(size, count, sum))
+        .collect();
+
+    let mut fits = vec![Candidates::none(); cells.len()];
+    for a in (0..size).filter(|&a| cells[a].contains(low)) {
+        for b in (0..size).filter(|&b| b != a && cells[b].contains(high)) {
+            let (from, to) = (a.min(b), a.max(b));
+            for &filling in &by_count[to - from - 1] {
+                let outside = digits.difference(filling).difference(crusts);
+                let part = |i: usize| -> Candidates {
+                    if i == a {
+                        [low].into_iter().collect()
+                    } else if i == b {
+                        [high].into_iter().collect()
+                    } else if from < i && i < to {
+                        filling
+                    } else {
+                        outside
+                    }
+                };
+
+                if (0..size).all(|i| !cells[i].intersection(part(i)).is_empty()) {
+                    for (i, fit) in fits.iter_mut().enumerate() {
+                        *fit = fit.union(cells[i].intersection(part(i)));
+                    }
+                }
+            }
+        }
+    }
+
+    fits
+}
+
+// The sums are one constraint together: in each row and column with a sum given, try its 1 and largest digit in every pair of cells that could hold them, with every set of digits adding up to the sum between. A candidate no arrangement has a place for can go.
+//
+// Eliminates a row or column at a time.
+impl Constraint for Sandwiches {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        for unit in puzzle.shape.units() {
+            let Some(sum) = self.of(unit) else {
+                continue;
+            };
+
+            let positions = unit.cells(puzzle.shape);
+            let cells: Vec<Candidates> = positions
+                .iter()
+                .map(|&(row, col)| match puzzle.grid[row][col].number {
+                    Some(number) => [number].into_iter().collect(),
+                    None => puzzle.grid[row][col].candidates,
+                })
+                .collect();
+
+            let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+            for (&(row, col), fit) in positions.iter().zip(fits(puzzle.shape.size(), sum, &cells)) {
+                if puzzle.grid[row][col].number.is_some() {
+                    continue;
+                }
+
+                for digit in puzzle.grid[row][col].candidates.difference(fit).iter() {
+                    puzzle.grid[row][col].remove_candidate(digit);
+                    log!(puzzle, "🥪🥪🥪🥪🥪 Sandwich of {} in {:?} eliminated candidate {} at grid position ({}, {})", sum, unit, digit, row, col);
+                    removals.push(((row, col), digit));
+                }
+            }
+
+            if !removals.is_empty() {
+                return Consolidation::eliminations(
+                    Technique::Sandwich,
+                    Justification::Sandwich(unit, sum),
+                    &removals,
+                );
+            }
+        }
+
+        vec![]
+    }
+
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        self.broken(puzzle)
+            .first()
+            .map(|&unit| IllDefinedReason::SandwichDoesNotAddUp(unit))
+    }
+
+    fn fits(&self, shape: Shape, cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        let mut found = vec![];
+        for unit in shape.units() {
+            let Some(sum) = self.of(unit) else {
+                continue;
+            };
+            let positions: Vec<Coord> = unit.cells(shape).into_iter().map(Coord::from).collect();
+            let cells: Vec<Candidates> = positions.iter().map(|&coord| cell(coord)).collect();
+            found.extend(positions.into_iter().zip(fits(shape.size(), sum, &cells)));
+        }
+        found
+    }
+}
+
+impl Sandwiches {
+    // The rows and columns whose 1, largest digit, and every digit between are placed, but don't add up to their sum.
+    fn broken(&self, puzzle: &Puzzle) -> Vec<Unit> {
+        let shape = puzzle.shape;
+        shape
+            .units()
+            .into_iter()
+            .filter(|&unit| {
+                let Some(sum) = self.of(unit) else {
+                    return false;
+                };
+                let numbers: Vec<Option<u8>> = unit
+                    .cells(shape)
+                    .iter()
+                    .map(|&(row, col)| puzzle.grid[row][col].number)
+                    .collect();
+                let at = |digit: u8| numbers.iter().position(|&n| n == Some(digit));
+
+                match (at(1), at(shape.size() as u8)) {
+                    (Some(a), Some(b)) => {
+                        let between = &numbers[a.min(b) + 1..a.max(b)];
+                        between.iter().all(Option::is_some)
+                            && between.iter().flatten().map(|&n| u16::from(n)).sum::<u16>() != sum
+                    }
+                    _ => false,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Puzzle {
+    // Sandwich sums: the digits between each row and column's 1 and largest digit must add up to its sum, where there's one given.
+    //
+    // Returns the eliminations made, a line at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_sandwiches(&mut self) -> Vec<Consolidation> {
+        if self.clues.sandwiches.is_empty() {
+            return vec![];
+        }
+
+        let clues = Arc::clone(&self.clues);
+        clues.sandwiches.prune(self)
+    }
+
+    // The rows and columns that don't add up to their sum.
+    pub(super) fn wrong_sandwiches(&self) -> Vec<Unit> {
+        self.clues.sandwiches.broken(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Puzzle, PuzzleStatus, Shape, SolverConfig, Technique, Unit};
+    use super::{fillings, fits, Sandwiches};
+
+    #[test]
+    fn arrangements() {
+        let set = |digits: &[u8]| -> Candidates { digits.iter().collect() };
+
+        assert_eq!(
+            vec![set(&[2, 3]), set(&[])],
+            [fillings(9, 2, 5), fillings(9, 0, 0)].concat()
+        );
+        assert_eq!(vec![set(&[2, 3, 4, 5, 6, 7, 8])], fillings(9, 7, 35));
+        assert!(fillings(9, 1, 9).is_empty());
+
+        // 0 takes the 1 and 9 side by side, and 35 at either end
+        let blank = vec![Shape::STANDARD.digits(); 9];
+        let zero = fits(9, 0, &blank);
+        assert!(zero.iter().all(|fit| fit.contains(1) && fit.contains(9)));
+        let full = fits(9, 35, &blank);
+        assert_eq!(set(&[1, 9]), full[0]);
+        assert_eq!(set(&[2, 3, 4, 5, 6, 7, 8]), full[4]);
+        assert_eq!(set(&[1, 9]), full[8]);
+
+        // With the 1 placed third, a sum of 2 needs the 9 two along from it
+        let mut cells = blank.clone();
+        cells[2] = set(&[1]);
+        let two = fits(9, 2, &cells);
+        let nines: Vec<usize> = (0..9).filter(|&i| two[i].contains(9)).collect();
+        assert_eq!(vec![0, 4], nines);
+        assert!(two[1].contains(2) && two[3].contains(2));
+        assert!(!two[5].contains(2));
+
+        // Nothing fits a sum too big for the cells
+        cells[0] = set(&[9]);
+        assert!(fits(9, 20, &cells).iter().all(Candidates::is_empty));
+    }
+
+    #[test]
+    fn sandwiches() {
+        let mut sandwiches = Sandwiches::NONE;
+        assert!(sandwiches.is_empty());
+        sandwiches.rows[0] = Some(35);
+        sandwiches.columns[3] = Some(0);
+        assert_eq!(Some(35), sandwiches.of(Unit::Row(0)));
+        assert_eq!(Some(0), sandwiches.of(Unit::Column(3)));
+        assert_eq!(None, sandwiches.of(Unit::Row(3)));
+        assert_eq!(None, sandwiches.of(Unit::Block(0)));
+
+        // A 1 and 9 at either end of the first row, and the second row's 1 and 9 already too far apart for 0
+        let mut puzzle = Puzzle::parse("");
+        sandwiches.rows[1] = Some(0);
+        puzzle.set_sandwiches(sandwiches);
+        puzzle.grid[1][0].number = Some(1);
+        puzzle.grid[1][4].number = Some(9);
+        puzzle.config = SolverConfig::with_techniques(vec![Technique::Sandwich]);
+        puzzle.assign_candidates();
+        let found = puzzle.reduce_candidates_with_sandwiches();
+        assert!(!found.is_empty());
+        assert_eq!(
+            vec![1, 9],
+            puzzle.grid[0][0]
+                .candidates
+                .union(puzzle.grid[0][8].candidates)
+                .to_vec()
+        );
+        assert!(!puzzle.grid[0][4].candidates.contains(1));
+
+        let mut row = puzzle.clone();
+        while !row.reduce_candidates_with_sandwiches().is_empty() {}
+        assert!(matches!(row.status(), PuzzleStatus::IllDefined(_)));
+
+        // Filled in, the digits between must add up
+        let mut filled = puzzle.clone();
+        filled.set_sandwiches(Sandwiches::NONE);
+        filled.grid[1][0].number = None;
+        filled.grid[1][4].number = None;
+        let mut solved = filled.solutions(1).remove(0);
+        assert!(solved.wrong_sandwiches().is_empty());
+        let mut sums = Sandwiches::NONE;
+        sums.columns[0] = Some(1);
+        solved.set_sandwiches(sums);
+        assert_eq!(vec![Unit::Column(0)], solved.wrong_sandwiches());
+    }
+}
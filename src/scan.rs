@@ -0,0 +1,608 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, DynamicImage, GrayImage, ImageDecoder, ImageReader};
+
+use super::{Candidates, Cell, Puzzle, Shape, MAX_SIZE};
+
+// Photos are shrunk to this many pixels along their longer side before being looked at, which is plenty to make out printed digits
+const WORKING_SIZE: u32 = 1200;
+
+// Ink is at least this much darker than the paper around it, in percent. Lighter marks, such as the puzzle on the other side of the page showing through, are ignored.
+const INK_CONTRAST: u64 = 22;
+
+// Grid lines can print more faintly in places, so are followed through anything at least this much darker than the paper
+const FAINT_CONTRAST: u64 = 8;
+
+// Glyphs are compared as a bitmap this many blocks wide and high
+const GLYPH_WIDTH: usize = 12;
+const GLYPH_HEIGHT: usize = 16;
+
+impl Puzzle {
+    // Make out the puzzle in a photo of it, JPEG or PNG, as printed in a book or newspaper. Only 9x9 grids are made out. The grid is found as the largest connected set of lines, so it needs to be the biggest thing in the photo, but may be skewed or curved as a page is. Each cell's printed digit, if any, is recognised by likeness to the digits of the photos in samples/.
+    pub fn scan(photo: &[u8]) -> Result<Puzzle, String> {
+        let mut grid = [[Cell {
+            number: None,
+            given: false,
+            candidates: Candidates::none(),
+        }; MAX_SIZE]; MAX_SIZE];
+
+        for (i, glyph) in glyphs(photo)?.iter().enumerate() {
+            if let Some(glyph) = glyph {
+                grid[i / 9][i % 9] = Cell {
+                    number: Some(recognise(glyph)),
+                    given: true,
+                    candidates: Candidates::none(),
+                };
+            }
+        }
+
+        Ok(Puzzle::with_grid(Shape::STANDARD, grid))
+    }
+}
+
+// The bitmaps of the digits printed in the grid, row by row, with `None` for blank cells.
+fn glyphs(photo: &[u8]) -> Result<Vec<Option<Vec<bool>>>, String> {
+    let image = load(photo)?;
+    let faint = ink(&image, FAINT_CONTRAST);
+    let ink = ink(&image, INK_CONTRAST);
+    let (width, height) = image.dimensions();
+    let (width, height) = (width as usize, height as usize);
+    let blots = Components::label(width, height, |x, y| ink[y * width + x], true);
+
+    // The grid is one of the biggest blots of ink, though shadows at the edges of the photo can be bigger, so the few biggest are tried in turn
+    let mut biggest: Vec<usize> = (0..blots.sizes.len()).collect();
+    biggest.sort_by_key(|&b| std::cmp::Reverse(blots.sizes[b]));
+    let mut failure = "No grid in the photo".to_string();
+    for (i, &blot) in biggest.iter().take(5).enumerate() {
+        match cells(&blots, blot, &faint) {
+            Ok((holes, cells)) => {
+                return Ok(cells
+                    .iter()
+                    .map(|&hole| glyph(&holes, hole, &ink))
+                    .collect())
+            }
+            Err(e) if i == 0 => failure = e,
+            Err(_) => {}
+        }
+    }
+
+    Err(failure)
+}
+
+// The 81 cells of the grid, if the blot is one, row by row. The cells are the holes in the grid about a ninth of its size along each side, found once its lines are followed through faint ink and thickened a little to close any gaps.
+fn cells(
+    blots: &Components,
+    grid: usize,
+    faint: &[bool],
+) -> Result<(Components, Vec<usize>), String> {
+    let (width, height) = (blots.width, blots.height);
+    let faint_blots = Components::label(width, height, |x, y| faint[y * width + x], true);
+    let start = blots.labels.iter().position(|&l| l == grid).unwrap_or(0);
+    let faint_grid = faint_blots.labels[start];
+
+    let mut lines = vec![false; width * height];
+    for (i, _) in faint_blots
+        .labels
+        .iter()
+        .enumerate()
+        .filter(|&(_, &l)| l == faint_grid)
+    {
+        let (x, y) = (i % width, i / width);
+        for ny in y.saturating_sub(2)..(y + 3).min(height) {
+            for nx in x.saturating_sub(2)..(x + 3).min(width) {
+                lines[ny * width + nx] = true;
+            }
+        }
+    }
+
+    let mut holes = Components::label(width, height, |x, y| !lines[y * width + x], false);
+    let smallest = blots.bounds[grid].area() / 81 / 3;
+    let mut cells: Vec<usize> = (0..holes.sizes.len())
+        .filter(|&hole| {
+            let b = holes.bounds[hole];
+            b.left > 0 && b.top > 0 && b.right < width - 1 && b.bottom < height - 1
+        })
+        .filter(|&hole| holes.sizes[hole] > smallest)
+        .collect();
+
+    // Where a line is missing two cells run together, so holes about twice the size of most are split in half
+    let mut sizes: Vec<usize> = cells.iter().map(|&hole| holes.sizes[hole]).collect();
+    sizes.sort();
+    let usual = sizes.get(sizes.len() / 2).copied().unwrap_or(0);
+    for hole in cells.clone() {
+        if holes.sizes[hole] * 2 > usual * 3 && holes.sizes[hole] * 2 < usual * 5 {
+            cells.push(holes.split(hole));
+        }
+    }
+    cells.retain(|&hole| holes.sizes[hole] * 2 < usual * 3);
+
+    if cells.len() != 81 {
+        return Err(format!(
+            "Made out {} cells in the grid rather than 81",
+            cells.len()
+        ));
+    }
+
+    let ordered = order(&holes, &cells)?;
+    Ok((holes, ordered))
+}
+
+// The photo in greyscale, the right way up, and shrunk to the working size.
+fn load(photo: &[u8]) -> Result<GrayImage, String> {
+    let unreadable = |e: &dyn std::fmt::Display| format!("Can't read the photo: {}", e);
+    let mut decoder = ImageReader::new(Cursor::new(photo))
+        .with_guessed_format()
+        .map_err(|e| unreadable(&e))?
+        .into_decoder()
+        .map_err(|e| unreadable(&e))?;
+    let orientation = decoder.orientation().map_err(|e| unreadable(&e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| unreadable(&e))?;
+    image.apply_orientation(orientation);
+
+    Ok(image
+        .resize(WORKING_SIZE, WORKING_SIZE, FilterType::Triangle)
+        .to_luma8())
+}
+
+// Which pixels are ink: those darker than the paper around them, judged against the average of a window about a cell across, so shadows across the page don't matter.
+fn ink(image: &GrayImage, contrast: u64) -> Vec<bool> {
+    let (width, height) = image.dimensions();
+    let (width, height) = (width as usize, height as usize);
+
+    // Sums of every pixel above and to the left, for the average of any window at a glance
+    let mut sums = vec![0u64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0u64;
+        for x in 0..width {
+            row += image.get_pixel(x as u32, y as u32)[0] as u64;
+            sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row;
+        }
+    }
+
+    let reach = width.max(height) / 24;
+    let mut ink = vec![false; width * height];
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(reach), (y + reach + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(reach), (x + reach + 1).min(width));
+            let sum = sums[bottom * (width + 1) + right] + sums[top * (width + 1) + left]
+                - sums[top * (width + 1) + right]
+                - sums[bottom * (width + 1) + left];
+            let count = ((bottom - top) * (right - left)) as u64;
+
+            let pixel = image.get_pixel(x as u32, y as u32)[0] as u64;
+            ink[y * width + x] = pixel * 100 * count < sum * (100 - contrast);
+        }
+    }
+
+    ink
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
+}
+
+impl Bounds {
+    fn width(&self) -> usize {
+        self.right - self.left + 1
+    }
+
+    fn height(&self) -> usize {
+        self.bottom - self.top + 1
+    }
+
+    fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    fn centre(&self) -> (f64, f64) {
+        (
+            (self.left + self.right) as f64 / 2.0,
+            (self.top + self.bottom) as f64 / 2.0,
+        )
+    }
+}
+
+// The connected sets of pixels picked out of an image, each pixel labelled with the set it's in.
+struct Components {
+    width: usize,
+    height: usize,
+    labels: Vec<usize>,
+    sizes: Vec<usize>,
+    bounds: Vec<Bounds>,
+}
+
+// Label of the pixels in no set
+const NONE: usize = usize::MAX;
+
+impl Components {
+    // Pixels are connected to those beside them, and with `diagonal` to those touching at the corners too.
+    fn label(
+        width: usize,
+        height: usize,
+        picked: impl Fn(usize, usize) -> bool,
+        diagonal: bool,
+    ) -> Components {
+        let mut components = Components {
+            width,
+            height,
+            labels: vec![NONE; width * height],
+            sizes: vec![],
+            bounds: vec![],
+        };
+
+        let mut stack = Vec::new();
+        for start in 0..width * height {
+            if components.labels[start] != NONE || !picked(start % width, start / width) {
+                continue;
+            }
+
+            let label = components.sizes.len();
+            let (x, y) = (start % width, start / width);
+            let mut bounds = Bounds {
+                left: x,
+                top: y,
+                right: x,
+                bottom: y,
+            };
+            let mut size = 0;
+            components.labels[start] = label;
+            stack.push(start);
+
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % width, i / width);
+                size += 1;
+                bounds.left = bounds.left.min(x);
+                bounds.right = bounds.right.max(x);
+                bounds.top = bounds.top.min(y);
+                bounds.bottom = bounds.bottom.max(y);
+
+                for (dx, dy) in [
+                    (-1, 0),
+                    (1, 0),
+                    (0, -1),
+                    (0, 1),
+                    (-1, -1),
+                    (1, -1),
+                    (-1, 1),
+                    (1, 1),
+                ]
+                .iter()
+                .take(if diagonal { 8 } else { 4 })
+                {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    let n = ny * width + nx;
+                    if components.labels[n] == NONE && picked(nx, ny) {
+                        components.labels[n] = label;
+                        stack.push(n);
+                    }
+                }
+            }
+
+            components.sizes.push(size);
+            components.bounds.push(bounds);
+        }
+
+        components
+    }
+
+    // Split the component in half across its longer side, the far half becoming a new component.
+    fn split(&mut self, c: usize) -> usize {
+        let bounds = self.bounds[c];
+        let across = bounds.width() > bounds.height();
+        let (middle_x, middle_y) = bounds.centre();
+
+        let half = self.sizes.len();
+        let mut size = 0;
+        let mut far: Option<Bounds> = None;
+        for y in bounds.top..=bounds.bottom {
+            for x in bounds.left..=bounds.right {
+                let i = y * self.width + x;
+                let beyond = if across {
+                    x as f64 > middle_x
+                } else {
+                    y as f64 > middle_y
+                };
+                if self.labels[i] != c || !beyond {
+                    continue;
+                }
+
+                self.labels[i] = half;
+                size += 1;
+                far = Some(match far {
+                    None => Bounds {
+                        left: x,
+                        top: y,
+                        right: x,
+                        bottom: y,
+                    },
+                    Some(b) => Bounds {
+                        left: b.left.min(x),
+                        top: b.top.min(y),
+                        right: b.right.max(x),
+                        bottom: b.bottom.max(y),
+                    },
+                });
+            }
+        }
+
+        self.sizes[c] -= size;
+        if across {
+            self.bounds[c].right = middle_x as usize;
+        } else {
+            self.bounds[c].bottom = middle_y as usize;
+        }
+        self.sizes.push(size);
+        self.bounds.push(far.unwrap_or(bounds));
+        half
+    }
+
+    fn largest(&self) -> Option<usize> {
+        (0..self.sizes.len()).max_by_key(|&c| self.sizes[c])
+    }
+}
+
+// The cells row by row, top left first. Rows are followed cell to cell rather than by height alone, as a curved page bends them.
+fn order(holes: &Components, cells: &[usize]) -> Result<Vec<usize>, String> {
+    let mut left: Vec<usize> = cells.to_vec();
+    let mut ordered = Vec::with_capacity(81);
+
+    for _ in 0..9 {
+        let first = *left
+            .iter()
+            .min_by(|&&a, &&b| {
+                let (ax, ay) = holes.bounds[a].centre();
+                let (bx, by) = holes.bounds[b].centre();
+                (ax + ay).total_cmp(&(bx + by))
+            })
+            .unwrap();
+        let mut row = vec![first];
+
+        while row.len() < 9 {
+            let last = holes.bounds[*row.last().unwrap()];
+            let (x, y) = last.centre();
+            let next = left
+                .iter()
+                .filter(|c| !row.contains(c))
+                .map(|&c| (c, holes.bounds[c].centre()))
+                .filter(|&(_, (cx, cy))| {
+                    cx - x > last.width() as f64 / 2.0
+                        && (cy - y).abs() < last.height() as f64 / 2.0
+                })
+                .min_by(|(_, (ax, _)), (_, (bx, _))| ax.total_cmp(bx));
+            match next {
+                Some((c, _)) => row.push(c),
+                None => return Err("Couldn't follow the rows of the grid".to_string()),
+            }
+        }
+
+        left.retain(|c| !row.contains(c));
+        ordered.extend(row);
+    }
+
+    Ok(ordered)
+}
+
+// The bitmap of the digit printed in the cell, or `None` if it's blank. The digit is the biggest blot of ink in the cell, if big enough to be one rather than a speck.
+fn glyph(holes: &Components, hole: usize, ink: &[bool]) -> Option<Vec<bool>> {
+    let width = holes.width;
+    let cell = holes.bounds[hole];
+    let inside = |x: usize, y: usize| {
+        let i = (cell.top + y) * width + cell.left + x;
+        holes.labels[i] == hole && ink[i]
+    };
+    let blots = Components::label(cell.width(), cell.height(), inside, true);
+
+    let digit = blots.largest()?;
+    let bounds = blots.bounds[digit];
+    if bounds.height() * 3 < cell.height() {
+        return None;
+    }
+
+    let mut bitmap = Vec::with_capacity(GLYPH_WIDTH * GLYPH_HEIGHT);
+    for gy in 0..GLYPH_HEIGHT {
+        for gx in 0..GLYPH_WIDTH {
+            let (x0, x1) = span(bounds.left, bounds.width(), gx, GLYPH_WIDTH);
+            let (y0, y1) = span(bounds.top, bounds.height(), gy, GLYPH_HEIGHT);
+            let inked = (y0..y1)
+                .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+                .filter(|&(x, y)| blots.labels[y * cell.width() + x] == digit)
+                .count();
+            bitmap.push(inked * 5 >= (x1 - x0) * (y1 - y0) * 2);
+        }
+    }
+
+    Some(bitmap)
+}
+
+// The pixels of the block'th of `blocks` blocks along a stretch of `length` pixels from `start`, never empty.
+fn span(start: usize, length: usize, block: usize, blocks: usize) -> (usize, usize) {
+    let from = start + block * length / blocks;
+    let to = start + (block + 1) * length / blocks;
+    (from, to.max(from + 1))
+}
+
+// The digit the glyph looks most like: that of the most alike of the digits known.
+fn recognise(glyph: &[bool]) -> u8 {
+    let bits = pack(glyph);
+    KNOWN
+        .iter()
+        .min_by_key(|(_, known)| {
+            let known = unpack(known);
+            (0..3)
+                .map(|i| (known[i] ^ bits[i]).count_ones())
+                .sum::<u32>()
+        })
+        .map(|(digit, _)| *digit)
+        .unwrap_or(1)
+}
+
+fn pack(glyph: &[bool]) -> [u64; 3] {
+    let mut bits = [0u64; 3];
+    for (i, &inked) in glyph.iter().enumerate() {
+        if inked {
+            bits[i / 64] |= 1 << (i % 64);
+        }
+    }
+    bits
+}
+
+fn unpack(hex: &str) -> [u64; 3] {
+    let mut bits = [0u64; 3];
+    for (i, word) in bits.iter_mut().enumerate() {
+        *word = u64::from_str_radix(&hex[i * 16..(i + 1) * 16], 16).unwrap_or(0);
+    }
+    bits
+}
+
+// Digits as printed in the photos in samples/, each packed as three 64-bit words in hex
+const KNOWN: [(u8, &str); 116] = [
+    (1, "0fc0fc0ffeffeffcc0fc0fc0fc0fc0fc7e0fc0fc0fc0fc0f"),
+    (1, "0fc0fc0fffffeff8c0fc0fc0fc0fc0fcfc0fc0fc0fc0fc0f"),
+    (1, "0fc0fc0fffffeffcc0fc0fc0fc0fe0fcfc0fc0fc0fc0fc0f"),
+    (1, "0fc0fc0ff0ffe7fcc0fc0fc0fc0fc0fcf80fc0fc0fc0fc0f"),
+    (1, "07c0fc0ffcfffffee07e07e07e07e07c7e07e07e07e07e07"),
+    (1, "0f80f80fffffeff8c0fc0fc0fc0fc0f8fc0fc0fc0fc0fc0f"),
+    (1, "07e07c07ff7fcff8f03f03f03e03e03e0f81f81f81f81f01"),
+    (1, "0fc0fc0fffffeff8e0fe0fc0fc0fc0fcfe0fe0fe0fe0fe0f"),
+    (1, "0fe0fe0fffffeffce0fe0fe0fe0fe0fe7e0fe0fe0fe0fe0f"),
+    (1, "0fe0fe0ffffffffce0fe0fe0fe0fe0fe7e0fe0fe0fe0fe0f"),
+    (1, "0fe0fe0fffffeffcc0fc0fc0fc0fc0fefc0fc0fc0fc0fc0f"),
+    (1, "0fc0fc0ffeffe7fcc0fc0fc0fc0fc0fcfc0fc0fc0fc0fc0f"),
+    (1, "0fe0fe0fffffeffce0fe0fe0fe0fe0fe7c0fe0fe0fe0fe0f"),
+    (1, "0fc0fc0ffeffeff8c0fc0fc0fc0fc0fcfc0fc0fc0fc0fc0f"),
+    (2, "0e0ee0e7fc3fc1f0e03c0780f00e00e0fffffe0fc0780f01"),
+    (2, "0e0ee0efbc7fc1f0e03c0780f00e00e0fffffe07c0780f01"),
+    (2, "0e0fe0efbe7fc1f8e03c0780f00e00e0fffffe03c0780f01"),
+    (2, "0e0ee0efbe7fc1f0e03c0780f00e00e0fffffe03c0780f01"),
+    (2, "0e0ff0f7fe3fc0f8e03c0780700f00e0fffffe07c0780f01"),
+    (2, "0e0ee0efbe7fc1f8e01c0380700f00e0fffffe1fc0780f00"),
+    (2, "0e0ee1effc7f81f0e03c0780700e00e0fffffe17c0780f01"),
+    (2, "0e0ee1effc7fc1f0e03c0780f00e00e0fffffe07c0780f01"),
+    (2, "0e0ee1e7fc7f81f0e03c0780700f00e0fffffe03c0780f01"),
+    (2, "0e0ee1efbc7fc3f0e03c0380700f00e0ffeffc03c0780f01"),
+    (2, "0e0770f7fe3fc0f8e03c0780780f00e0fffffe03c0780f01"),
+    (2, "0f0770f3ff1fe0f8f03c0780780f00f0ffcfff0fe07c0f81"),
+    (2, "4e0ef0e7fe3fc1f0e03c0780700f00e0fffffe0fc0780f01"),
+    (2, "0e0ef0e7fe3fc1f8e03c0780780f00e0fffffe7fc0780f01"),
+    (2, "0e07f0f7fe3fc1f8f01e03c0780f00e0ff8fff1fe03c0781"),
+    (2, "0e04f0e7fe3fc1f8f03e0780f00e00e0fc0fff1fe03c0781"),
+    (3, "0e00e0e7fc3fc1f000f007c07e0f00e01f87fcf1ee0fe00e"),
+    (3, "070670f7fe3fc0f800f007e03e0780701f87fcf9ee0fe07e"),
+    (3, "0e00e0ef1e7fc3f800f007c07c0fc0e03f87fef1ee0fe07e"),
+    (3, "0e00e0efbc7fc1f000f007c07e0fc0e01f87fef9ee0fe07e"),
+    (3, "0e0ee1cff87f03c000f00fc07c0780e00fc3fe7dff07e00e"),
+    (3, "0e00f0e7fc3fc1f800f007e07e0780f01f87fc79ef0fe06e"),
+    (3, "070070f79e3fe1fc00f007e03e07e0703f87fcf1ee0fe00e"),
+    (3, "0e0ef1e7fe7fc1f800f007e07e0780f01fc7fe79ef0fe07e"),
+    (4, "07807007006004003c7387707f07e07c700700fffffe7bc7"),
+    (4, "07807007006004001c7387707f07e07c700700fffffe71c7"),
+    (4, "03803003002000001c3383703e03e03c300300fffffe73c3"),
+    (4, "07807007006004001c7387707f07e07c700700fffffe71e7"),
+    (4, "07807007006004003c7787f07e07e07c700700fffffe79c7"),
+    (4, "07807007006004001c3383703f07e07c300300fffffe79e3"),
+    (4, "07807006006004003c7387707e07e07c300700fffffe71c7"),
+    (4, "07807007006004003c3383707e07e07c300300fffffe79c3"),
+    (4, "03c0380300300200bc3b83f83f03e03e700700fffffe79e3"),
+    (4, "07807807006004001c3383703f07e07c300700ffffff7fe3"),
+    (4, "03803803002002001c3383f83f03e03c300780ffffff79e3"),
+    (4, "03c03803003002001c3383783f03e03c700700fffffe7fe3"),
+    (4, "03803803002000001c3383f83f03e03c300300ffffff79e3"),
+    (4, "03803803006006001c3383703f03e03c300780fffffe7be3"),
+    (4, "07807807006004003c3383703e07e07c300700fffffe79c3"),
+    (5, "c038038ff0ff0ff000e00f0479c3fc1f1f83fe78ff07e00e"),
+    (5, "8038070ff0ff0fe000e00e04f8c7fc1f1f83fe78fe06e00e"),
+    (5, "00300700e0fe0fc0006006047983f81f07e0ff3863007006"),
+    (5, "8038038ff8ff07e000e00f0078c7fc1f1f83fe79ff06e00e"),
+    (5, "8038070ff0ff0fe000e00e00f9c7fc3f1f83fc79ef07e00e"),
+    (5, "c038038078ff0ff000e00e00f0c7fc3f0f83fc7fff0fe02e"),
+    (5, "8078078ff0ff0fe000e00e04f9c7fc1f1fc3fe79ff06e00e"),
+    (5, "8038038ff0ff0fe000e00f047dc7fc1f1fc3fe78ff06e00e"),
+    (5, "8038078ff0ff0fe000e00f047fc3fc1f1fc3fe78ff06e00e"),
+    (5, "8038078ff0ff0fe000e00e00f9c7fc3f1f83fe79ff07e00e"),
+    (5, "80780707f0ff0fe000e00e04f9c7f83f1f87fe79fe06e00c"),
+    (6, "80f01e01c038018007f0f79e7fe1fc071f83fc79ef0fe07e"),
+    (6, "80f01e01c038018007e0ff9e7fc1fc071f87fc79ee0fe07e"),
+    (6, "80f01e03c038010007e0f79e3fc1f8071f87fc79ee07e07e"),
+    (6, "80700e01c038018007e0f79e7fe1fc071f87fc78ee07e07e"),
+    (6, "80f01e01c038030007e07f9e7fc1fc071f87fc79ee07e07e"),
+    (6, "80f01e03c038030007e0efbe7fc1f8071f87fcf9ee0fe07e"),
+    (6, "00e01c038070020007e0ef1e7fc3f8070f83fc79ee07c07e"),
+    (6, "00e01e03c078030007e0ffbe7fc1f8073f87fef1ee0fe07e"),
+    (6, "00f01c038070020007e0ff9e7fc1f8070f87fe79ee0fe07e"),
+    (6, "c0780700e01e00c007f0779f3fe0fc031f07fcf1ee07e07e"),
+    (6, "80f01e03c038010007f0fffe7fe1fc073f87fcf9ee0fe07e"),
+    (6, "80f01e03c038010007f0f7fe3fc1fc073f87fcf9ee0fe07e"),
+    (6, "80f00e01e03c038007f07f9e7fe1fc071f83fc79ef0fe07e"),
+    (6, "80f01e03c038018007f0f7fe3fc1f8073f87fcf9ee0fe0fe"),
+    (6, "80f00e01c03c038007f0ff9e7fe1fc071f83fc79ef0fe07e"),
+    (6, "80f01e03c038018007e0fffe7fe1fc073f87fcfbee0fe07e"),
+    (6, "80f01e01c038018007f0f79f7fe1fc073f87fcf9ee0fe07e"),
+    (7, "03807807fefffffe380780700e00e01c00600700e01c01c0"),
+    (7, "03803807feffffff380380700e01e01c00600700e01c01c0"),
+    (7, "03c03807fffffffe380780700e01c01c00600700e01c01c0"),
+    (7, "03807807ffffffff380700f00e01c03c00600e01e01c0380"),
+    (7, "03803807feffffff380700700e01e01c00700f00e01c01c0"),
+    (7, "03807807ffffffff780700f00e01c01c00600e01e01c0380"),
+    (7, "03803807fefffffe380700700e01e01c00600700e01c01c0"),
+    (7, "03803807ffffffff380380700e01e01c00600700e01e01c0"),
+    (7, "03807807ffffffff380780700e01e01c00700700e00e01c0"),
+    (7, "03c03807ffffffff380780700f00e01c00700700e01e01c0"),
+    (7, "03c03807ffffffff380780700e01e01c00600f00e01e01c0"),
+    (7, "03807807feffffff380780f00e01c03c00700f00e01e03c0"),
+    (7, "03c03807feffffff380780700f00e01c00700700e01e01c0"),
+    (7, "03c03837ffffffc0380380700e00e01c00700f00e01e01c0"),
+    (7, "03807c0fff7ff00f380780700e01c03c00300700e01e01c0"),
+    (8, "ee0fe0effe7fc1f00ff1e7fe7fcfbee01f87fcf9ee0fe07e"),
+    (8, "ee0ee0e7bc3fc1f00ff0e7fe3fc79ce01f07fcf9ee0fe0fe"),
+    (8, "ee0ff1e7fe7fc1f007f1f7fe7fcfbef01f87fcf9ee0fe07e"),
+    (8, "ee0ff0efbe7fc1f007f0f7fe7fcf9ee01f87fef9ee0fe07e"),
+    (8, "ee0ee1effc7fc1f00ff1f7fe7fcf1ee03f07fef1ee0fe0fe"),
+    (8, "ee0ef1e7fe7fc1f00ff1e7fe7fcf3ce03f87fef9ee0fe0fe"),
+    (8, "ee0ee1effe7fc3f00ff1e7fe7fcf1ee03f87fef1fe0fe0fe"),
+    (8, "ee0ef1effc7f81f007f1e7fe7fcfbee01f87fef9ff07e07e"),
+    (8, "ee0ef0e7fe7fc1f80ff1e7fe7fcf1ee03f87fcfbee0fe07e"),
+    (8, "f7077073fe1fc0f80770e3fe3fc7be703f07fcffee0ee07e"),
+    (8, "f70f70f7fe3fc0f80ff1f7fe3fc79e701f87fcfbef0fe07f"),
+    (8, "ee0ef0e7be3fc1f807f0f7fe7fcf1ee03f87fcf1ee0fe07f"),
+    (8, "ee0ef1effe7fc1f00ff1e7fc3fc79ef01f87fef9ef0fe07e"),
+    (9, "7e07f0f7be3fc1f8e03fc7fe79ef0fe001801c0380700e01"),
+    (9, "7e07f0f7fe3fc0f0e03fc7fe79ee0fe001803c0380700e01"),
+    (9, "7e07f0f79e3fc1f8e03f87fef9ee0fe000801c0380700e01"),
+    (9, "7e07e07f9e7fc1f8e03f87fef1ee07e000801c0380700f01"),
+    (9, "7e07f0f7fe3fc0f0e03fc7fef9ee07e00180380780f00e01"),
+    (9, "7e07f0f79e7fc1f8e03f87fcf9ef0fe001803c0780f00e01"),
+    (9, "7e07f0f7fe3fc1f8e03fc7fef9ef07e001803c0380700e01"),
+];
+
+#[cfg(test)]
+mod test {
+    use super::super::Puzzle;
+
+    #[test]
+    fn scan() {
+        for name in ["easy", "medium", "medium-hard", "expert"] {
+            let photo = std::fs::read(format!("samples/{}.jpeg", name)).unwrap();
+            let text = std::fs::read_to_string(format!("samples/{}.txt", name)).unwrap();
+            assert_eq!(
+                Puzzle::parse(&text).grid,
+                Puzzle::scan(&photo).unwrap().grid
+            );
+        }
+    }
+
+    #[test]
+    fn unreadable() {
+        assert!(Puzzle::scan(b"not a photo")
+            .unwrap_err()
+            .starts_with("Can't read the photo"));
+    }
+}
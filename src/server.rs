@@ -0,0 +1,144 @@
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tungstenite::{Message, WebSocket};
+
+use super::engine::solve_reporting;
+use super::solution::{json_consolidation, json_outcome};
+use super::{Puzzle, PuzzleStatus};
+
+// Serve solves over WebSocket, so a browser can animate each one as it goes rather than polling for it. Each text message a client sends is a puzzle, in any format the solver reads, answered with a message for each consolidation as its step is taken:
+//
+//    {"iteration": 1, "consolidation": {"type": "assignment", "technique": "hidden-singles", ...}}
+//
+// each consolidation as it is in a trace, then one with the outcome, as `--format json` gives it:
+//
+//    {"status": "solved", "reason": null, "grid": "341562789768934152..."}
+//
+// The status is `solved`, `unsolved`, or `ill-defined`, and the grid is as far as the solve got, row by row, with `.` for blanks. A puzzle that can't be read is answered with `{"status": "unreadable", "reason": "...", "grid": null}`. Each client is served on a thread of its own, a puzzle at a time, and a solve ends early if the client goes.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        std::thread::spawn(move || client(stream));
+    }
+    Ok(())
+}
+
+fn client(stream: TcpStream) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if solve(&mut socket, text.as_str()).is_err() {
+                    return;
+                }
+            }
+            Ok(Message::Close(_)) | Err(_) => return,
+            Ok(_) => {}
+        }
+    }
+}
+
+fn solve(socket: &mut WebSocket<TcpStream>, text: &str) -> tungstenite::Result<()> {
+    let puzzle = match Puzzle::parse_with_report(text) {
+        Ok((puzzle, _)) => puzzle,
+        Err(report) => {
+            return socket.send(outcome("unreadable", Some(&report.to_string()), None));
+        }
+    };
+
+    // Once a message can't be sent the client's gone, so there's no one to solve for
+    let gone = AtomicBool::new(false);
+    let mut sent = Ok(());
+    let puzzle = solve_reporting(puzzle, &gone, |step| {
+        for consolidation in step.consolidations.iter() {
+            if sent.is_ok() {
+                sent = socket.send(Message::text(format!(
+                    "{{\"iteration\": {}, \"consolidation\": {}}}",
+                    step.iteration,
+                    json_consolidation(consolidation)
+                )));
+            }
+        }
+        if sent.is_err() {
+            gone.store(true, Ordering::Relaxed);
+        }
+    });
+    sent?;
+
+    let message = match puzzle.status() {
+        PuzzleStatus::Solved => outcome("solved", None, Some(&puzzle)),
+        PuzzleStatus::Unsolved => outcome("unsolved", None, Some(&puzzle)),
+        PuzzleStatus::IllDefined(reason) => {
+            outcome("ill-defined", Some(&format!("{:?}", reason)), Some(&puzzle))
+        }
+    };
+    socket.send(message)
+}
+
+fn outcome(status: &str, reason: Option<&str>, puzzle: Option<&Puzzle>) -> Message {
+    Message::text(json_outcome(status, reason, puzzle))
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+
+    use tungstenite::Message;
+
+    use super::super::json::Json;
+    use super::serve;
+
+    #[test]
+    fn streams_steps() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener));
+
+        let (mut socket, _) = tungstenite::connect(format!("ws://{}", address)).unwrap();
+        let mut replies = |puzzle: &str| -> Vec<Json> {
+            socket.send(Message::text(puzzle)).unwrap();
+            let mut replies = vec![];
+            loop {
+                let text = socket.read().unwrap().into_text().unwrap();
+                let reply = Json::parse(text.as_str()).unwrap();
+                let done = reply.get("status").is_some();
+                replies.push(reply);
+                if done {
+                    return replies;
+                }
+            }
+        };
+
+        let solved = replies(include_str!("../samples/easy.txt"));
+        assert!(solved.len() > 2);
+        assert_eq!(Some(1.0), solved[0].get("iteration").and_then(Json::as_f64));
+        let first = solved[0].get("consolidation").unwrap();
+        assert!(first.get("technique").and_then(Json::as_str).is_some());
+        let last = solved.last().unwrap();
+        assert_eq!(Some("solved"), last.get("status").and_then(Json::as_str));
+        assert_eq!(
+            Some(
+                "341562789768934152952718364574291638189346275623875491215687943496153827837429516"
+            ),
+            last.get("grid").and_then(Json::as_str)
+        );
+
+        // The connection carries on after a puzzle it can't read
+        let unreadable = replies("12345");
+        assert_eq!(1, unreadable.len());
+        assert_eq!(
+            Some("unreadable"),
+            unreadable[0].get("status").and_then(Json::as_str)
+        );
+        assert_eq!(
+            solved.len(),
+            replies(include_str!("../samples/easy.txt")).len()
+        );
+    }
+}
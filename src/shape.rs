@@ -0,0 +1,534 @@
+use super::{Candidates, Coord, Unit};
+
+// The most rows and columns a grid can have, the digits then running up to P. Grids are kept this big whatever their shape, the cells past their size left blank, so they can be copied about like any other value.
+pub const MAX_SIZE: usize = 25;
+
+/// The size of a grid and of the blocks it's divided into. Everything about a grid's geometry is asked of its shape, so any size up to `MAX_SIZE` that divides into blocks works the same, as do the irregular regions of a jigsaw. The digits run 1 thru the size, written 1 thru 9 then A on, as in a 16x16 hexadoku.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shape {
+    size: usize,
+    // For a jigsaw, those of the blocks its regions stand in for, which pencil marks are still laid out as
+    block_rows: usize,
+    block_cols: usize,
+    // X-Sudoku: the two main diagonals are units too, each digit in them once
+    diagonals: bool,
+    // The block each cell is in, looked up rather than worked out so the blocks can be any shape
+    blocks: [[u8; MAX_SIZE]; MAX_SIZE],
+    // Whether the blocks are irregular regions rather than rectangles
+    jigsaw: bool,
+}
+
+impl Shape {
+    // 4x4, of 2x2 blocks, for children
+    pub const MINI: Shape = Shape::regular(4, 2, 2);
+
+    // 6x6, of blocks 2 rows high and 3 columns wide
+    pub const SIX: Shape = Shape::regular(6, 2, 3);
+
+    // The classic 9x9 grid, of 3x3 blocks
+    pub const STANDARD: Shape = Shape::regular(9, 3, 3);
+
+    // 16x16, of 4x4 blocks
+    pub const HEXADOKU: Shape = Shape::regular(16, 4, 4);
+
+    // A grid with this many rows, divided into blocks this many rows high and columns wide. The blocks must fill the grid exactly, at least 2 by 2.
+    pub fn new(size: usize, block_rows: usize, block_cols: usize) -> Result<Shape, String> {
+        check_size(size)?;
+        if block_rows < 2 || block_cols < 2 || block_rows * block_cols != size {
+            return Err(format!(
+                "A {0}x{0} grid can't be divided into blocks {1} rows high and {2} columns wide",
+                size, block_rows, block_cols
+            ));
+        }
+
+        Ok(Shape::regular(size, block_rows, block_cols))
+    }
+
+    // Blocks counted left-to-right, top-to-bottom, with the cells past the size left in block 0.
+    const fn regular(size: usize, block_rows: usize, block_cols: usize) -> Shape {
+        let mut blocks = [[0; MAX_SIZE]; MAX_SIZE];
+        let mut row = 0;
+        while row < size {
+            let mut col = 0;
+            while col < size {
+                blocks[row][col] =
+                    ((row / block_rows) * (size / block_cols) + col / block_cols) as u8;
+                col += 1;
+            }
+            row += 1;
+        }
+
+        Shape {
+            size,
+            block_rows,
+            block_cols,
+            diagonals: false,
+            blocks,
+            jigsaw: false,
+        }
+    }
+
+    // The same shape with its two main diagonals as units too, or not, as in X-Sudoku.
+    pub fn with_diagonals(self, diagonals: bool) -> Shape {
+        Shape { diagonals, ..self }
+    }
+
+    pub fn has_diagonals(&self) -> bool {
+        self.diagonals
+    }
+
+    // The same shape with its blocks replaced by the regions of a jigsaw: a region for every cell, row by row, told apart by any numbers. There must be as many regions as rows, each of as many cells, all in one piece. They're numbered afresh as blocks are, in the order their first cells come.
+    pub fn with_regions(self, regions: &[usize]) -> Result<Shape, String> {
+        if regions.len() != self.cell_count() {
+            return Err(format!(
+                "Expected a region for each of the {} cells, not {}",
+                self.cell_count(),
+                regions.len()
+            ));
+        }
+
+        let mut labels: Vec<usize> = vec![];
+        let mut blocks = [[0; MAX_SIZE]; MAX_SIZE];
+        for (coord, region) in self.coords().zip(regions) {
+            let block = match labels.iter().position(|label| label == region) {
+                Some(block) => block,
+                None => {
+                    labels.push(*region);
+                    labels.len() - 1
+                }
+            };
+            if block >= self.size {
+                return Err(format!(
+                    "Expected {} regions, one for each row, not more",
+                    self.size
+                ));
+            }
+            blocks[coord.row][coord.col] = block as u8;
+        }
+
+        let shape = Shape {
+            blocks,
+            jigsaw: blocks != Shape::regular(self.size, self.block_rows, self.block_cols).blocks,
+            ..self
+        };
+        for block in 0..self.size {
+            let cells: Vec<Coord> = shape.block_coords(block).collect();
+            match cells.first() {
+                None => {
+                    return Err(format!(
+                        "Expected {0} regions, one for each row, not {1}",
+                        self.size, block
+                    ))
+                }
+                Some(&first) if cells.len() != self.size => {
+                    return Err(format!(
+                        "The region holding {} has {} cells rather than {}",
+                        first,
+                        cells.len(),
+                        self.size
+                    ))
+                }
+                Some(&first) if !shape.is_connected(block, first) => {
+                    return Err(format!(
+                        "The region holding {} isn't all in one piece",
+                        first
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(shape)
+    }
+
+    // Whether every cell of the block can be reached from the first, a step at a time up, down, left, or right within it.
+    fn is_connected(&self, block: usize, first: Coord) -> bool {
+        let mut reached = vec![first];
+        let mut i = 0;
+        while i < reached.len() {
+            let Coord { row, col } = reached[i];
+            let steps = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for (row, col) in steps {
+                if row < self.size && col < self.size {
+                    let next = Coord::new(row, col);
+                    if self.block_of(next) == block && !reached.contains(&next) {
+                        reached.push(next);
+                    }
+                }
+            }
+            i += 1;
+        }
+        reached.len() == self.size
+    }
+
+    // A grid with this many rows, its blocks as near square as they go, and wider than they're high when they can't be: 2x3 in a 6x6 grid, 3x4 in a 12x12. Grids of a prime size can't be divided into blocks.
+    pub fn of_size(size: usize) -> Result<Shape, String> {
+        check_size(size)?;
+        let block_rows = (2..=size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|rows| size.is_multiple_of(*rows))
+            .last();
+
+        match block_rows {
+            Some(rows) => Shape::new(size, rows, size / rows),
+            None => Err(format!("A {0}x{0} grid can't be divided into blocks", size)),
+        }
+    }
+
+    // The shape of a grid of this many cells, if there's one.
+    pub fn of_cell_count(cells: usize) -> Option<Shape> {
+        (2..=MAX_SIZE)
+            .find(|size| size * size == cells)
+            .and_then(|size| Shape::of_size(size).ok())
+    }
+
+    // Rows in the grid, and columns, blocks, and digits.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.size * self.size
+    }
+
+    // Rows and columns in each block.
+    pub fn block_rows(&self) -> usize {
+        self.block_rows
+    }
+
+    pub fn block_cols(&self) -> usize {
+        self.block_cols
+    }
+
+    // e.g. `9x9`
+    pub fn name(&self) -> String {
+        format!("{0}x{0}", self.size)
+    }
+
+    pub fn digits(&self) -> Candidates {
+        (1..=self.size as u8).collect()
+    }
+
+    // Every cell of the grid, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let size = self.size;
+        (0..size * size).map(move |i| Coord::new(i / size, i % size))
+    }
+
+    // The block holding the cell. Blocks are counted left-to-right, top-to-bottom from 0, by their first cells in a jigsaw.
+    pub fn block_of(&self, coord: Coord) -> usize {
+        self.blocks[coord.row][coord.col] as usize
+    }
+
+    // The cells of the block, read left-to-right, top-to-bottom.
+    pub fn block_coords(&self, block: usize) -> impl Iterator<Item = Coord> {
+        assert!(block < self.size, "Invalid block number: {}", block);
+        let shape = *self;
+
+        // A jigsaw's regions could be anywhere, where blocks are worked out as ever
+        let (rows, cols) = if self.jigsaw {
+            (0..self.size, 0..self.size)
+        } else {
+            let blocks_across = self.size / self.block_cols;
+            let (row, col) = (
+                (block / blocks_across) * self.block_rows,
+                (block % blocks_across) * self.block_cols,
+            );
+            (row..row + self.block_rows, col..col + self.block_cols)
+        };
+        rows.flat_map(move |row| cols.clone().map(move |col| Coord::new(row, col)))
+            .filter(move |&coord| shape.block_of(coord) == block)
+    }
+
+    // Whether the blocks are the irregular regions of a jigsaw.
+    pub fn is_jigsaw(&self) -> bool {
+        self.jigsaw
+    }
+
+    // Whether a block's border runs along the right of the cell, as it always does at the grid's edge.
+    pub fn border_right(&self, coord: Coord) -> bool {
+        coord.col + 1 == self.size
+            || self.block_of(coord) != self.block_of(Coord::new(coord.row, coord.col + 1))
+    }
+
+    // Whether a block's border runs along the bottom of the cell.
+    pub fn border_below(&self, coord: Coord) -> bool {
+        coord.row + 1 == self.size
+            || self.block_of(coord) != self.block_of(Coord::new(coord.row + 1, coord.col))
+    }
+
+    // Whether the cells are different ones sharing a row, column, or block, or a diagonal if it's a unit.
+    pub fn sees(&self, a: Coord, b: Coord) -> bool {
+        a != b
+            && (a.row == b.row
+                || a.col == b.col
+                || self.block_of(a) == self.block_of(b)
+                || self
+                    .diagonals_of(a)
+                    .any(|d| self.diagonals_of(b).any(|e| d == e)))
+    }
+
+    // The diagonals the cell's on, if they're units: 0 running from the top left, 1 from the top right. The centre of an odd-sized grid is on both.
+    pub fn diagonals_of(&self, coord: Coord) -> impl Iterator<Item = usize> {
+        let on = [
+            coord.row == coord.col,
+            coord.row + coord.col + 1 == self.size,
+        ];
+        let diagonals = self.diagonals;
+        (0..2).filter(move |&d| diagonals && on[d])
+    }
+
+    // The cells of the diagonal, from the top row down.
+    pub fn diagonal(&self, diagonal: usize) -> impl Iterator<Item = Coord> {
+        let last = self.size - 1;
+        (0..self.size).map(move |row| Coord::new(row, if diagonal == 0 { row } else { last - row }))
+    }
+
+    // Every row, then every column, then every block, then the diagonals if they're units.
+    pub fn units(&self) -> Vec<Unit> {
+        let diagonals = if self.diagonals { 2 } else { 0 };
+        (0..self.size)
+            .map(Unit::Row)
+            .chain((0..self.size).map(Unit::Column))
+            .chain((0..self.size).map(Unit::Block))
+            .chain((0..diagonals).map(Unit::Diagonal))
+            .collect()
+    }
+
+    // The cell in box notation, e.g. `b5p3` for the third cell of the fifth box. Boxes and their cells are counted from 1, left-to-right, top-to-bottom.
+    pub fn box_notation(&self, coord: Coord) -> String {
+        let block = self.block_of(coord);
+        let position = self
+            .block_coords(block)
+            .position(|c| c == coord)
+            .unwrap_or(0);
+        format!("b{}p{}", block + 1, position + 1)
+    }
+
+    // A cell named as in sudoku books, `r4c7` or `b5p3`, in either case, and within the grid.
+    pub fn parse_cell(&self, name: &str) -> Result<Coord, String> {
+        let lower = name.to_lowercase();
+        let numbers = |prefix: char, separator: char| {
+            lower
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.split_once(separator))
+                .and_then(|(a, b)| Some((a.parse::<usize>().ok()?, b.parse::<usize>().ok()?)))
+        };
+        let within = |n: usize| (1..=self.size).contains(&n);
+
+        match (numbers('r', 'c'), numbers('b', 'p')) {
+            (Some((row, col)), _) if within(row) && within(col) => Ok(Coord::new(row - 1, col - 1)),
+            (_, Some((block, position))) if within(block) && within(position) => Ok(self
+                .block_coords(block - 1)
+                .nth(position - 1)
+                .expect("Blocks have a cell for each row")),
+            _ => Err(format!(
+                "Expected a cell such as r4c7 or b5p3, not '{}'",
+                name
+            )),
+        }
+    }
+}
+
+// Grids are kept `MAX_SIZE` across, so can be no bigger.
+fn check_size(size: usize) -> Result<(), String> {
+    if size > MAX_SIZE {
+        return Err(format!(
+            "Grids can be at most {0}x{0}, not {1}x{1}",
+            MAX_SIZE, size
+        ));
+    }
+    Ok(())
+}
+
+// How a digit is written: 1 thru 9, then A for 10 on.
+pub fn symbol(digit: u8) -> char {
+    match digit {
+        1..=9 => (b'0' + digit) as char,
+        _ => (b'A' + digit - 10) as char,
+    }
+}
+
+// The digit a character writes, in either case, if it's one of a grid this size.
+pub(crate) fn digit(c: char, size: usize) -> Option<u8> {
+    let digit = match c.to_ascii_uppercase() {
+        c @ '1'..='9' => c as u8 - b'0',
+        c @ 'A'..='Z' => c as u8 - b'A' + 10,
+        _ => return None,
+    };
+    (digit as usize <= size).then_some(digit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Coord;
+    use super::{digit, symbol, Shape};
+
+    #[test]
+    fn blocks() {
+        let shape = Shape::STANDARD;
+        assert_eq!(0, shape.block_of(Coord::new(0, 0)));
+        assert_eq!(0, shape.block_of(Coord::new(2, 2)));
+        assert_eq!(2, shape.block_of(Coord::new(1, 6)));
+        assert_eq!(6, shape.block_of(Coord::new(7, 2)));
+        assert_eq!(8, shape.block_of(Coord::new(8, 8)));
+
+        assert_eq!(Coord::new(0, 0), shape.block_coords(0).next().unwrap());
+        assert_eq!(Coord::new(0, 3), shape.block_coords(1).next().unwrap());
+        assert_eq!(Coord::new(3, 0), shape.block_coords(3).next().unwrap());
+        assert_eq!(Coord::new(3, 3), shape.block_coords(4).next().unwrap());
+        assert_eq!(Coord::new(6, 6), shape.block_coords(8).next().unwrap());
+
+        assert_eq!(Some(Coord::new(7, 5)), shape.block_coords(7).nth(5));
+        assert_eq!(9, shape.block_coords(7).count());
+        assert!(shape.border_right(Coord::new(7, 5)));
+        assert!(!shape.border_below(Coord::new(7, 5)));
+        assert!(shape.border_below(Coord::new(8, 5)));
+
+        assert!(shape.sees(Coord::new(0, 0), Coord::new(2, 2)));
+        assert!(shape.sees(Coord::new(0, 0), Coord::new(0, 8)));
+        assert!(!shape.sees(Coord::new(0, 0), Coord::new(3, 3)));
+        assert!(!shape.sees(Coord::new(4, 4), Coord::new(4, 4)));
+
+        let hex = Shape::HEXADOKU;
+        assert_eq!(5, hex.block_of(Coord::new(4, 7)));
+        assert_eq!(Coord::new(12, 12), hex.block_coords(15).next().unwrap());
+        assert_eq!(48, hex.units().len());
+        assert_eq!(256, hex.coords().count());
+        assert_eq!(16, hex.digits().count());
+        assert_eq!(Ok(hex), Shape::of_size(16));
+        assert_eq!(Some(hex), Shape::of_cell_count(256));
+        assert_eq!(None, Shape::of_cell_count(100 + 1));
+
+        // Blocks wider than they're high
+        let six = Shape::SIX;
+        assert_eq!(1, six.block_of(Coord::new(1, 3)));
+        assert_eq!(2, six.block_of(Coord::new(2, 0)));
+        assert_eq!(5, six.block_of(Coord::new(5, 5)));
+        assert_eq!(Coord::new(4, 3), six.block_coords(5).next().unwrap());
+        assert_eq!(Coord::new(3, 2), six.block_coords(2).nth(5).unwrap());
+        assert!(six.sees(Coord::new(0, 0), Coord::new(1, 2)));
+        assert!(!six.sees(Coord::new(0, 0), Coord::new(2, 1)));
+        assert_eq!(Ok(Coord::new(3, 2)), six.parse_cell("b3p6"));
+        assert_eq!("b3p6", six.box_notation(Coord::new(3, 2)));
+
+        // Any size that divides into blocks
+        let twelve = Shape::of_size(12).unwrap();
+        assert_eq!((3, 4), (twelve.block_rows(), twelve.block_cols()));
+        assert_eq!(Ok(twelve), Shape::new(12, 3, 4));
+        assert_eq!(Ok(Shape::SIX), Shape::of_size(6));
+        assert_eq!(5, Shape::of_size(25).unwrap().block_cols());
+        assert_eq!(
+            Err("A 7x7 grid can't be divided into blocks".to_string()),
+            Shape::of_size(7)
+        );
+        assert_eq!(
+            Err("Grids can be at most 25x25, not 36x36".to_string()),
+            Shape::of_size(36)
+        );
+        assert!(Shape::new(12, 2, 4).is_err());
+        assert!(Shape::new(12, 1, 12).is_err());
+
+        // X-Sudoku's diagonals, the centre on both
+        let x = Shape::STANDARD.with_diagonals(true);
+        assert_eq!(29, x.units().len());
+        assert!(x.sees(Coord::new(0, 0), Coord::new(8, 8)));
+        assert!(x.sees(Coord::new(0, 8), Coord::new(4, 4)));
+        assert!(!x.sees(Coord::new(0, 0), Coord::new(8, 6)));
+        assert!(!shape.sees(Coord::new(0, 0), Coord::new(8, 8)));
+        assert_eq!(
+            vec![0, 1],
+            x.diagonals_of(Coord::new(4, 4)).collect::<Vec<_>>()
+        );
+        assert_eq!(0, shape.diagonals_of(Coord::new(4, 4)).count());
+        assert_eq!(Some(Coord::new(8, 0)), x.diagonal(1).last());
+        assert_ne!(Shape::STANDARD, x);
+    }
+
+    #[test]
+    fn regions() {
+        let regions = |labels: &str| -> Vec<usize> { labels.bytes().map(usize::from).collect() };
+
+        // Labelled any way, regions as the blocks are aren't a jigsaw
+        let mini = Shape::MINI.with_regions(&regions("aabbaabbccddccdd"));
+        assert_eq!(Ok(Shape::MINI), mini);
+
+        let jigsaw = Shape::MINI
+            .with_regions(&regions("1112122233343444"))
+            .unwrap();
+        assert!(jigsaw.is_jigsaw());
+        assert_eq!(1, jigsaw.block_of(Coord::new(1, 1)));
+        assert_eq!(
+            vec![
+                Coord::new(0, 3),
+                Coord::new(1, 1),
+                Coord::new(1, 2),
+                Coord::new(1, 3)
+            ],
+            jigsaw.block_coords(1).collect::<Vec<_>>()
+        );
+        assert!(jigsaw.border_right(Coord::new(0, 2)));
+        assert!(!jigsaw.border_right(Coord::new(1, 1)));
+        assert!(jigsaw.border_below(Coord::new(0, 1)));
+        assert!(!jigsaw.border_below(Coord::new(0, 0)));
+        assert!(jigsaw.sees(Coord::new(0, 3), Coord::new(1, 1)));
+        assert!(!jigsaw.sees(Coord::new(0, 0), Coord::new(1, 1)));
+        assert_eq!("b2p2", jigsaw.box_notation(Coord::new(1, 1)));
+        assert_eq!(Ok(Coord::new(1, 1)), jigsaw.parse_cell("b2p2"));
+
+        assert_eq!(
+            Err("Expected a region for each of the 16 cells, not 15".to_string()),
+            Shape::MINI.with_regions(&regions("111212223334344"))
+        );
+        assert_eq!(
+            Err("Expected 4 regions, one for each row, not more".to_string()),
+            Shape::MINI.with_regions(&regions("1112122233343445"))
+        );
+        assert_eq!(
+            Err("The region holding r1c1 has 5 cells rather than 4".to_string()),
+            Shape::MINI.with_regions(&regions("1111122223333444"))
+        );
+        assert_eq!(
+            Err("The region holding r1c1 isn't all in one piece".to_string()),
+            Shape::MINI.with_regions(&regions("1212121233443344"))
+        );
+    }
+
+    #[test]
+    fn notation() {
+        let shape = Shape::STANDARD;
+        assert_eq!(Ok(Coord::new(3, 6)), shape.parse_cell("r4c7"));
+        assert_eq!(Ok(Coord::new(0, 8)), shape.parse_cell("R1C9"));
+        assert_eq!(Ok(Coord::new(3, 5)), shape.parse_cell("b5p3"));
+        assert_eq!(Ok(Coord::new(8, 8)), shape.parse_cell("B9P9"));
+        assert!(shape.parse_cell("r0c1").is_err());
+        assert!(shape.parse_cell("b5p10").is_err());
+        assert!(shape.parse_cell("r4").is_err());
+
+        assert_eq!("b5p3", shape.box_notation(Coord::new(3, 5)));
+        for coord in shape.coords() {
+            assert_eq!(Ok(coord), shape.parse_cell(&coord.to_string()));
+            assert_eq!(Ok(coord), shape.parse_cell(&shape.box_notation(coord)));
+        }
+
+        let hex = Shape::HEXADOKU;
+        assert_eq!(Ok(Coord::new(15, 9)), hex.parse_cell("r16c10"));
+        assert_eq!(Ok(Coord::new(0, 7)), hex.parse_cell("b2p4"));
+        assert!(shape.parse_cell("r16c10").is_err());
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!('7', symbol(7));
+        assert_eq!('A', symbol(10));
+        assert_eq!('G', symbol(16));
+        assert_eq!(Some(16), digit('g', 16));
+        assert_eq!(None, digit('G', 9));
+        assert_eq!(None, digit('0', 16));
+    }
+}
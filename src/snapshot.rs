@@ -0,0 +1,74 @@
+use super::{Cell, Puzzle};
+
+/// The puzzle at a moment, to go back to with `Puzzle::restore()`: the grid's cells with their candidates, and how far the steps had got. Just the size² cells the grid has, not the whole puzzle with every step taken, nor the blanks past its size that every grid is stored with, so a few hundred bytes for a classic grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridState {
+    // Row by row
+    cells: Box<[Cell]>,
+    iteration: usize,
+    // Steps taken by then, those since being dropped on restoring
+    steps: usize,
+}
+
+impl Puzzle {
+    pub fn snapshot(&self) -> GridState {
+        let size = self.shape.size();
+        GridState {
+            cells: self.grid[..size]
+                .iter()
+                .flat_map(|row| row[..size].iter().copied())
+                .collect(),
+            iteration: self.iteration,
+            steps: self.steps.len(),
+        }
+    }
+
+    // Go back to how the puzzle was when the snapshot was taken, dropping the steps taken since. Steps undone since can't be brought back, so restoring a snapshot taken before an `undo()` leaves those steps off.
+    pub fn restore(&mut self, state: &GridState) {
+        let size = self.shape.size();
+        for (row, cells) in self.grid.iter_mut().zip(state.cells.chunks(size)) {
+            row[..size].copy_from_slice(cells);
+        }
+        self.invalidate_candidates();
+        self.iteration = state.iteration;
+        self.steps.truncate(state.steps);
+        self.last_consolidation = match self.steps.last() {
+            Some(step) => step.consolidations.clone(),
+            None => vec![],
+        };
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Puzzle, PuzzleStatus};
+
+    #[test]
+    fn restore() {
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        puzzle.step();
+        let state = puzzle.snapshot();
+        let stepped = puzzle.clone();
+
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+
+        puzzle.restore(&state);
+        assert_eq!(stepped.grid, puzzle.grid);
+        assert_eq!(stepped.iteration, puzzle.iteration);
+        assert_eq!(1, puzzle.solution().steps().len());
+        assert_eq!(stepped.last_consolidation, puzzle.last_consolidation);
+        assert_eq!(state, puzzle.snapshot());
+        assert_eq!(81, state.cells.len());
+
+        // Of a smaller grid, just its own cells
+        let mut mini = Puzzle::parse(include_str!("../samples/mini.txt"));
+        let state = mini.snapshot();
+        let given = mini.clone();
+        mini.solve().unwrap();
+        mini.restore(&state);
+        assert_eq!(given.grid, mini.grid);
+        assert_eq!(16, state.cells.len());
+    }
+}
@@ -0,0 +1,595 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(any(feature = "server", feature = "wasm"))]
+use super::engine::position;
+use super::{
+    Clues, Consolidation, Grid, Justification, Puzzle, PuzzleStatus, Shape, Technique, Unit,
+};
+
+/// One step towards the solution: the candidates going in, what the techniques did with them, and the grid coming out.
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub(super) iteration: usize,
+    pub(super) before: Grid,
+    pub(super) consolidations: Vec<Consolidation>,
+    pub(super) after: Grid,
+}
+
+impl Step {
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn consolidations(&self) -> &[Consolidation] {
+        &self.consolidations
+    }
+
+    // What happened in the step, one sentence per consolidation. See `Consolidation::describe()`.
+    pub fn describe(&self) -> String {
+        let sentences: Vec<String> = self.consolidations.iter().map(|c| c.describe()).collect();
+        sentences.join("\n")
+    }
+}
+
+/// Every step taken to solve a puzzle, and how far they got.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    shape: Shape,
+    // The variant's clues, for the walkthrough to draw
+    clues: Arc<Clues>,
+    steps: Vec<Step>,
+    status: PuzzleStatus,
+    elapsed: Duration,
+    guesses_by_depth: Vec<usize>,
+    technique_times: Vec<(Technique, Duration)>,
+}
+
+impl Solution {
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub(super) fn clues(&self) -> &Clues {
+        &self.clues
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn status(&self) -> PuzzleStatus {
+        self.status
+    }
+
+    // Figures on how the solve went. See `SolveStats`.
+    pub fn stats(&self) -> SolveStats {
+        let mut techniques: Vec<(Technique, usize)> = vec![];
+        let mut eliminations = 0;
+        for consolidation in self
+            .steps
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+        {
+            if let Consolidation::CandidatesEliminated { cells, digits, .. } = consolidation {
+                eliminations += cells.len() * digits.len();
+            }
+
+            let technique = match consolidation.technique() {
+                Some(technique) => technique,
+                None => continue,
+            };
+            match techniques.iter_mut().find(|(t, _)| *t == technique) {
+                Some((_, count)) => *count += 1,
+                None => techniques.push((technique, 1)),
+            }
+        }
+
+        SolveStats {
+            time: self.elapsed,
+            iterations: self.steps.len(),
+            techniques,
+            eliminations,
+            guesses: self.guesses_by_depth.iter().sum(),
+            technique_times: self.technique_times.clone(),
+        }
+    }
+
+    // The whole solve as JSON, so other tools can replay or analyse it:
+    //
+    //    * `status`: "solved", "unsolved", or "ill-defined" (with a `reason`)
+    //    * `steps`: each with its `iteration`, the grid `before` and `after`, and its `consolidations`
+    //
+    // Grids are as many rows of as many cells as the puzzle is across, 9 of 9 for most, each cell either its number or an array of its candidates. Cells are `[row, column]` pairs, counted from 0 like everything else.
+    pub fn trace(&self) -> String {
+        let status = match self.status {
+            PuzzleStatus::Solved => "\"solved\"".to_string(),
+            PuzzleStatus::Unsolved => "\"unsolved\"".to_string(),
+            PuzzleStatus::IllDefined(reason) => {
+                format!("\"ill-defined\",\n  \"reason\": \"{:?}\"", reason)
+            }
+        };
+
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let consolidations: Vec<String> = step
+                    .consolidations
+                    .iter()
+                    .map(|c| format!("        {}", json_consolidation(c)))
+                    .collect();
+
+                format!(
+                    "    {{\n      \"iteration\": {},\n      \"before\": {},\n      \"consolidations\": [\n{}\n      ],\n      \"after\": {}\n    }}",
+                    step.iteration,
+                    json_grid(self.shape, &step.before),
+                    consolidations.join(",\n"),
+                    json_grid(self.shape, &step.after)
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"status\": {},\n  \"steps\": [\n{}\n  ]\n}}\n",
+            status,
+            steps.join(",\n")
+        )
+    }
+}
+
+/// Figures on how a solve went: how long it took, what did the work, and whether it came to guessing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveStats {
+    // Time spent solving, including any search of guesses
+    pub time: Duration,
+    // Steps taken, not counting those of guesses abandoned along the way
+    pub iterations: usize,
+    // How many consolidations each technique made, in the order first used. Guesses aren't a technique, so aren't listed.
+    pub techniques: Vec<(Technique, usize)>,
+    // Candidates removed, counting each digit from each cell every time a technique removed it
+    pub eliminations: usize,
+    // Guesses made across every branch of the search, each a node of it. Nought if the techniques solved it alone.
+    pub guesses: usize,
+    // Time spent in each technique tried, in the order first tried, whether it found anything or not, across every branch of any search
+    pub technique_times: Vec<(Technique, Duration)>,
+}
+
+impl SolveStats {
+    pub fn guessed(&self) -> bool {
+        self.guesses > 0
+    }
+
+    // A few lines for people: e.g. "Took 6.7ms over 6 iterations, eliminating 42 candidates.", then a line per technique tried, the slowest first, with how many consolidations it made and the time spent in it, then the guessing.
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!(
+            "Took {:.1?} over {} iterations, eliminating {} candidates.",
+            self.time, self.iterations, self.eliminations
+        )];
+
+        let mut times = self.technique_times.clone();
+        times.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (technique, time) in times {
+            let count = self
+                .techniques
+                .iter()
+                .find(|(t, _)| *t == technique)
+                .map_or(0, |(_, count)| *count);
+            lines.push(format!("{}: {} in {:.1?}", technique.name(), count, time));
+        }
+        lines.push(if self.guessed() {
+            format!("Needed guessing, searching {} guesses.", self.guesses)
+        } else {
+            "No guesses needed.".to_string()
+        });
+        lines.join("\n")
+    }
+}
+
+/// How a search of guesses went: how many guesses were made at each depth, and which led to the solution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuessReport {
+    by_depth: Vec<usize>,
+    path: Vec<((usize, usize), u8)>,
+}
+
+impl GuessReport {
+    // Every guess made, across every branch of the search.
+    pub fn guesses(&self) -> usize {
+        self.by_depth.iter().sum()
+    }
+
+    // The guesses made at each depth, from those made on top of no other guess, then on top of one, and so on.
+    pub fn by_depth(&self) -> &[usize] {
+        &self.by_depth
+    }
+
+    // The guesses leading to the solution, each on top of those before it, as the cell's grid position and the number guessed.
+    pub fn path(&self) -> &[((usize, usize), u8)] {
+        &self.path
+    }
+
+    // E.g. "12 guesses: 3 at depth 1, 9 at depth 2. Solved by guessing R1C2 is 4, then R5C5 is 3."
+    pub fn describe(&self) -> String {
+        if self.by_depth.is_empty() {
+            return "No guesses needed.".to_string();
+        }
+
+        let depths: Vec<String> = self
+            .by_depth
+            .iter()
+            .enumerate()
+            .map(|(depth, guesses)| format!("{} at depth {}", guesses, depth + 1))
+            .collect();
+        let path: Vec<String> = self
+            .path
+            .iter()
+            .map(|((row, col), number)| format!("R{}C{} is {}", row + 1, col + 1, number))
+            .collect();
+
+        format!(
+            "{} guesses: {}. Solved by guessing {}.",
+            self.guesses(),
+            depths.join(", "),
+            path.join(", then ")
+        )
+    }
+}
+
+impl Puzzle {
+    // Taking a new step abandons any steps undone.
+    pub(super) fn record_step(&mut self, before: Grid) {
+        self.steps.push(Step {
+            iteration: self.iteration,
+            before,
+            consolidations: self.last_consolidation.clone(),
+            after: self.grid,
+        });
+        self.undone.clear();
+    }
+
+    // Take back the last step, restoring the grid as it was going into it. Steps record the grid on either side of them, so nothing more need be kept. Returns `false` if there's no step to take back.
+    pub fn undo(&mut self) -> bool {
+        let step = match self.steps.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        self.grid = step.before;
+        self.invalidate_candidates();
+        self.iteration = step.iteration - 1;
+        self.last_consolidation = match self.steps.last() {
+            Some(previous) => previous.consolidations.clone(),
+            None => vec![],
+        };
+        self.undone.push(step);
+        true
+    }
+
+    // Take the last step undone again. Returns `false` if there's no step to redo.
+    pub fn redo(&mut self) -> bool {
+        let step = match self.undone.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        self.grid = step.after;
+        self.invalidate_candidates();
+        self.iteration = step.iteration;
+        self.last_consolidation = step.consolidations.clone();
+        self.steps.push(step);
+        true
+    }
+
+    // Every step taken so far, including those of the guesses leading to this puzzle.
+    pub fn solution(&self) -> Solution {
+        Solution {
+            shape: self.shape,
+            clues: Arc::clone(&self.clues),
+            steps: self.steps.clone(),
+            status: self.status(),
+            elapsed: self.elapsed,
+            guesses_by_depth: self.guesses_by_depth.clone(),
+            technique_times: self.technique_times.clone(),
+        }
+    }
+
+    // How the search of guesses that solved this puzzle went. Empty unless it was solved by `solve_with_guesses()`.
+    pub fn guess_report(&self) -> GuessReport {
+        let path = self
+            .steps
+            .iter()
+            .flat_map(|step| step.consolidations.iter())
+            .filter(|consolidation| matches!(consolidation, Consolidation::Guess(_)))
+            .filter_map(Consolidation::assignment)
+            .collect();
+
+        GuessReport {
+            by_depth: self.guesses_by_depth.clone(),
+            path,
+        }
+    }
+}
+
+fn json_grid(shape: Shape, grid: &Grid) -> String {
+    let rows: Vec<String> = grid[..shape.size()]
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row[..shape.size()]
+                .iter()
+                .map(|cell| match cell.number {
+                    Some(number) => number.to_string(),
+                    None => json_digits(&cell.candidates.to_vec()),
+                })
+                .collect();
+            format!("[{}]", cells.join(", "))
+        })
+        .collect();
+
+    format!("[{}]", rows.join(", "))
+}
+
+fn json_digits(digits: &[u8]) -> String {
+    let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+    format!("[{}]", digits.join(", "))
+}
+
+fn json_cell(position: (usize, usize)) -> String {
+    format!("[{}, {}]", position.0, position.1)
+}
+
+fn json_cells(positions: &[(usize, usize)]) -> String {
+    let cells: Vec<String> = positions.iter().map(|&p| json_cell(p)).collect();
+    format!("[{}]", cells.join(", "))
+}
+
+fn json_unit(unit: &Unit) -> String {
+    match unit {
+        Unit::Row(row) => format!("{{\"row\": {}}}", row),
+        Unit::Column(col) => format!("{{\"column\": {}}}", col),
+        Unit::Block(b) => format!("{{\"block\": {}}}", b),
+        Unit::Diagonal(d) => format!("{{\"diagonal\": {}}}", d),
+    }
+}
+
+fn json_justification(justification: &Justification) -> String {
+    match justification {
+        Justification::Peers(peers) => {
+            format!("{{\"type\": \"peers\", \"cells\": {}}}", json_cells(peers))
+        }
+        Justification::OnlyPlaceInUnit(unit) => format!(
+            "{{\"type\": \"only-place-in-unit\", \"unit\": {}}}",
+            json_unit(unit)
+        ),
+        Justification::LockedCells(unit, cells) => format!(
+            "{{\"type\": \"locked-cells\", \"unit\": {}, \"cells\": {}}}",
+            json_unit(unit),
+            json_cells(cells)
+        ),
+        Justification::Pattern(cells) => {
+            format!(
+                "{{\"type\": \"pattern\", \"cells\": {}}}",
+                json_cells(cells)
+            )
+        }
+        Justification::Sandwich(unit, sum) => format!(
+            "{{\"type\": \"sandwich\", \"unit\": {}, \"sum\": {}}}",
+            json_unit(unit),
+            sum
+        ),
+        Justification::Arrow(cells) => {
+            format!("{{\"type\": \"arrow\", \"cells\": {}}}", json_cells(cells))
+        }
+        Justification::Xv(cells, mark) => format!(
+            "{{\"type\": \"xv\", \"cells\": {}, \"mark\": {}}}",
+            json_cells(cells),
+            mark.map_or("null".to_string(), |mark| format!("\"{}\"", mark.letter()))
+        ),
+        Justification::Line(kind, cells) => format!(
+            "{{\"type\": \"{}\", \"cells\": {}}}",
+            kind.name(),
+            json_cells(cells)
+        ),
+        Justification::Guess => "{\"type\": \"guess\"}".to_string(),
+    }
+}
+
+pub(super) fn json_consolidation(consolidation: &Consolidation) -> String {
+    let technique = match consolidation.technique() {
+        Some(technique) => technique.name(),
+        None => "guess",
+    };
+
+    match consolidation {
+        Consolidation::CandidatesEliminated {
+            cells,
+            digits,
+            justification,
+            ..
+        } => format!(
+            "{{\"type\": \"elimination\", \"technique\": \"{}\", \"cells\": {}, \"digits\": {}, \"justification\": {}}}",
+            technique,
+            json_cells(cells),
+            json_digits(digits),
+            json_justification(justification)
+        ),
+        Consolidation::SingleCandidateForCell(assignment)
+        | Consolidation::OnlyOnePossibleCandidateForBlock(assignment)
+        | Consolidation::OnlyOnePossibleCandidateForRow(assignment)
+        | Consolidation::OnlyOnePossibleCandidateForColumn(assignment)
+        | Consolidation::OnlyOnePossibleCandidateForDiagonal(assignment)
+        | Consolidation::Guess(assignment) => {
+            let (position, number) = consolidation.assignment().unwrap();
+            format!(
+                "{{\"type\": \"assignment\", \"technique\": \"{}\", \"cell\": {}, \"number\": {}, \"justification\": {}}}",
+                technique,
+                json_cell(position),
+                number,
+                json_justification(&assignment.justification)
+            )
+        }
+    }
+}
+
+// The outcome of a solve as `--format json` gives it, `{"status": "solved", "reason": null, "grid": "341562789768934152..."}`, the grid as far as the solve got with `.` for blanks, or null if the puzzle couldn't be read.
+#[cfg(any(feature = "server", feature = "wasm"))]
+pub(super) fn json_outcome(status: &str, reason: Option<&str>, puzzle: Option<&Puzzle>) -> String {
+    format!(
+        "{{\"status\": \"{}\", \"reason\": {}, \"grid\": {}}}",
+        status,
+        reason.map_or("null".to_string(), json_string),
+        puzzle.map_or("null".to_string(), |puzzle| json_string(&position(puzzle)))
+    )
+}
+
+#[cfg(any(feature = "server", feature = "wasm"))]
+pub(super) fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::super::{
+        solve_with_guesses, GuessOrder, Puzzle, PuzzleStatus, SolverConfig, Technique,
+    };
+
+    const EASY: &str = include_str!("../samples/easy.txt");
+
+    // Like `step()`, without the snapshots
+    fn solve(puzzle: &mut Puzzle) {
+        loop {
+            puzzle.iteration += 1;
+            puzzle.assign_candidates();
+            let before = puzzle.grid;
+            puzzle.last_consolidation = puzzle.run_pipeline();
+            puzzle.record_step(before);
+
+            if puzzle.status() != PuzzleStatus::Unsolved || puzzle.last_consolidation.is_empty() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let mut puzzle = Puzzle::parse(EASY);
+        solve(&mut puzzle);
+
+        let solution = puzzle.solution();
+        assert_eq!(PuzzleStatus::Solved, solution.status());
+        assert!(!solution.steps().is_empty());
+        for (i, step) in solution.steps().iter().enumerate() {
+            assert_eq!(i + 1, step.iteration());
+            assert!(!step.consolidations().is_empty());
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let mut puzzle = Puzzle::parse(EASY);
+        puzzle.solve().unwrap();
+        let stats = puzzle.solution().stats();
+        assert_eq!(puzzle.steps.len(), stats.iterations);
+        assert!(stats.time > Duration::ZERO);
+        assert!(stats.eliminations > 0);
+        assert!(stats
+            .techniques
+            .iter()
+            .any(|&(technique, count)| technique == Technique::NakedSingle && count > 0));
+        assert!(!stats.guessed());
+        let tried: Vec<Technique> = stats.technique_times.iter().map(|&(t, _)| t).collect();
+        assert!(puzzle.config.techniques.starts_with(&tried));
+        assert!(tried.contains(&Technique::NakedSingle));
+
+        // The singles alone get nowhere with the evil puzzle, so it comes to guessing
+        let mut puzzle = Puzzle::parse(include_str!("../samples/evil.txt"));
+        puzzle.set_config(SolverConfig::with_techniques(vec![
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+        ]));
+        puzzle.solve().unwrap();
+        let solved = solve_with_guesses(puzzle).unwrap().unwrap();
+        let stats = solved.solution().stats();
+        assert!(stats.guessed());
+        assert_eq!(solved.guess_report().guesses(), stats.guesses);
+        assert!(stats.describe().contains("Needed guessing"));
+        let guessing: Duration = stats.technique_times.iter().map(|&(_, time)| time).sum();
+        assert!(guessing <= stats.time);
+    }
+
+    #[test]
+    fn deterministic() {
+        // Guessing in a seeded order, through every technique, each solve of the same puzzle is traced byte for byte the same
+        let trace = || {
+            let mut puzzle = Puzzle::parse(include_str!("../samples/expert3.txt"));
+            puzzle.set_config(SolverConfig {
+                guess_order: GuessOrder::Random(42),
+                ..SolverConfig::default()
+            });
+            puzzle.solve().unwrap();
+            let solved = solve_with_guesses(puzzle.clone()).unwrap();
+            solved.unwrap_or(puzzle).solution().trace()
+        };
+        assert_eq!(trace(), trace());
+    }
+
+    #[test]
+    fn trace() {
+        let mut puzzle = Puzzle::parse("");
+        puzzle.assign_candidates();
+        puzzle.guess(4, 2, 7);
+
+        let trace = puzzle.solution().trace();
+        assert!(trace.starts_with("{\n  \"status\": \"unsolved\",\n  \"steps\": ["));
+        assert!(trace.contains("\"iteration\": 1"));
+        assert!(trace.contains("{\"type\": \"assignment\", \"technique\": \"guess\", \"cell\": [4, 2], \"number\": 7, \"justification\": {\"type\": \"guess\"}}"));
+        assert!(trace.contains("\"before\": [[[1, 2, 3, 4, 5, 6, 7, 8, 9], "));
+        assert!(trace.contains("[[1, 2, 3, 4, 5, 6, 7, 8, 9], [1, 2, 3, 4, 5, 6, 7, 8, 9], 7, [1, 2, 3, 4, 5, 6, 7, 8, 9]"));
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut puzzle = Puzzle::parse(EASY);
+        assert!(!puzzle.undo());
+        solve(&mut puzzle);
+        let solved = puzzle.grid;
+        let taken = puzzle.steps.len();
+
+        assert!(puzzle.undo());
+        assert!(puzzle.undo());
+        assert_eq!(taken - 2, puzzle.steps.len());
+        assert_eq!(taken - 2, puzzle.iteration);
+        assert_eq!(puzzle.undone[1].before, puzzle.grid);
+        assert_eq!(PuzzleStatus::Unsolved, puzzle.status());
+
+        assert!(puzzle.redo());
+        assert!(puzzle.redo());
+        assert!(!puzzle.redo());
+        assert_eq!(solved, puzzle.grid);
+        assert_eq!(taken, puzzle.iteration);
+
+        // A new step can't be followed by the old ones
+        while puzzle.undo() {}
+        assert_eq!(0, puzzle.iteration);
+        assert!(puzzle
+            .grid
+            .iter()
+            .flatten()
+            .all(|c| c.given || c.number.is_none()));
+        puzzle.guess(0, 0, 3);
+        assert!(!puzzle.redo());
+    }
+}
@@ -0,0 +1,670 @@
+use std::sync::Arc;
+
+use super::{
+    symbol, Arrow, Candidates, Cell, CellAssignment, Clues, Consolidation, Coord, Edge, Grid,
+    Justification, Line, LineKind, Mark, Puzzle, Shape, Step, Technique, Unit, MAX_SIZE,
+};
+
+// The first line of every saved state, should the format ever need to change
+const HEADER: &str = "christopher state 1";
+
+// A step as far as it's been read: its iteration, its `before` grid, and its consolidations
+type PartialStep = (usize, Option<Grid>, Vec<Consolidation>);
+
+impl Puzzle {
+    // Everything needed to carry on solving later, a line of text at a time: the grid with its candidates, the iteration, and every step taken so far. `load_state()` reads it back. The config isn't saved, so set it again once loaded.
+    //
+    //    christopher state 1
+    //    iteration 2
+    //    diagonals
+    //    grid 4 +6 [1,2] ...
+    //    regions 111222333...
+    //    sandwiches rows 10 . 35 ...
+    //    arrow r1c1,r1c2,r2c3
+    //    xv X r1c1,r1c2
+    //    line renban r2c1,r2c2,r3c3
+    //    last assign cell r1c5 6 peers r3c5,r1c6
+    //    step 1
+    //    before ...
+    //    consolidation eliminate pointing r1c2,r1c3 4 locked b1 r1c1,r1c2
+    //    after ...
+    //
+    // Grids are 81 cells row by row, or as many as another shape has: givens as their number, numbers placed since with a `+`, and blanks as their candidates in brackets. Cells and units are named as in sudoku books, counting from 1. `last` lines hold what the last step did. The `diagonals` line is only there for X-Sudoku, the `regions` line, each cell's region numbered as its block would be, for a jigsaw, `sandwiches` lines, a sum or `.` for each row or column, for sandwich sudoku, an `arrow` line for each arrow, its circle first, for arrow sudoku, and an `xv` line for each X or V and the cells either side, with `xv negative` under the negative constraint, for XV sudoku, and a `line` line for each renban or whisper line and its cells, from its start.
+    pub fn save_state(&self) -> String {
+        let mut lines = vec![HEADER.to_string(), format!("iteration {}", self.iteration)];
+        if self.shape.has_diagonals() {
+            lines.push("diagonals".to_string());
+        }
+        lines.push(format!("grid {}", save_grid(self.shape, &self.grid)));
+        if self.shape.is_jigsaw() {
+            let regions: String = self
+                .shape
+                .coords()
+                .map(|coord| symbol(self.shape.block_of(coord) as u8 + 1))
+                .collect();
+            lines.push(format!("regions {}", regions));
+        }
+        let sandwiches = self.clues.sandwiches;
+        for (name, sums) in [("rows", sandwiches.rows), ("columns", sandwiches.columns)] {
+            let sums = &sums[..self.shape.size()];
+            if sums.iter().any(Option::is_some) {
+                let sums: Vec<String> = sums
+                    .iter()
+                    .map(|sum| sum.map_or(".".to_string(), |sum| sum.to_string()))
+                    .collect();
+                lines.push(format!("sandwiches {} {}", name, sums.join(" ")));
+            }
+        }
+        for arrow in self.clues.arrows.iter() {
+            let cells: Vec<(usize, usize)> = arrow.cells().into_iter().map(Into::into).collect();
+            lines.push(format!("arrow {}", save_cells(&cells)));
+        }
+        let xv = &self.clues.xv;
+        if xv.is_negative() {
+            lines.push("xv negative".to_string());
+        }
+        for edge in xv.iter() {
+            let cells: Vec<(usize, usize)> = edge.cells().into_iter().map(Into::into).collect();
+            lines.push(format!(
+                "xv {} {}",
+                edge.mark().letter(),
+                save_cells(&cells)
+            ));
+        }
+        for line in self.clues.lines.iter() {
+            let cells: Vec<(usize, usize)> = line.cells().into_iter().map(Into::into).collect();
+            lines.push(format!(
+                "line {} {}",
+                line.kind().name(),
+                save_cells(&cells)
+            ));
+        }
+        for consolidation in &self.last_consolidation {
+            lines.push(format!("last {}", save_consolidation(consolidation)));
+        }
+
+        for step in &self.steps {
+            lines.push(format!("step {}", step.iteration));
+            lines.push(format!("before {}", save_grid(self.shape, &step.before)));
+            for consolidation in &step.consolidations {
+                lines.push(format!(
+                    "consolidation {}",
+                    save_consolidation(consolidation)
+                ));
+            }
+            lines.push(format!("after {}", save_grid(self.shape, &step.after)));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    // Read back the state `save_state()` saved, with the default config.
+    pub fn load_state(state: &str) -> Result<Puzzle, String> {
+        let mut lines = state
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, HEADER)) => {}
+            _ => return Err(format!("Expected a saved state starting '{}'", HEADER)),
+        }
+
+        let mut iteration = None;
+        let mut diagonals = false;
+        let mut grid = None;
+        // Told by the grid, which comes before any consolidations
+        let mut shape = Shape::STANDARD;
+        let mut clues = Clues::default();
+        let mut last_consolidation = vec![];
+        let mut steps: Vec<Step> = vec![];
+
+        // The step being read, until its `after` grid completes it
+        let mut step: Option<PartialStep> = None;
+
+        for (line_number, line) in lines {
+            let at = |message: String| format!("Line {}: {}", line_number, message);
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+            match (keyword, &mut step) {
+                ("iteration", _) => iteration = Some(load_number(rest).map_err(at)?),
+                ("diagonals", _) => diagonals = true,
+                ("grid", _) => {
+                    let (grid_shape, cells) = load_grid(rest).map_err(at)?;
+                    shape = grid_shape;
+                    grid = Some(cells);
+                }
+                ("regions", _) => {
+                    let regions: Vec<usize> = rest.chars().map(|c| c as usize).collect();
+                    shape = shape.with_regions(&regions).map_err(at)?;
+                }
+                ("sandwiches", _) => load_sandwiches(shape, &mut clues, rest).map_err(at)?,
+                ("arrow", _) => load_arrow(shape, &mut clues, rest).map_err(at)?,
+                ("xv", _) => load_xv(shape, &mut clues, rest).map_err(at)?,
+                ("line", _) => load_line(shape, &mut clues, rest).map_err(at)?,
+                ("last", _) => {
+                    last_consolidation.push(load_consolidation(shape, rest).map_err(at)?)
+                }
+                ("step", None) => step = Some((load_number(rest).map_err(at)?, None, vec![])),
+                ("before", Some((_, before, _))) => *before = Some(load_grid(rest).map_err(at)?.1),
+                ("consolidation", Some((_, _, consolidations))) => {
+                    consolidations.push(load_consolidation(shape, rest).map_err(at)?)
+                }
+                ("after", Some((iteration, Some(before), consolidations))) => {
+                    steps.push(Step {
+                        iteration: *iteration,
+                        before: *before,
+                        consolidations: std::mem::take(consolidations),
+                        after: load_grid(rest).map_err(at)?.1,
+                    });
+                    step = None;
+                }
+                ("after", Some((_, None, _))) => {
+                    return Err(at("The step has no 'before' grid".into()))
+                }
+                ("step", Some(_)) => return Err(at("The step before has no 'after' grid".into())),
+                ("before" | "consolidation" | "after", _) => {
+                    return Err(at(format!("'{}' outside a step", keyword)))
+                }
+                _ => return Err(at(format!("Unknown line '{}'", keyword))),
+            }
+        }
+        if step.is_some() {
+            return Err("The last step has no 'after' grid".to_string());
+        }
+
+        let shape = shape.with_diagonals(diagonals);
+        let mut puzzle = Puzzle::with_grid(shape, grid.ok_or("No grid saved")?);
+        puzzle.clues = Arc::new(clues);
+        puzzle.iteration = iteration.ok_or("No iteration saved")?;
+        puzzle.last_consolidation = last_consolidation;
+        puzzle.steps = steps;
+        Ok(puzzle)
+    }
+}
+
+fn save_grid(shape: Shape, grid: &Grid) -> String {
+    let cells: Vec<String> = shape
+        .coords()
+        .map(|coord| grid[coord.row][coord.col])
+        .map(|cell| match (cell.number, cell.given) {
+            (Some(number), true) => number.to_string(),
+            (Some(number), false) => format!("+{}", number),
+            (None, _) => format!("[{}]", save_digits(&cell.candidates.to_vec())),
+        })
+        .collect();
+    cells.join(" ")
+}
+
+fn save_digits(digits: &[u8]) -> String {
+    let digits: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+    digits.join(",")
+}
+
+// Comma separated, or `-` for none
+fn save_cells(cells: &[(usize, usize)]) -> String {
+    if cells.is_empty() {
+        return "-".to_string();
+    }
+    let names: Vec<String> = cells.iter().map(|&p| Coord::from(p).to_string()).collect();
+    names.join(",")
+}
+
+fn save_unit(unit: &Unit) -> String {
+    match unit {
+        Unit::Row(row) => format!("r{}", row + 1),
+        Unit::Column(col) => format!("c{}", col + 1),
+        Unit::Block(b) => format!("b{}", b + 1),
+        Unit::Diagonal(d) => format!("d{}", d + 1),
+    }
+}
+
+fn save_justification(justification: &Justification) -> String {
+    match justification {
+        Justification::Peers(peers) => format!("peers {}", save_cells(peers)),
+        Justification::OnlyPlaceInUnit(unit) => format!("only {}", save_unit(unit)),
+        Justification::LockedCells(unit, cells) => {
+            format!("locked {} {}", save_unit(unit), save_cells(cells))
+        }
+        Justification::Pattern(cells) => format!("pattern {}", save_cells(cells)),
+        Justification::Sandwich(unit, sum) => format!("sandwich {} {}", save_unit(unit), sum),
+        Justification::Arrow(cells) => format!("arrow {}", save_cells(cells)),
+        Justification::Xv(cells, mark) => format!(
+            "xv {} {}",
+            mark.map_or('-', |mark| mark.letter()),
+            save_cells(cells)
+        ),
+        Justification::Line(kind, cells) => format!("line {} {}", kind.name(), save_cells(cells)),
+        Justification::Guess => "guess".to_string(),
+    }
+}
+
+// `assign <how> <cell> <number> <justification>` or `eliminate <technique> <cells> <digits> <justification>`
+fn save_consolidation(consolidation: &Consolidation) -> String {
+    let (how, assignment) = match consolidation {
+        Consolidation::SingleCandidateForCell(assignment) => ("cell", assignment),
+        Consolidation::OnlyOnePossibleCandidateForBlock(assignment) => ("block", assignment),
+        Consolidation::OnlyOnePossibleCandidateForRow(assignment) => ("row", assignment),
+        Consolidation::OnlyOnePossibleCandidateForColumn(assignment) => ("column", assignment),
+        Consolidation::OnlyOnePossibleCandidateForDiagonal(assignment) => ("diagonal", assignment),
+        Consolidation::Guess(assignment) => ("guess", assignment),
+        Consolidation::CandidatesEliminated {
+            technique,
+            cells,
+            digits,
+            justification,
+        } => {
+            return format!(
+                "eliminate {} {} {} {}",
+                technique.name(),
+                save_cells(cells),
+                save_digits(digits),
+                save_justification(justification)
+            )
+        }
+    };
+
+    let (position, number) = consolidation.assignment().unwrap();
+    format!(
+        "assign {} {} {} {}",
+        how,
+        Coord::from(position),
+        number,
+        save_justification(&assignment.justification)
+    )
+}
+
+// Add the sums of a `sandwiches` line to the clues: `rows` or `columns`, then a sum or `.` for each.
+fn load_sandwiches(shape: Shape, clues: &mut Clues, text: &str) -> Result<(), String> {
+    let sandwiches = &mut clues.sandwiches;
+    let (name, sums) = text.split_once(' ').unwrap_or((text, ""));
+    let lines = match name {
+        "rows" => &mut sandwiches.rows,
+        "columns" => &mut sandwiches.columns,
+        _ => {
+            return Err(format!(
+                "Expected sandwiches for rows or columns, not '{}'",
+                name
+            ))
+        }
+    };
+
+    let sums: Vec<&str> = sums.split_whitespace().collect();
+    if sums.len() != shape.size() {
+        return Err(format!(
+            "Expected a sandwich for each of the {} {}, not {}",
+            shape.size(),
+            name,
+            sums.len()
+        ));
+    }
+    for (line, sum) in lines.iter_mut().zip(sums) {
+        *line = match sum {
+            "." => None,
+            _ => Some(
+                sum.parse()
+                    .map_err(|_| format!("Expected a sandwich sum, not '{}'", sum))?,
+            ),
+        };
+    }
+    Ok(())
+}
+
+// Add the arrow of an `arrow` line to the clues: its cells, the circle first.
+fn load_arrow(shape: Shape, clues: &mut Clues, text: &str) -> Result<(), String> {
+    let cells: Vec<Coord> = text
+        .split(',')
+        .map(|name| shape.parse_cell(name))
+        .collect::<Result<_, _>>()?;
+    let (&circle, path) = cells
+        .split_first()
+        .ok_or_else(|| "Expected an arrow's cells".to_string())?;
+
+    clues.arrows.push(Arrow::new(circle, path)?);
+    Ok(())
+}
+
+// Add an `xv` line's mark to the clues, or the negative constraint.
+fn load_xv(shape: Shape, clues: &mut Clues, text: &str) -> Result<(), String> {
+    if text == "negative" {
+        clues.xv = std::mem::take(&mut clues.xv).with_negative(true);
+        return Ok(());
+    }
+
+    let (mark, cells) = text
+        .split_once(' ')
+        .ok_or_else(|| format!("Expected an X or V and its cells, not '{}'", text))?;
+    let cells: Vec<Coord> = cells
+        .split(',')
+        .map(|name| shape.parse_cell(name))
+        .collect::<Result<_, _>>()?;
+    let [a, b] = cells[..] else {
+        return Err(format!("Expected two cells either side of the {}", mark));
+    };
+
+    clues.xv.push(Edge::new(a, b, Mark::parse(mark)?)?)
+}
+
+// Add a `line` line's renban or whisper to the clues: its kind, then its cells from the start.
+fn load_line(shape: Shape, clues: &mut Clues, text: &str) -> Result<(), String> {
+    let (kind, cells) = text
+        .split_once(' ')
+        .ok_or_else(|| format!("Expected a kind of line and its cells, not '{}'", text))?;
+    let cells: Vec<Coord> = cells
+        .split(',')
+        .map(|name| shape.parse_cell(name))
+        .collect::<Result<_, _>>()?;
+
+    clues.lines.push(Line::new(LineKind::parse(kind)?, &cells)?);
+    Ok(())
+}
+
+fn load_number(text: &str) -> Result<usize, String> {
+    text.parse()
+        .map_err(|_| format!("Expected a number, not '{}'", text))
+}
+
+// The grid and its shape, told by how many cells it has.
+fn load_grid(text: &str) -> Result<(Shape, Grid), String> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let shape = match Shape::of_cell_count(tokens.len()) {
+        Some(shape) => shape,
+        None => {
+            return Err(format!(
+                "Expected a grid's worth of cells, such as 81 for 9x9, not {}",
+                tokens.len()
+            ))
+        }
+    };
+
+    let mut grid = [[Cell {
+        number: None,
+        given: false,
+        candidates: Candidates::none(),
+    }; MAX_SIZE]; MAX_SIZE];
+    for (coord, token) in shape.coords().zip(tokens) {
+        let cell = &mut grid[coord.row][coord.col];
+        if let Some(digits) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            cell.candidates = load_digits(digits)?.iter().collect();
+        } else {
+            let (given, digit) = match token.strip_prefix('+') {
+                Some(digit) => (false, digit),
+                None => (true, token),
+            };
+            cell.number = Some(load_digit(digit)?);
+            cell.given = given;
+        }
+    }
+
+    Ok((shape, grid))
+}
+
+fn load_digit(text: &str) -> Result<u8, String> {
+    match text.parse::<u8>() {
+        Ok(digit) if (1..=MAX_SIZE as u8).contains(&digit) => Ok(digit),
+        _ => Err(format!(
+            "Expected a digit 1 thru {}, not '{}'",
+            MAX_SIZE, text
+        )),
+    }
+}
+
+fn load_digits(text: &str) -> Result<Vec<u8>, String> {
+    text.split(',')
+        .filter(|d| !d.is_empty())
+        .map(load_digit)
+        .collect()
+}
+
+fn load_cells(text: &str) -> Result<Vec<(usize, usize)>, String> {
+    if text == "-" {
+        return Ok(vec![]);
+    }
+    text.split(',')
+        .map(|name| name.parse::<Coord>().map(Into::into))
+        .collect()
+}
+
+fn load_unit(text: &str) -> Result<Unit, String> {
+    let number = text
+        .get(1..)
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| (1..=MAX_SIZE).contains(n));
+    match (text.chars().next(), number) {
+        (Some('r'), Some(n)) => Ok(Unit::Row(n - 1)),
+        (Some('c'), Some(n)) => Ok(Unit::Column(n - 1)),
+        (Some('b'), Some(n)) => Ok(Unit::Block(n - 1)),
+        (Some('d'), Some(n)) if n <= 2 => Ok(Unit::Diagonal(n - 1)),
+        _ => Err(format!(
+            "Expected a unit such as r4, c7, or b5, not '{}'",
+            text
+        )),
+    }
+}
+
+fn load_justification(words: &[&str]) -> Result<Justification, String> {
+    match words {
+        ["peers", cells] => Ok(Justification::Peers(load_cells(cells)?)),
+        ["only", unit] => Ok(Justification::OnlyPlaceInUnit(load_unit(unit)?)),
+        ["locked", unit, cells] => Ok(Justification::LockedCells(
+            load_unit(unit)?,
+            load_cells(cells)?,
+        )),
+        ["pattern", cells] => Ok(Justification::Pattern(load_cells(cells)?)),
+        ["sandwich", unit, sum] => Ok(Justification::Sandwich(
+            load_unit(unit)?,
+            sum.parse()
+                .map_err(|_| format!("Expected a sandwich sum, not '{}'", sum))?,
+        )),
+        ["arrow", cells] => Ok(Justification::Arrow(load_cells(cells)?)),
+        ["xv", mark, cells] => {
+            let mark = match *mark {
+                "-" => None,
+                mark => Some(Mark::parse(mark)?),
+            };
+            match load_cells(cells)?[..] {
+                [a, b] => Ok(Justification::Xv([a, b], mark)),
+                _ => Err(format!(
+                    "Expected the two cells either side of an XV mark, not '{}'",
+                    cells
+                )),
+            }
+        }
+        ["line", kind, cells] => Ok(Justification::Line(
+            LineKind::parse(kind)?,
+            load_cells(cells)?,
+        )),
+        ["guess"] => Ok(Justification::Guess),
+        _ => Err(format!("Unknown justification '{}'", words.join(" "))),
+    }
+}
+
+fn load_consolidation(shape: Shape, text: &str) -> Result<Consolidation, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["assign", how, cell, number, justification @ ..] => {
+            let coord: Coord = cell.parse()?;
+            let assignment = CellAssignment {
+                number: load_digit(number)?,
+                block: shape.block_of(coord),
+                row: coord.row,
+                col: coord.col,
+                justification: load_justification(justification)?,
+            };
+            match *how {
+                "cell" => Ok(Consolidation::SingleCandidateForCell(assignment)),
+                "block" => Ok(Consolidation::OnlyOnePossibleCandidateForBlock(assignment)),
+                "row" => Ok(Consolidation::OnlyOnePossibleCandidateForRow(assignment)),
+                "column" => Ok(Consolidation::OnlyOnePossibleCandidateForColumn(assignment)),
+                "diagonal" => Ok(Consolidation::OnlyOnePossibleCandidateForDiagonal(
+                    assignment,
+                )),
+                "guess" => Ok(Consolidation::Guess(assignment)),
+                _ => Err(format!("Unknown assignment '{}'", how)),
+            }
+        }
+        ["eliminate", technique, cells, digits, justification @ ..] => {
+            Ok(Consolidation::CandidatesEliminated {
+                technique: *Technique::ALL
+                    .iter()
+                    .find(|t| t.name() == *technique)
+                    .ok_or(format!("Unknown technique '{}'", technique))?,
+                cells: load_cells(cells)?,
+                digits: load_digits(digits)?,
+                justification: load_justification(justification)?,
+            })
+        }
+        _ => Err(format!("Unknown consolidation '{}'", text)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{solve_with_guesses, Puzzle, SolverConfig, Technique};
+
+    const EVIL: &str = include_str!("../samples/evil.txt");
+
+    // Too few techniques to solve evil without guessing
+    fn quiet(puzzle: &mut Puzzle) {
+        puzzle.set_config(SolverConfig {
+            techniques: vec![
+                Technique::WaterCannon,
+                Technique::NakedSingle,
+                Technique::HiddenSingle,
+            ],
+            ..SolverConfig::default()
+        });
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut puzzle = Puzzle::parse(EVIL);
+        quiet(&mut puzzle);
+        puzzle.solve().unwrap();
+        let solved = solve_with_guesses(puzzle).unwrap().unwrap();
+
+        let state = solved.save_state();
+        assert!(state.contains("\nconsolidation assign guess r"));
+        assert!(state.contains("\nconsolidation eliminate "));
+
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(solved.grid, loaded.grid);
+        assert_eq!(solved.iteration, loaded.iteration);
+        assert_eq!(solved.last_consolidation, loaded.last_consolidation);
+        assert_eq!(solved.steps.len(), loaded.steps.len());
+        for (saved, loaded) in solved.steps.iter().zip(&loaded.steps) {
+            assert_eq!(saved.iteration, loaded.iteration);
+            assert_eq!(saved.before, loaded.before);
+            assert_eq!(saved.consolidations, loaded.consolidations);
+            assert_eq!(saved.after, loaded.after);
+        }
+        assert_eq!(state, loaded.save_state());
+    }
+
+    #[test]
+    fn resume() {
+        let mut puzzle = Puzzle::parse(EVIL);
+        quiet(&mut puzzle);
+        for _ in 0..3 {
+            puzzle.step();
+        }
+
+        let mut resumed = Puzzle::load_state(&puzzle.save_state()).unwrap();
+        quiet(&mut resumed);
+        puzzle.solve().unwrap();
+        resumed.solve().unwrap();
+        assert_eq!(puzzle.grid, resumed.grid);
+        assert_eq!(puzzle.iteration, resumed.iteration);
+        assert_eq!(puzzle.steps.len(), resumed.steps.len());
+
+        // X-Sudoku stays X-Sudoku, and the diagonals' hidden singles come back as they were
+        let mut puzzle = Puzzle::parse(include_str!("../samples/x-sudoku.txt"));
+        quiet(&mut puzzle);
+        puzzle.solve().unwrap();
+        let state = puzzle.save_state();
+        assert!(state.contains("\ndiagonals\n"));
+        assert!(state.contains("\nconsolidation assign diagonal "));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert!(loaded.shape().has_diagonals());
+        assert_eq!(state, loaded.save_state());
+
+        // As does a jigsaw's every region
+        let mut puzzle = Puzzle::parse(include_str!("../samples/jigsaw.txt"));
+        quiet(&mut puzzle);
+        puzzle.step();
+        let state = puzzle.save_state();
+        assert!(state.contains("\nregions 122223333111122223111123333445555666"));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(puzzle.shape(), loaded.shape());
+        assert_eq!(state, loaded.save_state());
+        assert!(Puzzle::load_state(&state.replace("regions 1222", "regions 2222")).is_err());
+
+        // And sandwich sums with their eliminations
+        let mut puzzle = Puzzle::parse(include_str!("../samples/sandwich.txt"));
+        puzzle.step();
+        let state = puzzle.save_state();
+        assert!(state.contains("\nsandwiches rows "));
+        assert!(state.contains(" sandwich r"));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(puzzle.shape(), loaded.shape());
+        assert_eq!(state, loaded.save_state());
+        assert!(
+            Puzzle::load_state(&state.replace("sandwiches rows ", "sandwiches rows 1 ")).is_err()
+        );
+
+        // And arrows with theirs
+        let mut puzzle = Puzzle::parse(include_str!("../samples/arrow.txt"));
+        puzzle.step();
+        let state = puzzle.save_state();
+        assert!(state.contains("\narrow r4c4,r5c3\n"));
+        assert!(state.contains(" arrow r"));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(puzzle.shape(), loaded.shape());
+        assert_eq!(state, loaded.save_state());
+        assert!(Puzzle::load_state(&state.replace("arrow r4c4,r5c3", "arrow r4c4")).is_err());
+
+        // And XV marks, with the negative constraint
+        let mut puzzle = Puzzle::parse(include_str!("../samples/xv.txt"));
+        puzzle.step();
+        let state = puzzle.save_state();
+        assert!(state.contains("\nxv negative\nxv V r1c1,r1c2\n"));
+        assert!(state.contains(" xv "));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(puzzle.shape(), loaded.shape());
+        assert_eq!(state, loaded.save_state());
+        assert!(Puzzle::load_state(&state.replace("xv V r1c1,r1c2", "xv V r1c1,r2c2")).is_err());
+        assert!(Puzzle::load_state(&state.replace("xv V r1c1,r1c2", "xv W r1c1,r1c2")).is_err());
+
+        // And renban and whisper lines
+        let mut puzzle = Puzzle::parse(include_str!("../samples/lines.txt"));
+        puzzle.step();
+        let state = puzzle.save_state();
+        assert!(state.contains("\nline whisper r5c4,r6c5,r5c5,r4c4\n"));
+        assert!(state.contains(" line "));
+        let loaded = Puzzle::load_state(&state).unwrap();
+        assert_eq!(puzzle.shape(), loaded.shape());
+        assert_eq!(state, loaded.save_state());
+        assert!(
+            Puzzle::load_state(&state.replace("line whisper r5c4", "line thermo r5c4")).is_err()
+        );
+        assert!(Puzzle::load_state(&state.replace("r5c4,r6c5", "r5c4,r6c6")).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Puzzle::load_state("iteration 1").is_err());
+
+        let state = "christopher state 1\niteration 1\ngrid 1 2 3\n";
+        assert_eq!(
+            Err("Line 3: Expected a grid's worth of cells, such as 81 for 9x9, not 3".to_string()),
+            Puzzle::load_state(state).map(|_| ())
+        );
+
+        let state = "christopher state 1\nafter 1\n";
+        assert_eq!(
+            Err("Line 2: 'after' outside a step".to_string()),
+            Puzzle::load_state(state).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use super::{Consolidation, Puzzle, PuzzleStatus, SolverConfig, Step};
+
+/// A solve taken a step at a time, as each is asked for, so a front end can show each step and decide whether to carry on. See `Puzzle::steps()`.
+pub struct Steps {
+    puzzle: Puzzle,
+    // Set once a step assigns nothing, as the techniques have then run out
+    stuck: bool,
+}
+
+impl Puzzle {
+    // The steps of solving the puzzle with the config, none taken until asked for. They're taken as `solve()` takes them, but quietly whatever the config says, as whoever's asking will show them their own way. There's no guessing, so the steps end once the puzzle's solved, found ill-defined, or the techniques run out.
+    pub fn steps(&self, config: &SolverConfig) -> Steps {
+        let mut puzzle = self.clone();
+        puzzle.set_config(SolverConfig {
+            verbose: false,
+            ..config.clone()
+        });
+        puzzle.assign_candidates();
+
+        Steps {
+            puzzle,
+            stuck: false,
+        }
+    }
+}
+
+impl Steps {
+    // The puzzle as the steps so far have left it.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn into_puzzle(self) -> Puzzle {
+        self.puzzle
+    }
+}
+
+impl Iterator for Steps {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        if self.stuck || self.puzzle.status() != PuzzleStatus::Unsolved {
+            return None;
+        }
+
+        // Eliminations are made until a number's assigned, so a step without assignments is the last
+        let progress = self.puzzle.step();
+        self.stuck = !progress.iter().any(Consolidation::is_assignment);
+        if progress.is_empty() {
+            return None;
+        }
+
+        self.puzzle.steps.last().cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Puzzle, PuzzleStatus, SolverConfig, Technique};
+
+    #[test]
+    fn steps() {
+        let easy = Puzzle::parse(include_str!("../samples/easy.txt"));
+        let mut solved = easy.clone();
+        solved.solve().unwrap();
+
+        let mut steps = easy.steps(&SolverConfig::default());
+        let first = steps.next().unwrap();
+        assert_eq!(1, first.iteration());
+        assert_eq!(1, steps.puzzle().solution().steps().len());
+
+        let rest: Vec<usize> = steps.by_ref().map(|step| step.iteration()).collect();
+        assert_eq!(solved.solution().steps().len(), rest.len() + 1);
+        assert_eq!(PuzzleStatus::Solved, steps.puzzle().status());
+        assert!(steps.next().is_none());
+
+        // Singles alone run out on the evil puzzle, leaving it unsolved
+        let evil = Puzzle::parse(include_str!("../samples/evil.txt"));
+        let singles =
+            SolverConfig::with_techniques(vec![Technique::NakedSingle, Technique::HiddenSingle]);
+        let mut steps = evil.steps(&singles);
+        assert!(steps.by_ref().count() > 0);
+        assert_eq!(PuzzleStatus::Unsolved, steps.into_puzzle().status());
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::{BufRead, Write};
+
+use super::batch::{line_puzzle, push_row, solve_line, Tally, CSV_HEADER};
+use super::SolverConfig;
+
+// Puzzles read in at a time and shared out among the threads. Memory stays bounded by this however long the input runs.
+const CHUNK: usize = 4096;
+
+// Solve each puzzle read from the input, a line at a time as for `batch()`, writing each puzzle's line of CSV to the output as `Batch::csv()` would, a chunk of puzzles at a time and in the order read. Only a chunk of the input is ever held, so collections of millions of puzzles can be solved in bounded memory, spread over a thread per core. The lines read, the CSV written, and each thread's puzzle are reused chunk after chunk, each line read into its thread's puzzle in place of the last. The solver keeps quiet whatever the config says.
+pub fn stream<R: BufRead, W: Write>(
+    input: R,
+    output: W,
+    config: &SolverConfig,
+) -> std::io::Result<Tally> {
+    stream_in_chunks(input, output, config, CHUNK)
+}
+
+fn stream_in_chunks<R: BufRead, W: Write>(
+    mut input: R,
+    mut output: W,
+    config: &SolverConfig,
+    chunk: usize,
+) -> std::io::Result<Tally> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut lines = vec![String::new(); chunk];
+    let mut buffers = vec![String::new(); threads];
+    let mut puzzles = vec![line_puzzle(config); threads];
+    let mut tally = Tally::default();
+
+    output.write_all(CSV_HEADER.as_bytes())?;
+    loop {
+        let mut read = 0;
+        while read < chunk {
+            let line = &mut lines[read];
+            line.clear();
+            if input.read_line(line)? == 0 {
+                break;
+            }
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                read += 1;
+            }
+        }
+        if read == 0 {
+            break;
+        }
+
+        let share = read.div_ceil(threads);
+        let tallies: Vec<Tally> = std::thread::scope(|scope| {
+            let handles: Vec<_> = lines[..read]
+                .chunks(share)
+                .zip(buffers.iter_mut().zip(puzzles.iter_mut()))
+                .map(|(lines, (csv, puzzle))| {
+                    scope.spawn(move || {
+                        let mut tally = Tally::default();
+                        csv.clear();
+                        for line in lines {
+                            let result = solve_line(puzzle, line.trim());
+                            push_row(csv, &result);
+                            tally.add(&result);
+                        }
+                        tally
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        for (csv, counted) in buffers.iter().zip(tallies.iter()) {
+            output.write_all(csv.as_bytes())?;
+            tally.merge(counted);
+        }
+        if read < chunk {
+            break;
+        }
+    }
+    output.flush()?;
+
+    Ok(tally)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{batch, SolverConfig};
+    use super::{stream, stream_in_chunks};
+
+    const EASY: &str =
+        ".4.5.2...76....1.29...18.64..429...8.8.3.6.7.6...754..21.68...34.6....27...4.9.1.";
+
+    #[test]
+    fn streams() {
+        let input = format!(
+            "# A comment\n{}\n\n123\n{}\n{}\n44{}\n",
+            EASY,
+            EASY,
+            EASY,
+            &EASY[2..]
+        );
+        let batch = batch(&input, &SolverConfig::default());
+
+        let mut output = vec![];
+        let tally = stream(input.as_bytes(), &mut output, &SolverConfig::default()).unwrap();
+        assert_eq!(batch.csv(), String::from_utf8(output).unwrap());
+        assert_eq!(batch.summary(), tally.summary());
+
+        // Chunks smaller than the input, going round more than once, come out the same
+        let mut output = vec![];
+        let tally =
+            stream_in_chunks(input.as_bytes(), &mut output, &SolverConfig::default(), 2).unwrap();
+        assert_eq!(batch.csv(), String::from_utf8(output).unwrap());
+        assert_eq!(3, tally.solved);
+        assert_eq!(1, tally.ill_defined);
+        assert_eq!(1, tally.invalid);
+    }
+}
@@ -0,0 +1,132 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::engine::parse_position;
+use super::Puzzle;
+
+// The solution every arbitrary puzzle is disguised from
+const SOLUTION: &str =
+    "341562789768934152952718364574291638189346275623875491215687943496153827837429516";
+
+// Keeps two cells in five, around 32 givens, enough for the solver to get somewhere without a long search. Out of bytes, every cell is kept.
+const KEPT: (u8, u8) = (2, 5);
+
+// Randomly valid partial grids, for property tests and fuzzing: a solution disguised by relabelling its digits, shuffling its bands and the rows within them, likewise its stacks and columns, and maybe reflecting it about the diagonal, then with some of its cells blanked. Every one has a solution, though not always just the one. The solver keeps quiet, as a fuzzer solves millions.
+impl<'a> Arbitrary<'a> for Puzzle {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Puzzle> {
+        let solution: Vec<u8> = SOLUTION.bytes().map(|b| b - b'0').collect();
+
+        let mut digits: Vec<u8> = (1..=9).collect();
+        shuffle(u, &mut digits)?;
+        let rows = lines(u)?;
+        let cols = lines(u)?;
+        let reflect = bool::arbitrary(u)?;
+
+        let mut text = String::with_capacity(81);
+        for &row in rows.iter() {
+            for &col in cols.iter() {
+                let (r, c) = match reflect {
+                    true => (col, row),
+                    false => (row, col),
+                };
+                let digit = digits[solution[r * 9 + c] as usize - 1];
+                if u.ratio(KEPT.0, KEPT.1)? {
+                    text.push((b'0' + digit) as char);
+                } else {
+                    text.push('.');
+                }
+            }
+        }
+
+        parse_position(&text).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+// The rows, or columns, in a shuffled order that keeps each band, or stack, together.
+fn lines(u: &mut Unstructured) -> Result<Vec<usize>> {
+    let mut bands: Vec<usize> = (0..3).collect();
+    shuffle(u, &mut bands)?;
+
+    let mut lines = Vec::with_capacity(9);
+    for band in bands {
+        let mut within: Vec<usize> = (0..3).map(|i| band * 3 + i).collect();
+        shuffle(u, &mut within)?;
+        lines.extend(within);
+    }
+    Ok(lines)
+}
+
+fn shuffle<T>(u: &mut Unstructured, items: &mut [T]) -> Result<()> {
+    for i in (1..items.len()).rev() {
+        let j = u.int_in_range(0..=i)?;
+        items.swap(i, j);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use super::super::rng::Rng;
+    use super::super::{Puzzle, PuzzleStatus, SolverConfig, Technique};
+
+    // Arbitrary puzzles from seeded random bytes, the same every run.
+    fn puzzles(count: usize) -> Vec<Puzzle> {
+        let mut rng = Rng::new(2661);
+        (0..count)
+            .map(|_| {
+                let bytes: Vec<u8> = (0..256).map(|_| rng.next_u64() as u8).collect();
+                Puzzle::arbitrary(&mut Unstructured::new(&bytes)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arbitrary_puzzles_have_solutions() {
+        let puzzles = puzzles(20);
+        for puzzle in puzzles.iter() {
+            assert_eq!(1, puzzle.count_solutions(1));
+            assert!(puzzle.givens().len() > 17);
+        }
+        assert_ne!(puzzles[0].to_string(), puzzles[1].to_string());
+
+        // Out of bytes, every cell is kept
+        let plain = Puzzle::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(81, plain.givens().len());
+    }
+
+    #[test]
+    fn eliminations_keep_the_solution() {
+        // The uniqueness techniques assume a puzzle has just the one solution, which an arbitrary one needn't
+        let techniques: Vec<Technique> = Technique::ALL
+            .into_iter()
+            .filter(|t| {
+                ![
+                    Technique::UniqueRectangle,
+                    Technique::UniqueLoop,
+                    Technique::BugLite,
+                ]
+                .contains(t)
+            })
+            .collect();
+
+        for mut puzzle in puzzles(20) {
+            let solution = puzzle.solutions(1).remove(0);
+            puzzle.set_config(SolverConfig::with_techniques(techniques.clone()));
+            puzzle.solve().unwrap();
+
+            assert!(!matches!(puzzle.status(), PuzzleStatus::IllDefined(_)));
+            for coord in puzzle.shape.coords() {
+                let digit = solution[coord].number.unwrap();
+                match puzzle[coord].number {
+                    Some(number) => assert_eq!(digit, number),
+                    None => assert!(puzzle[coord].candidates.contains(digit)),
+                }
+            }
+        }
+    }
+}
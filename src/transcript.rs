@@ -0,0 +1,103 @@
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+type Writer = Arc<Mutex<BufWriter<Box<dyn Write + Send>>>>;
+
+/// Where the solver's workings go when the config's verbose, to set in `SolverConfig::transcript`: standard output unless given a writer of your own. Writes are buffered, so the lines the techniques write from their loops cost little, and flushed at the end of each step and of each search of guesses. Shared by every copy of the config, including those of any guesses, each line written whole.
+#[derive(Clone)]
+pub struct Transcript(Writer);
+
+impl Transcript {
+    pub fn new(writer: impl Write + Send + 'static) -> Transcript {
+        Transcript(Arc::new(Mutex::new(BufWriter::new(Box::new(writer)))))
+    }
+
+    // The one transcript to standard output, shared by every config writing there so their lines can't cross.
+    pub fn stdout() -> Transcript {
+        static STDOUT: OnceLock<Transcript> = OnceLock::new();
+        STDOUT.get_or_init(|| Transcript::new(io::stdout())).clone()
+    }
+
+    // Add the text to the transcript. Workings are only ever a help, so a transcript that can't be written to is left be rather than failing the solve.
+    pub(crate) fn write(&self, text: &str) {
+        let _ = self.writer().write_all(text.as_bytes());
+    }
+
+    pub fn flush(&self) {
+        let _ = self.writer().flush();
+    }
+
+    fn writer(&self) -> MutexGuard<'_, BufWriter<Box<dyn Write + Send>>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Transcript")
+    }
+}
+
+// Configs are the same only if they share the one transcript.
+impl PartialEq for Transcript {
+    fn eq(&self, other: &Transcript) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use super::super::{Puzzle, PuzzleStatus, SolverConfig};
+    use super::Transcript;
+
+    // A writer whose bytes can be read back once the solve's done with it.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transcript() {
+        let written = Shared::default();
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        puzzle.set_config(SolverConfig {
+            verbose: true,
+            plain: true,
+            transcript: Transcript::new(written.clone()),
+            ..SolverConfig::default()
+        });
+        puzzle.solve().unwrap();
+        assert_eq!(PuzzleStatus::Solved, puzzle.status());
+
+        // Flushed at the end of the last step
+        let text = String::from_utf8(written.0.lock().unwrap().clone()).unwrap();
+        assert!(text.starts_with("Starting step #1\n"));
+        let last = puzzle.solution().steps().len();
+        assert!(text.contains(&format!("Step {} progressed by", last)));
+
+        // Nothing at all when quiet, as by default
+        let quiet = Shared::default();
+        let mut puzzle = Puzzle::parse(include_str!("../samples/easy.txt"));
+        puzzle.set_config(SolverConfig {
+            transcript: Transcript::new(quiet.clone()),
+            ..SolverConfig::default()
+        });
+        puzzle.solve().unwrap();
+        assert!(quiet.0.lock().unwrap().is_empty());
+    }
+}
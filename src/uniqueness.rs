@@ -0,0 +1,929 @@
+use std::sync::Arc;
+
+use super::geometry::Geometry;
+use super::{
+    cells_see_each_other, combinations, units, Candidates, Consolidation, Coord, Justification,
+    Puzzle, Shape, Technique, Unit, MAX_SIZE,
+};
+
+// Longest unique loop considered.
+const UNIQUE_LOOP_MAX_LENGTH: usize = 10;
+
+// Most cells with candidates beyond the pair a unique loop takes in: one for type 1, and two for type 2.
+const UNIQUE_LOOP_MAX_ROOF: usize = 2;
+
+// Largest BUG-lite pattern considered.
+const BUG_LITE_MAX_CELLS: usize = 32;
+
+// Uniqueness techniques rely on the puzzle having exactly one solution. Four unsolved cells on two rows, two columns, and exactly two blocks that could all hold the same pair of digits form a "deadly pattern": if they were reduced to just that pair, the two digits could be swapped and the puzzle would have two solutions. Since that can't happen, whatever keeps the rectangle from collapsing into the pair must be true.
+//
+// Corners are ordered clockwise starting at the top left, so corners 0 & 2 and 1 & 3 are the diagonals:
+//
+//     0 1
+//     3 2
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rectangle {
+    rows: [usize; 2],
+    cols: [usize; 2],
+    digits: [u8; 2],
+}
+
+impl Rectangle {
+    fn corners(&self) -> [(usize, usize); 4] {
+        [
+            (self.rows[0], self.cols[0]),
+            (self.rows[0], self.cols[1]),
+            (self.rows[1], self.cols[1]),
+            (self.rows[1], self.cols[0]),
+        ]
+    }
+
+    fn pattern(&self) -> DeadlyPattern {
+        DeadlyPattern {
+            cells: self
+                .corners()
+                .iter()
+                .map(|&position| (position, self.digits))
+                .collect(),
+        }
+    }
+}
+
+// The general form of a deadly pattern: unsolved cells each restricted to a pair of digits (not necessarily the same pair), where every row, column, and block, and diagonal in X-Sudoku, holds each digit in either none or exactly two of the cells.
+//
+// Each unit then holds as many distinct digits as pattern cells, so any filling of the pattern uses every one of those digits exactly once per unit, and flipping every cell to the other digit of its pair gives a second valid filling. Cells holding nothing but their pair are the "floor"; cells with extra candidates are the "roof", and keep the pattern from being deadly.
+#[derive(Clone, Debug, PartialEq)]
+struct DeadlyPattern {
+    cells: Vec<((usize, usize), [u8; 2])>,
+}
+
+impl DeadlyPattern {
+    fn is_deadly(&self, geometry: &Geometry) -> bool {
+        // How many of the cells in each unit could hold each digit
+        let mut counts = vec![[0u8; MAX_SIZE + 1]; geometry.units().len()];
+        for &(position, pair) in &self.cells {
+            for &unit in geometry.units_of(Coord::from(position)) {
+                for digit in pair {
+                    counts[geometry.index_of(unit)][digit as usize] += 1;
+                }
+            }
+        }
+        counts
+            .iter()
+            .flatten()
+            .all(|&count| count == 0 || count == 2)
+    }
+
+    fn contains(&self, position: (usize, usize)) -> bool {
+        self.cells.iter().any(|(p, _)| *p == position)
+    }
+
+    fn positions(&self) -> Vec<(usize, usize)> {
+        self.cells.iter().map(|(p, _)| *p).collect()
+    }
+}
+
+impl Puzzle {
+    // Whether swapping a deadly pattern's pair really leaves another solution, as far as the rows, columns, and blocks go. Sandwich sums can tell the two apart, as the swap may move a 1 or 9, or carry a digit into or out of a sandwich, and so can arrows and XV marks, as it changes what they add up to, and lines, as it changes which digits sit side by side along them. House rules could tell them apart any way at all, so with any of `constraints()` there's no knowing.
+    fn has_deadly_patterns(&self) -> bool {
+        self.constraints().next().is_none()
+    }
+
+    // Every rectangle whose four corners are unsolved, span exactly two blocks, and share a pair of candidates. In X-Sudoku and jigsaws, only those still deadly.
+    fn unique_rectangles(&self) -> Vec<Rectangle> {
+        let mut rectangles: Vec<Rectangle> = Vec::new();
+
+        let size = self.shape.size();
+        for r0 in 0..size {
+            for r1 in (r0 + 1)..size {
+                for c0 in 0..size {
+                    for c1 in (c0 + 1)..size {
+                        let corners = [(r0, c0), (r0, c1), (r1, c1), (r1, c0)];
+                        let mut blocks =
+                            corners.map(|corner| self.shape.block_of(Coord::from(corner)));
+                        blocks.sort_unstable();
+                        if blocks.windows(2).filter(|pair| pair[0] != pair[1]).count() != 1 {
+                            // Either 1 block, or 3 or 4 in a jigsaw or 4 otherwise. None is a deadly pattern.
+                            continue;
+                        }
+
+                        if corners
+                            .iter()
+                            .any(|&(r, c)| self.grid[r][c].number.is_some())
+                        {
+                            continue;
+                        }
+
+                        let shared = corners
+                            .iter()
+                            .fold(self.shape.digits(), |shared, &(r, c)| {
+                                shared.intersection(self.grid[r][c].candidates)
+                            })
+                            .to_vec();
+
+                        for (i, a) in shared.iter().enumerate() {
+                            for b in shared.iter().skip(i + 1) {
+                                let rectangle = Rectangle {
+                                    rows: [r0, r1],
+                                    cols: [c0, c1],
+                                    digits: [*a, *b],
+                                };
+                                // Swapping the pair can repeat a digit in a diagonal when they're units, or in a jigsaw region holding three corners
+                                if (self.shape.has_diagonals() || self.shape.is_jigsaw())
+                                    && !rectangle.pattern().is_deadly(&self.geometry)
+                                {
+                                    continue;
+                                }
+                                rectangles.push(rectangle);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        rectangles
+    }
+
+    // Candidates of the given cell other than the rectangle's pair.
+    fn extras(&self, rectangle: &Rectangle, position: (usize, usize)) -> Candidates {
+        self.grid[position.0][position.1]
+            .candidates
+            .difference(rectangle.digits.iter().collect())
+    }
+
+    // Unique rectangles, types 1 through 6. Each rectangle is split into its "floor" (corners holding only the pair) and its "roof" (corners with extra candidates), and the shape of the roof determines which type applies:
+    //
+    //    * Type 1: a single roof corner. It can't be either digit of the pair.
+    //    * Type 2: two roof corners in the same row/column with the same single extra. The extra must be in one of them, so it can be removed from any cell seeing both.
+    //    * Type 3: two roof corners in the same unit with differing extras. Together the extras act as one virtual cell which may complete a naked subset with other cells of that unit.
+    //    * Type 4: two roof corners in the same unit where one digit of the pair is conjugate (only those two places in the unit). The other digit can be removed from both.
+    //    * Type 5: two diagonal (or three) roof corners with the same single extra. As with type 2, cells seeing all of them lose the extra.
+    //    * Type 6: two diagonal roof corners where one digit of the pair is locked to the rectangle in both rows (or both columns). That digit can be removed from the roof.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_unique_rectangles(&mut self) -> Vec<Consolidation> {
+        let mut eliminations: Vec<Consolidation> = Vec::new();
+        if !self.has_deadly_patterns() {
+            return eliminations;
+        }
+
+        for rectangle in self.unique_rectangles() {
+            if self.out_of_time() {
+                break;
+            }
+
+            // Earlier eliminations this pass may have broken the pattern
+            if !self.is_still_rectangle(&rectangle) {
+                continue;
+            }
+
+            let corners = rectangle.corners();
+            let roof: Vec<usize> = (0..4)
+                .filter(|&i| !self.extras(&rectangle, corners[i]).is_empty())
+                .collect();
+
+            let pattern = rectangle.pattern();
+            let removals = match roof.len() {
+                1 => self.eliminate_pair_from_lone_roof(&pattern, "Unique rectangle type 1"),
+                2 | 3 => {
+                    let (a, b) = (corners[roof[0]], corners[roof[1]]);
+                    let name = if roof.len() == 2 && (a.0 == b.0 || a.1 == b.1) {
+                        "Unique rectangle type 2"
+                    } else {
+                        "Unique rectangle type 5"
+                    };
+                    let mut found = self.eliminate_shared_extra_from_roof(&pattern, name);
+
+                    if roof.len() == 2 {
+                        if a.0 == b.0 || a.1 == b.1 {
+                            found.append(&mut self.unique_rectangle_type_3(&rectangle, a, b));
+                            found.append(&mut self.unique_rectangle_type_4(&rectangle, a, b));
+                        } else {
+                            found.append(&mut self.unique_rectangle_type_6(&rectangle, a, b));
+                        }
+                    }
+
+                    found
+                }
+                _ => vec![],
+            };
+
+            eliminations.append(&mut Consolidation::eliminations(
+                Technique::UniqueRectangle,
+                Justification::Pattern(pattern.positions()),
+                &removals,
+            ));
+        }
+
+        eliminations
+    }
+
+    fn is_still_rectangle(&self, rectangle: &Rectangle) -> bool {
+        rectangle.corners().iter().all(|&(r, c)| {
+            let candidates = self.grid[r][c].candidates;
+            self.grid[r][c].number.is_none()
+                && rectangle.digits.iter().all(|&d| candidates.contains(d))
+        })
+    }
+
+    // Candidates of each cell of the pattern beyond its pair. Cells with none are the floor, the rest the roof.
+    fn roof(&self, pattern: &DeadlyPattern) -> Vec<((usize, usize), Vec<u8>)> {
+        pattern
+            .cells
+            .iter()
+            .map(|&(position, pair)| {
+                let extras = self.grid[position.0][position.1]
+                    .candidates
+                    .difference(pair.iter().collect());
+                (position, extras.to_vec())
+            })
+            .filter(|(_, extras)| !extras.is_empty())
+            .collect()
+    }
+
+    // A pattern with a single roof cell: that cell can't take either digit of its pair. Returns the candidates removed.
+    fn eliminate_pair_from_lone_roof(
+        &mut self,
+        pattern: &DeadlyPattern,
+        name: &str,
+    ) -> Vec<((usize, usize), u8)> {
+        let roof = self.roof(pattern);
+        if roof.len() != 1 {
+            return vec![];
+        }
+
+        let (row, col) = roof[0].0;
+        let pair = pattern
+            .cells
+            .iter()
+            .find(|(p, _)| *p == (row, col))
+            .unwrap()
+            .1;
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for digit in pair {
+            if self.grid[row][col].remove_candidate(digit) {
+                log!(
+                    self,
+                    "🔲🔲🔲🔲🔲 {} on {:?} eliminated candidate {} at grid position ({}, {})",
+                    name,
+                    pattern_cells(pattern),
+                    digit,
+                    row,
+                    col
+                );
+                removals.push(((row, col), digit));
+            }
+        }
+
+        removals
+    }
+
+    // A pattern whose roof cells all have the same single extra candidate: one of them must hold it, so it can be removed from every cell seeing all of them.
+    fn eliminate_shared_extra_from_roof(
+        &mut self,
+        pattern: &DeadlyPattern,
+        name: &str,
+    ) -> Vec<((usize, usize), u8)> {
+        let roof = self.roof(pattern);
+        if roof.len() < 2 || roof[0].1.len() != 1 || roof.iter().any(|(_, e)| *e != roof[0].1) {
+            return vec![];
+        }
+        let extra = roof[0].1[0];
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                if pattern.contains((row, col))
+                    || !roof
+                        .iter()
+                        .all(|&(p, _)| cells_see_each_other(self.shape, p, (row, col)))
+                {
+                    continue;
+                }
+
+                if self.grid[row][col].remove_candidate(extra) {
+                    log!(
+                        self,
+                        "🔲🔲🔲🔲🔲 {} on {:?} eliminated candidate {} at grid position ({}, {})",
+                        name,
+                        pattern_cells(pattern),
+                        extra,
+                        row,
+                        col
+                    );
+                    removals.push(((row, col), extra));
+                }
+            }
+        }
+
+        removals
+    }
+
+    // Unique loops: like unique rectangles, but six or more cells sharing a pair of digits, linked alternately along rows and columns back to the start. When every unit the loop touches holds exactly two of its cells, it's a deadly pattern and the same type 1 and type 2 eliminations as rectangles apply.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_unique_loops(&mut self) -> Vec<Consolidation> {
+        if !self.has_deadly_patterns() {
+            return vec![];
+        }
+
+        let geometry = Arc::clone(&self.geometry);
+        let size = self.shape.size() as u8;
+        for a in 1..=size {
+            for b in (a + 1)..=size {
+                let pair: Candidates = [a, b].iter().collect();
+                let cells = self
+                    .shape
+                    .coords()
+                    .filter(|&coord| pair.is_subset(self[coord].candidates))
+                    .map(|coord| ((coord.row, coord.col), self[coord].candidates != pair))
+                    .collect();
+
+                // Each loop's checked as it closes, stopping at the first that eliminates anything
+                let mut eliminations: Vec<Consolidation> = Vec::new();
+                LoopSearch::new(&geometry, cells).run(&mut |loop_cells| {
+                    let pattern = DeadlyPattern {
+                        cells: loop_cells.iter().map(|&p| (p, [a, b])).collect(),
+                    };
+                    if !pattern.is_deadly(&geometry) {
+                        return false;
+                    }
+
+                    let mut removals =
+                        self.eliminate_pair_from_lone_roof(&pattern, "Unique loop type 1");
+                    removals.append(
+                        &mut self.eliminate_shared_extra_from_roof(&pattern, "Unique loop type 2"),
+                    );
+                    eliminations = Consolidation::eliminations(
+                        Technique::UniqueLoop,
+                        Justification::Pattern(pattern.positions()),
+                        &removals,
+                    );
+                    !eliminations.is_empty()
+                });
+                if !eliminations.is_empty() {
+                    return eliminations;
+                }
+            }
+        }
+
+        vec![]
+    }
+
+    // BUG-lite: a deadly pattern made of bivalue cells plus a single cell with more candidates. That cell can't take the pair it would contribute to the pattern. When the pattern covers every unsolved cell this is the classic "BUG+1" (bivalue universal grave).
+    //
+    // Patterns are grown from each candidate pair of each multi-valued cell by repeatedly adding a bivalue cell wherever a unit holds one of the pattern's digits only once.
+    //
+    // Returns the eliminations made. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_bug_lite(&mut self) -> Vec<Consolidation> {
+        if !self.has_deadly_patterns() {
+            return vec![];
+        }
+
+        let mut bivalues: Vec<(usize, usize)> = Vec::new();
+        let mut others: Vec<(usize, usize)> = Vec::new();
+        for row in 0..self.shape.size() {
+            for col in 0..self.shape.size() {
+                match self.grid[row][col].candidates.count() {
+                    0 | 1 => {}
+                    2 => bivalues.push((row, col)),
+                    _ => others.push((row, col)),
+                }
+            }
+        }
+
+        for (row, col) in others {
+            if self.out_of_time() {
+                return vec![];
+            }
+
+            let candidates = self.grid[row][col].candidates.to_vec();
+            for (i, &a) in candidates.iter().enumerate() {
+                for &b in candidates.iter().skip(i + 1) {
+                    let mut pattern = DeadlyPattern {
+                        cells: vec![((row, col), [a, b])],
+                    };
+
+                    if self.grow_bug_lite(&mut pattern, &bivalues) {
+                        let removals = self.eliminate_pair_from_lone_roof(&pattern, "BUG-lite");
+                        if !removals.is_empty() {
+                            return Consolidation::eliminations(
+                                Technique::BugLite,
+                                Justification::Pattern(pattern.positions()),
+                                &removals,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        vec![]
+    }
+
+    // Depth first search for bivalue cells completing the pattern. Leaves the pattern deadly and returns true on success.
+    fn grow_bug_lite(&self, pattern: &mut DeadlyPattern, bivalues: &[(usize, usize)]) -> bool {
+        if pattern.cells.len() > BUG_LITE_MAX_CELLS {
+            return false;
+        }
+
+        // Find a unit where some digit of the pattern appears only once
+        let mut lonely: Option<(Vec<(usize, usize)>, u8)> = None;
+        for unit in units(self.shape) {
+            for digit in self.shape.digits().iter() {
+                let count = pattern
+                    .cells
+                    .iter()
+                    .filter(|(p, pair)| unit.contains(p) && pair.contains(&digit))
+                    .count();
+
+                if count > 2 {
+                    return false;
+                }
+                if count == 1 && lonely.is_none() {
+                    lonely = Some((unit.clone(), digit));
+                }
+            }
+        }
+
+        let (unit, digit) = match lonely {
+            Some(lonely) => lonely,
+            None => return true,
+        };
+
+        for &(row, col) in bivalues.iter() {
+            if !unit.contains(&(row, col)) || pattern.contains((row, col)) {
+                continue;
+            }
+
+            let candidates = self.grid[row][col].candidates;
+            if !candidates.contains(digit) {
+                continue;
+            }
+
+            let mut pair = candidates.iter();
+            pattern
+                .cells
+                .push(((row, col), [pair.next().unwrap(), pair.next().unwrap()]));
+            if self.grow_bug_lite(pattern, bivalues) {
+                return true;
+            }
+            pattern.cells.pop();
+        }
+
+        false
+    }
+
+    fn unique_rectangle_type_3(
+        &mut self,
+        rectangle: &Rectangle,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> Vec<((usize, usize), u8)> {
+        let virtual_cell = self.extras(rectangle, a).union(self.extras(rectangle, b));
+
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+        for unit in shared_units(self.shape, a, b) {
+            let others: Vec<(usize, usize)> = unit
+                .into_iter()
+                .filter(|&(r, c)| (r, c) != a && (r, c) != b && self.grid[r][c].number.is_none())
+                .collect();
+
+            // Look for 1 to 3 other cells which together with the virtual cell hold exactly one more digit than cells
+            for size in 1..=3 {
+                for subset in combinations(others.len(), size) {
+                    let digits = subset.iter().fold(virtual_cell, |digits, &i| {
+                        let (r, c) = others[i];
+                        digits.union(self.grid[r][c].candidates)
+                    });
+
+                    if digits.count() != size + 1 {
+                        continue;
+                    }
+
+                    for (i, &(r, c)) in others.iter().enumerate() {
+                        if subset.contains(&i) {
+                            continue;
+                        }
+
+                        for d in digits.iter() {
+                            if self.grid[r][c].remove_candidate(d) {
+                                log!(self, "🔲🔲🔲🔲🔲 Unique rectangle type 3 on {:?} with naked subset {:?} eliminated candidate {} at grid position ({}, {})", rectangle.digits, digits, d, r, c);
+                                removals.push(((r, c), d));
+                            }
+                        }
+                    }
+
+                    if !removals.is_empty() {
+                        return removals;
+                    }
+                }
+            }
+        }
+
+        removals
+    }
+
+    fn unique_rectangle_type_4(
+        &mut self,
+        rectangle: &Rectangle,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> Vec<((usize, usize), u8)> {
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+
+        for unit in shared_units(self.shape, a, b) {
+            for (i, conjugate) in rectangle.digits.iter().enumerate() {
+                let count = unit
+                    .iter()
+                    .filter(|&&(r, c)| self.grid[r][c].candidates.contains(*conjugate))
+                    .count();
+                if count != 2 {
+                    continue;
+                }
+
+                let other = rectangle.digits[1 - i];
+                for (r, c) in [a, b] {
+                    if self.grid[r][c].remove_candidate(other) {
+                        log!(self, "🔲🔲🔲🔲🔲 Unique rectangle type 4 on {:?} (conjugate {}) eliminated candidate {} at grid position ({}, {})", rectangle.digits, conjugate, other, r, c);
+                        removals.push(((r, c), other));
+                    }
+                }
+
+                if !removals.is_empty() {
+                    return removals;
+                }
+            }
+        }
+
+        removals
+    }
+
+    fn unique_rectangle_type_6(
+        &mut self,
+        rectangle: &Rectangle,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> Vec<((usize, usize), u8)> {
+        let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+
+        for digit in rectangle.digits {
+            let locked_to_rows = rectangle.rows.iter().all(|&r| {
+                (0..self.shape.size()).all(|c| {
+                    rectangle.cols.contains(&c) || !self.grid[r][c].candidates.contains(digit)
+                })
+            });
+            let locked_to_cols = rectangle.cols.iter().all(|&c| {
+                (0..self.shape.size()).all(|r| {
+                    rectangle.rows.contains(&r) || !self.grid[r][c].candidates.contains(digit)
+                })
+            });
+
+            if !locked_to_rows && !locked_to_cols {
+                continue;
+            }
+
+            for (r, c) in [a, b] {
+                if self.grid[r][c].remove_candidate(digit) {
+                    log!(self, "🔲🔲🔲🔲🔲 Unique rectangle type 6 on {:?} eliminated candidate {} at grid position ({}, {})", rectangle.digits, digit, r, c);
+                    removals.push(((r, c), digit));
+                }
+            }
+        }
+
+        removals
+    }
+}
+
+// A search for closed loops through the cells holding both digits of a pair, moving alternately along a row and down a column, with between 6 and `UNIQUE_LOOP_MAX_LENGTH` cells.
+//
+// A loop with no roof eliminates nothing, so each is searched for from its first roof cell in grid order, and takes in bivalue cells and at most `UNIQUE_LOOP_MAX_ROOF` roof cells in all. A unit holding two of the loop's cells can't take a third and still be deadly, so the search turns back there.
+struct LoopSearch<'a> {
+    geometry: &'a Geometry,
+    // In grid order, each with whether it has candidates beyond the pair
+    cells: Vec<((usize, usize), bool)>,
+    path: Vec<(usize, usize)>,
+    roof: usize,
+    // How many of the path's cells are in each unit, in the order of `Geometry::units()`
+    in_unit: Vec<u8>,
+}
+
+impl<'a> LoopSearch<'a> {
+    fn new(geometry: &'a Geometry, cells: Vec<((usize, usize), bool)>) -> LoopSearch<'a> {
+        LoopSearch {
+            geometry,
+            cells,
+            path: Vec::new(),
+            roof: 0,
+            in_unit: vec![0; geometry.units().len()],
+        }
+    }
+
+    // Hand each loop to `found` as it closes, once, starting from its roof cell and setting off along a row. Stops once `found` returns true, and returns whether it did.
+    fn run(&mut self, found: &mut impl FnMut(&[(usize, usize)]) -> bool) -> bool {
+        for first in 0..self.cells.len() {
+            let (start, roof) = self.cells[first];
+            if !roof {
+                continue;
+            }
+
+            self.push(start, roof);
+            let done = self.extend(first, true, found);
+            self.pop(roof);
+            if done {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn extend(
+        &mut self,
+        first: usize,
+        along_row: bool,
+        found: &mut impl FnMut(&[(usize, usize)]) -> bool,
+    ) -> bool {
+        let start = self.path[0];
+        let current = self.path[self.path.len() - 1];
+
+        for i in 0..self.cells.len() {
+            let (next, roof) = self.cells[i];
+            let aligned = if along_row {
+                next.0 == current.0 && next.1 != current.1
+            } else {
+                next.1 == current.1 && next.0 != current.0
+            };
+            if !aligned {
+                continue;
+            }
+
+            if next == start {
+                // Closing the loop down a column means it alternated all the way round. Loops always set off along a row, so each is only found in one direction.
+                if !along_row && self.path.len() >= 6 && found(&self.path) {
+                    return true;
+                }
+                continue;
+            }
+
+            // Roof cells before the first were searched from already
+            if self.path.len() == UNIQUE_LOOP_MAX_LENGTH
+                || (roof && (i < first || self.roof == UNIQUE_LOOP_MAX_ROOF))
+                || self.path.contains(&next)
+                || self
+                    .geometry
+                    .units_of(Coord::from(next))
+                    .iter()
+                    .any(|&unit| self.in_unit[self.geometry.index_of(unit)] == 2)
+            {
+                continue;
+            }
+
+            self.push(next, roof);
+            let done = self.extend(first, !along_row, found);
+            self.pop(roof);
+            if done {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn push(&mut self, cell: (usize, usize), roof: bool) {
+        self.path.push(cell);
+        self.roof += roof as usize;
+        for &unit in self.geometry.units_of(Coord::from(cell)) {
+            self.in_unit[self.geometry.index_of(unit)] += 1;
+        }
+    }
+
+    fn pop(&mut self, roof: bool) {
+        let cell = self.path.pop().unwrap();
+        self.roof -= roof as usize;
+        for &unit in self.geometry.units_of(Coord::from(cell)) {
+            self.in_unit[self.geometry.index_of(unit)] -= 1;
+        }
+    }
+}
+
+// E.g. "[r1c1, r1c5, r2c5, r2c1]"
+fn pattern_cells(pattern: &DeadlyPattern) -> Vec<String> {
+    pattern
+        .cells
+        .iter()
+        .map(|(position, _)| Coord::from(*position).to_string())
+        .collect()
+}
+
+// The rows, columns, blocks, and any diagonals of a grid of the shape containing both cells, as lists of grid positions.
+fn shared_units(shape: Shape, a: (usize, usize), b: (usize, usize)) -> Vec<Vec<(usize, usize)>> {
+    let mut units: Vec<Vec<(usize, usize)>> = Vec::new();
+
+    if a.0 == b.0 {
+        units.push(Unit::Row(a.0).cells(shape));
+    }
+    if a.1 == b.1 {
+        units.push(Unit::Column(a.1).cells(shape));
+    }
+
+    let block = shape.block_of(Coord::from(a));
+    if block == shape.block_of(Coord::from(b)) {
+        units.push(Unit::Block(block).cells(shape));
+    }
+
+    for diagonal in shape.diagonals_of(Coord::from(a)) {
+        if shape.diagonals_of(Coord::from(b)).any(|d| d == diagonal) {
+            units.push(Unit::Diagonal(diagonal).cells(shape));
+        }
+    }
+
+    units
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::super::{Cell, Consolidation, Justification, Puzzle, Technique};
+
+    fn blank() -> Puzzle {
+        Puzzle::parse("")
+    }
+
+    #[test]
+    fn type_1() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2, 5, 7]);
+
+        assert_eq!(
+            vec![Consolidation::CandidatesEliminated {
+                technique: Technique::UniqueRectangle,
+                cells: vec![(1, 3)],
+                digits: vec![1, 2],
+                justification: Justification::Pattern(vec![(0, 0), (0, 3), (1, 3), (1, 0)]),
+            }],
+            puzzle.reduce_candidates_with_unique_rectangles()
+        );
+        assert_eq!(vec![5, 7], puzzle.grid[1][3].candidates.to_vec());
+    }
+
+    #[test]
+    fn type_2() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[1][5] = Cell::with_candidates(vec![1, 2, 9]);
+        puzzle.grid[1][8] = Cell::with_candidates(vec![4, 6]);
+        puzzle.grid[2][8] = Cell::with_candidates(vec![4, 6]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_unique_rectangles().len());
+        assert_eq!(vec![4], puzzle.grid[1][8].candidates.to_vec());
+        assert_eq!(vec![4, 6], puzzle.grid[2][8].candidates.to_vec());
+    }
+
+    #[test]
+    fn type_3() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2, 7]);
+        for (col, number) in [(1, 3), (2, 5), (4, 8), (6, 9)] {
+            puzzle.grid[1][col].number = Some(number);
+        }
+        puzzle.grid[1][5] = Cell::with_candidates(vec![6, 7]);
+        puzzle.grid[1][7] = Cell::with_candidates(vec![4, 6, 7]);
+        puzzle.grid[1][8] = Cell::with_candidates(vec![1, 2, 4]);
+
+        // The roof's extras make a virtual 67 cell, a naked pair with r2c6
+        assert_eq!(
+            vec![Consolidation::CandidatesEliminated {
+                technique: Technique::UniqueRectangle,
+                cells: vec![(1, 7)],
+                digits: vec![6, 7],
+                justification: Justification::Pattern(vec![(0, 0), (0, 3), (1, 3), (1, 0)]),
+            }],
+            puzzle.reduce_candidates_with_unique_rectangles()
+        );
+        assert_eq!(vec![4], puzzle.grid[1][7].candidates.to_vec());
+        assert_eq!(vec![1, 2, 4], puzzle.grid[1][8].candidates.to_vec());
+        assert_eq!(vec![6, 7], puzzle.grid[1][5].candidates.to_vec());
+    }
+
+    #[test]
+    fn type_4() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2, 7]);
+        puzzle.grid[1][5] = Cell::with_candidates(vec![2, 6, 7]);
+
+        // 1 only appears at the roof in row 1, so the roof can't be 2
+        puzzle.reduce_candidates_with_unique_rectangles();
+        assert_eq!(vec![1, 6], puzzle.grid[1][0].candidates.to_vec());
+        assert_eq!(vec![1, 7], puzzle.grid[1][3].candidates.to_vec());
+    }
+
+    #[test]
+    fn type_5() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2, 6]);
+        puzzle.grid[0][7] = Cell::with_candidates(vec![1, 2, 4]);
+        puzzle.grid[5][0] = Cell::with_candidates(vec![1, 2, 4]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![6, 8]);
+        puzzle.grid[1][2] = Cell::with_candidates(vec![5, 6]);
+        puzzle.grid[2][8] = Cell::with_candidates(vec![6, 9]);
+
+        // One of the diagonal roof corners must be 6, so the cells seeing both can't be
+        assert_eq!(
+            vec![Consolidation::CandidatesEliminated {
+                technique: Technique::UniqueRectangle,
+                cells: vec![(0, 4), (1, 2)],
+                digits: vec![6],
+                justification: Justification::Pattern(vec![(0, 0), (0, 3), (1, 3), (1, 0)]),
+            }],
+            puzzle.reduce_candidates_with_unique_rectangles()
+        );
+        assert_eq!(vec![8], puzzle.grid[0][4].candidates.to_vec());
+        assert_eq!(vec![5], puzzle.grid[1][2].candidates.to_vec());
+        assert_eq!(vec![6, 9], puzzle.grid[2][8].candidates.to_vec());
+    }
+
+    #[test]
+    fn type_6() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![1, 2, 8]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2, 9]);
+        puzzle.grid[0][6] = Cell::with_candidates(vec![2, 8]);
+        puzzle.grid[1][7] = Cell::with_candidates(vec![2, 9]);
+        puzzle.grid[5][0] = Cell::with_candidates(vec![2, 5]);
+
+        // 1 is locked to the rectangle in both rows
+        puzzle.reduce_candidates_with_unique_rectangles();
+        assert_eq!(vec![2, 8], puzzle.grid[0][3].candidates.to_vec());
+        assert_eq!(vec![2, 9], puzzle.grid[1][0].candidates.to_vec());
+    }
+
+    #[test]
+    fn unique_loop() {
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][4] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][4] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][7] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[2][7] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[2][0] = Cell::with_candidates(vec![1, 2, 9]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_unique_loops().len());
+        assert_eq!(vec![9], puzzle.grid[2][0].candidates.to_vec());
+    }
+
+    #[test]
+    fn unique_loops_give_up_quickly() {
+        // Every cell of a blank grid holds every pair, and every path through them was once followed
+        let mut puzzle = blank();
+        puzzle.assign_candidates();
+        let start = Instant::now();
+        assert!(puzzle.reduce_candidates_with_unique_loops().is_empty());
+
+        // As many bivalue cells as there could be, and a roof to search from
+        let mut pairs = blank();
+        for row in 0..9 {
+            for col in 0..9 {
+                pairs.grid[row][col] = Cell::with_candidates(vec![1, 2]);
+            }
+        }
+        pairs.grid[8][8] = Cell::with_candidates(vec![1, 2, 3]);
+        pairs.reduce_candidates_with_unique_loops();
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn bug_lite() {
+        // Three different pairs spread over two rows. Every row, column, and block holds each digit exactly twice.
+        let mut puzzle = blank();
+        puzzle.grid[0][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[0][3] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[0][6] = Cell::with_candidates(vec![1, 3]);
+        puzzle.grid[1][0] = Cell::with_candidates(vec![1, 2]);
+        puzzle.grid[1][3] = Cell::with_candidates(vec![2, 3]);
+        puzzle.grid[1][6] = Cell::with_candidates(vec![1, 3, 7]);
+
+        assert_eq!(1, puzzle.reduce_candidates_with_bug_lite().len());
+        assert_eq!(vec![7], puzzle.grid[1][6].candidates.to_vec());
+    }
+}
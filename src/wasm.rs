@@ -0,0 +1,97 @@
+use js_sys::JSON;
+use wasm_bindgen::prelude::*;
+
+use super::solution::{json_consolidation, json_outcome, json_string};
+use super::{solve_with_guesses, Puzzle, PuzzleStatus};
+
+// Solving in the browser through wasm-bindgen. The puzzles are solved quietly, so neither export writes to stdout or the filesystem, and guesses are searched on the one thread.
+
+// Solve the puzzle, in any format the solver reads, guessing once the techniques run out. Returns the outcome as `--format json` gives it: `{"status": "solved", "reason": null, "grid": "341562789768934152..."}`.
+#[wasm_bindgen]
+pub fn solve(puzzle: &str) -> String {
+    solve_json(puzzle)
+}
+
+// The next step to take in the puzzle, as an object `{"description": "R1C5 must be 6: ...", "consolidation": {...}}` with the consolidation as in a trace. Null if the techniques are stuck or the puzzle can't be read.
+#[wasm_bindgen]
+pub fn hint(puzzle: &str) -> JsValue {
+    match hint_json(puzzle) {
+        Some(json) => JSON::parse(&json).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+fn read(text: &str) -> Result<Puzzle, String> {
+    let (puzzle, _) = Puzzle::parse_with_report(text).map_err(|report| report.to_string())?;
+    Ok(puzzle)
+}
+
+fn solve_json(text: &str) -> String {
+    let mut puzzle = match read(text) {
+        Ok(puzzle) => puzzle,
+        Err(message) => return json_outcome("unreadable", Some(&message), None),
+    };
+
+    let solved = puzzle.solve().map(|()| puzzle.status());
+    if let Ok(PuzzleStatus::Unsolved) = solved {
+        if let Ok(Some(guessed)) = solve_with_guesses(puzzle.clone()) {
+            puzzle = guessed;
+        }
+    }
+
+    match (solved, puzzle.status()) {
+        (Err(error), _) => json_outcome("budget-exceeded", Some(&error.to_string()), Some(&puzzle)),
+        (_, PuzzleStatus::Solved) => json_outcome("solved", None, Some(&puzzle)),
+        (_, PuzzleStatus::Unsolved) => json_outcome("unsolved", None, Some(&puzzle)),
+        (_, PuzzleStatus::IllDefined(reason)) => {
+            json_outcome("ill-defined", Some(&format!("{:?}", reason)), Some(&puzzle))
+        }
+    }
+}
+
+fn hint_json(text: &str) -> Option<String> {
+    let hint = read(text).ok()?.hint()?;
+    Some(format!(
+        "{{\"description\": {}, \"consolidation\": {}}}",
+        json_string(&hint.describe()),
+        json_consolidation(&hint)
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::json::Json;
+    use super::{hint_json, solve_json};
+
+    #[test]
+    fn exports() {
+        let easy = include_str!("../samples/easy.txt");
+        let solved = Json::parse(&solve_json(easy)).unwrap();
+        assert_eq!(Some("solved"), solved.get("status").and_then(Json::as_str));
+        assert_eq!(
+            Some(
+                "341562789768934152952718364574291638189346275623875491215687943496153827837429516"
+            ),
+            solved.get("grid").and_then(Json::as_str)
+        );
+
+        let unreadable = Json::parse(&solve_json("12345")).unwrap();
+        assert_eq!(
+            Some("unreadable"),
+            unreadable.get("status").and_then(Json::as_str)
+        );
+        assert_eq!(Some(&Json::Null), unreadable.get("grid"));
+
+        let hint = Json::parse(&hint_json(easy).unwrap()).unwrap();
+        assert!(hint
+            .get("description")
+            .and_then(Json::as_str)
+            .unwrap()
+            .contains(" must be "));
+        assert!(hint
+            .get("consolidation")
+            .and_then(|c| c.get("technique"))
+            .is_some());
+        assert_eq!(None, hint_json("12345"));
+    }
+}
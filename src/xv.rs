@@ -0,0 +1,390 @@
+use std::sync::Arc;
+
+use super::{
+    Candidates, Consolidation, Constraint, Coord, IllDefinedReason, Justification, Puzzle, Shape,
+    Technique,
+};
+
+/// An XV sudoku mark between two cells side by side: an X where their digits add up to 10, a V where they add up to 5, as the Roman numerals go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    X,
+    V,
+}
+
+impl Mark {
+    pub fn sum(&self) -> u8 {
+        match self {
+            Mark::X => 10,
+            Mark::V => 5,
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            Mark::X => 'X',
+            Mark::V => 'V',
+        }
+    }
+
+    // The mark written as its letter, in either case.
+    pub fn parse(name: &str) -> Result<Mark, String> {
+        match name {
+            "X" | "x" => Ok(Mark::X),
+            "V" | "v" => Ok(Mark::V),
+            _ => Err(format!("Expected an X or V, not '{}'", name)),
+        }
+    }
+}
+
+/// A mark and the two cells side by side it sits between, the first above or left of the second.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    cells: [Coord; 2],
+    mark: Mark,
+}
+
+impl Edge {
+    // The mark between the two cells, which must be side by side, in either order.
+    pub fn new(a: Coord, b: Coord, mark: Mark) -> Result<Edge, String> {
+        let (a, b) = if (a.row, a.col) < (b.row, b.col) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        if !((a.row == b.row && a.col + 1 == b.col) || (a.col == b.col && a.row + 1 == b.row)) {
+            return Err(format!(
+                "{} marks go between cells side by side, not {} and {}",
+                mark.letter(),
+                a,
+                b
+            ));
+        }
+
+        Ok(Edge {
+            cells: [a, b],
+            mark,
+        })
+    }
+
+    pub fn cells(&self) -> [Coord; 2] {
+        self.cells
+    }
+
+    pub fn mark(&self) -> Mark {
+        self.mark
+    }
+}
+
+/// XV sudoku's marks, in the order given, as many as there are, and whether they're all there are: with the negative constraint, no two cells side by side without a mark may add up to 5 or 10.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edges {
+    edges: Vec<Edge>,
+    negative: bool,
+}
+
+impl Edges {
+    pub const NONE: Edges = Edges {
+        edges: Vec::new(),
+        negative: false,
+    };
+
+    // Add a mark. Two between the same cells are one too many.
+    pub fn push(&mut self, edge: Edge) -> Result<(), String> {
+        if self.iter().any(|other| other.cells == edge.cells) {
+            let [a, b] = edge.cells();
+            return Err(format!("{} and {} already have a mark between them", a, b));
+        }
+        self.edges.push(edge);
+        Ok(())
+    }
+
+    // The same marks, with or without the negative constraint.
+    pub fn with_negative(self, negative: bool) -> Edges {
+        Edges { negative, ..self }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    // Whether there's nothing to it at all: no marks, and no negative constraint.
+    pub fn is_none(&self) -> bool {
+        *self == Edges::NONE
+    }
+
+    // The mark between two cells, if any.
+    pub fn between(&self, a: Coord, b: Coord) -> Option<Mark> {
+        let cells = Edge::new(a, b, Mark::X).ok()?.cells;
+        self.iter()
+            .find(|edge| edge.cells == cells)
+            .map(|edge| edge.mark)
+    }
+
+    // Every pair of cells side by side the marks say something about, the first above or left of the second, and the mark between them. Without a mark, under the negative constraint, they're to add up to neither.
+    pub(super) fn pairs(&self, shape: Shape) -> Vec<([Coord; 2], Option<Mark>)> {
+        if !self.negative {
+            return self
+                .iter()
+                .map(|edge| (edge.cells(), Some(edge.mark)))
+                .collect();
+        }
+
+        let size = shape.size();
+        let mut pairs = vec![];
+        for a in shape.coords() {
+            let right = (a.col + 1 < size).then(|| Coord::new(a.row, a.col + 1));
+            let below = (a.row + 1 < size).then(|| Coord::new(a.row + 1, a.col));
+            for b in right.into_iter().chain(below) {
+                pairs.push(([a, b], self.between(a, b)));
+            }
+        }
+        pairs
+    }
+}
+
+impl Default for Edges {
+    fn default() -> Edges {
+        Edges::NONE
+    }
+}
+
+// Whether two cells side by side may hold the digits, with the mark between them or, under the negative constraint, none.
+pub(super) fn allows(mark: Option<Mark>, a: u8, b: u8) -> bool {
+    match mark {
+        Some(mark) => a + b == mark.sum(),
+        None => a + b != Mark::X.sum() && a + b != Mark::V.sum(),
+    }
+}
+
+// What each of two cells could hold with some digit of the other's the relation allows. Cells are given as the digits they could be, a number as itself alone. Any constraint on a pair of cells works this way, whatever it asks of their digits.
+pub(super) fn fits(
+    a: Candidates,
+    b: Candidates,
+    relation: impl Fn(u8, u8) -> bool,
+) -> (Candidates, Candidates) {
+    (
+        a.iter()
+            .filter(|&x| b.iter().any(|y| relation(x, y)))
+            .collect(),
+        b.iter()
+            .filter(|&y| a.iter().any(|x| relation(x, y)))
+            .collect(),
+    )
+}
+
+// The marks, with the negative constraint if it's on, are one constraint together: a candidate with nothing next door to make the sum can go. Under the negative constraint, where there's no mark neither sum may be made, so a cell beside a number can't hold what would add up to either.
+//
+// Eliminates a pair of cells at a time.
+impl Constraint for Edges {
+    fn prune(&self, puzzle: &mut Puzzle) -> Vec<Consolidation> {
+        for (cells, mark) in self.pairs(puzzle.shape) {
+            let [a, b] = cells.map(|coord| match puzzle[coord].number {
+                Some(number) => [number].into_iter().collect(),
+                None => puzzle[coord].candidates,
+            });
+            let (a, b) = fits(a, b, |x, y| allows(mark, x, y));
+
+            let mut removals: Vec<((usize, usize), u8)> = Vec::new();
+            for (coord, fit) in cells.into_iter().zip([a, b]) {
+                if puzzle[coord].number.is_some() {
+                    continue;
+                }
+
+                for digit in puzzle[coord].candidates.difference(fit).iter() {
+                    puzzle[coord].remove_candidate(digit);
+                    log!(puzzle, "✖️✖️✖️✖️✖️ XV between {} and {} eliminated candidate {} at grid position ({}, {})", cells[0], cells[1], digit, coord.row, coord.col);
+                    removals.push((coord.into(), digit));
+                }
+            }
+
+            if !removals.is_empty() {
+                return Consolidation::eliminations(
+                    Technique::Xv,
+                    Justification::Xv(cells.map(Into::into), mark),
+                    &removals,
+                );
+            }
+        }
+
+        vec![]
+    }
+
+    fn check(&self, puzzle: &Puzzle) -> Option<IllDefinedReason> {
+        self.broken(puzzle)
+            .first()
+            .map(|&[a, b]| IllDefinedReason::XvBroken(a.into(), b.into()))
+    }
+
+    fn fits(&self, shape: Shape, cell: &dyn Fn(Coord) -> Candidates) -> Vec<(Coord, Candidates)> {
+        self.pairs(shape)
+            .into_iter()
+            .flat_map(|([a, b], mark)| {
+                let (fit_a, fit_b) = fits(cell(a), cell(b), |x, y| allows(mark, x, y));
+                [(a, fit_a), (b, fit_b)]
+            })
+            .collect()
+    }
+}
+
+impl Edges {
+    // The pairs of cells side by side, both filled in, whose digits break their mark, or add up to 5 or 10 without one under the negative constraint.
+    fn broken(&self, puzzle: &Puzzle) -> Vec<[Coord; 2]> {
+        self.pairs(puzzle.shape)
+            .into_iter()
+            .filter(
+                |&([a, b], mark)| match (puzzle[a].number, puzzle[b].number) {
+                    (Some(x), Some(y)) => !allows(mark, x, y),
+                    _ => false,
+                },
+            )
+            .map(|(cells, _)| cells)
+            .collect()
+    }
+}
+
+impl Puzzle {
+    // XV: the digits either side of an X must add up to 10, and either side of a V to 5.
+    //
+    // Returns the eliminations made, a pair of cells at a time. Like the other reductions, should be called repeatedly until no further simplifications can be made.
+    pub fn reduce_candidates_with_xv(&mut self) -> Vec<Consolidation> {
+        let clues = Arc::clone(&self.clues);
+        if clues.xv.is_none() {
+            return vec![];
+        }
+
+        clues.xv.prune(self)
+    }
+
+    // The pairs of cells side by side breaking the XV rule between them.
+    pub(super) fn wrong_xv(&self) -> Vec<[Coord; 2]> {
+        self.clues.xv.broken(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Candidates, Coord, Puzzle, PuzzleStatus, SolverConfig, Technique};
+    use super::{allows, fits, Edge, Edges, Mark};
+
+    #[test]
+    fn edges() {
+        let coord = |row, col| Coord::new(row, col);
+        let edge = Edge::new(coord(0, 1), coord(0, 0), Mark::V).unwrap();
+        assert_eq!([coord(0, 0), coord(0, 1)], edge.cells());
+        assert_eq!(Mark::V, edge.mark());
+        assert!(Edge::new(coord(0, 0), coord(1, 1), Mark::X).is_err());
+        assert!(Edge::new(coord(0, 0), coord(0, 2), Mark::X).is_err());
+        assert!(Edge::new(coord(0, 0), coord(0, 0), Mark::X).is_err());
+
+        let mut edges = Edges::NONE;
+        assert!(edges.is_none());
+        edges.push(edge).unwrap();
+        assert_eq!(Some(Mark::V), edges.between(coord(0, 1), coord(0, 0)));
+        assert_eq!(None, edges.between(coord(0, 1), coord(1, 1)));
+        assert!(edges
+            .push(Edge::new(coord(0, 0), coord(0, 1), Mark::X).unwrap())
+            .is_err());
+
+        // As many marks as are given, here one on every border across the rows
+        for i in 1..72 {
+            let (row, col) = (i / 8, i % 8);
+            edges
+                .push(Edge::new(coord(row, col), coord(row, col + 1), Mark::X).unwrap())
+                .unwrap();
+        }
+        assert_eq!(72, edges.len());
+
+        // Only the marks say anything, unless there's the negative constraint too
+        let shape = super::super::Shape::STANDARD;
+        let mut one = Edges::NONE;
+        one.push(edge).unwrap();
+        assert_eq!(1, one.pairs(shape).len());
+        let negative = Edges::NONE.with_negative(true);
+        assert!(!negative.is_none() && negative.is_empty());
+        assert_eq!(144, negative.pairs(shape).len());
+    }
+
+    #[test]
+    fn pairs() {
+        let set = |digits: &[u8]| -> Candidates { digits.iter().collect() };
+        let all = super::super::Shape::STANDARD.digits();
+
+        // A V leaves 1 thru 4 either side, an X all but the 5 twice over
+        let v = |x, y| allows(Some(Mark::V), x, y);
+        assert_eq!((set(&[1, 2, 3, 4]), set(&[1, 2, 3, 4])), fits(all, all, v));
+        assert_eq!((set(&[1]), set(&[4])), fits(all, set(&[4]), v));
+        let x = |a, b| allows(Some(Mark::X), a, b);
+        assert_eq!(set(&[1, 2, 3, 4, 5, 6, 7, 8, 9]), fits(all, all, x).0);
+        assert_eq!((set(&[3]), set(&[7])), fits(set(&[3, 5]), set(&[7, 8]), x));
+
+        // Without a mark, a 2 beside rules out the 3 and 8 that would add up
+        let none = |a, b| allows(None, a, b);
+        assert_eq!(set(&[1, 2, 4, 5, 6, 7, 9]), fits(all, set(&[2]), none).0);
+        assert!(fits(set(&[3]), set(&[2]), none).0.is_empty());
+    }
+
+    #[test]
+    fn reduce() {
+        let coord = |row, col| Coord::new(row, col);
+        let mut edges = Edges::NONE;
+        edges
+            .push(Edge::new(coord(0, 0), coord(0, 1), Mark::X).unwrap())
+            .unwrap();
+
+        let mut puzzle = Puzzle::parse("");
+        puzzle.set_xv(edges.clone());
+        puzzle.config = SolverConfig::with_techniques(vec![Technique::Xv]);
+        puzzle.grid[0][0].number = Some(4);
+        puzzle.assign_candidates();
+
+        // Beside an X, the 4 needs a 6
+        let found = puzzle.reduce_candidates_with_xv();
+        assert_eq!(Some(Technique::Xv), found[0].technique());
+        assert_eq!(vec![6], puzzle.grid[0][1].candidates.to_vec());
+        assert!(puzzle.reduce_candidates_with_xv().is_empty());
+
+        // Under the negative constraint, the 4's other neighbours can't be a 1 or 6
+        puzzle.set_xv(edges.with_negative(true));
+        while !puzzle.reduce_candidates_with_xv().is_empty() {}
+        assert_eq!(
+            vec![2, 3, 5, 7, 8, 9],
+            puzzle.grid[1][0].candidates.to_vec()
+        );
+
+        // Filled in, they must add up, and the rest mustn't
+        let mut filled = puzzle.clone();
+        filled.grid[0][1].number = Some(6);
+        filled.grid[1][0].number = Some(2);
+        assert!(filled.wrong_xv().is_empty());
+        filled.grid[0][1].number = Some(5);
+        filled.grid[1][0].number = Some(1);
+        assert_eq!(
+            vec![[coord(0, 0), coord(0, 1)], [coord(0, 0), coord(1, 0)]],
+            filled.wrong_xv()
+        );
+
+        // Two sides of a V can't both be large
+        let mut stuck = Puzzle::parse("");
+        let mut v = Edges::NONE;
+        v.push(Edge::new(coord(0, 0), coord(1, 0), Mark::V).unwrap())
+            .unwrap();
+        stuck.set_xv(v);
+        stuck.grid[0][0].number = Some(7);
+        stuck.assign_candidates();
+        while !stuck.reduce_candidates_with_xv().is_empty() {}
+        assert!(matches!(stuck.status(), PuzzleStatus::IllDefined(_)));
+    }
+}